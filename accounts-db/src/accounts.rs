@@ -5,7 +5,7 @@ use {
             AccountStorageEntry, AccountsAddRootTiming, AccountsDb, LoadHint, LoadedAccount,
             ScanAccountStorageData, ScanStorageResult, VerifyAccountsHashAndLamportsConfig,
         },
-        accounts_index::{IndexKey, ScanConfig, ScanError, ScanResult},
+        accounts_index::{IndexKey, ScanConfig, ScanError, ScanResult, ScanStart},
         ancestors::Ancestors,
         storable_accounts::StorableAccounts,
     },
@@ -474,6 +474,71 @@ impl Accounts {
         Self::maybe_abort_scan(result, &config)
     }
 
+    /// Loads, in pubkey order from `start`, at most `limit` accounts of a secondary index matching
+    /// `filter`. The index must include `index_key`. The page is cut short once the accounts
+    /// exceed `byte_limit_for_scan`, or once `visit_limit` keys have been visited, even if few or
+    /// none of them matched. Also returns the last visited key if the scan stopped before the end
+    /// of the index, to continue the scan after.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load_page_by_index_key_with_filter<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        ancestors: &Ancestors,
+        bank_id: BankId,
+        index_key: &IndexKey,
+        filter: F,
+        start: ScanStart,
+        limit: usize,
+        visit_limit: usize,
+        byte_limit_for_scan: Option<usize>,
+    ) -> ScanResult<(Vec<TransactionAccount>, Option<Pubkey>)> {
+        let sum = AtomicUsize::default();
+        let num_found = AtomicUsize::default();
+        let mut num_visited = 0;
+        let mut last_visited = None;
+        let config = ScanConfig {
+            abort: Some(Arc::default()),
+            collect_all_unsorted: false,
+            ordered_start: Some(start),
+        };
+        let mut collector = Vec::new();
+        self.accounts_db.index_scan_accounts(
+            ancestors,
+            bank_id,
+            *index_key,
+            |some_account_tuple| {
+                if let Some((pubkey, _, _)) = &some_account_tuple {
+                    last_visited = Some(**pubkey);
+                }
+                num_visited += 1;
+                if num_visited >= visit_limit {
+                    config.abort();
+                }
+                Self::load_while_filtering(&mut collector, some_account_tuple, |account| {
+                    let use_account = filter(account);
+                    if use_account
+                        && (num_found.fetch_add(1, Ordering::Relaxed) + 1 >= limit
+                            || Self::accumulate_and_check_scan_result_size(
+                                &sum,
+                                account,
+                                &byte_limit_for_scan,
+                            ))
+                    {
+                        // the page is full
+                        config.abort();
+                    }
+                    use_account
+                });
+            },
+            &config,
+        )?;
+        let next_start = if config.is_aborted() {
+            last_visited
+        } else {
+            None
+        };
+        Ok((collector, next_start))
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.accounts_db.account_indexes.include_key(key)
     }
@@ -713,6 +778,7 @@ impl Accounts {
 mod tests {
     use {
         super::*,
+        crate::accounts_index::{AccountIndex, AccountSecondaryIndexes},
         solana_sdk::{
             account::{AccountSharedData, WritableAccount},
             address_lookup_table::state::LookupTableMeta,
@@ -945,6 +1011,65 @@ mod tests {
         assert_eq!(loaded, vec![]);
     }
 
+    #[test]
+    fn test_load_page_by_index_key_with_filter() {
+        let accounts_db = AccountsDb {
+            account_indexes: AccountSecondaryIndexes {
+                keys: None,
+                indexes: HashSet::from([AccountIndex::ProgramId]),
+            },
+            ..AccountsDb::new_single_for_tests()
+        };
+        let accounts = Accounts::new(Arc::new(accounts_db));
+        let program_id = Pubkey::new_unique();
+        let mut pubkeys = (0..5)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                let account = AccountSharedData::new(1, 0, &program_id);
+                accounts.store_slow_uncached(0, &pubkey, &account);
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        pubkeys.sort_unstable();
+        let ancestors = Ancestors::from(vec![0]);
+        let load_page = |filter: fn(&AccountSharedData) -> bool, start, visit_limit| {
+            let (page, next_start) = accounts
+                .load_page_by_index_key_with_filter(
+                    &ancestors,
+                    0,
+                    &IndexKey::ProgramId(program_id),
+                    filter,
+                    start,
+                    2,
+                    visit_limit,
+                    None,
+                )
+                .unwrap();
+            let page = page
+                .into_iter()
+                .map(|(pubkey, _)| pubkey)
+                .collect::<Vec<_>>();
+            (page, next_start)
+        };
+
+        // A full page continues after its last account
+        let (page, next_start) = load_page(|_| true, ScanStart::First, 10);
+        assert_eq!(page, pubkeys[..2]);
+        assert_eq!(next_start, Some(pubkeys[1]));
+        let (page, next_start) = load_page(|_| true, ScanStart::After(pubkeys[3]), 10);
+        assert_eq!(page, pubkeys[4..]);
+        assert_eq!(next_start, None);
+
+        // Once the visited keys run out, the page continues after the last visited key even if
+        // it matched nothing
+        let (page, next_start) = load_page(|_| false, ScanStart::First, 3);
+        assert!(page.is_empty());
+        assert_eq!(next_start, Some(pubkeys[2]));
+        let (page, next_start) = load_page(|_| false, ScanStart::After(pubkeys[2]), 3);
+        assert!(page.is_empty());
+        assert_eq!(next_start, None);
+    }
+
     #[test]
     fn test_lock_accounts_with_duplicates() {
         let accounts_db = AccountsDb::new_single_for_tests();
@@ -19,7 +19,9 @@
 //! commit for each slot entry would be indexed.
 
 mod geyser_plugin_utils;
+pub mod owner_stats;
 mod scan_account_storage;
+pub mod shrink_control;
 pub mod stats;
 pub mod tests;
 
@@ -32,9 +34,13 @@ use {
             meta::StoredAccountMeta, AccountStorage, AccountStorageStatus, ShrinkInProgress,
         },
         accounts_cache::{AccountsCache, CachedAccount, SlotCache},
-        accounts_db::stats::{
-            AccountsStats, CleanAccountsStats, FlushStats, PurgeStats, ShrinkAncientStats,
-            ShrinkStats, ShrinkStatsSub, StoreAccountsTiming,
+        accounts_db::{
+            owner_stats::{OwnerStatsReport, OwnerStatsTracker},
+            shrink_control::{ShrinkControl, ShrinkRequestKind, SHRINK_REQUEST_CHUNK_SLOTS},
+            stats::{
+                AccountsStats, CleanAccountsStats, FlushStats, PurgeStats, ShrinkAncientStats,
+                ShrinkStats, ShrinkStatsSub, StoreAccountsTiming,
+            },
         },
         accounts_file::{
             AccountsFile, AccountsFileError, AccountsFileProvider, MatchAccountOwnerError,
@@ -116,6 +122,7 @@ use {
         time::{Duration, Instant},
     },
     tempfile::TempDir,
+    thiserror::Error,
 };
 
 // when the accounts write cache exceeds this many bytes, we will flush it
@@ -156,6 +163,8 @@ pub enum CreateAncientStorage {
     /// ancient storages are created by 1-shot write to pack multiple accounts together more efficiently with new formats
     #[default]
     Pack,
+    /// ancient storages are packed like `Pack`, but written as block-compressed cold tiered storages
+    PackCold,
 }
 
 #[derive(Debug)]
@@ -520,6 +529,7 @@ pub const ACCOUNTS_DB_CONFIG_FOR_TESTING: AccountsDbConfig = AccountsDbConfig {
     num_foreground_threads: None,
     num_hash_threads: None,
     hash_calculation_pubkey_bins: Some(4),
+    account_history_slots: None,
 };
 pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig {
     index: Some(ACCOUNTS_INDEX_CONFIG_FOR_BENCHMARKS),
@@ -547,6 +557,7 @@ pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig
     num_foreground_threads: None,
     num_hash_threads: None,
     hash_calculation_pubkey_bins: None,
+    account_history_slots: None,
 };
 
 pub type BinnedHashData = Vec<Vec<CalculateHashIntermediate>>;
@@ -678,6 +689,25 @@ pub struct AccountsDbConfig {
     pub num_foreground_threads: Option<NonZeroUsize>,
     /// Number of threads for background accounts hashing (`thread_pool_hash`)
     pub num_hash_threads: Option<NonZeroUsize>,
+    /// Some(n) keeps every account version written in the `n` slots before the highest root, so
+    /// accounts can be loaded as of any root among them with `load_account_at_slot()`. Skipped
+    /// slots count towards `n`.
+    pub account_history_slots: Option<Slot>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum AccountHistoryError {
+    #[error("account history is not retained by this node")]
+    NotRetained,
+    #[error("slot {slot} is older than the oldest retained slot {oldest_retained_slot}")]
+    SlotTooOld {
+        slot: Slot,
+        oldest_retained_slot: Slot,
+    },
+    #[error("slot {slot} is newer than the highest root {max_root}")]
+    SlotNotRooted { slot: Slot, max_root: Slot },
+    #[error("slot {0} was skipped")]
+    SlotSkipped(Slot),
 }
 
 #[cfg(not(test))]
@@ -1225,7 +1255,7 @@ impl AccountStorageEntry {
         file_size: u64,
         provider: AccountsFileProvider,
     ) -> Self {
-        let tail = AccountsFile::file_name(slot, id);
+        let tail = provider.file_name(slot, id);
         let path = Path::new(path).join(tail);
         let accounts = provider.new_writable(path, file_size);
 
@@ -1462,7 +1492,7 @@ struct RemoveUnrootedSlotsSynchronization {
     signal: Condvar,
 }
 
-type AccountInfoAccountsIndex = AccountsIndex<AccountInfo, AccountInfo>;
+pub(crate) type AccountInfoAccountsIndex = AccountsIndex<AccountInfo, AccountInfo>;
 
 // This structure handles the load/store of the accounts
 #[derive(Debug)]
@@ -1474,6 +1504,9 @@ pub struct AccountsDb {
     /// Some(offset) means for slots up to (max_slot - (slots_per_epoch - 'offset')), put them in ancient append vecs
     pub ancient_append_vec_offset: Option<i64>,
     pub ancient_storage_ideal_size: u64,
+
+    /// Some(n) iff clean keeps the account versions of the `n` slots before the highest root
+    account_history_slots: Option<Slot>,
     pub max_ancient_storages: usize,
     /// true iff we want to skip the initial hash calculation on startup
     pub skip_initial_hash_calc: bool,
@@ -1538,6 +1571,12 @@ pub struct AccountsDb {
 
     pub stats: AccountsStats,
 
+    /// Per-storage owner summaries backing `owner_stats_report()`
+    owner_stats: OwnerStatsTracker,
+
+    /// Pausing of background shrink and shrink requests made by the operator
+    pub shrink_control: ShrinkControl,
+
     clean_accounts_stats: CleanAccountsStats,
 
     // Stats for purges called outside of clean_accounts()
@@ -2017,6 +2056,7 @@ impl AccountsDb {
             ancient_append_vec_offset: accounts_db_config
                 .ancient_append_vec_offset
                 .or(ANCIENT_APPEND_VEC_DEFAULT_OFFSET),
+            account_history_slots: accounts_db_config.account_history_slots,
             ancient_storage_ideal_size: accounts_db_config
                 .ancient_storage_ideal_size
                 .unwrap_or(DEFAULT_ANCIENT_STORAGE_IDEAL_SIZE),
@@ -2071,6 +2111,8 @@ impl AccountsDb {
             shrink_stats: ShrinkStats::default(),
             shrink_ancient_stats: ShrinkAncientStats::default(),
             stats: AccountsStats::default(),
+            owner_stats: OwnerStatsTracker::default(),
+            shrink_control: ShrinkControl::default(),
             #[cfg(test)]
             load_delay: u64::default(),
             #[cfg(test)]
@@ -2119,13 +2161,17 @@ impl AccountsDb {
     }
 
     fn new_storage_entry(&self, slot: Slot, path: &Path, size: u64) -> AccountStorageEntry {
-        AccountStorageEntry::new(
-            path,
-            slot,
-            self.next_id(),
-            size,
-            self.accounts_file_provider,
-        )
+        self.new_storage_entry_with_provider(slot, path, size, self.accounts_file_provider)
+    }
+
+    fn new_storage_entry_with_provider(
+        &self,
+        slot: Slot,
+        path: &Path,
+        size: u64,
+        provider: AccountsFileProvider,
+    ) -> AccountStorageEntry {
+        AccountStorageEntry::new(path, slot, self.next_id(), size, provider)
     }
 
     /// Returns if the experimental accounts lattice hash is enabled
@@ -2410,7 +2456,7 @@ impl AccountsDb {
     }
 
     fn max_clean_root(&self, proposed_clean_root: Option<Slot>) -> Option<Slot> {
-        match (
+        let max_clean_root = match (
             self.accounts_index.min_ongoing_scan_root(),
             proposed_clean_root,
         ) {
@@ -2420,9 +2466,62 @@ impl AccountsDb {
             (Some(min_scan_root), Some(proposed_clean_root)) => {
                 Some(std::cmp::min(min_scan_root, proposed_clean_root))
             }
+        };
+        // Versions written after the oldest retained slot must survive clean. The newest version
+        // at or before it is kept too, since it is the state of the account at that slot.
+        match (max_clean_root, self.oldest_retained_history_slot()) {
+            (max_clean_root, None) => max_clean_root,
+            (None, Some(oldest_retained_slot)) => Some(oldest_retained_slot),
+            (Some(max_clean_root), Some(oldest_retained_slot)) => {
+                Some(std::cmp::min(max_clean_root, oldest_retained_slot))
+            }
         }
     }
 
+    /// The oldest slot `load_account_at_slot()` can load accounts at, if account history is kept
+    fn oldest_retained_history_slot(&self) -> Option<Slot> {
+        self.account_history_slots.map(|account_history_slots| {
+            self.accounts_index
+                .max_root_inclusive()
+                .saturating_sub(account_history_slots)
+        })
+    }
+
+    /// Loads `pubkey` as it was at the end of the rooted `slot`, which has to be within the
+    /// `account_history_slots` slots before the highest root.
+    /// Returns None if the account did not exist or had zero lamports at `slot`.
+    pub fn load_account_at_slot(
+        &self,
+        pubkey: &Pubkey,
+        slot: Slot,
+    ) -> Result<Option<AccountSharedData>, AccountHistoryError> {
+        let oldest_retained_slot = self
+            .oldest_retained_history_slot()
+            .ok_or(AccountHistoryError::NotRetained)?;
+        let max_root = self.accounts_index.max_root_inclusive();
+        if slot > max_root {
+            return Err(AccountHistoryError::SlotNotRooted { slot, max_root });
+        }
+        if slot < oldest_retained_slot {
+            return Err(AccountHistoryError::SlotTooOld {
+                slot,
+                oldest_retained_slot,
+            });
+        }
+        if !self.accounts_index.is_alive_root(slot) {
+            return Err(AccountHistoryError::SlotSkipped(slot));
+        }
+        Ok(self
+            .do_load(
+                &Ancestors::default(),
+                pubkey,
+                Some(slot),
+                LoadHint::Unspecified,
+                LoadZeroLamports::None,
+            )
+            .map(|(account, _slot)| account))
+    }
+
     /// get the oldest slot that is within one epoch of the highest known root.
     /// The slot will have been offset by `self.ancient_append_vec_offset`
     fn get_oldest_non_ancient_slot(&self, epoch_schedule: &EpochSchedule) -> Slot {
@@ -4106,6 +4205,26 @@ impl AccountsDb {
         self.storage.shrinking_in_progress(slot, shrunken_store)
     }
 
+    /// Returns a new storage to pack ancient accounts into.
+    /// When ancient storages are created with `PackCold`, the new storage is a cold tiered storage.
+    pub(crate) fn get_ancient_store_for_shrink(
+        &self,
+        slot: Slot,
+        size: u64,
+    ) -> ShrinkInProgress<'_> {
+        if self.create_ancient_storage != CreateAncientStorage::PackCold {
+            return self.get_store_for_shrink(slot, size);
+        }
+        let shrunken_store = self.create_store_with_provider(
+            slot,
+            size,
+            "ancient",
+            self.shrink_paths.as_slice(),
+            AccountsFileProvider::ColdStorage,
+        );
+        self.storage.shrinking_in_progress(slot, shrunken_store)
+    }
+
     // Reads all accounts in given slot's AppendVecs and filter only to alive,
     // then create a minimum AppendVec filled with the alive.
     fn shrink_slot_forced(&self, slot: Slot) {
@@ -4121,6 +4240,97 @@ impl AccountsDb {
         }
     }
 
+    /// Processes the next chunk of the shrink and ancient pack requests queued in
+    /// `shrink_control`, which run oldest first.
+    ///
+    /// At most `SHRINK_REQUEST_CHUNK_SLOTS` slots are processed per call, so a request for a large
+    /// range is spread across AccountsBackgroundService iterations. Ancient pack requests are
+    /// skipped unless `ancient_storages_enabled`, in which case shrink requests skip the ancient
+    /// slots instead, as those are packed rather than shrunk.
+    pub fn process_shrink_requests(
+        &self,
+        epoch_schedule: &EpochSchedule,
+        ancient_storages_enabled: bool,
+    ) {
+        let Some((request, next_slot)) = self.shrink_control.next_request_chunk() else {
+            return;
+        };
+        let ancient_storages_enabled =
+            ancient_storages_enabled && self.ancient_append_vec_offset.is_some();
+        let slots = next_slot..=request.end_slot;
+        let mut remaining_slots = match request.kind {
+            ShrinkRequestKind::Shrink => {
+                let oldest_non_ancient_slot = if ancient_storages_enabled {
+                    self.get_oldest_non_ancient_slot(epoch_schedule)
+                } else {
+                    0
+                };
+                let mut shrink_slots = self.all_slots_in_storage();
+                shrink_slots
+                    .retain(|slot| slots.contains(slot) && *slot >= oldest_non_ancient_slot);
+                shrink_slots.sort_unstable();
+                shrink_slots
+            }
+            ShrinkRequestKind::AncientPack if ancient_storages_enabled => {
+                let oldest_non_ancient_slot = self.get_oldest_non_ancient_slot(epoch_schedule);
+                let mut sorted_slots =
+                    self.get_sorted_potential_ancient_slots(oldest_non_ancient_slot);
+                sorted_slots.retain(|slot| slots.contains(slot));
+                sorted_slots
+            }
+            ShrinkRequestKind::AncientPack => {
+                warn!("Skipping ancient pack of slots {slots:?}: ancient storages are not enabled");
+                vec![]
+            }
+        };
+        if next_slot == request.start_slot {
+            self.shrink_control
+                .set_active_slots_total(remaining_slots.len());
+        }
+
+        let chunk_len = remaining_slots.len().min(SHRINK_REQUEST_CHUNK_SLOTS);
+        let chunk: Vec<_> = remaining_slots.drain(..chunk_len).collect();
+        let capacity_in_chunk = || {
+            chunk
+                .iter()
+                .filter_map(|slot| self.storage.get_slot_storage_entry(*slot))
+                .map(|store| store.capacity())
+                .sum::<u64>()
+        };
+        let capacity_before = capacity_in_chunk();
+        match request.kind {
+            ShrinkRequestKind::Shrink => {
+                let _guard = self.active_stats.activate(ActiveStatItem::Shrink);
+                self.thread_pool_clean.install(|| {
+                    chunk
+                        .par_iter()
+                        .for_each(|slot| self.shrink_slot_forced(*slot))
+                });
+            }
+            ShrinkRequestKind::AncientPack => {
+                let can_randomly_shrink = false;
+                if self.create_ancient_storage == CreateAncientStorage::Append {
+                    self.combine_ancient_slots(chunk.clone(), can_randomly_shrink);
+                } else {
+                    self.combine_ancient_slots_packed(chunk.clone(), can_randomly_shrink);
+                }
+            }
+        }
+        let bytes_reclaimed = capacity_before.saturating_sub(capacity_in_chunk());
+
+        let next_slot = remaining_slots.first().copied();
+        if next_slot.is_none() {
+            info!(
+                "Finished {:?} request {} for slots {:?}",
+                request.kind,
+                request.id,
+                request.start_slot..=request.end_slot,
+            );
+        }
+        self.shrink_control
+            .finish_active_chunk(chunk.len(), bytes_reclaimed, next_slot);
+    }
+
     fn all_slots_in_storage(&self) -> Vec<Slot> {
         self.storage.all_slots()
     }
@@ -4226,7 +4436,9 @@ impl AccountsDb {
     /// get a sorted list of slots older than an epoch
     /// squash those slots into ancient append vecs
     pub fn shrink_ancient_slots(&self, epoch_schedule: &EpochSchedule) {
-        if self.ancient_append_vec_offset.is_none() {
+        if self.ancient_append_vec_offset.is_none()
+            || self.shrink_control.is_background_shrink_paused()
+        {
             return;
         }
 
@@ -4599,6 +4811,11 @@ impl AccountsDb {
     }
 
     pub fn shrink_candidate_slots(&self, epoch_schedule: &EpochSchedule) -> usize {
+        if self.shrink_control.is_background_shrink_paused() {
+            // candidates are kept for when background shrink is resumed
+            return 0;
+        }
+
         let oldest_non_ancient_slot = self.get_oldest_non_ancient_slot(epoch_schedule);
 
         let shrink_candidates_slots =
@@ -4866,11 +5083,13 @@ impl AccountsDb {
         F: FnMut(Option<(&Pubkey, AccountSharedData, Slot)>),
     {
         let key = match &index_key {
-            IndexKey::ProgramId(key) => key,
-            IndexKey::SplTokenMint(key) => key,
-            IndexKey::SplTokenOwner(key) => key,
+            IndexKey::ProgramId(key) => Some(key),
+            IndexKey::SplTokenMint(key) => Some(key),
+            IndexKey::SplTokenOwner(key) => Some(key),
+            // include/exclude keys do not apply to user-defined indexes
+            IndexKey::Custom(..) => None,
         };
-        if !self.account_indexes.include_key(key) {
+        if key.is_some_and(|key| !self.account_indexes.include_key(key)) {
             // the requested key was not indexed in the secondary index, so do a normal scan
             let used_index = false;
             self.scan_accounts(ancestors, bank_id, scan_func, config)?;
@@ -5593,12 +5812,28 @@ impl AccountsDb {
         size: u64,
         from: &str,
         paths: &[PathBuf],
+    ) -> Arc<AccountStorageEntry> {
+        self.create_store_with_provider(slot, size, from, paths, self.accounts_file_provider)
+    }
+
+    fn create_store_with_provider(
+        &self,
+        slot: Slot,
+        size: u64,
+        from: &str,
+        paths: &[PathBuf],
+        provider: AccountsFileProvider,
     ) -> Arc<AccountStorageEntry> {
         self.stats
             .create_store_count
             .fetch_add(1, Ordering::Relaxed);
         let path_index = thread_rng().gen_range(0..paths.len());
-        let store = Arc::new(self.new_storage_entry(slot, Path::new(&paths[path_index]), size));
+        let store = Arc::new(self.new_storage_entry_with_provider(
+            slot,
+            Path::new(&paths[path_index]),
+            size,
+            provider,
+        ));
 
         debug!(
             "creating store: {} slot: {} len: {} size: {} from: {} path: {}",
@@ -6270,6 +6505,8 @@ impl AccountsDb {
         requested_flush_root: Option<Slot>,
         should_clean: Option<(&mut usize, &mut usize)>,
     ) -> (usize, usize, FlushStats) {
+        // Older versions in the roots being flushed are history that has to be written too
+        let should_clean = should_clean.filter(|_| self.account_history_slots.is_none());
         let max_clean_root = should_clean.as_ref().and_then(|_| {
             // If there is a long running scan going on, this could prevent any cleaning
             // based on updates from slots > `max_clean_root`.
@@ -6840,7 +7077,7 @@ impl AccountsDb {
     }
 
     /// `oldest_non_ancient_slot` is only applicable when `Append` is used for ancient append vec packing.
-    /// If `Pack` is used for ancient append vec packing, return None.
+    /// If `Pack` or `PackCold` is used for ancient append vec packing, return None.
     /// Otherwise, return a slot 'max_slot_inclusive' - (slots_per_epoch - `self.ancient_append_vec_offset`)
     /// If ancient append vecs are not enabled, return 0.
     fn get_oldest_non_ancient_slot_for_hash_calc_scan(
@@ -6848,7 +7085,7 @@ impl AccountsDb {
         max_slot_inclusive: Slot,
         config: &CalcAccountsHashConfig<'_>,
     ) -> Option<Slot> {
-        if self.create_ancient_storage == CreateAncientStorage::Pack {
+        if self.create_ancient_storage != CreateAncientStorage::Append {
             // oldest_non_ancient_slot is only applicable when ancient storages are created with `Append`. When ancient storages are created with `Pack`, ancient storages
             // can be created in between non-ancient storages. Return None, because oldest_non_ancient_slot is not applicable here.
             None
@@ -9154,6 +9391,26 @@ impl AccountsDb {
         timings.storage_size_storages_us = storage_size_storages_time.as_us();
     }
 
+    /// Breaks down the accounts index and storages by owner, keeping the `limit` owners with the
+    /// most storage bytes
+    ///
+    /// Only storages created or changed since the previous report are read, see [`owner_stats`].
+    pub fn owner_stats_report(&self, limit: Option<usize>) -> OwnerStatsReport {
+        let (owners, num_storages_summarized) = self.thread_pool_clean.install(|| {
+            self.owner_stats
+                .collect(&self.storage, &self.accounts_index)
+        });
+        let (index_entries_in_mem, index_entries_on_disk) =
+            self.accounts_index.in_mem_and_disk_entry_counts();
+        OwnerStatsReport {
+            index_entries_in_mem: index_entries_in_mem as u64,
+            index_entries_on_disk: index_entries_on_disk as u64,
+            num_storages: self.storage.len() as u64,
+            num_storages_summarized: num_storages_summarized as u64,
+            ..owner_stats::build_report(owners, limit)
+        }
+    }
+
     pub fn print_accounts_stats(&self, label: &str) {
         self.print_index(label);
         self.print_count_and_status(label);
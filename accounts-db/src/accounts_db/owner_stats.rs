@@ -0,0 +1,360 @@
+//! Per-owner breakdown of the accounts held by AccountsDb
+//!
+//! Every storage is summarized by owner the first time a report is requested after the storage
+//! was created. Summaries are kept until clean or shrink changes which accounts in their storage
+//! are alive, so later reports only read the storages that changed since the previous report
+//! instead of scanning every account again.
+use {
+    crate::{
+        account_info::{AccountInfo, Offset},
+        account_storage::AccountStorage,
+        accounts_db::{AccountInfoAccountsIndex, AccountStorageEntry, AccountsFileId},
+        accounts_file::AccountsFile,
+        accounts_index::IsCached,
+    },
+    rayon::prelude::*,
+    serde_derive::{Deserialize, Serialize},
+    solana_sdk::{account::ReadableAccount, clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{HashMap, HashSet},
+        fmt,
+        sync::Mutex,
+    },
+};
+
+/// What the accounts owned by one program occupy in the accounts index and storages
+///
+/// Only the alive version of each account is counted. A version stays counted until clean
+/// reclaims it once a newer version is stored, and where index entries are held is as of when
+/// their storage was last read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerStats {
+    /// Number of non-zero-lamport accounts, which is also their number of index entries
+    pub num_accounts: u64,
+    /// Number of zero-lamport accounts awaiting clean
+    pub num_zero_lamport_accounts: u64,
+    /// Sum of the accounts' data lengths
+    pub data_bytes: u64,
+    /// Storage file bytes taken by the accounts in append vecs
+    pub append_vec_bytes: u64,
+    /// Storage file bytes taken by the accounts in tiered storages
+    pub tiered_storage_bytes: u64,
+    /// Index entries of the accounts held in memory
+    pub index_entries_in_mem: u64,
+    /// Index entries of the accounts in the disk index; an entry can be both in memory and on disk
+    pub index_entries_on_disk: u64,
+}
+
+impl OwnerStats {
+    fn accumulate(&mut self, other: &Self) {
+        self.num_accounts += other.num_accounts;
+        self.num_zero_lamport_accounts += other.num_zero_lamport_accounts;
+        self.data_bytes += other.data_bytes;
+        self.append_vec_bytes += other.append_vec_bytes;
+        self.tiered_storage_bytes += other.tiered_storage_bytes;
+        self.index_entries_in_mem += other.index_entries_in_mem;
+        self.index_entries_on_disk += other.index_entries_on_disk;
+    }
+
+    pub fn storage_bytes(&self) -> u64 {
+        self.append_vec_bytes + self.tiered_storage_bytes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerStatsEntry {
+    pub owner: String,
+    #[serde(flatten)]
+    pub stats: OwnerStats,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerStatsReport {
+    /// Owners sorted by descending storage bytes
+    pub owners: Vec<OwnerStatsEntry>,
+    /// Totals over all owners, including the ones left out of `owners`
+    pub total: OwnerStats,
+    /// Accounts index entries held in memory
+    pub index_entries_in_mem: u64,
+    /// Accounts index entries in the disk index; an entry can be both in memory and on disk
+    pub index_entries_on_disk: u64,
+    pub num_storages: u64,
+    /// Storages that had to be read for this report because they were new since the last one
+    pub num_storages_summarized: u64,
+}
+
+impl fmt::Display for OwnerStatsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let write_row = |f: &mut fmt::Formatter, name: &str, stats: &OwnerStats| {
+            writeln!(
+                f,
+                "{:<44}  {:>12}  {:>12}  {:>16}  {:>16}  {:>16}  {:>12}  {:>12}",
+                name,
+                stats.num_accounts,
+                stats.num_zero_lamport_accounts,
+                stats.data_bytes,
+                stats.append_vec_bytes,
+                stats.tiered_storage_bytes,
+                stats.index_entries_in_mem,
+                stats.index_entries_on_disk,
+            )
+        };
+        writeln!(
+            f,
+            "{:<44}  {:>12}  {:>12}  {:>16}  {:>16}  {:>16}  {:>12}  {:>12}",
+            "Owner",
+            "Accounts",
+            "Zero lamport",
+            "Data bytes",
+            "Append vec bytes",
+            "Tiered bytes",
+            "Index in mem",
+            "Index on disk",
+        )?;
+        for entry in &self.owners {
+            write_row(f, &entry.owner, &entry.stats)?;
+        }
+        write_row(f, "Total", &self.total)?;
+        writeln!(
+            f,
+            "Index entries: {} in memory, {} on disk",
+            self.index_entries_in_mem, self.index_entries_on_disk,
+        )?;
+        writeln!(
+            f,
+            "Storages: {} ({} read for this report)",
+            self.num_storages, self.num_storages_summarized,
+        )
+    }
+}
+
+/// Owner breakdown of one storage, valid while the storage keeps the same length and number of
+/// alive accounts
+#[derive(Debug)]
+struct StorageSummary {
+    len: usize,
+    alive_count: usize,
+    owners: HashMap<Pubkey, OwnerStats>,
+}
+
+impl StorageSummary {
+    fn is_current(&self, store: &AccountStorageEntry) -> bool {
+        self.len == store.accounts.len() && self.alive_count == store.count()
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct OwnerStatsTracker {
+    summaries: Mutex<HashMap<AccountsFileId, StorageSummary>>,
+}
+
+impl OwnerStatsTracker {
+    /// Returns the breakdown of all storages in `storage` by owner, and the number of storages
+    /// that were summarized by this call
+    pub(crate) fn collect(
+        &self,
+        storage: &AccountStorage,
+        accounts_index: &AccountInfoAccountsIndex,
+    ) -> (HashMap<Pubkey, OwnerStats>, usize) {
+        let storages: Vec<_> = storage.iter().map(|(_slot, store)| store).collect();
+
+        // Summaries of storages that no longer exist are dropped, and storages that are new or
+        // changed since they were summarized are read again
+        let stale_storages: Vec<_> = {
+            let mut summaries = self.summaries.lock().unwrap();
+            let live_ids: HashSet<_> = storages.iter().map(|store| store.id()).collect();
+            summaries.retain(|id, _| live_ids.contains(id));
+            storages
+                .iter()
+                .filter(|store| {
+                    summaries
+                        .get(&store.id())
+                        .map_or(true, |summary| !summary.is_current(store))
+                })
+                .collect()
+        };
+        // the lock is not held while reading storages, so a slow report does not block others
+        let new_summaries: Vec<_> = stale_storages
+            .par_iter()
+            .map(|store| (store.id(), summarize_storage(store, accounts_index)))
+            .collect();
+        let num_summarized = new_summaries.len();
+        let mut summaries = self.summaries.lock().unwrap();
+        summaries.extend(new_summaries);
+
+        let mut owners = HashMap::<Pubkey, OwnerStats>::new();
+        for summary in summaries.values() {
+            for (owner, stats) in &summary.owners {
+                owners.entry(*owner).or_default().accumulate(stats);
+            }
+        }
+        (owners, num_summarized)
+    }
+}
+
+/// Returns true if the latest version of an account in `slot_list` which is not in the write
+/// cache is the one stored at `offset` in storage `store_id` of `slot`
+fn is_alive_version(
+    slot_list: &[(Slot, AccountInfo)],
+    slot: Slot,
+    store_id: AccountsFileId,
+    offset: Offset,
+) -> bool {
+    slot_list
+        .iter()
+        .filter(|(_slot, info)| !info.is_cached())
+        .max_by_key(|(slot, _info)| *slot)
+        .is_some_and(|(latest_slot, info)| {
+            *latest_slot == slot && info.store_id() == store_id && info.offset() == offset
+        })
+}
+
+/// Reads the alive accounts in `store`, attributing the storage's file bytes to owners in
+/// proportion to the size of their stored accounts
+fn summarize_storage(
+    store: &AccountStorageEntry,
+    accounts_index: &AccountInfoAccountsIndex,
+) -> StorageSummary {
+    let len = store.accounts.len();
+    let alive_count = store.count();
+    let mut owners = HashMap::<Pubkey, (OwnerStats, u64)>::new();
+    let mut total_stored_size = 0u64;
+    store.accounts.scan_accounts(|account| {
+        let index_location = accounts_index.get_without_caching_and_then(
+            account.pubkey(),
+            |slot_list, in_mem, on_disk| {
+                slot_list
+                    .is_some_and(|slot_list| {
+                        is_alive_version(slot_list, store.slot(), store.id(), account.offset())
+                    })
+                    .then_some((in_mem, on_disk))
+            },
+        );
+        let Some((in_mem, on_disk)) = index_location else {
+            return;
+        };
+        let (stats, stored_size) = owners.entry(*account.owner()).or_default();
+        stats.index_entries_in_mem += in_mem as u64;
+        stats.index_entries_on_disk += on_disk as u64;
+        if account.lamports() == 0 {
+            stats.num_zero_lamport_accounts += 1;
+        } else {
+            stats.num_accounts += 1;
+        }
+        stats.data_bytes += account.data_len() as u64;
+        *stored_size += account.stored_size() as u64;
+        total_stored_size += account.stored_size() as u64;
+    });
+
+    let is_tiered = matches!(store.accounts, AccountsFile::TieredStorage(_));
+    let owners = owners
+        .into_iter()
+        .map(|(owner, (mut stats, stored_size))| {
+            let bytes = (len as u128 * stored_size as u128)
+                .checked_div(total_stored_size as u128)
+                .unwrap_or_default() as u64;
+            if is_tiered {
+                stats.tiered_storage_bytes = bytes;
+            } else {
+                stats.append_vec_bytes = bytes;
+            }
+            (owner, stats)
+        })
+        .collect();
+    StorageSummary {
+        len,
+        alive_count,
+        owners,
+    }
+}
+
+/// Builds the report out of the per-owner totals, keeping the `limit` owners with the most
+/// storage bytes
+pub(crate) fn build_report(
+    owners: HashMap<Pubkey, OwnerStats>,
+    limit: Option<usize>,
+) -> OwnerStatsReport {
+    let mut total = OwnerStats::default();
+    let mut owners: Vec<_> = owners
+        .into_iter()
+        .inspect(|(_owner, stats)| total.accumulate(stats))
+        .collect();
+    owners.sort_unstable_by(|(owner_a, a), (owner_b, b)| {
+        b.storage_bytes()
+            .cmp(&a.storage_bytes())
+            .then_with(|| owner_a.cmp(owner_b))
+    });
+    owners.truncate(limit.unwrap_or(usize::MAX));
+    OwnerStatsReport {
+        owners: owners
+            .into_iter()
+            .map(|(owner, stats)| OwnerStatsEntry {
+                owner: owner.to_string(),
+                stats,
+            })
+            .collect(),
+        total,
+        ..OwnerStatsReport::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report() {
+        let owner_a = Pubkey::new_unique();
+        let owner_b = Pubkey::new_unique();
+        let owners = HashMap::from([
+            (
+                owner_a,
+                OwnerStats {
+                    num_accounts: 1,
+                    data_bytes: 10,
+                    append_vec_bytes: 100,
+                    ..OwnerStats::default()
+                },
+            ),
+            (
+                owner_b,
+                OwnerStats {
+                    num_accounts: 2,
+                    data_bytes: 20,
+                    append_vec_bytes: 50,
+                    tiered_storage_bytes: 100,
+                    ..OwnerStats::default()
+                },
+            ),
+        ]);
+
+        let report = build_report(owners.clone(), None);
+        assert_eq!(
+            report
+                .owners
+                .iter()
+                .map(|entry| entry.owner.clone())
+                .collect::<Vec<_>>(),
+            vec![owner_b.to_string(), owner_a.to_string()],
+        );
+        assert_eq!(
+            report.total,
+            OwnerStats {
+                num_accounts: 3,
+                num_zero_lamport_accounts: 0,
+                data_bytes: 30,
+                append_vec_bytes: 150,
+                tiered_storage_bytes: 100,
+                ..OwnerStats::default()
+            }
+        );
+
+        let report = build_report(owners, Some(1));
+        assert_eq!(report.owners.len(), 1);
+        assert_eq!(report.owners[0].owner, owner_b.to_string());
+        assert_eq!(report.total.num_accounts, 3);
+    }
+}
@@ -0,0 +1,243 @@
+//! Operator control over shrink and ancient packing while the validator runs
+//!
+//! Background shrink can be paused, e.g. while the validator is leader for many slots, and shrink
+//! or ancient pack of a slot range can be requested to catch up afterwards. Requests are queued
+//! here and run by AccountsBackgroundService, so they never race with clean or snapshotting. Each
+//! AccountsBackgroundService iteration only processes a bounded chunk of the active request, so
+//! a large request does not hold up clean and snapshots.
+use {
+    serde_derive::{Deserialize, Serialize},
+    solana_sdk::clock::Slot,
+    std::{
+        collections::VecDeque,
+        fmt,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, Ordering},
+            Mutex,
+        },
+    },
+};
+
+/// Number of finished requests kept for `ShrinkControlStatus`
+const MAX_COMPLETED_REQUESTS: usize = 16;
+
+/// Number of slots of the active request processed by each AccountsBackgroundService iteration
+pub(crate) const SHRINK_REQUEST_CHUNK_SLOTS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShrinkRequestKind {
+    /// Rewrite the alive accounts of each storage in the range to a new, smaller storage
+    Shrink,
+    /// Combine the ancient storages in the range, as `shrink_ancient_slots()` does
+    AncientPack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShrinkRequest {
+    pub id: u64,
+    pub kind: ShrinkRequestKind,
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShrinkRequestProgress {
+    pub request: ShrinkRequest,
+    /// Slots in the range with a storage to process
+    pub slots_total: u64,
+    pub slots_processed: u64,
+    /// Decrease of the storage capacity of the slots processed so far
+    pub bytes_reclaimed: u64,
+}
+
+#[derive(Debug)]
+struct ActiveShrinkRequest {
+    progress: ShrinkRequestProgress,
+    /// The first slot of the range that is left to process
+    next_slot: Slot,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShrinkControlStatus {
+    pub background_shrink_paused: bool,
+    pub active_request: Option<ShrinkRequestProgress>,
+    pub pending_requests: Vec<ShrinkRequest>,
+    /// The most recently completed requests, oldest first
+    pub completed_requests: Vec<ShrinkRequestProgress>,
+    /// Bytes reclaimed by all the requests completed since startup
+    pub total_bytes_reclaimed: u64,
+}
+
+impl fmt::Display for ShrinkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{} {:?} of slots {}..={}",
+            self.id, self.kind, self.start_slot, self.end_slot
+        )
+    }
+}
+
+impl fmt::Display for ShrinkControlStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Background shrink: {}",
+            if self.background_shrink_paused {
+                "paused"
+            } else {
+                "running"
+            }
+        )?;
+        if let Some(progress) = &self.active_request {
+            writeln!(
+                f,
+                "Active request: {}, {}/{} slots processed",
+                progress.request, progress.slots_processed, progress.slots_total
+            )?;
+        }
+        for request in &self.pending_requests {
+            writeln!(f, "Pending request: {request}")?;
+        }
+        for progress in &self.completed_requests {
+            writeln!(
+                f,
+                "Completed request: {}, {} slots, {} bytes reclaimed",
+                progress.request, progress.slots_total, progress.bytes_reclaimed
+            )?;
+        }
+        writeln!(f, "Total bytes reclaimed: {}", self.total_bytes_reclaimed)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ShrinkControl {
+    background_shrink_paused: AtomicBool,
+    next_request_id: AtomicU64,
+    pending_requests: Mutex<VecDeque<ShrinkRequest>>,
+    active_request: Mutex<Option<ActiveShrinkRequest>>,
+    completed_requests: Mutex<VecDeque<ShrinkRequestProgress>>,
+    total_bytes_reclaimed: AtomicU64,
+}
+
+impl ShrinkControl {
+    /// Stops `shrink_candidate_slots()` and `shrink_ancient_slots()` from doing any work. Shrink
+    /// candidates keep accumulating and are handled once background shrink is resumed.
+    pub fn pause_background_shrink(&self) {
+        self.background_shrink_paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume_background_shrink(&self) {
+        self.background_shrink_paused
+            .store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_background_shrink_paused(&self) -> bool {
+        self.background_shrink_paused.load(Ordering::Relaxed)
+    }
+
+    /// Queues a request for the inclusive slot range `start_slot..=end_slot`. Requests run even
+    /// while background shrink is paused.
+    pub fn request(
+        &self,
+        kind: ShrinkRequestKind,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> ShrinkRequest {
+        let request = ShrinkRequest {
+            id: self.next_request_id.fetch_add(1, Ordering::Relaxed),
+            kind,
+            start_slot,
+            end_slot,
+        };
+        self.pending_requests.lock().unwrap().push_back(request);
+        request
+    }
+
+    pub fn status(&self) -> ShrinkControlStatus {
+        ShrinkControlStatus {
+            background_shrink_paused: self.is_background_shrink_paused(),
+            active_request: self
+                .active_request
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|active| active.progress),
+            pending_requests: self
+                .pending_requests
+                .lock()
+                .unwrap()
+                .iter()
+                .copied()
+                .collect(),
+            completed_requests: self
+                .completed_requests
+                .lock()
+                .unwrap()
+                .iter()
+                .copied()
+                .collect(),
+            total_bytes_reclaimed: self.total_bytes_reclaimed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the active request and the first slot it has left to process, making the oldest
+    /// pending request the active one if there is none
+    pub(crate) fn next_request_chunk(&self) -> Option<(ShrinkRequest, Slot)> {
+        let mut active_request = self.active_request.lock().unwrap();
+        if active_request.is_none() {
+            let request = self.pending_requests.lock().unwrap().pop_front()?;
+            *active_request = Some(ActiveShrinkRequest {
+                progress: ShrinkRequestProgress {
+                    request,
+                    slots_total: 0,
+                    slots_processed: 0,
+                    bytes_reclaimed: 0,
+                },
+                next_slot: request.start_slot,
+            });
+        }
+        active_request
+            .as_ref()
+            .map(|active| (active.progress.request, active.next_slot))
+    }
+
+    pub(crate) fn set_active_slots_total(&self, slots_total: usize) {
+        if let Some(active) = self.active_request.lock().unwrap().as_mut() {
+            active.progress.slots_total = slots_total as u64;
+        }
+    }
+
+    /// Records a processed chunk of the active request, which continues at `next_slot`, or is
+    /// complete if `next_slot` is None
+    pub(crate) fn finish_active_chunk(
+        &self,
+        slots_processed: usize,
+        bytes_reclaimed: u64,
+        next_slot: Option<Slot>,
+    ) {
+        let mut active_request = self.active_request.lock().unwrap();
+        let Some(active) = active_request.as_mut() else {
+            return;
+        };
+        active.progress.slots_processed += slots_processed as u64;
+        active.progress.bytes_reclaimed += bytes_reclaimed;
+        self.total_bytes_reclaimed
+            .fetch_add(bytes_reclaimed, Ordering::Relaxed);
+        if let Some(next_slot) = next_slot {
+            active.next_slot = next_slot;
+            return;
+        }
+
+        let progress = active_request.take().unwrap().progress;
+        let mut completed_requests = self.completed_requests.lock().unwrap();
+        if completed_requests.len() == MAX_COMPLETED_REQUESTS {
+            completed_requests.pop_front();
+        }
+        completed_requests.push_back(progress);
+    }
+}
@@ -4203,7 +4203,7 @@ define_accounts_db_test!(
                     0
                 );
             }
-            AccountsFileProvider::HotStorage => {
+            AccountsFileProvider::HotStorage | AccountsFileProvider::ColdStorage => {
                 // For tired-storage, alive bytes are only an approximation.
                 // Therefore, it won't be zero.
                 assert!(
@@ -6002,6 +6002,7 @@ define_accounts_db_test!(test_many_unrefs, |db| {
 
 #[test_case(CreateAncientStorage::Append; "append")]
 #[test_case(CreateAncientStorage::Pack; "pack")]
+#[test_case(CreateAncientStorage::PackCold; "pack_cold")]
 fn test_get_oldest_non_ancient_slot_for_hash_calc_scan(
     create_ancient_storage: CreateAncientStorage,
 ) {
@@ -8193,3 +8194,214 @@ fn test_clean_old_storages_with_reclaims_unrooted() {
     assert_eq!(slot_list.len(), slots.len());
     assert!(slot_list.iter().map(|(slot, _)| slot).eq(slots.iter()));
 }
+
+#[test]
+fn test_owner_stats_report() {
+    let db = AccountsDb::new_single_for_tests();
+    let owner_a = Pubkey::new_unique();
+    let owner_b = Pubkey::new_unique();
+    let account_a = AccountSharedData::new(1, 10, &owner_a);
+    let account_b = AccountSharedData::new(1, 1_000, &owner_b);
+    let pubkey_a = Pubkey::new_unique();
+    db.store_for_tests(
+        0,
+        &[
+            (&pubkey_a, &account_a),
+            (&Pubkey::new_unique(), &account_a),
+            (&Pubkey::new_unique(), &account_b),
+        ],
+    );
+    db.add_root_and_flush_write_cache(0);
+
+    let report = db.owner_stats_report(None);
+    assert_eq!(report.num_storages, 1);
+    assert_eq!(report.num_storages_summarized, 1);
+    assert_eq!(report.owners.len(), 2);
+    assert_eq!(report.owners[0].owner, owner_b.to_string());
+    assert_eq!(report.owners[0].stats.num_accounts, 1);
+    assert_eq!(report.owners[0].stats.data_bytes, 1_000);
+    assert_eq!(report.owners[1].owner, owner_a.to_string());
+    assert_eq!(report.owners[1].stats.num_accounts, 2);
+    assert_eq!(report.owners[1].stats.data_bytes, 20);
+    let storage = db.storage.get_slot_storage_entry(0).unwrap();
+    // storage bytes are split between owners with rounding down
+    let storage_len = storage.accounts.len() as u64;
+    assert!(report.total.storage_bytes() <= storage_len);
+    assert!(report.total.storage_bytes() + 2 >= storage_len);
+
+    // only the new storage is read by the next report
+    db.store_for_tests(1, &[(&Pubkey::new_unique(), &account_a)]);
+    db.add_root_and_flush_write_cache(1);
+    let report = db.owner_stats_report(Some(1));
+    assert_eq!(report.num_storages, 2);
+    assert_eq!(report.num_storages_summarized, 1);
+    assert_eq!(report.owners.len(), 1);
+    assert_eq!(report.total.num_accounts, 4);
+    // every index entry is in memory, on disk, or both
+    assert!(report.total.index_entries_in_mem + report.total.index_entries_on_disk >= 4);
+
+    // only the alive version of an account is counted, and the storage holding the older
+    // version is read again once clean reclaims it
+    db.store_for_tests(2, &[(&pubkey_a, &account_a)]);
+    db.calculate_accounts_delta_hash(2);
+    db.add_root_and_flush_write_cache(2);
+    db.clean_accounts_for_tests();
+    let report = db.owner_stats_report(None);
+    assert_eq!(report.num_storages, 3);
+    assert_eq!(report.num_storages_summarized, 2);
+    assert_eq!(report.total.num_accounts, 4);
+    assert!(report.total.index_entries_in_mem + report.total.index_entries_on_disk >= 4);
+    assert_eq!(report.owners[1].stats.num_accounts, 3);
+    assert_eq!(report.owners[1].stats.data_bytes, 30);
+}
+
+#[test]
+fn test_shrink_control() {
+    let epoch_schedule = EpochSchedule::default();
+    let db = AccountsDb::new_single_for_tests();
+    let account = AccountSharedData::new(1, 100, &Pubkey::default());
+    let pubkeys: Vec<_> = (0..100).map(|_| Pubkey::new_unique()).collect();
+    for pubkey in &pubkeys {
+        db.store_for_tests(1, &[(pubkey, &account)]);
+    }
+    db.calculate_accounts_delta_hash(1);
+    db.add_root_and_flush_write_cache(1);
+    for pubkey in &pubkeys[..90] {
+        db.store_for_tests(2, &[(pubkey, &account)]);
+    }
+    db.calculate_accounts_delta_hash(2);
+    db.add_root_and_flush_write_cache(2);
+    db.clean_accounts_for_tests();
+    assert_eq!(db.all_account_count_in_accounts_file(1), 100);
+
+    // background shrink does nothing while paused, and keeps its candidates
+    db.shrink_control.pause_background_shrink();
+    db.shrink_candidate_slots.lock().unwrap().insert(1);
+    assert_eq!(db.shrink_candidate_slots(&epoch_schedule), 0);
+    assert_eq!(db.all_account_count_in_accounts_file(1), 100);
+    assert!(db.shrink_candidate_slots.lock().unwrap().contains(&1));
+
+    // requested shrinks still run
+    let request = db.shrink_control.request(ShrinkRequestKind::Shrink, 0, 1);
+    let status = db.shrink_control.status();
+    assert!(status.background_shrink_paused);
+    assert_eq!(status.pending_requests, vec![request]);
+    let capacity_before = db.storage.get_slot_storage_entry(1).unwrap().capacity();
+    db.process_shrink_requests(&epoch_schedule, false);
+    assert_eq!(db.all_account_count_in_accounts_file(1), 10);
+    let capacity_after = db.storage.get_slot_storage_entry(1).unwrap().capacity();
+
+    let status = db.shrink_control.status();
+    assert!(status.pending_requests.is_empty());
+    assert!(status.active_request.is_none());
+    assert_eq!(status.completed_requests.len(), 1);
+    let completed = status.completed_requests[0];
+    assert_eq!(completed.request, request);
+    assert_eq!(completed.slots_total, 1);
+    assert_eq!(completed.slots_processed, 1);
+    assert_eq!(completed.bytes_reclaimed, capacity_before - capacity_after);
+    assert_eq!(status.total_bytes_reclaimed, completed.bytes_reclaimed);
+
+    db.shrink_control.resume_background_shrink();
+    assert!(!db.shrink_control.status().background_shrink_paused);
+}
+
+#[test]
+fn test_shrink_control_chunks() {
+    let mut db = AccountsDb::new_single_for_tests();
+    let account = AccountSharedData::new(1, 0, &Pubkey::default());
+    let num_slots = SHRINK_REQUEST_CHUNK_SLOTS as Slot + 44;
+    for slot in 1..=num_slots {
+        db.store_for_tests(slot, &[(&Pubkey::new_unique(), &account)]);
+        db.calculate_accounts_delta_hash(slot);
+        db.add_root_and_flush_write_cache(slot);
+    }
+
+    // each call processes one chunk of the request
+    let epoch_schedule = EpochSchedule::default();
+    let request = db
+        .shrink_control
+        .request(ShrinkRequestKind::Shrink, 0, Slot::MAX);
+    db.process_shrink_requests(&epoch_schedule, false);
+    let progress = db.shrink_control.status().active_request.unwrap();
+    assert_eq!(progress.request, request);
+    assert_eq!(progress.slots_total, num_slots);
+    assert_eq!(progress.slots_processed, SHRINK_REQUEST_CHUNK_SLOTS as u64);
+    db.process_shrink_requests(&epoch_schedule, false);
+    let status = db.shrink_control.status();
+    assert!(status.active_request.is_none());
+    assert_eq!(status.completed_requests[0].slots_processed, num_slots);
+    db.process_shrink_requests(&epoch_schedule, false);
+    assert_eq!(db.shrink_control.status().completed_requests.len(), 1);
+
+    // ancient slots are left to ancient pack requests
+    db.ancient_append_vec_offset = Some(0);
+    let epoch_schedule = EpochSchedule::custom(32, 32, false);
+    db.shrink_control
+        .request(ShrinkRequestKind::Shrink, 0, Slot::MAX);
+    db.process_shrink_requests(&epoch_schedule, true);
+    let status = db.shrink_control.status();
+    assert!(status.active_request.is_none());
+    assert_eq!(status.completed_requests[1].slots_total, 32);
+    assert_eq!(status.completed_requests[1].slots_processed, 32);
+}
+
+#[test]
+fn test_load_account_at_slot() {
+    let db = AccountsDb::new_with_config(
+        Vec::new(),
+        Some(AccountsDbConfig {
+            account_history_slots: Some(2),
+            ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+        }),
+        None,
+        Arc::default(),
+    );
+    let pubkey = Pubkey::new_unique();
+    let new_pubkey = Pubkey::new_unique();
+    // slot 4 is skipped
+    for (slot, lamports) in [(1, 1), (2, 2), (3, 3), (5, 4)] {
+        let account = AccountSharedData::new(lamports, 0, &Pubkey::default());
+        db.store_cached((slot, &[(&pubkey, &account)][..]), None);
+        if slot == 5 {
+            db.store_cached((slot, &[(&new_pubkey, &account)][..]), None);
+        }
+        db.add_root(slot);
+    }
+    db.flush_accounts_cache(true, Some(5));
+    db.clean_accounts_for_tests();
+
+    let lamports_at = |pubkey, slot| {
+        db.load_account_at_slot(pubkey, slot)
+            .unwrap()
+            .map(|account| account.lamports())
+    };
+    assert_eq!(lamports_at(&pubkey, 3), Some(3));
+    assert_eq!(lamports_at(&pubkey, 5), Some(4));
+    assert_eq!(lamports_at(&new_pubkey, 3), None);
+    assert_eq!(lamports_at(&new_pubkey, 5), Some(4));
+    assert_eq!(
+        db.load_account_at_slot(&pubkey, 4),
+        Err(AccountHistoryError::SlotSkipped(4))
+    );
+    assert_eq!(
+        db.load_account_at_slot(&pubkey, 2),
+        Err(AccountHistoryError::SlotTooOld {
+            slot: 2,
+            oldest_retained_slot: 3,
+        })
+    );
+    assert_eq!(
+        db.load_account_at_slot(&pubkey, 6),
+        Err(AccountHistoryError::SlotNotRooted {
+            slot: 6,
+            max_root: 5,
+        })
+    );
+
+    let db = AccountsDb::new_single_for_tests();
+    assert_eq!(
+        db.load_account_at_slot(&pubkey, 0),
+        Err(AccountHistoryError::NotRetained)
+    );
+}
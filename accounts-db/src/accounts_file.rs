@@ -6,7 +6,7 @@ use {
         append_vec::{AppendVec, AppendVecError, IndexInfo},
         storable_accounts::StorableAccounts,
         tiered_storage::{
-            error::TieredStorageError, hot::HOT_FORMAT, index::IndexOffset, TieredStorage,
+            cold::COLD_FORMAT, error::TieredStorageError, index::IndexOffset, TieredStorage,
         },
     },
     solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
@@ -59,6 +59,10 @@ pub enum StorageAccess {
 
 pub type Result<T> = std::result::Result<T, AccountsFileError>;
 
+/// The extension of tiered storage file names, which tells them apart from
+/// append vecs without having to read the files
+pub const TIERED_STORAGE_FILE_EXTENSION: &str = "tiered";
+
 #[derive(Debug)]
 /// An enum for accessing an accounts file which can be implemented
 /// under different formats.
@@ -70,6 +74,9 @@ pub enum AccountsFile {
 impl AccountsFile {
     /// Create an AccountsFile instance from the specified path.
     ///
+    /// The format of the accounts file is chosen by its file name, see
+    /// [`AccountsFile::is_tiered_storage_path`].
+    ///
     /// The second element of the returned tuple is the number of accounts in the
     /// accounts file.
     pub fn new_from_file(
@@ -77,6 +84,20 @@ impl AccountsFile {
         current_len: usize,
         storage_access: StorageAccess,
     ) -> Result<(Self, usize)> {
+        let path = path.into();
+        if Self::is_tiered_storage_path(&path) {
+            // tiered storages are written in one go, so anything but the
+            // length they were written with means the file is truncated
+            let file_len = std::fs::metadata(&path)?.len() as usize;
+            if file_len != current_len {
+                return Err(
+                    TieredStorageError::IncorrectLength(path, file_len, current_len).into(),
+                );
+            }
+            let ts = TieredStorage::new_readonly(path)?;
+            let num_accounts = ts.reader().map_or(0, |reader| reader.num_accounts());
+            return Ok((Self::TieredStorage(ts), num_accounts));
+        }
         let (av, num_accounts) = AppendVec::new_from_file(path, current_len, storage_access)?;
         Ok((Self::AppendVec(av), num_accounts))
     }
@@ -153,6 +174,30 @@ impl AccountsFile {
         format!("{slot}.{id}")
     }
 
+    /// Returns the file name of a tiered storage with `slot` and `id`.
+    pub fn tiered_storage_file_name(slot: Slot, id: AccountsFileId) -> String {
+        format!(
+            "{}.{TIERED_STORAGE_FILE_EXTENSION}",
+            Self::file_name(slot, id)
+        )
+    }
+
+    /// Returns true if `path` names a tiered storage file rather than an append vec.
+    pub fn is_tiered_storage_path(path: impl AsRef<Path>) -> bool {
+        path.as_ref()
+            .extension()
+            .is_some_and(|extension| extension == TIERED_STORAGE_FILE_EXTENSION)
+    }
+
+    /// Returns the file name for this accounts file with `slot` and `id`,
+    /// which keeps its format recognizable from the name.
+    pub fn storage_file_name(&self, slot: Slot, id: AccountsFileId) -> String {
+        match self {
+            Self::AppendVec(_) => Self::file_name(slot, id),
+            Self::TieredStorage(_) => Self::tiered_storage_file_name(slot, id),
+        }
+    }
+
     /// calls `callback` with the account located at the specified index offset.
     pub fn get_stored_account_meta_callback<Ret>(
         &self,
@@ -283,7 +328,7 @@ impl AccountsFile {
             // assumes all offsets are multiple of 8 while TieredStorage uses
             // IndexOffset that is equivalent to AccountInfo::reduced_offset.
             Self::TieredStorage(ts) => ts
-                .write_accounts(accounts, skip, &HOT_FORMAT)
+                .write_accounts(accounts, skip, ts.format())
                 .map(|mut stored_accounts_info| {
                     stored_accounts_info.offsets.iter_mut().for_each(|offset| {
                         *offset = AccountInfo::reduced_offset_to_offset(*offset as u32);
@@ -313,6 +358,7 @@ pub enum AccountsFileProvider {
     #[default]
     AppendVec,
     HotStorage,
+    ColdStorage,
 }
 
 impl AccountsFileProvider {
@@ -322,6 +368,19 @@ impl AccountsFileProvider {
                 AccountsFile::AppendVec(AppendVec::new(path, true, file_size as usize))
             }
             Self::HotStorage => AccountsFile::TieredStorage(TieredStorage::new_writable(path)),
+            Self::ColdStorage => AccountsFile::TieredStorage(
+                TieredStorage::new_writable_with_format(path, COLD_FORMAT),
+            ),
+        }
+    }
+
+    /// Returns the name of the file to create for an accounts file with `slot` and `id`.
+    pub fn file_name(&self, slot: Slot, id: AccountsFileId) -> String {
+        match self {
+            Self::AppendVec => AccountsFile::file_name(slot, id),
+            Self::HotStorage | Self::ColdStorage => {
+                AccountsFile::tiered_storage_file_name(slot, id)
+            }
         }
     }
 }
@@ -346,7 +405,14 @@ pub struct StoredAccountsInfo {
 
 #[cfg(test)]
 pub mod tests {
-    use crate::accounts_file::AccountsFile;
+    use {
+        crate::{
+            accounts_file::{AccountsFile, AccountsFileError, AccountsFileProvider, StorageAccess},
+            tiered_storage::{error::TieredStorageError, hot::HOT_FORMAT, TieredStorage},
+        },
+        solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey},
+        std::mem::ManuallyDrop,
+    };
     impl AccountsFile {
         pub(crate) fn set_current_len_for_tests(&self, len: usize) {
             match self {
@@ -355,4 +421,51 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_storage_file_name() {
+        assert_eq!(AccountsFileProvider::AppendVec.file_name(12, 34), "12.34");
+        assert_eq!(
+            AccountsFileProvider::HotStorage.file_name(12, 34),
+            "12.34.tiered"
+        );
+        assert_eq!(
+            AccountsFileProvider::ColdStorage.file_name(12, 34),
+            "12.34.tiered"
+        );
+
+        assert!(!AccountsFile::is_tiered_storage_path("accounts/12.34"));
+        assert!(AccountsFile::is_tiered_storage_path(
+            "accounts/12.34.tiered"
+        ));
+    }
+
+    #[test]
+    fn test_new_from_file_tiered_storage_length() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir
+            .path()
+            .join(AccountsFile::tiered_storage_file_name(12, 34));
+        let len = {
+            let tiered_storage = ManuallyDrop::new(TieredStorage::new_writable(&path));
+            let account_refs = Vec::<(&Pubkey, &AccountSharedData)>::new();
+            tiered_storage
+                .write_accounts(&(Slot::MAX, account_refs.as_slice()), 0, &HOT_FORMAT)
+                .unwrap();
+            tiered_storage.len()
+        };
+
+        // a length other than the written one means the file is truncated
+        assert!(matches!(
+            AccountsFile::new_from_file(&path, len + 1, StorageAccess::File),
+            Err(AccountsFileError::TieredStorageError(
+                TieredStorageError::IncorrectLength(_, actual, expected)
+            )) if actual == len && expected == len + 1
+        ));
+
+        let (accounts_file, num_accounts) =
+            AccountsFile::new_from_file(&path, len, StorageAccess::File).unwrap();
+        assert_eq!(num_accounts, 0);
+        assert_eq!(accounts_file.len(), len);
+    }
 }
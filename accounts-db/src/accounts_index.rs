@@ -10,6 +10,7 @@ use {
         rolling_bit_field::RollingBitField,
         secondary_index::*,
     },
+    dashmap::DashMap,
     in_mem_accounts_index::{InMemAccountsIndex, InsertNewEntryResults, StartupStats},
     log::*,
     rand::{thread_rng, Rng},
@@ -21,7 +22,7 @@ use {
     solana_sdk::{
         account::ReadableAccount,
         clock::{BankId, Slot},
-        pubkey::Pubkey,
+        pubkey::{Pubkey, PUBKEY_BYTES},
     },
     std::{
         collections::{btree_map::BTreeMap, HashSet},
@@ -33,6 +34,7 @@ use {
             Range, RangeBounds,
         },
         path::PathBuf,
+        str::FromStr,
         sync::{
             atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
             Arc, Mutex, OnceLock, RwLock,
@@ -115,6 +117,17 @@ pub struct ScanConfig {
     /// true to allow return of all matching items and allow them to be unsorted.
     /// This is more efficient.
     pub collect_all_unsorted: bool,
+
+    /// When set, secondary index scans visit keys in pubkey order from this point on, fetching
+    /// them from the index in batches. Not supported by other scans.
+    pub ordered_start: Option<ScanStart>,
+}
+
+/// Where a key-ordered scan starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanStart {
+    First,
+    After(Pubkey),
 }
 
 impl Default for ScanConfig {
@@ -122,6 +135,7 @@ impl Default for ScanConfig {
         Self {
             abort: None,
             collect_all_unsorted: true,
+            ordered_start: None,
         }
     }
 }
@@ -146,6 +160,7 @@ impl ScanConfig {
         ScanConfig {
             abort: Some(self.abort.clone().unwrap_or_default()),
             collect_all_unsorted: self.collect_all_unsorted,
+            ordered_start: self.ordered_start,
         }
     }
 
@@ -190,6 +205,7 @@ pub enum IndexKey {
     ProgramId(Pubkey),
     SplTokenMint(Pubkey),
     SplTokenOwner(Pubkey),
+    Custom(CustomAccountIndex, Pubkey),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -197,6 +213,77 @@ pub enum AccountIndex {
     ProgramId,
     SplTokenMint,
     SplTokenOwner,
+    Custom(CustomAccountIndex),
+}
+
+/// A user-defined secondary index over the `len` bytes found at `offset`
+/// in the data of the accounts owned by `program_id`.
+///
+/// Index keys shorter than a pubkey are zero-padded to PUBKEY_BYTES.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomAccountIndex {
+    pub program_id: Pubkey,
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl CustomAccountIndex {
+    /// Returns None if `len` is zero or larger than PUBKEY_BYTES.
+    pub fn new(program_id: Pubkey, offset: usize, len: usize) -> Option<Self> {
+        (1..=PUBKEY_BYTES).contains(&len).then_some(Self {
+            program_id,
+            offset,
+            len,
+        })
+    }
+
+    /// Returns the index key for the indexed field value `bytes`, or None if
+    /// `bytes` is not exactly `len` bytes long.
+    pub fn index_key(&self, bytes: &[u8]) -> Option<Pubkey> {
+        if bytes.len() != self.len {
+            return None;
+        }
+        let mut key = [0u8; PUBKEY_BYTES];
+        key[..self.len].copy_from_slice(bytes);
+        Some(Pubkey::new_from_array(key))
+    }
+
+    /// Returns the index key of an account, or None if the account is not
+    /// owned by `program_id` or its data is too short.
+    fn account_index_key(&self, account_owner: &Pubkey, account_data: &[u8]) -> Option<Pubkey> {
+        if *account_owner != self.program_id {
+            return None;
+        }
+        account_data
+            .get(self.offset..self.offset.checked_add(self.len)?)
+            .and_then(|bytes| self.index_key(bytes))
+    }
+}
+
+impl FromStr for CustomAccountIndex {
+    type Err = String;
+
+    /// Parses `PROGRAM_ID:OFFSET:LENGTH`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let (Some(program_id), Some(offset), Some(len), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!(
+                "invalid custom account index '{s}', expected PROGRAM_ID:OFFSET:LENGTH"
+            ));
+        };
+        let program_id = Pubkey::from_str(program_id)
+            .map_err(|err| format!("invalid program id '{program_id}': {err}"))?;
+        let offset = offset
+            .parse::<usize>()
+            .map_err(|err| format!("invalid offset '{offset}': {err}"))?;
+        let len = len
+            .parse::<usize>()
+            .map_err(|err| format!("invalid length '{len}': {err}"))?;
+        Self::new(program_id, offset, len)
+            .ok_or_else(|| format!("invalid length {len}, must be between 1 and {PUBKEY_BYTES}"))
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -245,6 +332,13 @@ impl AccountSecondaryIndexes {
     pub fn contains(&self, index: &AccountIndex) -> bool {
         self.indexes.contains(index)
     }
+    /// Returns the user-defined secondary indexes
+    pub fn custom_indexes(&self) -> impl Iterator<Item = &CustomAccountIndex> {
+        self.indexes.iter().filter_map(|index| match index {
+            AccountIndex::Custom(custom_index) => Some(custom_index),
+            _ => None,
+        })
+    }
     pub fn include_key(&self, key: &Pubkey) -> bool {
         match &self.keys {
             Some(options) => options.exclude ^ options.keys.contains(key),
@@ -666,6 +760,8 @@ pub struct AccountsIndex<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> {
     program_id_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
     spl_token_mint_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
     spl_token_owner_index: SecondaryIndex<RwLockSecondaryIndexEntry>,
+    /// user-defined secondary indexes, created on first insert
+    custom_indexes: DashMap<CustomAccountIndex, SecondaryIndex<RwLockSecondaryIndexEntry>>,
     pub roots_tracker: RwLock<RootsTracker>,
     ongoing_scan_roots: RwLock<BTreeMap<Slot, u64>>,
     // Each scan has some latest slot `S` that is the tip of the fork the scan
@@ -725,6 +821,7 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             spl_token_owner_index: SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
                 "spl_token_owner_index_stats",
             ),
+            custom_indexes: DashMap::default(),
             roots_tracker: RwLock::<RootsTracker>::default(),
             ongoing_scan_roots: RwLock::<BTreeMap<Slot, u64>>::default(),
             removed_bank_ids: Mutex::<HashSet<BankId>>::default(),
@@ -978,6 +1075,19 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
                     config,
                 );
             }
+            ScanTypes::Indexed(IndexKey::Custom(custom_index, index_key)) => {
+                // nothing has been inserted into this index yet, so there is nothing to scan
+                if let Some(index) = self.custom_indexes.get(&custom_index) {
+                    self.do_scan_secondary_index(
+                        ancestors,
+                        func,
+                        index.value(),
+                        &index_key,
+                        Some(max_root),
+                        config,
+                    );
+                }
+            }
         }
 
         {
@@ -1099,21 +1209,54 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
     ) where
         F: FnMut(&Pubkey, (&T, Slot)),
     {
-        for pubkey in index.get(index_key) {
-            if config.is_aborted() {
-                break;
+        let Some(ordered_start) = config.ordered_start else {
+            for pubkey in index.get(index_key) {
+                if config.is_aborted() {
+                    break;
+                }
+                self.scan_secondary_index_key(ancestors, &mut func, &pubkey, max_root);
             }
-            if let Some(entry) = self.get_cloned(&pubkey) {
-                self.get_account_info_with_and_then(
-                    &entry,
-                    Some(ancestors),
-                    max_root,
-                    |(slot, account_info)| func(&pubkey, (&account_info, slot)),
-                );
+            return;
+        };
+
+        let mut start_after = match ordered_start {
+            ScanStart::First => None,
+            ScanStart::After(pubkey) => Some(pubkey),
+        };
+        loop {
+            let pubkeys = index.get_after(index_key, start_after.as_ref(), ITER_BATCH_SIZE);
+            let Some(last) = pubkeys.last().copied() else {
+                break;
             };
+            for pubkey in pubkeys {
+                if config.is_aborted() {
+                    return;
+                }
+                self.scan_secondary_index_key(ancestors, &mut func, &pubkey, max_root);
+            }
+            start_after = Some(last);
         }
     }
 
+    fn scan_secondary_index_key<F>(
+        &self,
+        ancestors: &Ancestors,
+        func: &mut F,
+        pubkey: &Pubkey,
+        max_root: Option<Slot>,
+    ) where
+        F: FnMut(&Pubkey, (&T, Slot)),
+    {
+        if let Some(entry) = self.get_cloned(pubkey) {
+            self.get_account_info_with_and_then(
+                &entry,
+                Some(ancestors),
+                max_root,
+                |(slot, account_info)| func(pubkey, (&account_info, slot)),
+            );
+        };
+    }
+
     /// Gets the index's entry for `pubkey` and applies `callback` to it
     ///
     /// If `callback`'s boolean return value is true, add this entry to the in-mem cache.
@@ -1125,6 +1268,17 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         self.get_bin(pubkey).get_internal_inner(pubkey, callback)
     }
 
+    /// Gets the index's entry for `pubkey` without adding it to the in-mem cache, and calls
+    /// `callback` with its slot list, whether the entry is held in memory, and whether it is in
+    /// the disk index
+    pub(crate) fn get_without_caching_and_then<R>(
+        &self,
+        pubkey: &Pubkey,
+        callback: impl FnOnce(Option<&[(Slot, T)]>, bool, bool) -> R,
+    ) -> R {
+        self.get_bin(pubkey).get_without_caching(pubkey, callback)
+    }
+
     /// Gets the index's entry for `pubkey`, with `ancestors` and `max_root`,
     /// and applies `callback` to it
     pub(crate) fn get_with_and_then<R>(
@@ -1578,6 +1732,29 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         }
     }
 
+    /// Returns the number of entries held in memory and the number of entries in the disk index.
+    /// An entry that is being flushed or was read back from disk is counted by both.
+    pub fn in_mem_and_disk_entry_counts(&self) -> (usize, usize) {
+        let in_mem = self
+            .storage
+            .storage
+            .stats
+            .count_in_mem
+            .load(Ordering::Relaxed);
+        let on_disk = self
+            .storage
+            .storage
+            .disk
+            .as_ref()
+            .map(|disk| {
+                (0..disk.num_buckets())
+                    .map(|ix| disk.get_bucket_from_index(ix).bucket_len() as usize)
+                    .sum()
+            })
+            .unwrap_or_default();
+        (in_mem, on_disk)
+    }
+
     pub fn get_index_key_size(&self, index: &AccountIndex, index_key: &Pubkey) -> Option<usize> {
         match index {
             AccountIndex::ProgramId => self.program_id_index.index.get(index_key).map(|x| x.len()),
@@ -1591,6 +1768,10 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
                 .index
                 .get(index_key)
                 .map(|x| x.len()),
+            AccountIndex::Custom(custom_index) => self
+                .custom_indexes
+                .get(custom_index)
+                .and_then(|index| index.index.get(index_key).map(|x| x.len())),
         }
     }
 
@@ -1608,6 +1789,12 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             info!("secondary index: {:?}", AccountIndex::SplTokenOwner);
             self.spl_token_owner_index.log_contents();
         }
+        for entry in self.custom_indexes.iter() {
+            if !entry.value().index.is_empty() {
+                info!("secondary index: {:?}", AccountIndex::Custom(*entry.key()));
+                entry.value().log_contents();
+            }
+        }
     }
 
     pub(crate) fn update_secondary_indexes(
@@ -1656,6 +1843,24 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
             account_data,
             account_indexes,
         );
+
+        // The include/exclude keys only apply to pubkey-valued indexes, so
+        // they are not consulted for user-defined indexes.
+        for custom_index in account_indexes.custom_indexes() {
+            if let Some(index_key) = custom_index.account_index_key(account_owner, account_data) {
+                let index = self.custom_indexes.get(custom_index).unwrap_or_else(|| {
+                    self.custom_indexes
+                        .entry(*custom_index)
+                        .or_insert_with(|| {
+                            SecondaryIndex::<RwLockSecondaryIndexEntry>::new(
+                                "custom_secondary_index_stats",
+                            )
+                        })
+                        .downgrade()
+                });
+                index.insert(&index_key, pubkey);
+            }
+        }
     }
 
     pub(crate) fn get_bin(&self, pubkey: &Pubkey) -> AccountMaps<T, U> {
@@ -1896,6 +2101,12 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> AccountsIndex<T, U> {
         if account_indexes.contains(&AccountIndex::SplTokenMint) {
             self.spl_token_mint_index.remove_by_inner_key(inner_key);
         }
+
+        for custom_index in account_indexes.custom_indexes() {
+            if let Some(index) = self.custom_indexes.get(custom_index) {
+                index.remove_by_inner_key(inner_key);
+            }
+        }
     }
 
     fn purge_older_root_entries(
@@ -3693,6 +3904,83 @@ pub mod tests {
         assert!(secondary_index.reverse_index.is_empty());
     }
 
+    #[test]
+    fn test_custom_account_index_from_str() {
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            CustomAccountIndex::from_str(&format!("{program_id}:8:32")),
+            Ok(CustomAccountIndex {
+                program_id,
+                offset: 8,
+                len: 32,
+            })
+        );
+        assert!(CustomAccountIndex::from_str(&format!("{program_id}:8:0")).is_err());
+        assert!(CustomAccountIndex::from_str(&format!("{program_id}:8:33")).is_err());
+        assert!(CustomAccountIndex::from_str(&format!("{program_id}:8")).is_err());
+        assert!(CustomAccountIndex::from_str(&format!("{program_id}:8:4:4")).is_err());
+        assert!(CustomAccountIndex::from_str("not-a-pubkey:8:4").is_err());
+    }
+
+    #[test]
+    fn test_custom_secondary_index() {
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex::new(program_id, 8, 4).unwrap();
+        let secondary_indexes = AccountSecondaryIndexes {
+            keys: None,
+            indexes: HashSet::from([AccountIndex::Custom(custom_index)]),
+        };
+        let index = AccountsIndex::<bool, bool>::default_for_tests();
+        let account_key = Pubkey::new_unique();
+        let mut account_data = vec![0u8; 16];
+        account_data[8..12].copy_from_slice(&[1, 2, 3, 4]);
+        let index_key = custom_index.index_key(&[1, 2, 3, 4]).unwrap();
+
+        // Wrong program id
+        index.update_secondary_indexes(
+            &account_key,
+            &AccountSharedData::create(0, account_data.clone(), Pubkey::default(), false, 0),
+            &secondary_indexes,
+        );
+        // Account data too short
+        index.update_secondary_indexes(
+            &account_key,
+            &AccountSharedData::create(0, account_data[..11].to_vec(), program_id, false, 0),
+            &secondary_indexes,
+        );
+        assert!(index.custom_indexes.get(&custom_index).is_none());
+
+        // Just right. Inserting the same index multiple times should be ok
+        for _ in 0..2 {
+            index.update_secondary_indexes(
+                &account_key,
+                &AccountSharedData::create(0, account_data.clone(), program_id, false, 0),
+                &secondary_indexes,
+            );
+            check_secondary_index_mapping_correct(
+                index.custom_indexes.get(&custom_index).unwrap().value(),
+                &[index_key],
+                &account_key,
+            );
+        }
+        assert_eq!(
+            index.get_index_key_size(&AccountIndex::Custom(custom_index), &index_key),
+            Some(1)
+        );
+
+        // a different custom index over the same account is not populated
+        let other_index = CustomAccountIndex::new(program_id, 0, 4).unwrap();
+        assert_eq!(
+            index.get_index_key_size(&AccountIndex::Custom(other_index), &index_key),
+            None
+        );
+
+        index.purge_secondary_indexes_by_inner_key(&account_key, &secondary_indexes);
+        let secondary_index = index.custom_indexes.get(&custom_index).unwrap();
+        assert!(secondary_index.index.is_empty());
+        assert!(secondary_index.reverse_index.is_empty());
+    }
+
     #[test]
     fn test_spl_token_mint_secondary_index() {
         let (key_start, key_end, secondary_indexes) = create_spl_token_mint_secondary_index_state();
@@ -4246,4 +4534,32 @@ pub mod tests {
         let config = config.recreate_with_abort();
         assert!(config.is_aborted());
     }
+
+    #[test]
+    fn test_secondary_index_get_after() {
+        let rwlock_index = SecondaryIndex::<RwLockSecondaryIndexEntry>::new("test");
+        let dashmap_index = SecondaryIndex::<DashMapSecondaryIndexEntry>::new("test");
+        let key = Pubkey::new_unique();
+        let mut inner_keys = (0..10)
+            .map(|_| solana_pubkey::new_rand())
+            .collect::<Vec<_>>();
+        for inner_key in &inner_keys {
+            rwlock_index.insert(&key, inner_key);
+            dashmap_index.insert(&key, inner_key);
+        }
+        inner_keys.sort_unstable();
+
+        for (start_after, expected) in [
+            (None, &inner_keys[..3]),
+            (Some(&inner_keys[4]), &inner_keys[5..8]),
+            (Some(&inner_keys[8]), &inner_keys[9..]),
+            (Some(&inner_keys[9]), &[][..]),
+        ] {
+            assert_eq!(rwlock_index.get_after(&key, start_after, 3), expected);
+            assert_eq!(dashmap_index.get_after(&key, start_after, 3), expected);
+        }
+        assert!(rwlock_index
+            .get_after(&Pubkey::new_unique(), None, 3)
+            .is_empty());
+    }
 }
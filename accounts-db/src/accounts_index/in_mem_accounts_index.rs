@@ -323,6 +323,36 @@ impl<T: IndexValue, U: DiskIndexValue + From<T> + Into<T>> InMemAccountsIndex<T,
         result
     }
 
+    /// lookup 'pubkey' in memory and on disk without adding it to the in_mem cache.
+    /// call 'callback' with its slot list, whether it is held in memory, and whether it is on disk
+    pub(super) fn get_without_caching<RT>(
+        &self,
+        pubkey: &K,
+        callback: impl FnOnce(Option<&[(Slot, T)]>, bool, bool) -> RT,
+    ) -> RT {
+        let in_mem_slot_list = self
+            .map_internal
+            .read()
+            .unwrap()
+            .get(pubkey)
+            .map(|entry| entry.slot_list.read().unwrap().clone());
+        let disk_entry = self.load_from_disk(pubkey);
+        let on_disk = disk_entry.is_some();
+        match in_mem_slot_list {
+            // the in_mem entry is the most recent one when an entry is in both places
+            Some(slot_list) => callback(Some(&slot_list), true, on_disk),
+            None => {
+                let slot_list: Option<SlotList<T>> = disk_entry.map(|(slot_list, _ref_count)| {
+                    slot_list
+                        .into_iter()
+                        .map(|(slot, info)| (slot, info.into()))
+                        .collect()
+                });
+                callback(slot_list.as_deref(), false, on_disk)
+            }
+        }
+    }
+
     /// set age of 'entry' to the future
     /// if 'is_cached', age will be set farther
     fn set_age_to_future(&self, entry: &AccountMapEntry<T>, is_cached: bool) {
@@ -544,7 +544,7 @@ impl AccountsDb {
     ) {
         let target_slot = accounts_to_write.target_slot();
         let (shrink_in_progress, create_and_insert_store_elapsed_us) =
-            measure_us!(self.get_store_for_shrink(target_slot, bytes));
+            measure_us!(self.get_ancient_store_for_shrink(target_slot, bytes));
         let (store_accounts_timing, rewrite_elapsed_us) = measure_us!(
             self.store_accounts_frozen(accounts_to_write, shrink_in_progress.new_storage(),)
         );
@@ -1225,7 +1225,7 @@ pub mod tests {
                     get_account_from_account_from_storage, get_all_accounts,
                     remove_account_for_tests, CAN_RANDOMLY_SHRINK_FALSE,
                 },
-                ShrinkCollectRefs,
+                CreateAncientStorage, ShrinkCollectRefs,
             },
             accounts_file::StorageAccess,
             accounts_hash::AccountHash,
@@ -1235,7 +1235,9 @@ pub mod tests {
                 MAXIMUM_APPEND_VEC_FILE_SIZE,
             },
             storable_accounts::{tests::build_accounts_from_storage, StorableAccountsBySlot},
+            tiered_storage::cold::COLD_FORMAT,
         },
+        assert_matches::assert_matches,
         rand::seq::SliceRandom as _,
         solana_sdk::{
             account::{AccountSharedData, ReadableAccount, WritableAccount},
@@ -3306,6 +3308,51 @@ pub mod tests {
             .collect::<Vec<_>>()
     }
 
+    #[test]
+    fn test_write_ancient_accounts_pack_cold() {
+        let num_slots = 3;
+        let (mut db, storages, slots, _infos) = get_sample_storages(num_slots, None);
+        db.create_ancient_storage = CreateAncientStorage::PackCold;
+        let initial_accounts = get_all_accounts(&db, slots.clone());
+
+        let accounts_byval = storages
+            .iter()
+            .map(|storage| {
+                let mut accounts = Vec::default();
+                storage.accounts.scan_accounts(|account| {
+                    accounts.push(AccountFromStorage::new(&account));
+                });
+                (storage.slot(), accounts)
+            })
+            .collect::<Vec<_>>();
+        let accounts_byval2 = build_refs_accounts_from_storage_with_slot(&accounts_byval);
+        let accounts = build_refs_accounts_from_storage_with_slot2(&accounts_byval2);
+        let target_slot = slots.start;
+        let accounts_to_write = StorableAccountsBySlot::new(target_slot, &accounts, &db);
+        let bytes = storages
+            .iter()
+            .map(|storage| storage.written_bytes())
+            .sum::<u64>();
+
+        let mut write_ancient_accounts = WriteAncientAccounts::default();
+        db.write_ancient_accounts(bytes, accounts_to_write, &mut write_ancient_accounts);
+        let mut result = write_ancient_accounts.shrinks_in_progress;
+        let one = result.drain().collect::<Vec<_>>();
+        assert_eq!(1, one.len());
+        let new_storage = one.first().unwrap().1.new_storage();
+
+        // the packed storage is a cold tiered storage
+        assert_matches!(
+            &new_storage.accounts,
+            AccountsFile::TieredStorage(ts) if ts.format() == &COLD_FORMAT
+        );
+        let mut two = Vec::default();
+        new_storage.accounts.scan_accounts(|meta| {
+            two.push((*meta.pubkey(), meta.to_account_shared_data()));
+        });
+        compare_all_accounts(&initial_accounts, &two[..]);
+    }
+
     #[test]
     fn test_write_ancient_accounts() {
         for data_size in [None, Some(10_000_000)] {
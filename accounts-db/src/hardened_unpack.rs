@@ -1,4 +1,5 @@
 use {
+    crate::accounts_file::TIERED_STORAGE_FILE_EXTENSION,
     bzip2::bufread::BzDecoder,
     log::*,
     rand::{thread_rng, Rng},
@@ -11,6 +12,7 @@ use {
             Component::{self, CurDir, Normal},
             Path, PathBuf,
         },
+        sync::atomic::{AtomicU64, Ordering},
         time::Instant,
     },
     tar::{
@@ -71,6 +73,48 @@ fn checked_total_count_increment(total_count: u64, limit_count: u64) -> Result<u
     Ok(total_count)
 }
 
+/// Running totals of the entries unpacked so far, checked against the unpack limits
+///
+/// Sharing one instance across several archives applies the limits to all of them together,
+/// e.g. to every frame of a seekable snapshot archive.
+#[derive(Debug, Default)]
+pub struct UnpackTotals {
+    apparent_size: AtomicU64,
+    actual_size: AtomicU64,
+    count: AtomicU64,
+}
+
+impl UnpackTotals {
+    fn add_entry(
+        &self,
+        apparent_size: u64,
+        actual_size: u64,
+        apparent_limit_size: u64,
+        actual_limit_size: u64,
+        limit_count: u64,
+    ) -> Result<()> {
+        let fetch_saturating_add = |total: &AtomicU64, size: u64| {
+            total
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+                    Some(total.saturating_add(size))
+                })
+                .unwrap()
+        };
+        checked_total_size_sum(
+            fetch_saturating_add(&self.apparent_size, apparent_size),
+            apparent_size,
+            apparent_limit_size,
+        )?;
+        checked_total_size_sum(
+            fetch_saturating_add(&self.actual_size, actual_size),
+            actual_size,
+            actual_limit_size,
+        )?;
+        checked_total_count_increment(fetch_saturating_add(&self.count, 1), limit_count)?;
+        Ok(())
+    }
+}
+
 fn check_unpack_result(unpack_result: bool, path: String) -> Result<()> {
     if !unpack_result {
         return Err(UnpackError::Archive(format!("failed to unpack: {path:?}")));
@@ -90,6 +134,7 @@ fn unpack_archive<'a, A, C, D>(
     apparent_limit_size: u64,
     actual_limit_size: u64,
     limit_count: u64,
+    totals: &UnpackTotals,
     mut entry_checker: C, // checks if entry is valid
     entry_processor: D,   // processes entry after setting permissions
 ) -> Result<()>
@@ -98,10 +143,6 @@ where
     C: FnMut(&[&str], tar::EntryType) -> UnpackPath<'a>,
     D: Fn(PathBuf),
 {
-    let mut apparent_total_size: u64 = 0;
-    let mut actual_total_size: u64 = 0;
-    let mut total_count: u64 = 0;
-
     let mut total_entries = 0;
     for entry in archive.entries()? {
         let mut entry = entry?;
@@ -146,17 +187,13 @@ where
             UnpackPath::Valid(unpack_dir) => unpack_dir,
         };
 
-        apparent_total_size = checked_total_size_sum(
-            apparent_total_size,
+        totals.add_entry(
             entry.header().size()?,
-            apparent_limit_size,
-        )?;
-        actual_total_size = checked_total_size_sum(
-            actual_total_size,
             entry.header().entry_size()?,
+            apparent_limit_size,
             actual_limit_size,
+            limit_count,
         )?;
-        total_count = checked_total_count_increment(total_count, limit_count)?;
 
         let account_filename = match parts.as_slice() {
             ["accounts", account_filename] => Some(PathBuf::from(account_filename)),
@@ -315,6 +352,7 @@ pub fn unpack_snapshot<A: Read>(
         ledger_dir,
         account_paths,
         parallel_selector,
+        &UnpackTotals::default(),
         |file, path| {
             unpacked_append_vec_map.insert(file.to_string(), path.join("accounts").join(file));
         },
@@ -330,12 +368,35 @@ pub fn streaming_unpack_snapshot<A: Read>(
     account_paths: &[PathBuf],
     parallel_selector: Option<ParallelSelector>,
     sender: &crossbeam_channel::Sender<PathBuf>,
+) -> Result<()> {
+    streaming_unpack_snapshot_with_totals(
+        archive,
+        ledger_dir,
+        account_paths,
+        parallel_selector,
+        sender,
+        &UnpackTotals::default(),
+    )
+}
+
+/// Like `streaming_unpack_snapshot()`, but counts the unpacked entries towards `totals`
+///
+/// Use this to unpack a snapshot split across several archives, so the snapshot unpack limits
+/// apply to the whole snapshot rather than to each archive on its own.
+pub fn streaming_unpack_snapshot_with_totals<A: Read>(
+    archive: &mut Archive<A>,
+    ledger_dir: &Path,
+    account_paths: &[PathBuf],
+    parallel_selector: Option<ParallelSelector>,
+    sender: &crossbeam_channel::Sender<PathBuf>,
+    totals: &UnpackTotals,
 ) -> Result<()> {
     unpack_snapshot_with_processors(
         archive,
         ledger_dir,
         account_paths,
         parallel_selector,
+        totals,
         |_, _| {},
         |entry_path_buf| {
             if entry_path_buf.is_file() {
@@ -356,6 +417,7 @@ fn unpack_snapshot_with_processors<A, F, G>(
     ledger_dir: &Path,
     account_paths: &[PathBuf],
     parallel_selector: Option<ParallelSelector>,
+    totals: &UnpackTotals,
     mut accounts_path_processor: F,
     entry_processor: G,
 ) -> Result<()>
@@ -372,6 +434,7 @@ where
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_APPARENT_SIZE,
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_ACTUAL_SIZE,
         MAX_SNAPSHOT_ARCHIVE_UNPACKED_COUNT,
+        totals,
         |parts, kind| {
             if is_valid_snapshot_archive_entry(parts, kind) {
                 i += 1;
@@ -417,6 +480,11 @@ fn all_digits(v: &str) -> bool {
 }
 
 fn like_storage(v: &str) -> bool {
+    // tiered storages are named like append vecs plus an extension
+    let v = v
+        .strip_suffix(TIERED_STORAGE_FILE_EXTENSION)
+        .and_then(|v| v.strip_suffix('.'))
+        .unwrap_or(v);
     let mut periods = 0;
     let mut saw_numbers = false;
     for x in v.chars() {
@@ -519,6 +587,7 @@ fn unpack_genesis<A: Read>(
         max_genesis_archive_unpacked_size,
         max_genesis_archive_unpacked_size,
         MAX_GENESIS_ARCHIVE_UNPACKED_COUNT,
+        &UnpackTotals::default(),
         |p, k| is_valid_genesis_archive_entry(unpack_dir, p, k),
         |_| {},
     )
@@ -620,6 +689,10 @@ mod tests {
             &["accounts", "01829.077"],
             tar::EntryType::Regular
         ));
+        assert!(is_valid_snapshot_archive_entry(
+            &["accounts", "01829.077.tiered"],
+            tar::EntryType::Regular
+        ));
 
         assert!(!is_valid_snapshot_archive_entry(
             &["accounts", "1.2.34"],
@@ -629,6 +702,14 @@ mod tests {
             &["accounts", "12."],
             tar::EntryType::Regular
         ));
+        assert!(!is_valid_snapshot_archive_entry(
+            &["accounts", "12.tiered"],
+            tar::EntryType::Regular
+        ));
+        assert!(!is_valid_snapshot_archive_entry(
+            &["accounts", "1.2.hot"],
+            tar::EntryType::Regular
+        ));
         assert!(!is_valid_snapshot_archive_entry(
             &["accounts", ".12"],
             tar::EntryType::Regular
@@ -794,7 +875,15 @@ mod tests {
 
     fn finalize_and_unpack_snapshot(archive: tar::Builder<Vec<u8>>) -> Result<()> {
         with_finalize_and_unpack(archive, |a, b| {
-            unpack_snapshot_with_processors(a, b, &[PathBuf::new()], None, |_, _| {}, |_| {})
+            unpack_snapshot_with_processors(
+                a,
+                b,
+                &[PathBuf::new()],
+                None,
+                &UnpackTotals::default(),
+                |_, _| {},
+                |_| {},
+            )
         })
     }
 
@@ -1034,6 +1123,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_archive_unpack_totals() {
+        // the limits apply to everything counted towards the same totals
+        let totals = UnpackTotals::default();
+        assert_matches!(totals.add_entry(400, 40, 1000, 100, 3), Ok(()));
+        assert_matches!(totals.add_entry(400, 40, 1000, 100, 3), Ok(()));
+        assert_matches!(
+            totals.add_entry(400, 10, 1000, 100, 3),
+            Err(UnpackError::Archive(ref message))
+                if message == "too large archive: 1200 than limit: 1000"
+        );
+
+        let totals = UnpackTotals::default();
+        assert_matches!(totals.add_entry(0, 0, 1000, 100, 2), Ok(()));
+        assert_matches!(totals.add_entry(0, 0, 1000, 100, 2), Ok(()));
+        assert_matches!(
+            totals.add_entry(0, 0, 1000, 100, 2),
+            Err(UnpackError::Archive(ref message)) if message == "too many files in snapshot: 3"
+        );
+    }
+
     #[test]
     fn test_archive_unpack_account_path() {
         let mut header = Header::new_gnu();
@@ -1050,6 +1160,7 @@ mod tests {
                 tmp,
                 &[tmp.join("accounts_dest")],
                 None,
+                &UnpackTotals::default(),
                 |_, _| {},
                 |path| assert_eq!(path, tmp.join("accounts_dest/123.456")),
             )
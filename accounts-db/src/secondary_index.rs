@@ -3,8 +3,9 @@ use {
     log::*,
     solana_sdk::{pubkey::Pubkey, timing::AtomicInterval},
     std::{
-        collections::HashSet,
+        collections::{BTreeSet, BinaryHeap, HashSet},
         fmt::Debug,
+        ops::Bound::{Excluded, Unbounded},
         sync::{
             atomic::{AtomicU64, Ordering},
             RwLock,
@@ -24,6 +25,8 @@ pub trait SecondaryIndexEntry: Debug {
     fn remove_inner_key(&self, key: &Pubkey) -> bool;
     fn is_empty(&self) -> bool;
     fn keys(&self) -> Vec<Pubkey>;
+    /// Returns, in order, at most `limit` keys greater than `start_after`
+    fn keys_after(&self, start_after: Option<&Pubkey>, limit: usize) -> Vec<Pubkey>;
     fn len(&self) -> usize;
 }
 
@@ -62,6 +65,21 @@ impl SecondaryIndexEntry for DashMapSecondaryIndexEntry {
             .collect()
     }
 
+    fn keys_after(&self, start_after: Option<&Pubkey>, limit: usize) -> Vec<Pubkey> {
+        // The keys are unordered, so keep the smallest `limit` of them
+        let mut keys = BinaryHeap::with_capacity(limit.saturating_add(1));
+        for entry_ref in self.account_keys.iter() {
+            let key = entry_ref.key();
+            if start_after.map_or(true, |start_after| key > start_after) {
+                keys.push(*key);
+                if keys.len() > limit {
+                    keys.pop();
+                }
+            }
+        }
+        keys.into_sorted_vec()
+    }
+
     fn len(&self) -> usize {
         self.account_keys.len()
     }
@@ -69,7 +87,7 @@ impl SecondaryIndexEntry for DashMapSecondaryIndexEntry {
 
 #[derive(Debug, Default)]
 pub struct RwLockSecondaryIndexEntry {
-    account_keys: RwLock<HashSet<Pubkey>>,
+    account_keys: RwLock<BTreeSet<Pubkey>>,
 }
 
 impl SecondaryIndexEntry for RwLockSecondaryIndexEntry {
@@ -97,6 +115,17 @@ impl SecondaryIndexEntry for RwLockSecondaryIndexEntry {
         self.account_keys.read().unwrap().iter().cloned().collect()
     }
 
+    fn keys_after(&self, start_after: Option<&Pubkey>, limit: usize) -> Vec<Pubkey> {
+        let start = start_after.map_or(Unbounded, Excluded);
+        self.account_keys
+            .read()
+            .unwrap()
+            .range((start, Unbounded))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
     fn len(&self) -> usize {
         self.account_keys.read().unwrap().len()
     }
@@ -226,6 +255,21 @@ impl<SecondaryIndexEntryType: SecondaryIndexEntry + Default + Sync + Send>
         }
     }
 
+    /// Returns, in order, at most `limit` of the keys indexed under `key` that are greater than
+    /// `start_after`
+    pub fn get_after(
+        &self,
+        key: &Pubkey,
+        start_after: Option<&Pubkey>,
+        limit: usize,
+    ) -> Vec<Pubkey> {
+        if let Some(inner_keys_map) = self.index.get(key) {
+            inner_keys_map.keys_after(start_after, limit)
+        } else {
+            vec![]
+        }
+    }
+
     /// log top 20 (owner, # accounts) in descending order of # accounts
     pub fn log_contents(&self) {
         let mut entries = self
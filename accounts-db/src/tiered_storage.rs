@@ -1,6 +1,7 @@
 #![allow(dead_code)]
 
 pub mod byte_block;
+pub mod cold;
 pub mod error;
 pub mod file;
 pub mod footer;
@@ -14,6 +15,7 @@ mod test_utils;
 
 use {
     crate::{accounts_file::StoredAccountsInfo, storable_accounts::StorableAccounts},
+    cold::{ColdStorageWriter, COLD_FORMAT},
     error::TieredStorageError,
     footer::{AccountBlockFormat, AccountMetaFormat},
     hot::{HotStorageWriter, HOT_FORMAT},
@@ -54,6 +56,8 @@ pub struct TieredStorage {
     already_written: AtomicBool,
     /// The path to the file that stores accounts.
     path: PathBuf,
+    /// The format of the accounts file.
+    format: TieredStorageFormat,
 }
 
 impl Drop for TieredStorage {
@@ -78,10 +82,20 @@ impl TieredStorage {
     /// Note that the actual file will not be created until write_accounts
     /// is called.
     pub fn new_writable(path: impl Into<PathBuf>) -> Self {
+        Self::new_writable_with_format(path, HOT_FORMAT)
+    }
+
+    /// Creates a new writable instance of TieredStorage that will write its
+    /// accounts using the specified TieredStorageFormat.
+    ///
+    /// Note that the actual file will not be created until write_accounts
+    /// is called.
+    pub fn new_writable_with_format(path: impl Into<PathBuf>, format: TieredStorageFormat) -> Self {
         Self {
             reader: OnceLock::<TieredStorageReader>::new(),
             already_written: false.into(),
             path: path.into(),
+            format,
         }
     }
 
@@ -89,10 +103,16 @@ impl TieredStorage {
     /// specified path.
     pub fn new_readonly(path: impl Into<PathBuf>) -> TieredStorageResult<Self> {
         let path = path.into();
+        let reader = TieredStorageReader::new_from_path(&path)?;
+        let format = match reader.footer().account_meta_format {
+            AccountMetaFormat::Hot => HOT_FORMAT,
+            AccountMetaFormat::Cold => COLD_FORMAT,
+        };
         Ok(Self {
-            reader: TieredStorageReader::new_from_path(&path).map(OnceLock::from)?,
+            reader: OnceLock::from(reader),
             already_written: true.into(),
             path,
+            format,
         })
     }
 
@@ -101,6 +121,11 @@ impl TieredStorage {
         self.path.as_path()
     }
 
+    /// Returns the format of this TieredStorage.
+    pub fn format(&self) -> &TieredStorageFormat {
+        &self.format
+    }
+
     /// Writes the specified accounts into this TieredStorage.
     ///
     /// Note that this function can only be called once per a TieredStorage
@@ -117,12 +142,17 @@ impl TieredStorage {
             panic!("cannot write same tiered storage file more than once");
         }
 
-        if format == &HOT_FORMAT {
-            let stored_accounts_info = {
+        if format == &HOT_FORMAT || format == &COLD_FORMAT {
+            let stored_accounts_info = if format == &HOT_FORMAT {
                 let mut writer = HotStorageWriter::new(&self.path)?;
                 let stored_accounts_info = writer.write_accounts(accounts, skip)?;
                 writer.flush()?;
                 stored_accounts_info
+            } else {
+                let mut writer = ColdStorageWriter::new(&self.path)?;
+                let stored_accounts_info = writer.write_accounts(accounts, skip)?;
+                writer.flush()?;
+                stored_accounts_info
             };
 
             // panic here if self.reader.get() is not None as self.reader can only be
@@ -439,4 +469,47 @@ mod tests {
             HOT_FORMAT.clone(),
         );
     }
+
+    #[test]
+    fn test_write_accounts_cold_mixed_size() {
+        do_test_write_accounts(
+            "test_write_accounts_cold_mixed_size",
+            &[
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 1000, 2000, 3000, 4000, 9, 8, 7, 6, 5, 4, 3, 2, 1,
+                100_000, 0, 1,
+            ],
+            COLD_FORMAT.clone(),
+        );
+    }
+
+    #[test]
+    fn test_write_accounts_cold_one_max_len() {
+        do_test_write_accounts(
+            "test_write_accounts_cold_one_max_len",
+            &[MAX_PERMITTED_DATA_LENGTH],
+            COLD_FORMAT.clone(),
+        );
+    }
+
+    #[test]
+    fn test_new_readonly_keeps_format() {
+        let temp_dir = tempdir().unwrap();
+        let tiered_storage_path = temp_dir.path().join("test_new_readonly_keeps_format");
+        let (stored_meta, account) = create_test_account(10);
+        let account_refs = [(&stored_meta.pubkey, &account)];
+        let storable_accounts = (Slot::MAX, &account_refs[..]);
+        {
+            let tiered_storage = ManuallyDrop::new(TieredStorage::new_writable_with_format(
+                &tiered_storage_path,
+                COLD_FORMAT,
+            ));
+            tiered_storage
+                .write_accounts(&storable_accounts, 0, tiered_storage.format())
+                .unwrap();
+        }
+
+        let tiered_storage_readonly = TieredStorage::new_readonly(&tiered_storage_path).unwrap();
+        assert_eq!(tiered_storage_readonly.format(), &COLD_FORMAT);
+        assert_eq!(tiered_storage_readonly.reader().unwrap().num_accounts(), 1);
+    }
 }
@@ -0,0 +1,949 @@
+//! The cold tier of the accounts tiered storage.
+//!
+//! A cold accounts file stores its account entries using the same entry
+//! layout as the hot tier (HotAccountMeta + data + padding + optional
+//! fields), but groups consecutive entries into account blocks that are
+//! individually compressed.  Accounts are decompressed on demand, one
+//! account block at a time, when they are read, and the last few account
+//! blocks used by point reads are cached.
+//!
+//! A cold accounts file consists of the following blocks:
+//!
+//! * compressed account blocks
+//! * 0-7 bytes padding
+//! * index block (addresses then ColdAccountOffsets)
+//! * owners block
+//! * account block table (one ColdBlockEntry per account block)
+//! * footer
+
+use {
+    crate::{
+        account_info::AccountInfo,
+        account_storage::meta::StoredAccountMeta,
+        accounts_file::{MatchAccountOwnerError, StoredAccountsInfo},
+        append_vec::{IndexInfo, IndexInfoInner},
+        tiered_storage::{
+            byte_block::{self, ByteBlockReader, ByteBlockWriter},
+            file::{TieredReadableFile, TieredWritableFile},
+            footer::{AccountBlockFormat, AccountMetaFormat, TieredStorageFooter, FOOTER_SIZE},
+            hot::{
+                padding_bytes, stored_size, HotAccount, HotAccountMeta, HOT_BLOCK_ALIGNMENT,
+                PADDING_BUFFER,
+            },
+            index::{AccountIndexWriterEntry, AccountOffset, IndexBlockFormat, IndexOffset},
+            meta::{
+                AccountAddressRange, AccountMetaFlags, AccountMetaOptionalFields, TieredAccountMeta,
+            },
+            mmap_utils::{get_pod, get_slice},
+            owners::{OwnerOffset, OwnersBlockFormat, OwnersTable},
+            StorableAccounts, TieredStorageError, TieredStorageFormat, TieredStorageResult,
+        },
+    },
+    bytemuck_derive::{Pod, Zeroable},
+    memmap2::{Mmap, MmapOptions},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        pubkey::Pubkey,
+        rent_collector::RENT_EXEMPT_RENT_EPOCH,
+        stake_history::Epoch,
+    },
+    std::{
+        collections::VecDeque,
+        io::Write,
+        path::Path,
+        sync::{Arc, Mutex},
+    },
+};
+
+pub const COLD_FORMAT: TieredStorageFormat = TieredStorageFormat {
+    meta_entry_size: std::mem::size_of::<HotAccountMeta>(),
+    account_meta_format: AccountMetaFormat::Cold,
+    owners_block_format: OwnersBlockFormat::AddressesOnly,
+    index_block_format: IndexBlockFormat::AddressesThenOffsets,
+    account_block_format: AccountBlockFormat::Lz4,
+};
+
+/// The targeted size of an account block before compression.
+///
+/// Account entries are appended to the current account block until it
+/// reaches this size.  An account entry that is larger than this size is
+/// stored in its own account block.
+pub const COLD_ACCOUNT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// The number of decompressed account blocks each cold storage reader keeps
+/// around for point reads, so that repeated loads of accounts stored close
+/// to each other do not decompress the same block again.
+const COLD_BLOCK_CACHE_CAPACITY: usize = 4;
+
+/// A helper function that creates a new default footer for cold
+/// accounts storage.
+fn new_cold_footer() -> TieredStorageFooter {
+    TieredStorageFooter {
+        account_meta_format: COLD_FORMAT.account_meta_format,
+        account_meta_entry_size: COLD_FORMAT.meta_entry_size as u32,
+        account_block_format: COLD_FORMAT.account_block_format,
+        account_block_size: COLD_ACCOUNT_BLOCK_SIZE as u64,
+        index_block_format: COLD_FORMAT.index_block_format,
+        owners_block_format: COLD_FORMAT.owners_block_format,
+        ..TieredStorageFooter::default()
+    }
+}
+
+/// The offset to access a cold account: the account block that contains
+/// the account, and the offset of the account entry inside the decompressed
+/// account block.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Pod, Zeroable)]
+pub struct ColdAccountOffset {
+    /// The index of the account block in the account block table.
+    pub block: u32,
+    /// The offset of the account entry inside the decompressed account block.
+    pub offset_in_block: u32,
+}
+
+// Ensure there are no implicit padding bytes
+const _: () = assert!(std::mem::size_of::<ColdAccountOffset>() == 8);
+
+impl AccountOffset for ColdAccountOffset {}
+
+/// An entry of the account block table that describes where a compressed
+/// account block is located inside a cold accounts file.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Pod, Zeroable)]
+pub struct ColdBlockEntry {
+    /// The offset of the compressed account block in the file.
+    pub offset: u64,
+    /// The size of the compressed account block.
+    pub compressed_len: u32,
+    /// The size of the account block after decompression.
+    pub raw_len: u32,
+}
+
+// Ensure there are no implicit padding bytes
+const _: () = assert!(std::mem::size_of::<ColdBlockEntry>() == 16);
+
+/// A decompressed account block.
+///
+/// The decompressed bytes are kept in a u64 buffer so that the account metas
+/// and optional fields inside the block can be read in place.
+#[derive(Debug)]
+struct DecodedAccountBlock {
+    /// The index of this account block in the account block table.
+    block: u32,
+    /// The aligned buffer that holds the decompressed bytes.
+    words: Vec<u64>,
+    /// The number of valid bytes inside `words`.
+    len: usize,
+}
+
+impl DecodedAccountBlock {
+    fn new(block: u32, raw: &[u8]) -> Self {
+        let mut words = vec![0u64; raw.len().div_ceil(std::mem::size_of::<u64>())];
+        bytemuck::cast_slice_mut::<u64, u8>(&mut words)[..raw.len()].copy_from_slice(raw);
+        Self {
+            block,
+            words,
+            len: raw.len(),
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &bytemuck::cast_slice::<u64, u8>(&self.words)[..self.len]
+    }
+}
+
+/// The reader to a cold accounts file.
+#[derive(Debug)]
+pub struct ColdStorageReader {
+    mmap: Mmap,
+    footer: TieredStorageFooter,
+    /// The offset of the account block table.
+    block_table_offset: usize,
+    /// The number of account blocks.
+    num_blocks: usize,
+    /// The total size of all the account blocks after decompression.
+    raw_accounts_len: u64,
+    /// The most recently used decompressed account blocks, most recent first.
+    block_cache: Mutex<VecDeque<Arc<DecodedAccountBlock>>>,
+}
+
+impl ColdStorageReader {
+    pub fn new(file: TieredReadableFile) -> TieredStorageResult<Self> {
+        let mmap = unsafe { MmapOptions::new().map(&file.0)? };
+        let footer = *TieredStorageFooter::new_from_mmap(&mmap)?;
+
+        let block_table_offset = footer.owners_block_offset as usize
+            + std::mem::size_of::<Pubkey>() * footer.owner_count as usize;
+        let block_table_end = mmap.len().saturating_sub(FOOTER_SIZE);
+        if block_table_offset > block_table_end {
+            return Err(TieredStorageError::OffsetOutOfBounds(
+                block_table_offset,
+                block_table_end,
+            ));
+        }
+        let num_blocks =
+            (block_table_end - block_table_offset) / std::mem::size_of::<ColdBlockEntry>();
+
+        let mut reader = Self {
+            mmap,
+            footer,
+            block_table_offset,
+            num_blocks,
+            raw_accounts_len: 0,
+            block_cache: Mutex::default(),
+        };
+        for block in 0..num_blocks {
+            reader.raw_accounts_len += reader.get_block_entry(block as u32)?.raw_len as u64;
+        }
+
+        Ok(reader)
+    }
+
+    /// Returns the size of the underlying storage.
+    pub fn len(&self) -> usize {
+        self.mmap.len()
+    }
+
+    /// Returns whether the underlying storage is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the capacity of the underlying storage.
+    ///
+    /// As the stored sizes reported for cold accounts are their decompressed
+    /// sizes, the capacity accounts for the decompressed account blocks so
+    /// that it stays comparable with the alive bytes of this storage.
+    pub fn capacity(&self) -> u64 {
+        (self.len() as u64).max(self.raw_accounts_len)
+    }
+
+    /// Returns the footer of the underlying tiered-storage accounts file.
+    pub fn footer(&self) -> &TieredStorageFooter {
+        &self.footer
+    }
+
+    /// Returns the number of files inside the underlying tiered-storage
+    /// accounts file.
+    pub fn num_accounts(&self) -> usize {
+        self.footer.account_entry_count as usize
+    }
+
+    /// Returns the entry of the account block table at the specified index.
+    fn get_block_entry(&self, block: u32) -> TieredStorageResult<&ColdBlockEntry> {
+        if block as usize >= self.num_blocks {
+            return Err(TieredStorageError::OffsetOutOfBounds(
+                block as usize,
+                self.num_blocks,
+            ));
+        }
+        let offset =
+            self.block_table_offset + std::mem::size_of::<ColdBlockEntry>() * block as usize;
+        let (entry, _) = get_pod::<ColdBlockEntry>(&self.mmap, offset)?;
+        Ok(entry)
+    }
+
+    /// Decompresses the account block at the specified index.
+    fn decode_account_block(&self, block: u32) -> TieredStorageResult<DecodedAccountBlock> {
+        let entry = self.get_block_entry(block)?;
+        let (compressed, _) = get_slice(
+            &self.mmap,
+            entry.offset as usize,
+            entry.compressed_len as usize,
+        )?;
+        let raw = ByteBlockReader::decode(self.footer.account_block_format, compressed)?;
+        debug_assert_eq!(raw.len(), entry.raw_len as usize);
+        Ok(DecodedAccountBlock::new(block, &raw))
+    }
+
+    /// Returns the decompressed account block at the specified index from the
+    /// block cache, decompressing and caching it on a miss.
+    fn get_cached_block(&self, block: u32) -> TieredStorageResult<Arc<DecodedAccountBlock>> {
+        {
+            let mut block_cache = self.block_cache.lock().unwrap();
+            if let Some(position) = block_cache.iter().position(|cached| cached.block == block) {
+                let cached = block_cache.remove(position).unwrap();
+                block_cache.push_front(Arc::clone(&cached));
+                return Ok(cached);
+            }
+        }
+
+        // decompress without holding the lock
+        let decoded = Arc::new(self.decode_account_block(block)?);
+        let mut block_cache = self.block_cache.lock().unwrap();
+        if !block_cache.iter().any(|cached| cached.block == block) {
+            block_cache.push_front(Arc::clone(&decoded));
+            block_cache.truncate(COLD_BLOCK_CACHE_CAPACITY);
+        }
+        Ok(decoded)
+    }
+
+    /// Returns the offset to the account given the specified index.
+    pub(super) fn get_account_offset(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<ColdAccountOffset> {
+        self.footer
+            .index_block_format
+            .get_account_offset::<ColdAccountOffset>(&self.mmap, &self.footer, index_offset)
+    }
+
+    /// Returns the address of the account associated with the specified index.
+    fn get_account_address(&self, index: IndexOffset) -> TieredStorageResult<&Pubkey> {
+        self.footer
+            .index_block_format
+            .get_account_address(&self.mmap, &self.footer, index)
+    }
+
+    /// Returns the address of the account owner given the specified
+    /// owner_offset.
+    fn get_owner_address(&self, owner_offset: OwnerOffset) -> TieredStorageResult<&Pubkey> {
+        self.footer
+            .owners_block_format
+            .get_owner_address(&self.mmap, &self.footer, owner_offset)
+    }
+
+    /// Returns the decompressed account block that contains the account at
+    /// `account_offset`, reusing `cached` when it already holds that block.
+    fn get_decoded_block<'a>(
+        &self,
+        account_offset: ColdAccountOffset,
+        cached: &'a mut Option<Arc<DecodedAccountBlock>>,
+    ) -> TieredStorageResult<&'a DecodedAccountBlock> {
+        if cached
+            .as_ref()
+            .map_or(true, |decoded| decoded.block != account_offset.block)
+        {
+            *cached = Some(Arc::new(self.decode_account_block(account_offset.block)?));
+        }
+        Ok(cached.as_deref().unwrap())
+    }
+
+    /// Returns the account meta and the account block (account data +
+    /// padding + optional fields) of the account located at the specified
+    /// index inside the specified decompressed account block.
+    ///
+    /// The size of an account entry is not stored in the cold accounts file.
+    /// It is derived from the offset of the next account entry when both
+    /// entries share the same account block, or the end of the account block
+    /// otherwise.
+    fn get_account_from_block<'a>(
+        &self,
+        decoded: &'a DecodedAccountBlock,
+        account_offset: ColdAccountOffset,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<(&'a HotAccountMeta, &'a [u8])> {
+        let bytes = decoded.bytes();
+        let entry_start = account_offset.offset_in_block as usize;
+        let entry_end = if index_offset.0.saturating_add(1) < self.footer.account_entry_count {
+            let next_offset = self.get_account_offset(IndexOffset(index_offset.0 + 1))?;
+            if next_offset.block == account_offset.block {
+                next_offset.offset_in_block as usize
+            } else {
+                bytes.len()
+            }
+        } else {
+            bytes.len()
+        };
+
+        let meta_end = entry_start.saturating_add(std::mem::size_of::<HotAccountMeta>());
+        if meta_end > entry_end || entry_end > bytes.len() {
+            return Err(TieredStorageError::OffsetOutOfBounds(
+                entry_end,
+                bytes.len(),
+            ));
+        }
+        let meta = byte_block::read_pod::<HotAccountMeta>(bytes, entry_start)
+            .ok_or(TieredStorageError::OffsetOutOfBounds(meta_end, bytes.len()))?;
+        Ok((meta, &bytes[meta_end..entry_end]))
+    }
+
+    /// Returns Ok(index_of_matching_owner) if the account owner at
+    /// `index_offset` is one of the pubkeys in `owners`.
+    ///
+    /// Returns Err(MatchAccountOwnerError::NoMatch) if the account has 0
+    /// lamports or the owner is not one of the pubkeys in `owners`.
+    ///
+    /// Returns Err(MatchAccountOwnerError::UnableToLoad) if there is any internal
+    /// error that causes the data unable to load, including `index_offset`
+    /// causes a data overrun.
+    pub fn account_matches_owners(
+        &self,
+        index_offset: IndexOffset,
+        owners: &[Pubkey],
+    ) -> Result<usize, MatchAccountOwnerError> {
+        let account_offset = self
+            .get_account_offset(index_offset)
+            .map_err(|_| MatchAccountOwnerError::UnableToLoad)?;
+        let decoded = self
+            .get_cached_block(account_offset.block)
+            .map_err(|_| MatchAccountOwnerError::UnableToLoad)?;
+        let (meta, _) = self
+            .get_account_from_block(&decoded, account_offset, index_offset)
+            .map_err(|_| MatchAccountOwnerError::UnableToLoad)?;
+
+        if meta.lamports() == 0 {
+            Err(MatchAccountOwnerError::NoMatch)
+        } else {
+            let account_owner = self
+                .get_owner_address(meta.owner_offset())
+                .map_err(|_| MatchAccountOwnerError::UnableToLoad)?;
+
+            owners
+                .iter()
+                .position(|candidate| account_owner == candidate)
+                .ok_or(MatchAccountOwnerError::NoMatch)
+        }
+    }
+
+    /// Calls `callback` with the account located at the specified index
+    /// offset, using `cached` to avoid decompressing the same account block
+    /// more than once.
+    fn get_stored_account_meta_with_cache<Ret>(
+        &self,
+        index_offset: IndexOffset,
+        cached: &mut Option<Arc<DecodedAccountBlock>>,
+        mut callback: impl for<'local> FnMut(StoredAccountMeta<'local>) -> Ret,
+    ) -> TieredStorageResult<Option<Ret>> {
+        if index_offset.0 >= self.footer.account_entry_count {
+            return Ok(None);
+        }
+
+        let account_offset = self.get_account_offset(index_offset)?;
+        let decoded = self.get_decoded_block(account_offset, cached)?;
+        let (meta, account_block) =
+            self.get_account_from_block(decoded, account_offset, index_offset)?;
+        let address = self.get_account_address(index_offset)?;
+        let owner = self.get_owner_address(meta.owner_offset())?;
+
+        Ok(Some(callback(StoredAccountMeta::Hot(HotAccount {
+            meta,
+            address,
+            owner,
+            index: index_offset,
+            account_block,
+        }))))
+    }
+
+    /// calls `callback` with the account located at the specified index offset.
+    pub fn get_stored_account_meta_callback<Ret>(
+        &self,
+        index_offset: IndexOffset,
+        callback: impl for<'local> FnMut(StoredAccountMeta<'local>) -> Ret,
+    ) -> TieredStorageResult<Option<Ret>> {
+        if index_offset.0 >= self.footer.account_entry_count {
+            return Ok(None);
+        }
+
+        let account_offset = self.get_account_offset(index_offset)?;
+        let mut cached = Some(self.get_cached_block(account_offset.block)?);
+        self.get_stored_account_meta_with_cache(index_offset, &mut cached, callback)
+    }
+
+    /// Returns the account located at the specified index offset.
+    pub fn get_account_shared_data(
+        &self,
+        index_offset: IndexOffset,
+    ) -> TieredStorageResult<Option<AccountSharedData>> {
+        if index_offset.0 >= self.footer.account_entry_count {
+            return Ok(None);
+        }
+
+        let account_offset = self.get_account_offset(index_offset)?;
+        let decoded = self.get_cached_block(account_offset.block)?;
+        let (meta, account_block) =
+            self.get_account_from_block(&decoded, account_offset, index_offset)?;
+
+        let lamports = meta.lamports();
+        let data = meta.account_data(account_block).to_vec();
+        let owner = *self.get_owner_address(meta.owner_offset())?;
+        let executable = meta.flags().executable();
+        let rent_epoch = meta.final_rent_epoch(account_block);
+        Ok(Some(AccountSharedData::create(
+            lamports, data, owner, executable, rent_epoch,
+        )))
+    }
+
+    /// iterate over all pubkeys
+    pub fn scan_pubkeys(&self, mut callback: impl FnMut(&Pubkey)) -> TieredStorageResult<()> {
+        for i in 0..self.footer.account_entry_count {
+            let address = self.get_account_address(IndexOffset(i))?;
+            callback(address);
+        }
+        Ok(())
+    }
+
+    /// for each offset in `sorted_offsets`, return the account size
+    pub(crate) fn get_account_sizes(
+        &self,
+        sorted_offsets: &[usize],
+    ) -> TieredStorageResult<Vec<usize>> {
+        let mut result = Vec::with_capacity(sorted_offsets.len());
+        let mut cached = None;
+        for &offset in sorted_offsets {
+            let index_offset = IndexOffset(AccountInfo::get_reduced_offset(offset));
+            let account_offset = self.get_account_offset(index_offset)?;
+            let decoded = self.get_decoded_block(account_offset, &mut cached)?;
+            let (meta, account_block) =
+                self.get_account_from_block(decoded, account_offset, index_offset)?;
+            result.push(stored_size(meta.account_data_size(account_block)));
+        }
+        Ok(result)
+    }
+
+    /// Iterate over all accounts and call `callback` with each account.
+    ///
+    /// Each account block is decompressed only once.
+    pub(crate) fn scan_accounts(
+        &self,
+        mut callback: impl for<'local> FnMut(StoredAccountMeta<'local>),
+    ) -> TieredStorageResult<()> {
+        let mut cached = None;
+        for i in 0..self.footer.account_entry_count {
+            self.get_stored_account_meta_with_cache(IndexOffset(i), &mut cached, &mut callback)?;
+        }
+        Ok(())
+    }
+
+    /// iterate over all entries to put in index
+    pub(crate) fn scan_index(
+        &self,
+        mut callback: impl FnMut(IndexInfo),
+    ) -> TieredStorageResult<()> {
+        let mut cached = None;
+        for i in 0..self.footer.account_entry_count {
+            let index_offset = IndexOffset(i);
+            let account_offset = self.get_account_offset(index_offset)?;
+            let decoded = self.get_decoded_block(account_offset, &mut cached)?;
+            let (meta, account_block) =
+                self.get_account_from_block(decoded, account_offset, index_offset)?;
+            let pubkey = self.get_account_address(index_offset)?;
+            let data_len = meta.account_data_size(account_block);
+            callback(IndexInfo {
+                index_info: {
+                    IndexInfoInner {
+                        pubkey: *pubkey,
+                        lamports: meta.lamports(),
+                        offset: AccountInfo::reduced_offset_to_offset(i),
+                        data_len: data_len as u64,
+                        executable: meta.flags().executable(),
+                        rent_epoch: meta.final_rent_epoch(account_block),
+                    }
+                },
+                stored_size_aligned: stored_size(data_len),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns a slice suitable for use when archiving cold storages
+    pub fn data_for_archive(&self) -> &[u8] {
+        self.mmap.as_ref()
+    }
+}
+
+/// The writer that creates a cold accounts file.
+#[derive(Debug)]
+pub struct ColdStorageWriter {
+    storage: TieredWritableFile,
+}
+
+impl ColdStorageWriter {
+    /// Create a new ColdStorageWriter with the specified path.
+    pub fn new(file_path: impl AsRef<Path>) -> TieredStorageResult<Self> {
+        Ok(Self {
+            storage: TieredWritableFile::new(file_path)?,
+        })
+    }
+
+    /// Appends an account entry with the specified information to the
+    /// specified account block writer.
+    fn write_account(
+        block_writer: &mut ByteBlockWriter,
+        lamports: u64,
+        owner_offset: OwnerOffset,
+        account_data: &[u8],
+        executable: bool,
+        rent_epoch: Option<Epoch>,
+    ) -> TieredStorageResult<()> {
+        let optional_fields = AccountMetaOptionalFields { rent_epoch };
+
+        let mut flags = AccountMetaFlags::new_from(&optional_fields);
+        flags.set_executable(executable);
+
+        let padding_len = padding_bytes(account_data.len());
+        let meta = HotAccountMeta::new()
+            .with_lamports(lamports)
+            .with_owner_offset(owner_offset)
+            .with_account_data_size(account_data.len() as u64)
+            .with_account_data_padding(padding_len)
+            .with_flags(&flags);
+
+        block_writer.write_pod(&meta)?;
+        block_writer.write(account_data)?;
+        block_writer.write(&PADDING_BUFFER[0..(padding_len as usize)])?;
+        block_writer.write_optional_fields(&optional_fields)?;
+
+        Ok(())
+    }
+
+    /// Compresses and persists the specified account block, and records it
+    /// in `block_table`.  Returns the number of bytes written.
+    fn write_account_block(
+        &mut self,
+        block_writer: ByteBlockWriter,
+        cursor: usize,
+        block_table: &mut Vec<ColdBlockEntry>,
+    ) -> TieredStorageResult<usize> {
+        let raw_len = block_writer.raw_len();
+        let compressed = block_writer.finish()?;
+        let bytes_written = self.storage.write_bytes(&compressed)?;
+        block_table.push(ColdBlockEntry {
+            offset: cursor as u64,
+            compressed_len: compressed.len() as u32,
+            raw_len: raw_len as u32,
+        });
+        Ok(bytes_written)
+    }
+
+    /// Persists `accounts` into the underlying cold accounts file associated
+    /// with this ColdStorageWriter.  The first `skip` number of accounts are
+    /// *not* persisted.
+    pub fn write_accounts<'a>(
+        &mut self,
+        accounts: &impl StorableAccounts<'a>,
+        skip: usize,
+    ) -> TieredStorageResult<StoredAccountsInfo> {
+        let mut footer = new_cold_footer();
+        let mut index = vec![];
+        let mut owners_table = OwnersTable::default();
+        let mut block_table = vec![];
+        let mut block_writer = ByteBlockWriter::new(footer.account_block_format);
+        let mut cursor = 0;
+        let mut address_range = AccountAddressRange::default();
+
+        let len = accounts.len();
+        let total_input_accounts = len.saturating_sub(skip);
+        let mut offsets = Vec::with_capacity(total_input_accounts);
+
+        // writing accounts blocks
+        for i in skip..len {
+            accounts.account_default_if_zero_lamport::<TieredStorageResult<()>>(i, |account| {
+                // Seal the current account block if this account does not
+                // fit in it, so that large accounts get their own block.
+                let entry_size = std::mem::size_of::<HotAccountMeta>() + account.data().len();
+                if block_writer.raw_len() > 0
+                    && block_writer.raw_len() + entry_size > COLD_ACCOUNT_BLOCK_SIZE
+                {
+                    let full_block = std::mem::replace(
+                        &mut block_writer,
+                        ByteBlockWriter::new(footer.account_block_format),
+                    );
+                    cursor += self.write_account_block(full_block, cursor, &mut block_table)?;
+                }
+
+                let index_entry = AccountIndexWriterEntry {
+                    address: *account.pubkey(),
+                    offset: ColdAccountOffset {
+                        block: block_table.len() as u32,
+                        offset_in_block: block_writer.raw_len() as u32,
+                    },
+                };
+                address_range.update(account.pubkey());
+
+                // Obtain necessary fields from the account, or default fields
+                // for a zero-lamport account in the None case.
+                let (lamports, owner, data, executable, rent_epoch) = {
+                    (
+                        account.lamports(),
+                        account.owner(),
+                        account.data(),
+                        account.executable(),
+                        // only persist rent_epoch for those rent-paying accounts
+                        (account.rent_epoch() != RENT_EXEMPT_RENT_EPOCH)
+                            .then_some(account.rent_epoch()),
+                    )
+                };
+                let owner_offset = owners_table.insert(owner);
+                Self::write_account(
+                    &mut block_writer,
+                    lamports,
+                    owner_offset,
+                    data,
+                    executable,
+                    rent_epoch,
+                )?;
+
+                offsets.push(index.len());
+                index.push(index_entry);
+                Ok(())
+            })?;
+        }
+        if block_writer.raw_len() > 0 {
+            cursor += self.write_account_block(block_writer, cursor, &mut block_table)?;
+        }
+        footer.account_entry_count = total_input_accounts as u32;
+
+        // compressed account blocks have arbitrary sizes, so pad to ensure
+        // the following blocks are aligned.
+        cursor += self
+            .storage
+            .write_bytes(&PADDING_BUFFER[0..(padding_bytes(cursor) as usize)])?;
+
+        // writing index block
+        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
+        footer.index_block_offset = cursor as u64;
+        cursor += footer
+            .index_block_format
+            .write_index_block(&mut self.storage, &index)?;
+
+        // writing owners block
+        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
+        footer.owners_block_offset = cursor as u64;
+        footer.owner_count = owners_table.len() as u32;
+        cursor += footer
+            .owners_block_format
+            .write_owners_block(&mut self.storage, &owners_table)?;
+
+        // writing account block table
+        assert!(cursor % HOT_BLOCK_ALIGNMENT == 0);
+        for block_entry in &block_table {
+            cursor += self.storage.write_pod(block_entry)?;
+        }
+
+        // writing footer
+        footer.min_account_address = address_range.min;
+        footer.max_account_address = address_range.max;
+        cursor += footer.write_footer_block(&mut self.storage)?;
+
+        Ok(StoredAccountsInfo {
+            offsets,
+            size: cursor,
+        })
+    }
+
+    /// Flushes any buffered data to the file
+    pub fn flush(&mut self) -> TieredStorageResult<()> {
+        self.storage
+            .0
+            .flush()
+            .map_err(TieredStorageError::FlushColdWriter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tiered_storage::test_utils::{create_test_account, verify_test_account},
+        assert_matches::assert_matches,
+        solana_sdk::{account::ReadableAccount, slot_history::Slot},
+        tempfile::TempDir,
+    };
+
+    /// Writes the specified accounts into a new cold storage file and returns
+    /// the reader of that file.
+    fn write_and_read(
+        temp_dir: &TempDir,
+        accounts: &[(Pubkey, AccountSharedData)],
+    ) -> ColdStorageReader {
+        let path = temp_dir.path().join("test");
+        let account_refs: Vec<_> = accounts
+            .iter()
+            .map(|(address, account)| (address, account))
+            .collect();
+        let storable_accounts = (Slot::MAX, account_refs.as_slice());
+        {
+            let mut writer = ColdStorageWriter::new(&path).unwrap();
+            let stored_accounts_info = writer.write_accounts(&storable_accounts, 0).unwrap();
+            writer.flush().unwrap();
+            assert_eq!(stored_accounts_info.offsets.len(), accounts.len());
+        }
+        ColdStorageReader::new(TieredReadableFile::new(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_cold_account_offset_layout() {
+        assert_eq!(std::mem::size_of::<ColdAccountOffset>(), 8);
+        assert_eq!(std::mem::size_of::<ColdBlockEntry>(), 16);
+    }
+
+    #[test]
+    fn test_cold_storage_write_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        // include accounts that are larger than one account block
+        let seeds = [1, 2, 3, 100, 4096, 65_535, 70_000, 5, 200_000, 7];
+        let accounts: Vec<_> = seeds
+            .iter()
+            .map(|seed| {
+                let (stored_meta, account) = create_test_account(*seed);
+                (stored_meta.pubkey, account)
+            })
+            .collect();
+
+        let reader = write_and_read(&temp_dir, &accounts);
+        let footer = reader.footer();
+        assert_eq!(footer.account_meta_format, AccountMetaFormat::Cold);
+        assert_eq!(footer.account_block_format, AccountBlockFormat::Lz4);
+        assert_eq!(reader.num_accounts(), accounts.len());
+        assert!(reader.num_blocks > 1);
+
+        for (i, (address, account)) in accounts.iter().enumerate() {
+            let index_offset = IndexOffset(i as u32);
+            reader
+                .get_stored_account_meta_callback(index_offset, |stored_account_meta| {
+                    verify_test_account(&stored_account_meta, account, address);
+                })
+                .unwrap()
+                .unwrap();
+
+            let account_shared_data = reader
+                .get_account_shared_data(index_offset)
+                .unwrap()
+                .unwrap();
+            assert_eq!(account_shared_data.lamports(), account.lamports());
+            assert_eq!(account_shared_data.data(), account.data());
+            assert_eq!(account_shared_data.owner(), account.owner());
+            assert_eq!(account_shared_data.executable(), account.executable());
+            assert_eq!(account_shared_data.rent_epoch(), account.rent_epoch());
+        }
+        assert_matches!(
+            reader.get_account_shared_data(IndexOffset(accounts.len() as u32)),
+            Ok(None)
+        );
+
+        // scans should visit every account in order
+        let mut i = 0;
+        reader
+            .scan_accounts(|stored_account_meta| {
+                let (address, account) = &accounts[i];
+                verify_test_account(&stored_account_meta, account, address);
+                i += 1;
+            })
+            .unwrap();
+        assert_eq!(i, accounts.len());
+
+        let mut index_infos = vec![];
+        reader
+            .scan_index(|index_info| index_infos.push(index_info))
+            .unwrap();
+        assert_eq!(index_infos.len(), accounts.len());
+        for (index_info, (address, account)) in index_infos.iter().zip(accounts.iter()) {
+            assert_eq!(index_info.index_info.pubkey, *address);
+            assert_eq!(index_info.index_info.lamports, account.lamports());
+            assert_eq!(index_info.index_info.data_len, account.data().len() as u64);
+        }
+
+        let offsets: Vec<_> = (0..accounts.len())
+            .map(|i| AccountInfo::reduced_offset_to_offset(i as u32))
+            .collect();
+        let sizes = reader.get_account_sizes(&offsets).unwrap();
+        for (size, (_, account)) in sizes.iter().zip(accounts.iter()) {
+            assert_eq!(*size, stored_size(account.data().len()));
+        }
+    }
+
+    #[test]
+    fn test_cold_storage_compresses() {
+        let temp_dir = TempDir::new().unwrap();
+        let accounts: Vec<_> = (0..64)
+            .map(|_| {
+                let (stored_meta, account) = create_test_account(4096);
+                (stored_meta.pubkey, account)
+            })
+            .collect();
+
+        let reader = write_and_read(&temp_dir, &accounts);
+        let raw_len: usize = accounts
+            .iter()
+            .map(|(_, account)| account.data().len())
+            .sum();
+        assert!(reader.len() < raw_len);
+        assert!(reader.capacity() >= raw_len as u64);
+    }
+
+    #[test]
+    fn test_cold_storage_account_matches_owners() {
+        let temp_dir = TempDir::new().unwrap();
+        let accounts: Vec<_> = (0..10)
+            .map(|seed| {
+                let (stored_meta, account) = create_test_account(seed);
+                (stored_meta.pubkey, account)
+            })
+            .collect();
+        let reader = write_and_read(&temp_dir, &accounts);
+
+        let owners: Vec<_> = accounts[1..]
+            .iter()
+            .map(|(_, account)| *account.owner())
+            .collect();
+        for (i, (_, account)) in accounts.iter().enumerate() {
+            let result = reader.account_matches_owners(IndexOffset(i as u32), &owners);
+            if account.lamports() == 0 {
+                assert_matches!(result, Err(MatchAccountOwnerError::NoMatch));
+            } else {
+                assert_eq!(result, Ok(i - 1));
+            }
+        }
+        assert_matches!(
+            reader.account_matches_owners(IndexOffset(0), &[Pubkey::new_unique()]),
+            Err(MatchAccountOwnerError::NoMatch)
+        );
+    }
+
+    #[test]
+    fn test_cold_storage_block_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        // every account fills an account block of its own
+        let accounts: Vec<_> = (0..2 * COLD_BLOCK_CACHE_CAPACITY)
+            .map(|_| {
+                let (stored_meta, account) = create_test_account(COLD_ACCOUNT_BLOCK_SIZE as u64);
+                (stored_meta.pubkey, account)
+            })
+            .collect();
+        let reader = write_and_read(&temp_dir, &accounts);
+        assert_eq!(reader.num_blocks, accounts.len());
+        let cached_blocks = || -> Vec<u32> {
+            let block_cache = reader.block_cache.lock().unwrap();
+            block_cache.iter().map(|cached| cached.block).collect()
+        };
+
+        // scans do not go through the block cache
+        reader.scan_accounts(|_| {}).unwrap();
+        assert!(cached_blocks().is_empty());
+
+        // point reads keep the most recently used blocks
+        for (i, (_, account)) in accounts.iter().enumerate() {
+            let account_shared_data = reader
+                .get_account_shared_data(IndexOffset(i as u32))
+                .unwrap()
+                .unwrap();
+            assert_eq!(account_shared_data.data(), account.data());
+        }
+        let last_block = accounts.len() as u32 - 1;
+        assert_eq!(cached_blocks().len(), COLD_BLOCK_CACHE_CAPACITY);
+        assert_eq!(cached_blocks()[0], last_block);
+
+        // a hit moves the block to the front without growing the cache
+        let oldest_block = *cached_blocks().last().unwrap();
+        reader
+            .get_stored_account_meta_callback(IndexOffset(oldest_block), |stored_account_meta| {
+                let (address, account) = &accounts[oldest_block as usize];
+                verify_test_account(&stored_account_meta, account, address);
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached_blocks().len(), COLD_BLOCK_CACHE_CAPACITY);
+        assert_eq!(cached_blocks()[0], oldest_block);
+        assert_eq!(cached_blocks()[1], last_block);
+    }
+
+    #[test]
+    fn test_cold_storage_empty() {
+        let temp_dir = TempDir::new().unwrap();
+        let reader = write_and_read(&temp_dir, &[]);
+        assert_eq!(reader.num_accounts(), 0);
+        assert_eq!(reader.num_blocks, 0);
+        assert_eq!(reader.len(), FOOTER_SIZE);
+    }
+}
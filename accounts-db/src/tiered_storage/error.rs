@@ -17,6 +17,9 @@ pub enum TieredStorageError {
     #[error("Unsupported: the feature is not yet supported")]
     Unsupported(),
 
+    #[error("IncorrectLength: file {0} is {1} bytes long, expected {2}")]
+    IncorrectLength(PathBuf, usize, usize),
+
     #[error("invalid footer size: {0}, expected: {1}")]
     InvalidFooterSize(u64, u64),
 
@@ -34,4 +37,7 @@ pub enum TieredStorageError {
 
     #[error("failed to flush hot storage writer: {0}")]
     FlushHotWriter(#[source] std::io::Error),
+
+    #[error("failed to flush cold storage writer: {0}")]
+    FlushColdWriter(#[source] std::io::Error),
 }
@@ -72,7 +72,7 @@ pub(crate) const HOT_BLOCK_ALIGNMENT: usize = 8;
 const MAX_HOT_ACCOUNT_OFFSET: usize = u32::MAX as usize * HOT_ACCOUNT_ALIGNMENT;
 
 // returns the required number of padding
-fn padding_bytes(data_len: usize) -> u8 {
+pub(super) fn padding_bytes(data_len: usize) -> u8 {
     ((HOT_ACCOUNT_ALIGNMENT - (data_len % HOT_ACCOUNT_ALIGNMENT)) % HOT_ACCOUNT_ALIGNMENT) as u8
 }
 
@@ -80,7 +80,7 @@ fn padding_bytes(data_len: usize) -> u8 {
 const MAX_HOT_PADDING: u8 = 7;
 
 /// The buffer that is used for padding.
-const PADDING_BUFFER: [u8; 8] = [0u8; HOT_ACCOUNT_ALIGNMENT];
+pub(super) const PADDING_BUFFER: [u8; 8] = [0u8; HOT_ACCOUNT_ALIGNMENT];
 
 #[bitfield(bits = 32)]
 #[repr(C)]
@@ -650,7 +650,7 @@ impl HotStorageReader {
 
 /// return an approximation of the cost to store an account.
 /// Some fields like owner are shared across multiple accounts.
-fn stored_size(data_len: usize) -> usize {
+pub(super) fn stored_size(data_len: usize) -> usize {
     data_len + std::mem::size_of::<Pubkey>()
 }
 
@@ -7,7 +7,7 @@ use {
     rayon::prelude::*,
     solana_client::connection_cache::ConnectionCache,
     solana_core::{
-        banking_stage::BankingStage,
+        banking_stage::{forwarder::ForwardingMode, BankingStage},
         banking_trace::{
             BankingPacketBatch, BankingTracer, Channels, BANKING_TRACE_DIR_DEFAULT_BYTE_LIMIT,
         },
@@ -491,6 +491,8 @@ fn main() {
         bank_forks.clone(),
         &Arc::new(PrioritizationFeeCache::new(0u64)),
         false,
+        ForwardingMode::default(),
+        None,
         HashSet::default(),
         BundleAccountLocker::default(),
     );
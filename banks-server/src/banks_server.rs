@@ -203,7 +203,8 @@ fn simulate_transaction(
         units_consumed,
         return_data,
         inner_instructions,
-    } = bank.simulate_transaction_unchecked(&sanitized_transaction, true);
+        execution_trace: _,
+    } = bank.simulate_transaction_unchecked(&sanitized_transaction, true, false);
 
     let simulation_details = TransactionSimulationDetails {
         logs,
@@ -208,11 +208,12 @@ impl LogTransactionService {
             rewards: Some(true),
             commitment: Some(commitment),
             max_supported_transaction_version: Some(0),
+            ..RpcBlockConfig::default()
         };
         let mut measure_process_blocks = Measure::start("measure_process_blocks");
         let blocks = block_slots
             .iter()
-            .map(|slot| client.get_block_with_config(*slot, rpc_block_config));
+            .map(|slot| client.get_block_with_config(*slot, rpc_block_config.clone()));
         let num_blocks = blocks.len();
         let mut last_block_time = None;
         for (block, slot) in blocks.zip(&block_slots) {
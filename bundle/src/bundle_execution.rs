@@ -237,6 +237,8 @@ pub fn load_and_execute_bundle<'a>(
     // Upper bound on execution time for a bundle
     max_processing_time: &Duration,
     transaction_status_sender_enabled: bool,
+    // records a per-instruction execution trace for each transaction, used by simulation
+    enable_trace_recording: bool,
     log_messages_bytes_limit: &Option<usize>,
     // simulation will not use the Bank's account locks when building the TransactionBatch
     // if simulating on an unfrozen bank, this is helpful to avoid stalling replay and use whatever
@@ -378,9 +380,12 @@ pub fn load_and_execute_bundle<'a>(
                     compute_budget: bank.compute_budget(),
                     log_messages_bytes_limit: *log_messages_bytes_limit,
                     limit_to_load_programs: true,
-                    recording_config: ExecutionRecordingConfig::new_single_setting(
-                        transaction_status_sender_enabled
-                    ),
+                    recording_config: ExecutionRecordingConfig {
+                        enable_trace_recording,
+                        ..ExecutionRecordingConfig::new_single_setting(
+                            transaction_status_sender_enabled,
+                        )
+                    },
                     transaction_account_lock_limit: Some(bank.get_transaction_account_lock_limit()),
                 },
             ));
@@ -637,6 +642,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &MAX_PROCESSING_TIME,
             true,
+            false,
             &LOG_MESSAGE_BYTES_LIMITS,
             false,
             None,
@@ -718,6 +724,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &MAX_PROCESSING_TIME,
             true,
+            false,
             &LOG_MESSAGE_BYTES_LIMITS,
             false,
             None,
@@ -796,6 +803,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &MAX_PROCESSING_TIME,
             true,
+            false,
             &LOG_MESSAGE_BYTES_LIMITS,
             false,
             None,
@@ -1064,6 +1072,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &MAX_PROCESSING_TIME,
             true,
+            false,
             &LOG_MESSAGE_BYTES_LIMITS,
             false,
             None,
@@ -1123,6 +1132,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &Duration::from_millis(100),
             false,
+            false,
             &None,
             false,
             None,
@@ -1166,6 +1176,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &Duration::from_millis(100),
             false,
+            false,
             &None,
             true,
             None,
@@ -1222,6 +1233,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &Duration::from_secs(2),
             false,
+            false,
             &None,
             false,
             None,
@@ -1255,6 +1267,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &Duration::from_millis(100),
             false,
+            false,
             &None,
             false,
             None,
@@ -1272,6 +1285,7 @@ mod tests {
             MAX_PROCESSING_AGE,
             &Duration::from_millis(100),
             false,
+            false,
             &None,
             false,
             None,
@@ -113,6 +113,13 @@ impl ComputeBudgetInstructionDetails {
         Ok(compute_budget_instruction_details)
     }
 
+    /// The unsanitized compute unit limit set by a `SetComputeUnitLimit`
+    /// instruction, if the transaction contains one
+    pub fn requested_compute_unit_limit(&self) -> Option<u32> {
+        self.requested_compute_unit_limit
+            .map(|(_index, requested_compute_unit_limit)| requested_compute_unit_limit)
+    }
+
     pub fn sanitize_and_convert_to_compute_budget_limits(
         &self,
         feature_set: &FeatureSet,
@@ -18,6 +18,7 @@ use {
         banking_stage::{
             committer::Committer,
             consumer::Consumer,
+            forwarder::ForwardingMode,
             leader_slot_metrics::LeaderSlotMetricsTracker,
             qos_service::QosService,
             unprocessed_packet_batches::*,
@@ -325,6 +326,8 @@ fn bench_banking(bencher: &mut Bencher, tx_type: TransactionType) {
         bank_forks,
         &Arc::new(PrioritizationFeeCache::new(0u64)),
         false,
+        ForwardingMode::default(),
+        None,
         HashSet::default(),
         BundleAccountLocker::default(),
     );
@@ -4,7 +4,7 @@ use {
     itertools::Itertools,
     solana_client::connection_cache::ConnectionCache,
     solana_core::banking_stage::{
-        forwarder::Forwarder,
+        forwarder::{Forwarder, ForwardingMode},
         leader_slot_metrics::LeaderSlotMetricsTracker,
         unprocessed_packet_batches::{DeserializedPacket, UnprocessedPacketBatches},
         unprocessed_transaction_storage::{ThreadType, UnprocessedTransactionStorage},
@@ -104,6 +104,7 @@ fn setup(num_packets: usize, contentious_transaction: bool) -> BenchSetup {
         cluster_info,
         Arc::new(connection_cache),
         Arc::new(data_budget),
+        ForwardingMode::default(),
     );
 
     BenchSetup {
@@ -7,7 +7,7 @@ use {
         committer::Committer,
         consumer::Consumer,
         decision_maker::{BufferedPacketsDecision, DecisionMaker},
-        forwarder::Forwarder,
+        forwarder::{Forwarder, ForwardingMode},
         latest_unprocessed_votes::{LatestUnprocessedVotes, VoteSource},
         leader_slot_metrics::LeaderSlotMetricsTracker,
         packet_receiver::PacketReceiver,
@@ -30,6 +30,7 @@ use {
     crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender},
     histogram::Histogram,
     solana_client::connection_cache::ConnectionCache,
+    solana_cost_model::program_cost_estimator::ProgramCostEstimator,
     solana_gossip::{cluster_info::ClusterInfo, contact_info::ContactInfo},
     solana_ledger::blockstore_processor::TransactionStatusSender,
     solana_measure::measure_us,
@@ -364,6 +365,8 @@ impl BankingStage {
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
         enable_forwarding: bool,
+        forwarding_mode: ForwardingMode,
+        program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
     ) -> Self {
@@ -382,6 +385,8 @@ impl BankingStage {
             bank_forks,
             prioritization_fee_cache,
             enable_forwarding,
+            forwarding_mode,
+            program_cost_estimator,
             blacklisted_accounts,
             bundle_account_locker,
         )
@@ -403,6 +408,8 @@ impl BankingStage {
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
         enable_forwarding: bool,
+        forwarding_mode: ForwardingMode,
+        program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
     ) -> Self {
@@ -421,6 +428,8 @@ impl BankingStage {
                 bank_forks,
                 prioritization_fee_cache,
                 enable_forwarding,
+                forwarding_mode,
+                program_cost_estimator,
                 blacklisted_accounts,
                 bundle_account_locker,
             ),
@@ -442,6 +451,8 @@ impl BankingStage {
         bank_forks: Arc<RwLock<BankForks>>,
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
         enable_forwarding: bool,
+        forwarding_mode: ForwardingMode,
+        program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
         blacklisted_accounts: HashSet<Pubkey>,
         bundle_account_locker: BundleAccountLocker,
     ) -> Self {
@@ -457,11 +468,14 @@ impl BankingStage {
         };
 
         let decision_maker = DecisionMaker::new(cluster_info.id(), poh_recorder.clone());
-        let committer = Committer::new(
+        let mut committer = Committer::new(
             transaction_status_sender.clone(),
             replay_vote_sender.clone(),
             prioritization_fee_cache.clone(),
         );
+        if let Some(program_cost_estimator) = &program_cost_estimator {
+            committer = committer.with_program_cost_estimator(program_cost_estimator.clone());
+        }
         let transaction_recorder = poh_recorder.read().unwrap().new_recorder();
 
         // + 1 for the central scheduler thread
@@ -485,6 +499,7 @@ impl BankingStage {
                     cluster_info.clone(),
                     connection_cache.clone(),
                     data_budget.clone(),
+                    forwarding_mode,
                 ),
                 UnprocessedTransactionStorage::new_vote_storage(
                     latest_unprocessed_votes.clone(),
@@ -505,13 +520,18 @@ impl BankingStage {
         let mut worker_metrics = Vec::with_capacity(num_workers as usize);
         for (index, work_receiver) in work_receivers.into_iter().enumerate() {
             let id = (index as u32).saturating_add(NUM_VOTE_PROCESSING_THREADS);
+            let mut qos_service = QosService::new(id);
+            if let Some(program_cost_estimator) = &program_cost_estimator {
+                qos_service =
+                    qos_service.with_program_cost_estimator(program_cost_estimator.clone());
+            }
             let consume_worker = ConsumeWorker::new(
                 id,
                 work_receiver,
                 Consumer::new(
                     committer.clone(),
                     poh_recorder.read().unwrap().new_recorder(),
-                    QosService::new(id),
+                    qos_service,
                     log_messages_bytes_limit,
                     blacklisted_accounts.clone(),
                     bundle_account_locker.clone(),
@@ -538,6 +558,7 @@ impl BankingStage {
                 cluster_info.clone(),
                 connection_cache.clone(),
                 data_budget.clone(),
+                forwarding_mode,
             )
         });
 
@@ -548,6 +569,7 @@ impl BankingStage {
                 packet_deserializer,
                 bank_forks.clone(),
                 forwarder.is_some(),
+                program_cost_estimator,
             );
             let scheduler = PrioGraphScheduler::new(
                 work_senders,
@@ -838,6 +860,8 @@ mod tests {
                 bank_forks,
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
                 false,
+                ForwardingMode::default(),
+                None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
             );
@@ -900,6 +924,8 @@ mod tests {
                 bank_forks,
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
                 false,
+                ForwardingMode::default(),
+                None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
             );
@@ -991,6 +1017,8 @@ mod tests {
                 bank_forks.clone(), // keep a local-copy of bank-forks so worker threads do not lose weak access to bank-forks
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
                 false,
+                ForwardingMode::default(),
+                None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
             );
@@ -1168,6 +1196,8 @@ mod tests {
                     bank_forks,
                     &Arc::new(PrioritizationFeeCache::new(0u64)),
                     false,
+                    ForwardingMode::default(),
+                    None,
                     HashSet::default(),
                     BundleAccountLocker::default(),
                 );
@@ -1375,6 +1405,8 @@ mod tests {
                 bank_forks,
                 &Arc::new(PrioritizationFeeCache::new(0u64)),
                 false,
+                ForwardingMode::default(),
+                None,
                 HashSet::default(),
                 BundleAccountLocker::default(),
             );
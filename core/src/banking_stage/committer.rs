@@ -1,6 +1,7 @@
 use {
     super::leader_slot_timing_metrics::LeaderExecuteAndCommitTimings,
     itertools::Itertools,
+    solana_cost_model::program_cost_estimator::ProgramCostEstimator,
     solana_ledger::{
         blockstore_processor::TransactionStatusSender, token_balances::collect_token_balances,
     },
@@ -20,6 +21,7 @@ use {
             TransactionProcessingResult, TransactionProcessingResultExtensions,
         },
     },
+    solana_svm_transaction::svm_message::SVMMessage,
     solana_transaction_status::{token_balances::TransactionTokenBalancesSet, PreBalanceInfo},
     std::sync::Arc,
 };
@@ -38,6 +40,7 @@ pub struct Committer {
     transaction_status_sender: Option<TransactionStatusSender>,
     replay_vote_sender: ReplayVoteSender,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
 }
 
 impl Committer {
@@ -50,9 +53,20 @@ impl Committer {
             transaction_status_sender,
             replay_vote_sender,
             prioritization_fee_cache,
+            program_cost_estimator: None,
         }
     }
 
+    /// Feeds the compute units consumed by committed transactions into
+    /// `program_cost_estimator`
+    pub fn with_program_cost_estimator(
+        mut self,
+        program_cost_estimator: Arc<ProgramCostEstimator>,
+    ) -> Self {
+        self.program_cost_estimator = Some(program_cost_estimator);
+        self
+    }
+
     pub(super) fn transaction_status_sender_enabled(&self) -> bool {
         self.transaction_status_sender.is_some()
     }
@@ -97,6 +111,22 @@ impl Committer {
             })
             .collect();
 
+        if let Some(program_cost_estimator) = &self.program_cost_estimator {
+            for (tx, commit_result) in batch.sanitized_transactions().iter().zip(&commit_results) {
+                // failed transactions may stop early, so they do not reflect
+                // the cost of their instructions
+                if let Ok(committed_tx) = commit_result {
+                    if committed_tx.status.is_ok() {
+                        program_cost_estimator.record_executed_transaction(
+                            tx.program_instructions_iter(),
+                            committed_tx.executed_units,
+                            &bank.feature_set,
+                        );
+                    }
+                }
+            }
+        }
+
         let ((), find_and_send_votes_us) = measure_us!({
             bank_utils::find_and_send_votes(
                 batch.sanitized_transactions(),
@@ -9,8 +9,9 @@ use {
         banking_stage::{
             immutable_deserialized_packet::ImmutableDeserializedPacket, LikeClusterInfo,
         },
-        next_leader::{next_leader, next_leader_tpu_vote},
+        next_leader::{next_leader, next_leader_tpu_vote, upcoming_leaders},
     },
+    lru::LruCache,
     solana_client::connection_cache::ConnectionCache,
     solana_connection_cache::client_connection::ClientConnection as TpuConnection,
     solana_feature_set::FeatureSet,
@@ -23,12 +24,100 @@ use {
     solana_sdk::{pubkey::Pubkey, transport::TransportError},
     solana_streamer::sendmmsg::batch_send,
     std::{
+        cell::RefCell,
+        collections::{hash_map::DefaultHasher, HashMap},
+        hash::{Hash, Hasher},
         iter::repeat,
         net::{SocketAddr, UdpSocket},
         sync::{atomic::Ordering, Arc, RwLock},
     },
 };
 
+/// Number of (packet, leader) pairs remembered to avoid forwarding the same
+/// packet to the same leader in a later forwarding round
+const FORWARDED_PACKETS_CACHE_CAPACITY: usize = 200_000;
+
+/// Leaders whose last sends failed have their forwarding weight halved per
+/// consecutive failure, up to this many times
+const MAX_CONNECTIVITY_PENALTY_SHIFT: u32 = 8;
+
+/// Selects which upcoming leaders forwarded transactions are sent to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForwardingMode {
+    /// Forward all transactions to the next leader
+    #[default]
+    NextLeader,
+    /// Spread transactions across the next `num_leaders` distinct leaders in
+    /// the leader schedule, weighted by stake and by how reliably each leader
+    /// could be reached. Packets are not forwarded to the same leader twice.
+    Lookahead { num_leaders: usize },
+}
+
+/// Forwarding state kept across rounds in `ForwardingMode::Lookahead`
+struct LookaheadState {
+    /// (packet key, leader) pairs which have already been forwarded
+    forwarded: LruCache<(u64, Pubkey), ()>,
+    /// Number of consecutive failed sends per leader
+    send_failures: HashMap<Pubkey, u32>,
+}
+
+impl LookaheadState {
+    fn new() -> Self {
+        Self {
+            forwarded: LruCache::new(FORWARDED_PACKETS_CACHE_CAPACITY),
+            send_failures: HashMap::new(),
+        }
+    }
+
+    fn weight(&self, leader: &Pubkey, stake: u64) -> u64 {
+        let failures = self
+            .send_failures
+            .get(leader)
+            .copied()
+            .unwrap_or_default()
+            .min(MAX_CONNECTIVITY_PENALTY_SHIFT);
+        // unstaked leaders still receive a small share
+        stake.max(1) >> failures
+    }
+
+    fn record_send_result(&mut self, leader: Pubkey, success: bool) {
+        if success {
+            self.send_failures.remove(&leader);
+        } else {
+            let failures = self.send_failures.entry(leader).or_default();
+            *failures = failures.saturating_add(1);
+        }
+    }
+}
+
+/// Stable key identifying a packet across forwarding rounds
+fn packet_key(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Picks the leader for a packet, proportionally to `weights`. The choice is
+/// a pure function of the packet key so that a packet keeps going to the same
+/// leader while the set of upcoming leaders does not change.
+fn select_leader(key: u64, weights: &[u64]) -> Option<usize> {
+    let total_weight = weights
+        .iter()
+        .fold(0u64, |total, weight| total.saturating_add(*weight));
+    if total_weight == 0 {
+        return None;
+    }
+    let mut point = key % total_weight;
+    weights.iter().position(|weight| {
+        if point < *weight {
+            true
+        } else {
+            point -= *weight;
+            false
+        }
+    })
+}
+
 pub struct Forwarder<T: LikeClusterInfo> {
     poh_recorder: Arc<RwLock<PohRecorder>>,
     bank_forks: Arc<RwLock<BankForks>>,
@@ -37,6 +126,8 @@ pub struct Forwarder<T: LikeClusterInfo> {
     connection_cache: Arc<ConnectionCache>,
     data_budget: Arc<DataBudget>,
     forward_packet_batches_by_accounts: ForwardPacketBatchesByAccounts,
+    forwarding_mode: ForwardingMode,
+    lookahead_state: RefCell<LookaheadState>,
 }
 
 impl<T: LikeClusterInfo> Forwarder<T> {
@@ -46,6 +137,7 @@ impl<T: LikeClusterInfo> Forwarder<T> {
         cluster_info: T,
         connection_cache: Arc<ConnectionCache>,
         data_budget: Arc<DataBudget>,
+        forwarding_mode: ForwardingMode,
     ) -> Self {
         Self {
             poh_recorder,
@@ -56,6 +148,8 @@ impl<T: LikeClusterInfo> Forwarder<T> {
             data_budget,
             forward_packet_batches_by_accounts:
                 ForwardPacketBatchesByAccounts::new_with_default_batch_limits(),
+            forwarding_mode,
+            lookahead_state: RefCell::new(LookaheadState::new()),
         }
     }
 
@@ -183,6 +277,12 @@ impl<T: LikeClusterInfo> Forwarder<T> {
         u64,
         Option<Pubkey>,
     ) {
+        if let (ForwardOption::ForwardTransaction, ForwardingMode::Lookahead { num_leaders }) =
+            (forward_option, self.forwarding_mode)
+        {
+            return self.forward_packets_to_upcoming_leaders(num_leaders, forwardable_packets);
+        }
+
         let Some((leader_pubkey, addr)) = self.get_leader_and_addr(forward_option) else {
             return (Ok(()), 0, 0, None);
         };
@@ -209,6 +309,102 @@ impl<T: LikeClusterInfo> Forwarder<T> {
         (res, packet_vec_len, forward_us, Some(leader_pubkey))
     }
 
+    /// Spreads the forwardable packets across the next `num_leaders` leaders.
+    /// Packets which were already sent to their selected leader in an earlier
+    /// round are skipped, while packets whose send failed are retried. Leaders
+    /// whose sends fail get penalized. Returns the first send error if any,
+    /// the number of packets sent, the time spent forwarding in us, and the
+    /// first leader packets were sent to.
+    fn forward_packets_to_upcoming_leaders<'a>(
+        &self,
+        num_leaders: usize,
+        forwardable_packets: impl Iterator<Item = &'a Packet>,
+    ) -> (
+        std::result::Result<(), TransportError>,
+        usize,
+        u64,
+        Option<Pubkey>,
+    ) {
+        let leaders = upcoming_leaders(
+            &self.cluster_info,
+            &self.poh_recorder,
+            num_leaders,
+            |node| node.tpu_forwards(self.connection_cache.protocol()),
+        );
+        if leaders.is_empty() {
+            return (Ok(()), 0, 0, None);
+        }
+
+        let staked_nodes = self
+            .bank_forks
+            .read()
+            .unwrap()
+            .working_bank()
+            .current_epoch_staked_nodes();
+        let mut lookahead_state = self.lookahead_state.borrow_mut();
+        let weights: Vec<u64> = leaders
+            .iter()
+            .map(|(leader, _)| {
+                lookahead_state.weight(leader, staked_nodes.get(leader).copied().unwrap_or(0))
+            })
+            .collect();
+
+        self.update_data_budget();
+        let mut packets_by_leader: Vec<Vec<Vec<u8>>> = vec![Vec::new(); leaders.len()];
+        let mut keys_by_leader: Vec<Vec<u64>> = vec![Vec::new(); leaders.len()];
+        for packet in forwardable_packets
+            .filter(|p| !p.meta().forwarded())
+            .filter(|p| p.meta().is_from_staked_node())
+        {
+            let Some(data) = packet.data(..) else {
+                continue;
+            };
+            let key = packet_key(data);
+            let Some(index) = select_leader(key, &weights) else {
+                continue;
+            };
+            if lookahead_state.forwarded.contains(&(key, leaders[index].0)) {
+                continue;
+            }
+            if !self.data_budget.take(packet.meta().size) {
+                continue;
+            }
+            packets_by_leader[index].push(data.to_vec());
+            keys_by_leader[index].push(key);
+        }
+
+        let mut result = Ok(());
+        let mut num_packets: usize = 0;
+        let mut forward_us: u64 = 0;
+        let mut first_leader = None;
+        for (((leader, addr), packet_vec), keys) in leaders
+            .into_iter()
+            .zip(packets_by_leader)
+            .zip(keys_by_leader)
+        {
+            if packet_vec.is_empty() {
+                continue;
+            }
+            first_leader.get_or_insert(leader);
+            num_packets = num_packets.saturating_add(packet_vec.len());
+            let (res, us) = measure_us!(self
+                .connection_cache
+                .get_connection(&addr)
+                .send_data_batch_async(packet_vec));
+            forward_us = forward_us.saturating_add(us);
+            lookahead_state.record_send_result(leader, res.is_ok());
+            if res.is_ok() {
+                for key in keys {
+                    lookahead_state.forwarded.put((key, leader), ());
+                }
+            } else if result.is_ok() {
+                result = res;
+            }
+        }
+
+        (result, num_packets, forward_us, first_leader)
+    }
+
     /// Forwards all valid, unprocessed packets in the buffer, up to a rate limit. Returns
     /// the number of successfully forwarded packets in second part of tuple
     fn forward_buffered_packets<'a>(
@@ -459,6 +655,7 @@ mod tests {
                 cluster_info.clone(),
                 Arc::new(ConnectionCache::new("connection_cache_test")),
                 Arc::new(data_budget),
+                ForwardingMode::default(),
             );
             let unprocessed_packet_batches: UnprocessedPacketBatches =
                 UnprocessedPacketBatches::from_iter(
@@ -563,6 +760,7 @@ mod tests {
             cluster_info,
             Arc::new(connection_cache),
             Arc::new(DataBudget::default()),
+            ForwardingMode::default(),
         );
         let runtime = rt("solQuicTestRt".to_string());
         for (name, hold, expected_num_unprocessed, expected_num_processed) in test_cases {
@@ -590,4 +788,111 @@ mod tests {
         exit.store(true, Ordering::Relaxed);
         poh_service.join().unwrap();
     }
+
+    #[test]
+    fn test_handle_forwarding_lookahead() {
+        let TestSetup {
+            blockhash,
+            bank_forks,
+            poh_recorder,
+            exit,
+            poh_service,
+            cluster_info,
+            local_node,
+            ..
+        } = setup();
+
+        let (expected_packet_size, packet) = {
+            let transaction = system_transaction::transfer(
+                &Keypair::new(),
+                &solana_pubkey::new_rand(),
+                1,
+                blockhash,
+            );
+            let mut packet = Packet::from_data(None, transaction).unwrap();
+            packet.meta_mut().set_from_staked_node(true);
+            (packet.meta().size, DeserializedPacket::new(packet).unwrap())
+        };
+        let mut unprocessed_packet_batches = UnprocessedTransactionStorage::new_transaction_storage(
+            UnprocessedPacketBatches::from_iter(vec![packet], 1),
+            ThreadType::Transactions,
+        );
+
+        let leader = cluster_info.id();
+        let mut forwarder = Forwarder::new(
+            poh_recorder,
+            bank_forks,
+            cluster_info,
+            Arc::new(ConnectionCache::new("connection_cache_test")),
+            Arc::new(DataBudget::default()),
+            ForwardingMode::Lookahead { num_leaders: 2 },
+        );
+        // a failure from an earlier round is cleared by a successful send
+        forwarder
+            .lookahead_state
+            .borrow_mut()
+            .record_send_result(leader, false);
+
+        // the only upcoming leader receives the packet once while it is held
+        let runtime = rt("solQuicTestRt".to_string());
+        for expected_num_forwarded in [1, 0] {
+            forwarder.handle_forwarding(
+                &mut unprocessed_packet_batches,
+                true,
+                &mut LeaderSlotMetricsTracker::new(0),
+                &BankingStageStats::default(),
+            );
+
+            let recv_socket = &local_node.sockets.tpu_forwards_quic[0];
+            runtime.block_on(check_all_received(
+                (*recv_socket).try_clone().unwrap(),
+                expected_num_forwarded,
+                expected_packet_size,
+                &blockhash,
+            ));
+            assert!(forwarder.lookahead_state.borrow().send_failures.is_empty());
+        }
+
+        exit.store(true, Ordering::Relaxed);
+        poh_service.join().unwrap();
+    }
+
+    #[test]
+    fn test_select_leader() {
+        assert_eq!(select_leader(7, &[]), None);
+        assert_eq!(select_leader(7, &[0, 0]), None);
+
+        let weights = [1, 0, 3];
+        assert_eq!(select_leader(0, &weights), Some(0));
+        assert_eq!(select_leader(1, &weights), Some(2));
+        assert_eq!(select_leader(3, &weights), Some(2));
+        assert_eq!(select_leader(4, &weights), Some(0));
+
+        // selection is proportional to weight
+        let counts = (0..4_000u64).fold([0usize; 3], |mut counts, key| {
+            counts[select_leader(key, &weights).unwrap()] += 1;
+            counts
+        });
+        assert_eq!(counts, [1_000, 0, 3_000]);
+    }
+
+    #[test]
+    fn test_lookahead_state_weight() {
+        let leader = Pubkey::new_unique();
+        let mut state = LookaheadState::new();
+        assert_eq!(state.weight(&leader, 0), 1);
+        assert_eq!(state.weight(&leader, 1_000), 1_000);
+
+        state.record_send_result(leader, false);
+        state.record_send_result(leader, false);
+        assert_eq!(state.weight(&leader, 1_000), 250);
+
+        for _ in 0..2 * MAX_CONNECTIVITY_PENALTY_SHIFT {
+            state.record_send_result(leader, false);
+        }
+        assert_eq!(state.weight(&leader, 1 << 20), 1 << 12);
+
+        state.record_send_result(leader, true);
+        assert_eq!(state.weight(&leader, 1_000), 1_000);
+    }
 }
@@ -1,5 +1,9 @@
 use {
     super::packet_filter::PacketFilterFailure,
+    agave_transaction_view::{
+        result::TransactionViewError, transaction_data::TransactionData,
+        transaction_view::SanitizedTransactionView,
+    },
     solana_compute_budget::compute_budget_limits::ComputeBudgetLimits,
     solana_compute_budget_instruction::instructions_processor::process_compute_budget_instructions,
     solana_perf::packet::Packet,
@@ -10,30 +14,20 @@ use {
         clock::Slot,
         feature_set::FeatureSet,
         hash::Hash,
-        message::{v0::LoadedAddresses, AddressLoaderError, Message, SimpleAddressLoader},
+        message::{v0::LoadedAddresses, AddressLoaderError, SimpleAddressLoader, VersionedMessage},
         pubkey::Pubkey,
-        signature::Signature,
-        transaction::{
-            MessageHash, SanitizedTransaction, SanitizedVersionedTransaction, VersionedTransaction,
-        },
+        transaction::{MessageHash, SanitizedTransaction, VersionedTransaction},
     },
-    solana_short_vec::decode_shortu16_len,
-    solana_svm_transaction::{
-        instruction::SVMInstruction, message_address_table_lookup::SVMMessageAddressTableLookup,
-    },
-    std::{cmp::Ordering, collections::HashSet, mem::size_of},
+    std::{cmp::Ordering, collections::HashSet},
     thiserror::Error,
 };
 
 #[derive(Debug, Error)]
 pub enum DeserializedPacketError {
-    #[error("ShortVec Failed to Deserialize")]
-    // short_vec::decode_shortu16_len() currently returns () on error
-    ShortVecError(()),
+    #[error("Transaction view failed to parse or sanitize: {0:?}")]
+    TransactionViewError(TransactionViewError),
     #[error("Deserialization Error: {0}")]
     DeserializationError(#[from] bincode::Error),
-    #[error("overflowed on signature size {0}")]
-    SignatureOverflowed(usize),
     #[error("packet failed sanitization {0}")]
     SanitizeError(#[from] SanitizeError),
     #[error("transaction failed prioritization")]
@@ -50,10 +44,33 @@ lazy_static::lazy_static! {
     static ref FEATURE_SET: FeatureSet = FeatureSet::all_enabled();
 }
 
+/// Owns a packet so that a `TransactionView` can be parsed directly over its
+/// payload, without copying or deserializing the transaction.
+///
+/// The packet is never mutated after construction, so the payload seen by
+/// the view cannot change underneath it.
+#[derive(Debug)]
+pub struct PacketData(Packet);
+
+impl PacketData {
+    pub fn packet(&self) -> &Packet {
+        &self.0
+    }
+}
+
+impl TransactionData for PacketData {
+    #[inline]
+    fn data(&self) -> &[u8] {
+        self.0.data(..).unwrap_or_default()
+    }
+}
+
+/// Sanitized view over the transaction contained in a packet
+pub type PacketTransactionView = SanitizedTransactionView<PacketData>;
+
 #[derive(Debug)]
 pub struct ImmutableDeserializedPacket {
-    original_packet: Packet,
-    transaction: SanitizedVersionedTransaction,
+    transaction: PacketTransactionView,
     message_hash: Hash,
     is_simple_vote: bool,
     compute_unit_price: u64,
@@ -62,11 +79,10 @@ pub struct ImmutableDeserializedPacket {
 
 impl ImmutableDeserializedPacket {
     pub fn new(packet: Packet) -> Result<Self, DeserializedPacketError> {
-        let versioned_transaction: VersionedTransaction = packet.deserialize_slice(..)?;
-        let sanitized_transaction = SanitizedVersionedTransaction::try_from(versioned_transaction)?;
-        let message_bytes = packet_message(&packet)?;
-        let message_hash = Message::hash_raw_message(message_bytes);
         let is_simple_vote = packet.meta().is_simple_vote_tx();
+        let transaction = SanitizedTransactionView::try_new_sanitized(PacketData(packet))
+            .map_err(DeserializedPacketError::TransactionViewError)?;
+        let message_hash = VersionedMessage::hash_raw_message(transaction.message_data());
 
         // drop transaction if prioritization fails.
         let ComputeBudgetLimits {
@@ -74,10 +90,7 @@ impl ImmutableDeserializedPacket {
             compute_unit_limit,
             ..
         } = process_compute_budget_instructions(
-            sanitized_transaction
-                .get_message()
-                .program_instructions_iter()
-                .map(|(pubkey, ix)| (pubkey, SVMInstruction::from(ix))),
+            transaction.program_instructions_iter(),
             &FEATURE_SET,
         )
         .map_err(|_| DeserializedPacketError::PrioritizationFailure)?;
@@ -88,8 +101,7 @@ impl ImmutableDeserializedPacket {
         };
 
         Ok(Self {
-            original_packet: packet,
-            transaction: sanitized_transaction,
+            transaction,
             message_hash,
             is_simple_vote,
             compute_unit_price,
@@ -98,10 +110,10 @@ impl ImmutableDeserializedPacket {
     }
 
     pub fn original_packet(&self) -> &Packet {
-        &self.original_packet
+        self.transaction.inner_data().packet()
     }
 
-    pub fn transaction(&self) -> &SanitizedVersionedTransaction {
+    pub fn transaction(&self) -> &PacketTransactionView {
         &self.transaction
     }
 
@@ -121,8 +133,9 @@ impl ImmutableDeserializedPacket {
         u64::from(self.compute_unit_limit)
     }
 
-    // This function deserializes packets into transactions, computes the blake3 hash of transaction
-    // messages.
+    // This function converts the packet's transaction into a runtime transaction. This is
+    // deferred until the packet is scheduled, so that packets which are dropped or never
+    // scheduled are not fully deserialized.
     // Additionally, this returns the minimum deactivation slot of the resolved addresses.
     pub fn build_sanitized_transaction(
         &self,
@@ -138,36 +151,28 @@ impl ImmutableDeserializedPacket {
         let (loaded_addresses, deactivation_slot) =
             Self::resolve_addresses_with_deactivation(self.transaction(), bank).ok()?;
         let address_loader = SimpleAddressLoader::Enabled(loaded_addresses);
-        let tx = RuntimeTransaction::<SanitizedVersionedTransaction>::try_from(
-            self.transaction.clone(),
+        let versioned_transaction: VersionedTransaction =
+            self.original_packet().deserialize_slice(..).ok()?;
+        let tx = RuntimeTransaction::<SanitizedTransaction>::try_create(
+            versioned_transaction,
             MessageHash::Precomputed(self.message_hash),
             Some(self.is_simple_vote),
+            address_loader,
+            reserved_account_keys,
         )
-        .and_then(|tx| {
-            RuntimeTransaction::<SanitizedTransaction>::try_from(
-                tx,
-                address_loader,
-                reserved_account_keys,
-            )
-        })
         .ok()?;
         Some((tx, deactivation_slot))
     }
 
     fn resolve_addresses_with_deactivation(
-        transaction: &SanitizedVersionedTransaction,
+        transaction: &PacketTransactionView,
         bank: &Bank,
     ) -> Result<(LoadedAddresses, Slot), AddressLoaderError> {
-        let Some(address_table_lookups) = transaction.get_message().message.address_table_lookups()
-        else {
+        if transaction.num_address_table_lookups() == 0 {
             return Ok((LoadedAddresses::default(), Slot::MAX));
-        };
+        }
 
-        bank.load_addresses_from_ref(
-            address_table_lookups
-                .iter()
-                .map(SVMMessageAddressTableLookup::from),
-        )
+        bank.load_addresses_from_ref(transaction.address_table_lookup_iter())
     }
 }
 
@@ -191,19 +196,6 @@ impl Ord for ImmutableDeserializedPacket {
     }
 }
 
-/// Read the transaction message from packet data
-fn packet_message(packet: &Packet) -> Result<&[u8], DeserializedPacketError> {
-    let (sig_len, sig_size) = packet
-        .data(..)
-        .and_then(|bytes| decode_shortu16_len(bytes).ok())
-        .ok_or(DeserializedPacketError::ShortVecError(()))?;
-    sig_len
-        .checked_mul(size_of::<Signature>())
-        .and_then(|v| v.checked_add(sig_size))
-        .and_then(|msg_start| packet.data(msg_start..))
-        .ok_or(DeserializedPacketError::SignatureOverflowed(sig_size))
-}
-
 #[cfg(test)]
 mod tests {
     use {
@@ -228,6 +220,41 @@ mod tests {
         assert!(deserialized_packet.is_ok());
     }
 
+    #[test]
+    fn deserialized_packet_matches_transaction() {
+        let keypair = Keypair::new();
+        let tx = system_transaction::transfer(
+            &keypair,
+            &solana_pubkey::new_rand(),
+            1,
+            Hash::new_unique(),
+        );
+        let packet = Packet::from_data(None, &tx).unwrap();
+        let deserialized_packet = ImmutableDeserializedPacket::new(packet.clone()).unwrap();
+
+        assert_eq!(deserialized_packet.original_packet(), &packet);
+        assert_eq!(deserialized_packet.message_hash(), &tx.message.hash());
+        assert_eq!(
+            deserialized_packet.transaction().signatures(),
+            tx.signatures.as_slice()
+        );
+        assert_eq!(
+            deserialized_packet.transaction().static_account_keys(),
+            tx.message.account_keys.as_slice()
+        );
+
+        // trailing bytes are rejected, as with bincode deserialization
+        let mut data = bincode::serialize(&tx).unwrap();
+        data.push(0);
+        let mut packet = Packet::default();
+        packet.buffer_mut()[..data.len()].copy_from_slice(&data);
+        packet.meta_mut().size = data.len();
+        assert!(matches!(
+            ImmutableDeserializedPacket::new(packet),
+            Err(DeserializedPacketError::TransactionViewError(_))
+        ));
+    }
+
     #[test]
     fn compute_unit_limit_above_static_builtins() {
         // Cases:
@@ -66,8 +66,8 @@ impl LatestValidatorVotePacket {
         vote_source: VoteSource,
         deprecate_legacy_vote_ixs: bool,
     ) -> Result<Self, DeserializedPacketError> {
-        let message = vote.transaction().get_message();
-        let (_, instruction) = message
+        let transaction = vote.transaction();
+        let (_, instruction) = transaction
             .program_instructions_iter()
             .next()
             .ok_or(DeserializedPacketError::VoteTransactionError)?;
@@ -83,7 +83,7 @@ impl LatestValidatorVotePacket {
             }
         };
 
-        match limited_deserialize::<VoteInstruction>(&instruction.data) {
+        match limited_deserialize::<VoteInstruction>(instruction.data) {
             Ok(vote_state_update_instruction)
                 if instruction_filter(&vote_state_update_instruction) =>
             {
@@ -92,8 +92,7 @@ impl LatestValidatorVotePacket {
                     .first()
                     .copied()
                     .ok_or(DeserializedPacketError::VoteTransactionError)?;
-                let vote_pubkey = message
-                    .message
+                let vote_pubkey = transaction
                     .static_account_keys()
                     .get(vote_account_index as usize)
                     .copied()
@@ -47,9 +47,8 @@ pub struct PacketReceiverStats {
 impl PacketReceiverStats {
     pub fn increment_error_count(&mut self, err: &DeserializedPacketError) {
         match err {
-            DeserializedPacketError::ShortVecError(..)
+            DeserializedPacketError::TransactionViewError(..)
             | DeserializedPacketError::DeserializationError(..)
-            | DeserializedPacketError::SignatureOverflowed(..)
             | DeserializedPacketError::SanitizeError(..) => {
                 saturating_add_assign!(self.failed_sanitization_count, 1);
             }
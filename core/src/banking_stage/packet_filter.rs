@@ -31,7 +31,7 @@ impl ImmutableDeserializedPacket {
     /// result in no useful state-change.
     pub fn check_insufficent_compute_unit_limit(&self) -> Result<(), PacketFilterFailure> {
         let mut static_builtin_cost_sum: u64 = 0;
-        for (program_id, _) in self.transaction().get_message().program_instructions_iter() {
+        for (program_id, _) in self.transaction().program_instructions_iter() {
             if let Some(ix_cost) = get_builtin_instruction_cost(program_id, &FEATURE_SET) {
                 saturating_add_assign!(static_builtin_cost_sum, ix_cost);
             }
@@ -48,7 +48,7 @@ impl ImmutableDeserializedPacket {
     /// performed by the transaction is not excessive.
     pub fn check_excessive_precompiles(&self) -> Result<(), PacketFilterFailure> {
         let mut num_precompile_signatures: u64 = 0;
-        for (program_id, ix) in self.transaction().get_message().program_instructions_iter() {
+        for (program_id, ix) in self.transaction().program_instructions_iter() {
             if secp256k1_program::check_id(program_id)
                 || ed25519_program::check_id(program_id)
                 || secp256r1_program::check_id(program_id)
@@ -8,6 +8,7 @@ use {
     solana_cost_model::{
         cost_model::CostModel,
         cost_tracker::{CostTracker, UpdatedCosts},
+        program_cost_estimator::ProgramCostEstimator,
         transaction_cost::TransactionCost,
     },
     solana_feature_set::FeatureSet,
@@ -19,7 +20,10 @@ use {
         saturating_add_assign,
         transaction::{self, TransactionError},
     },
-    std::sync::atomic::{AtomicU64, Ordering},
+    std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 // QosService is local to each banking thread, each instance of QosService provides services to
@@ -29,15 +33,27 @@ use {
 //
 pub struct QosService {
     metrics: QosServiceMetrics,
+    program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
 }
 
 impl QosService {
     pub fn new(id: u32) -> Self {
         Self {
             metrics: QosServiceMetrics::new(id),
+            program_cost_estimator: None,
         }
     }
 
+    /// Reserves the execution cost `program_cost_estimator` expects instead of
+    /// the requested compute units, while keeping room for the difference
+    pub fn with_program_cost_estimator(
+        mut self,
+        program_cost_estimator: Arc<ProgramCostEstimator>,
+    ) -> Self {
+        self.program_cost_estimator = Some(program_cost_estimator);
+        self
+    }
+
     /// Calculate cost of transactions, if not already filtered out, determine which ones to
     /// include in the slot, and accumulate costs in the cost tracker.
     /// Returns a vector of results containing selected transaction costs, and the number of
@@ -80,7 +96,14 @@ impl QosService {
         let mut compute_cost_time = Measure::start("compute_cost_time");
         let txs_costs: Vec<_> = transactions
             .zip(pre_results)
-            .map(|(tx, pre_result)| pre_result.map(|()| CostModel::calculate_cost(tx, feature_set)))
+            .map(|(tx, pre_result)| {
+                pre_result.map(|()| match &self.program_cost_estimator {
+                    Some(estimator) => {
+                        CostModel::calculate_cost_with_estimator(tx, feature_set, estimator)
+                    }
+                    None => CostModel::calculate_cost(tx, feature_set),
+                })
+            })
             .collect();
         compute_cost_time.stop();
         self.metrics
@@ -971,6 +994,7 @@ mod tests {
                         programs_execution_cost,
                         loaded_accounts_data_size_cost: 0,
                         allocated_accounts_data_size: 0,
+                        max_execution_cost_overrun: 0,
                     }))
                 } else {
                     Err(TransactionError::WouldExceedMaxBlockCostLimit)
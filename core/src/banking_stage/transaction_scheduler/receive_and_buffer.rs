@@ -14,7 +14,7 @@ use {
     core::time::Duration,
     crossbeam_channel::RecvTimeoutError,
     solana_accounts_db::account_locks::validate_account_locks,
-    solana_cost_model::cost_model::CostModel,
+    solana_cost_model::{cost_model::CostModel, program_cost_estimator::ProgramCostEstimator},
     solana_measure::measure_us,
     solana_runtime::{bank::Bank, bank_forks::BankForks},
     solana_runtime_transaction::{
@@ -52,6 +52,9 @@ pub(crate) struct SanitizedTransactionReceiveAndBuffer {
     bank_forks: Arc<RwLock<BankForks>>,
 
     forwarding_enabled: bool,
+    /// Learned execution costs used for transactions that do not request a
+    /// compute unit limit
+    program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
 }
 
 impl ReceiveAndBuffer for SanitizedTransactionReceiveAndBuffer {
@@ -135,11 +138,13 @@ impl SanitizedTransactionReceiveAndBuffer {
         packet_receiver: PacketDeserializer,
         bank_forks: Arc<RwLock<BankForks>>,
         forwarding_enabled: bool,
+        program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
     ) -> Self {
         Self {
             packet_receiver,
             bank_forks,
             forwarding_enabled,
+            program_cost_estimator,
         }
     }
 
@@ -235,8 +240,12 @@ impl SanitizedTransactionReceiveAndBuffer {
             {
                 saturating_add_assign!(post_transaction_check_count, 1);
 
-                let (priority, cost) =
-                    calculate_priority_and_cost(&transaction, &fee_budget_limits, &working_bank);
+                let (priority, cost) = calculate_priority_and_cost(
+                    &transaction,
+                    &fee_budget_limits,
+                    &working_bank,
+                    self.program_cost_estimator.as_deref(),
+                );
                 let transaction_ttl = SanitizedTransactionTTL {
                     transaction,
                     max_age,
@@ -300,8 +309,15 @@ fn calculate_priority_and_cost(
     transaction: &RuntimeTransaction<SanitizedTransaction>,
     fee_budget_limits: &FeeBudgetLimits,
     bank: &Bank,
+    program_cost_estimator: Option<&ProgramCostEstimator>,
 ) -> (u64, u64) {
-    let cost = CostModel::calculate_cost(transaction, &bank.feature_set).sum();
+    let cost = match program_cost_estimator {
+        Some(estimator) => {
+            CostModel::calculate_cost_with_estimator(transaction, &bank.feature_set, estimator)
+        }
+        None => CostModel::calculate_cost(transaction, &bank.feature_set),
+    }
+    .sum();
     let reward = bank.calculate_reward_for_transaction(transaction, fee_budget_limits);
 
     // We need a multiplier here to avoid rounding down too aggressively.
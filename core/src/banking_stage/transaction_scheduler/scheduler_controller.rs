@@ -550,6 +550,7 @@ mod tests {
             packet_deserializer,
             bank_forks.clone(),
             false,
+            None,
         );
 
         let scheduler = PrioGraphScheduler::new(
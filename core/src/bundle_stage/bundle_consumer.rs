@@ -606,6 +606,7 @@ impl BundleConsumer {
             MAX_PROCESSING_AGE,
             &max_bundle_retry_duration,
             transaction_status_sender_enabled,
+            false,
             log_messages_bytes_limit,
             false,
             None,
@@ -29,6 +29,7 @@ pub mod optimistic_confirmation_verifier;
 pub mod packet_bundle;
 pub mod poh_timing_report_service;
 pub mod poh_timing_reporter;
+pub mod program_cost_estimates_service;
 pub mod proxy;
 pub mod repair;
 pub mod replay_stage;
@@ -6,7 +6,10 @@ use {
         contact_info::{ContactInfo, Protocol},
     },
     solana_poh::poh_recorder::PohRecorder,
-    solana_sdk::{clock::FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET, pubkey::Pubkey},
+    solana_sdk::{
+        clock::{FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET, NUM_CONSECUTIVE_LEADER_SLOTS},
+        pubkey::Pubkey,
+    },
     std::{net::SocketAddr, sync::RwLock},
 };
 
@@ -61,3 +64,37 @@ where
         .lookup_contact_info(&leader_pubkey, port_selector)?
         .map(|addr| (leader_pubkey, addr))
 }
+
+/// Returns up to `num_leaders` distinct upcoming leaders and their sockets, in
+/// leader schedule order, starting with the leader that transactions are
+/// normally forwarded to. Leaders without a socket are skipped.
+pub(crate) fn upcoming_leaders<F>(
+    cluster_info: &impl LikeClusterInfo,
+    poh_recorder: &RwLock<PohRecorder>,
+    num_leaders: usize,
+    port_selector: F,
+) -> Vec<(Pubkey, SocketAddr)>
+where
+    F: Fn(&ContactInfo) -> Option<SocketAddr>,
+{
+    let lookahead_slots = (num_leaders as u64).saturating_mul(NUM_CONSECUTIVE_LEADER_SLOTS);
+    let upcoming_leaders = {
+        let poh_recorder = poh_recorder.read().unwrap();
+        (FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET
+            ..FORWARD_TRANSACTIONS_TO_LEADER_AT_SLOT_OFFSET.saturating_add(lookahead_slots))
+            .filter_map(|n_slots| poh_recorder.leader_after_n_slots(n_slots))
+            .dedup()
+            .collect_vec()
+    };
+
+    upcoming_leaders
+        .into_iter()
+        .unique()
+        .filter_map(|leader_pubkey| {
+            cluster_info
+                .lookup_contact_info(&leader_pubkey, &port_selector)?
+                .map(|addr| (leader_pubkey, addr))
+        })
+        .take(num_leaders)
+        .collect()
+}
@@ -0,0 +1,81 @@
+//! Periodically saves the program cost estimates so they survive a validator
+//! that does not shut down cleanly.
+
+use {
+    solana_cost_model::program_cost_estimator::ProgramCostEstimator,
+    std::{
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+const SAVE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const SLEEP_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct ProgramCostEstimatesService {
+    thread_hdl: JoinHandle<()>,
+    program_cost_estimator: Arc<ProgramCostEstimator>,
+    program_cost_estimates_path: PathBuf,
+}
+
+impl ProgramCostEstimatesService {
+    pub fn new(
+        program_cost_estimator: Arc<ProgramCostEstimator>,
+        program_cost_estimates_path: PathBuf,
+        exit: Arc<AtomicBool>,
+    ) -> Self {
+        let thread_hdl = Builder::new()
+            .name("solCostEstSave".to_string())
+            .spawn({
+                let program_cost_estimator = program_cost_estimator.clone();
+                let program_cost_estimates_path = program_cost_estimates_path.clone();
+                move || {
+                    let mut last_save_time = Instant::now();
+                    while !exit.load(Ordering::Relaxed) {
+                        if last_save_time.elapsed() >= SAVE_INTERVAL {
+                            last_save_time = Instant::now();
+                            Self::save(&program_cost_estimator, &program_cost_estimates_path);
+                        }
+                        sleep(SLEEP_INTERVAL);
+                    }
+                }
+            })
+            .unwrap();
+
+        Self {
+            thread_hdl,
+            program_cost_estimator,
+            program_cost_estimates_path,
+        }
+    }
+
+    fn save(program_cost_estimator: &ProgramCostEstimator, path: &Path) {
+        match program_cost_estimator.save(path) {
+            Ok(()) => info!(
+                "Saved {} program cost estimates into {}",
+                program_cost_estimator.num_entries(),
+                path.display()
+            ),
+            Err(err) => warn!(
+                "Failed to save program cost estimates to {}: {err}",
+                path.display()
+            ),
+        }
+    }
+
+    /// Stops the service and saves the estimates one last time; call it after
+    /// banking stage has stopped so the final save includes everything
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()?;
+        Self::save(
+            &self.program_cost_estimator,
+            &self.program_cost_estimates_path,
+        );
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@
 pub use solana_sdk::net::DEFAULT_TPU_COALESCE;
 use {
     crate::{
-        banking_stage::BankingStage,
+        banking_stage::{forwarder::ForwardingMode, BankingStage},
         banking_trace::{BankingTracer, Channels, TracerThread},
         bundle_stage::{bundle_account_locker::BundleAccountLocker, BundleStage},
         cluster_info_vote_listener::{
@@ -27,6 +27,7 @@ use {
     bytes::Bytes,
     crossbeam_channel::{unbounded, Receiver},
     solana_client::connection_cache::ConnectionCache,
+    solana_cost_model::program_cost_estimator::ProgramCostEstimator,
     solana_gossip::cluster_info::ClusterInfo,
     solana_ledger::{
         blockstore::Blockstore, blockstore_processor::TransactionStatusSender,
@@ -135,6 +136,8 @@ impl Tpu {
         prioritization_fee_cache: &Arc<PrioritizationFeeCache>,
         block_production_method: BlockProductionMethod,
         enable_block_production_forwarding: bool,
+        block_production_forwarding_mode: ForwardingMode,
+        program_cost_estimator: Option<Arc<ProgramCostEstimator>>,
         _generator_config: Option<GeneratorConfig>, /* vestigial code for replay invalidator */
         block_engine_config: Arc<Mutex<BlockEngineConfig>>,
         relayer_config: Arc<Mutex<RelayerConfig>>,
@@ -348,6 +351,8 @@ impl Tpu {
             bank_forks.clone(),
             prioritization_fee_cache,
             enable_block_production_forwarding,
+            block_production_forwarding_mode,
+            program_cost_estimator,
             blacklisted_accounts,
             bundle_account_locker.clone(),
         );
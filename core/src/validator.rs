@@ -5,6 +5,7 @@ use {
     crate::{
         accounts_hash_verifier::AccountsHashVerifier,
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
+        banking_stage::forwarder::ForwardingMode,
         banking_trace::{self, BankingTracer, TraceError},
         cluster_info_vote_listener::VoteTracker,
         completed_data_sets_service::CompletedDataSetsService,
@@ -14,6 +15,7 @@ use {
             ExternalRootSource, Tower,
         },
         poh_timing_report_service::PohTimingReportService,
+        program_cost_estimates_service::ProgramCostEstimatesService,
         proxy::{block_engine_stage::BlockEngineConfig, relayer_stage::RelayerConfig},
         repair::{
             self,
@@ -32,6 +34,7 @@ use {
         tpu::{Tpu, TpuSockets, DEFAULT_TPU_COALESCE},
         tvu::{Tvu, TvuConfig, TvuSockets},
     },
+    agave_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin,
     anyhow::{anyhow, Context, Result},
     crossbeam_channel::{bounded, unbounded, Receiver},
     lazy_static::lazy_static,
@@ -45,6 +48,7 @@ use {
         utils::{move_and_async_delete_path, move_and_async_delete_path_contents},
     },
     solana_client::connection_cache::{ConnectionCache, Protocol},
+    solana_cost_model::program_cost_estimator::{self, ProgramCostEstimator},
     solana_entry::poh::compute_hash_time,
     solana_geyser_plugin_manager::{
         geyser_plugin_service::GeyserPluginService, GeyserPluginManagerRequest,
@@ -91,6 +95,7 @@ use {
         },
         rpc::JsonRpcConfig,
         rpc_completed_slots_service::RpcCompletedSlotsService,
+        rpc_grpc_service::{GrpcStreamingConfig, GrpcStreamingService},
         rpc_pubsub_service::{PubSubConfig, PubSubService},
         rpc_service::JsonRpcService,
         rpc_subscriptions::RpcSubscriptions,
@@ -155,6 +160,9 @@ use {
 };
 
 const MAX_COMPLETED_DATA_SETS_IN_CHANNEL: usize = 100_000;
+/// Name of the file in the ledger directory that learned program costs are
+/// persisted to across restarts
+const PROGRAM_COST_ESTIMATES_FILE: &str = "program_cost_estimates.bin";
 const WAIT_FOR_SUPERMAJORITY_THRESHOLD_PERCENT: u64 = 80;
 // Right now since we reuse the wait for supermajority code, the
 // following threshold should always greater than or equal to
@@ -234,6 +242,9 @@ pub struct ValidatorConfig {
     pub geyser_plugin_always_enabled: bool,
     pub rpc_addrs: Option<(SocketAddr, SocketAddr)>, // (JsonRpc, JsonRpcPubSub)
     pub pubsub_config: PubSubConfig,
+    /// Address of the built-in gRPC streaming service, which is disabled if unset
+    pub grpc_streaming_addr: Option<SocketAddr>,
+    pub grpc_streaming_config: GrpcStreamingConfig,
     pub snapshot_config: SnapshotConfig,
     pub max_ledger_shreds: Option<u64>,
     pub blockstore_options: BlockstoreOptions,
@@ -280,6 +291,10 @@ pub struct ValidatorConfig {
     pub block_verification_method: BlockVerificationMethod,
     pub block_production_method: BlockProductionMethod,
     pub enable_block_production_forwarding: bool,
+    pub block_production_forwarding_mode: ForwardingMode,
+    /// Estimate the execution cost of transactions without a compute unit
+    /// limit from the observed cost of their instructions when packing blocks
+    pub enable_program_cost_estimation: bool,
     pub generator_config: Option<GeneratorConfig>,
     pub use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup,
     pub wen_restart_proto_path: Option<PathBuf>,
@@ -316,6 +331,8 @@ impl Default for ValidatorConfig {
             geyser_plugin_always_enabled: false,
             rpc_addrs: None,
             pubsub_config: PubSubConfig::default(),
+            grpc_streaming_addr: None,
+            grpc_streaming_config: GrpcStreamingConfig::default(),
             snapshot_config: SnapshotConfig::new_load_only(),
             broadcast_stage_type: BroadcastStageType::Standard,
             turbine_disabled: Arc::<AtomicBool>::default(),
@@ -358,6 +375,8 @@ impl Default for ValidatorConfig {
             block_verification_method: BlockVerificationMethod::default(),
             block_production_method: BlockProductionMethod::default(),
             enable_block_production_forwarding: false,
+            block_production_forwarding_mode: ForwardingMode::default(),
+            enable_program_cost_estimation: false,
             generator_config: None,
             use_snapshot_archives_at_startup: UseSnapshotArchivesAtStartup::default(),
             wen_restart_proto_path: None,
@@ -390,6 +409,8 @@ impl ValidatorConfig {
             rpc_config: JsonRpcConfig::default_for_test(),
             block_production_method: BlockProductionMethod::default(),
             enable_block_production_forwarding: true, // enable forwarding by default for tests
+            block_production_forwarding_mode: ForwardingMode::default(),
+            enable_program_cost_estimation: false,
             rayon_global_threads: max_thread_count,
             replay_forks_threads: NonZeroUsize::new(1).expect("1 is non-zero"),
             replay_transactions_threads: max_thread_count,
@@ -509,6 +530,7 @@ pub struct Validator {
     validator_exit: Arc<RwLock<Exit>>,
     json_rpc_service: Option<JsonRpcService>,
     pubsub_service: Option<PubSubService>,
+    grpc_streaming_service: Option<GrpcStreamingService>,
     rpc_completed_slots_service: Option<JoinHandle<()>>,
     optimistically_confirmed_bank_tracker: Option<OptimisticallyConfirmedBankTracker>,
     transaction_status_service: Option<TransactionStatusService>,
@@ -526,6 +548,7 @@ pub struct Validator {
     poh_service: PohService,
     tpu: Tpu,
     tvu: Tvu,
+    program_cost_estimates_service: Option<ProgramCostEstimatesService>,
     ip_echo_server: Option<solana_net_utils::IpEchoServer>,
     pub cluster_info: Arc<ClusterInfo>,
     pub bank_forks: Arc<RwLock<BankForks>>,
@@ -601,13 +624,31 @@ impl Validator {
 
         let exit = Arc::new(AtomicBool::new(false));
 
+        let (grpc_streaming_service, grpc_streaming_plugin) =
+            if let Some(grpc_streaming_addr) = config.grpc_streaming_addr {
+                let (trigger, grpc_streaming_service, grpc_streaming_plugin) =
+                    GrpcStreamingService::new(
+                        config.grpc_streaming_config.clone(),
+                        grpc_streaming_addr,
+                    );
+                config
+                    .validator_exit
+                    .write()
+                    .unwrap()
+                    .register_exit(Box::new(move || trigger.cancel()));
+                (Some(grpc_streaming_service), Some(grpc_streaming_plugin))
+            } else {
+                (None, None)
+            };
+
+        // The gRPC streaming service is fed by a built-in plugin, so it needs the
+        // Geyser plugin service even when no other plugins are configured
         let geyser_plugin_config_files = config
             .on_start_geyser_plugin_config_files
             .as_ref()
             .map(Cow::Borrowed)
             .or_else(|| {
-                config
-                    .geyser_plugin_always_enabled
+                (config.geyser_plugin_always_enabled || grpc_streaming_plugin.is_some())
                     .then_some(Cow::Owned(vec![]))
             });
         let geyser_plugin_service =
@@ -621,6 +662,10 @@ impl Validator {
                         confirmed_bank_receiver,
                         config.geyser_plugin_always_enabled,
                         geyser_plugin_config_files.as_ref(),
+                        grpc_streaming_plugin
+                            .into_iter()
+                            .map(|plugin| Box::new(plugin) as Box<dyn GeyserPlugin>)
+                            .collect(),
                         rpc_to_plugin_manager_receiver_and_exit,
                     )
                     .map_err(|err| {
@@ -901,6 +946,24 @@ impl Validator {
         // (by both replay stage and banking stage)
         let prioritization_fee_cache = Arc::new(PrioritizationFeeCache::default());
 
+        let program_cost_estimates_path = ledger_path.join(PROGRAM_COST_ESTIMATES_FILE);
+        let program_cost_estimator = config.enable_program_cost_estimation.then(|| {
+            Arc::new(ProgramCostEstimator::load_or_default(
+                &program_cost_estimates_path,
+                program_cost_estimator::DEFAULT_MAX_ENTRIES,
+            ))
+        });
+        let program_cost_estimates_service =
+            program_cost_estimator
+                .as_ref()
+                .map(|program_cost_estimator| {
+                    ProgramCostEstimatesService::new(
+                        program_cost_estimator.clone(),
+                        program_cost_estimates_path,
+                        exit.clone(),
+                    )
+                });
+
         match &config.block_verification_method {
             BlockVerificationMethod::BlockstoreProcessor => {
                 info!("no scheduler pool is installed for block verification...");
@@ -1561,6 +1624,8 @@ impl Validator {
             &prioritization_fee_cache,
             config.block_production_method.clone(),
             config.enable_block_production_forwarding,
+            config.block_production_forwarding_mode,
+            program_cost_estimator.clone(),
             config.generator_config.clone(),
             config.block_engine_config.clone(),
             config.relayer_config.clone(),
@@ -1603,6 +1668,7 @@ impl Validator {
             serve_repair_service,
             json_rpc_service,
             pubsub_service,
+            grpc_streaming_service,
             rpc_completed_slots_service,
             optimistically_confirmed_bank_tracker,
             transaction_status_service,
@@ -1615,6 +1681,7 @@ impl Validator {
             completed_data_sets_service,
             tpu,
             tvu,
+            program_cost_estimates_service,
             poh_service,
             poh_recorder,
             ip_echo_server,
@@ -1688,6 +1755,12 @@ impl Validator {
             pubsub_service.join().expect("pubsub_service");
         }
 
+        if let Some(grpc_streaming_service) = self.grpc_streaming_service {
+            grpc_streaming_service
+                .join()
+                .expect("grpc_streaming_service");
+        }
+
         if let Some(rpc_completed_slots_service) = self.rpc_completed_slots_service {
             rpc_completed_slots_service
                 .join()
@@ -1767,6 +1840,11 @@ impl Validator {
         }
         self.tpu.join().expect("tpu");
         self.tvu.join().expect("tvu");
+        if let Some(program_cost_estimates_service) = self.program_cost_estimates_service {
+            program_cost_estimates_service
+                .join()
+                .expect("program_cost_estimates_service");
+        }
         if let Some(turbine_quic_endpoint_join_handle) = self.turbine_quic_endpoint_join_handle {
             self.turbine_quic_endpoint_runtime
                 .map(|runtime| runtime.block_on(turbine_quic_endpoint_join_handle))
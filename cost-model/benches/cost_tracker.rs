@@ -57,6 +57,7 @@ fn get_costs(
                 programs_execution_cost: 9999,
                 loaded_accounts_data_size_cost: 0,
                 allocated_accounts_data_size: 0,
+                max_execution_cost_overrun: 0,
             })
         })
         .collect_vec()
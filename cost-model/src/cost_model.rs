@@ -6,7 +6,9 @@
 //!
 
 use {
-    crate::{block_cost_limits::*, transaction_cost::*},
+    crate::{
+        block_cost_limits::*, program_cost_estimator::ProgramCostEstimator, transaction_cost::*,
+    },
     solana_bincode::limited_deserialize,
     solana_borsh::v1::try_from_slice_unchecked,
     solana_builtins_default_costs::get_builtin_instruction_cost,
@@ -65,6 +67,42 @@ impl CostModel {
         }
     }
 
+    /// Same as `calculate_cost()`, except that transactions which do not
+    /// request a compute unit limit are charged the execution cost learned by
+    /// `estimator`, when it is available and lower than the default. Only
+    /// meant for a leader's packing decisions.
+    ///
+    /// The difference to the default is kept as the cost's
+    /// `max_execution_cost_overrun`, so the cost tracker can keep the block
+    /// within its limits whatever the transaction actually consumes.
+    pub fn calculate_cost_with_estimator<'a, Tx: TransactionWithMeta>(
+        transaction: &'a Tx,
+        feature_set: &FeatureSet,
+        estimator: &ProgramCostEstimator,
+    ) -> TransactionCost<'a, Tx> {
+        let mut transaction_cost = Self::calculate_cost(transaction, feature_set);
+        if let TransactionCost::Transaction(usage_cost_details) = &mut transaction_cost {
+            if transaction
+                .compute_budget_instruction_details()
+                .requested_compute_unit_limit()
+                .is_none()
+            {
+                if let Some(estimated_cost) = estimator.estimate_programs_execution_cost(
+                    transaction.program_instructions_iter(),
+                    feature_set,
+                ) {
+                    let default_cost = usage_cost_details.programs_execution_cost;
+                    if estimated_cost < default_cost {
+                        usage_cost_details.programs_execution_cost = estimated_cost;
+                        usage_cost_details.max_execution_cost_overrun =
+                            default_cost - estimated_cost;
+                    }
+                }
+            }
+        }
+        transaction_cost
+    }
+
     // Calculate executed transaction CU cost, with actual execution and loaded accounts size
     // costs.
     pub fn calculate_cost_for_executed_transaction<'a, Tx: TransactionWithMeta>(
@@ -145,6 +183,7 @@ impl CostModel {
             programs_execution_cost,
             loaded_accounts_data_size_cost,
             allocated_accounts_data_size,
+            max_execution_cost_overrun: 0,
         };
 
         TransactionCost::Transaction(usage_cost_details)
@@ -1059,4 +1098,61 @@ mod tests {
             assert_eq!(expected_execution_cost, programs_execution_cost);
         }
     }
+
+    #[test]
+    fn test_calculate_cost_with_estimator() {
+        let (mint_keypair, start_hash) = test_setup();
+        let program_id = Pubkey::new_unique();
+        let feature_set = FeatureSet::default();
+        let estimator = ProgramCostEstimator::default();
+
+        let build_transaction = |instructions: &[Instruction]| {
+            RuntimeTransaction::from_transaction_for_tests(Transaction::new_signed_with_payer(
+                instructions,
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair],
+                start_hash,
+            ))
+        };
+        let program_instruction = Instruction::new_with_bincode(program_id, &0_u8, vec![]);
+        let transaction = build_transaction(&[program_instruction.clone()]);
+        let limited_transaction = build_transaction(&[
+            program_instruction,
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000),
+        ]);
+
+        // without estimates, the default cost is used
+        let tx_cost =
+            CostModel::calculate_cost_with_estimator(&transaction, &feature_set, &estimator);
+        assert_eq!(
+            u64::from(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT),
+            tx_cost.programs_execution_cost()
+        );
+
+        for _ in 0..16 {
+            estimator.record_executed_transaction(
+                transaction.program_instructions_iter(),
+                1_234,
+                &feature_set,
+            );
+        }
+        let tx_cost =
+            CostModel::calculate_cost_with_estimator(&transaction, &feature_set, &estimator);
+        assert_eq!(1_234, tx_cost.programs_execution_cost());
+        assert_eq!(
+            u64::from(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT) - 1_234,
+            tx_cost.max_execution_cost_overrun()
+        );
+
+        // requested limits are always respected
+        let tx_cost = CostModel::calculate_cost_with_estimator(
+            &limited_transaction,
+            &feature_set,
+            &estimator,
+        );
+        assert_eq!(
+            CostModel::calculate_cost(&limited_transaction, &feature_set).programs_execution_cost(),
+            tx_cost.programs_execution_cost()
+        );
+    }
 }
@@ -76,6 +76,13 @@ pub struct CostTracker {
     /// removal if the transaction does not end up getting committed.
     in_flight_transaction_count: Saturating<usize>,
     secp256r1_instruction_signature_count: Saturating<u64>,
+    /// How much the execution cost of in-flight transactions that were added
+    /// with an estimated cost can exceed their estimates. The block keeps room
+    /// for it, so it stays within its limits whatever they actually consume.
+    in_flight_execution_cost_overrun: u64,
+    /// `in_flight_execution_cost_overrun` per writable account, so that only
+    /// the accounts written by those transactions keep room for it
+    in_flight_execution_cost_overrun_by_account: HashMap<Pubkey, u64, ahash::RandomState>,
 }
 
 impl Default for CostTracker {
@@ -103,6 +110,8 @@ impl Default for CostTracker {
             ed25519_instruction_signature_count: Saturating(0),
             in_flight_transaction_count: Saturating(0),
             secp256r1_instruction_signature_count: Saturating(0),
+            in_flight_execution_cost_overrun: 0,
+            in_flight_execution_cost_overrun_by_account: HashMap::default(),
         }
     }
 }
@@ -128,6 +137,8 @@ impl CostTracker {
         self.secp256k1_instruction_signature_count = Saturating(0);
         self.ed25519_instruction_signature_count = Saturating(0);
         self.in_flight_transaction_count = Saturating(0);
+        self.in_flight_execution_cost_overrun = 0;
+        self.in_flight_execution_cost_overrun_by_account.clear();
     }
 
     /// Get the overall block limit.
@@ -181,6 +192,8 @@ impl CostTracker {
         actual_execution_units: u64,
         actual_loaded_accounts_data_size_cost: u64,
     ) {
+        // the actual cost replaces the estimate, along with its overrun
+        self.sub_execution_cost_overrun(estimated_tx_cost);
         let actual_load_and_execution_units =
             actual_execution_units.saturating_add(actual_loaded_accounts_data_size_cost);
         let estimated_load_and_execution_units = estimated_tx_cost
@@ -285,7 +298,10 @@ impl CostTracker {
         &self,
         tx_cost: &TransactionCost<impl TransactionWithMeta>,
     ) -> Result<(), CostTrackerError> {
-        let cost: u64 = tx_cost.sum();
+        // there must be room for the most the transaction can end up costing
+        let cost: u64 = tx_cost
+            .sum()
+            .saturating_add(tx_cost.max_execution_cost_overrun());
 
         if tx_cost.is_simple_vote() {
             // if vote transaction, check if it exceeds vote_transaction_limit
@@ -294,7 +310,12 @@ impl CostTracker {
             }
         }
 
-        if self.block_cost.saturating_add(cost) > self.block_cost_limit {
+        if self
+            .block_cost
+            .saturating_add(self.in_flight_execution_cost_overrun)
+            .saturating_add(cost)
+            > self.block_cost_limit
+        {
             // check against the total package cost
             return Err(CostTrackerError::WouldExceedBlockMaxLimit);
         }
@@ -311,11 +332,21 @@ impl CostTracker {
             return Err(CostTrackerError::WouldExceedAccountDataBlockLimit);
         }
 
-        // check each account against account_cost_limit,
+        // check each account against account_cost_limit, leaving room for the
+        // in-flight transactions that wrote it to exceed their estimates
         for account_key in tx_cost.writable_accounts() {
             match self.cost_by_writable_accounts.get(account_key) {
                 Some(chained_cost) => {
-                    if chained_cost.saturating_add(cost) > self.account_cost_limit {
+                    let in_flight_overrun = self
+                        .in_flight_execution_cost_overrun_by_account
+                        .get(account_key)
+                        .copied()
+                        .unwrap_or_default();
+                    if chained_cost
+                        .saturating_add(in_flight_overrun)
+                        .saturating_add(cost)
+                        > self.account_cost_limit
+                    {
                         return Err(CostTrackerError::WouldExceedAccountMaxLimit);
                     } else {
                         continue;
@@ -338,6 +369,7 @@ impl CostTracker {
         self.ed25519_instruction_signature_count += tx_cost.num_ed25519_instruction_signatures();
         self.secp256r1_instruction_signature_count +=
             tx_cost.num_secp256r1_instruction_signatures();
+        self.add_execution_cost_overrun(tx_cost);
         self.add_transaction_execution_cost(tx_cost, tx_cost.sum())
     }
 
@@ -352,6 +384,49 @@ impl CostTracker {
         self.ed25519_instruction_signature_count -= tx_cost.num_ed25519_instruction_signatures();
         self.secp256r1_instruction_signature_count -=
             tx_cost.num_secp256r1_instruction_signatures();
+        self.sub_execution_cost_overrun(tx_cost);
+    }
+
+    /// Reserve room for `tx_cost` to exceed its estimated execution cost, in
+    /// the block and in each account it writes
+    fn add_execution_cost_overrun(&mut self, tx_cost: &TransactionCost<impl TransactionWithMeta>) {
+        let overrun = tx_cost.max_execution_cost_overrun();
+        if overrun == 0 {
+            return;
+        }
+        self.in_flight_execution_cost_overrun = self
+            .in_flight_execution_cost_overrun
+            .saturating_add(overrun);
+        for account_key in tx_cost.writable_accounts() {
+            let account_overrun = self
+                .in_flight_execution_cost_overrun_by_account
+                .entry(*account_key)
+                .or_insert(0);
+            *account_overrun = account_overrun.saturating_add(overrun);
+        }
+    }
+
+    /// Release the room reserved by `add_execution_cost_overrun()`
+    fn sub_execution_cost_overrun(&mut self, tx_cost: &TransactionCost<impl TransactionWithMeta>) {
+        let overrun = tx_cost.max_execution_cost_overrun();
+        if overrun == 0 {
+            return;
+        }
+        self.in_flight_execution_cost_overrun = self
+            .in_flight_execution_cost_overrun
+            .saturating_sub(overrun);
+        for account_key in tx_cost.writable_accounts() {
+            if let Some(account_overrun) = self
+                .in_flight_execution_cost_overrun_by_account
+                .get_mut(account_key)
+            {
+                *account_overrun = account_overrun.saturating_sub(overrun);
+                if *account_overrun == 0 {
+                    self.in_flight_execution_cost_overrun_by_account
+                        .remove(account_key);
+                }
+            }
+        }
     }
 
     /// Apply additional actual execution units to cost_tracker
@@ -450,6 +525,7 @@ mod tests {
             programs_execution_cost,
             loaded_accounts_data_size_cost: 0,
             allocated_accounts_data_size: 0,
+            max_execution_cost_overrun: 0,
         }
     }
 
@@ -971,4 +1047,82 @@ mod tests {
         assert_eq!(0, cost_tracker.vote_cost);
         assert_eq!(0, cost_tracker.allocated_accounts_data_size.0);
     }
+
+    #[test]
+    fn test_execution_cost_overrun() {
+        let transaction = WritableKeysTransaction(vec![Pubkey::new_unique()]);
+        let mut usage_cost = simple_usage_cost_details(&transaction, 100);
+        usage_cost.max_execution_cost_overrun = 50;
+        let tx_cost = TransactionCost::Transaction(usage_cost);
+
+        // the transaction only fits if there is room for its overrun
+        let cost_tracker = CostTracker::new(149, 149, 0);
+        assert!(cost_tracker.would_fit(&tx_cost).is_err());
+        let cost_tracker = CostTracker::new(150, 150, 0);
+        assert!(cost_tracker.would_fit(&tx_cost).is_ok());
+
+        // only the estimate is added to the block, but the overrun stays reserved
+        let mut cost_tracker = CostTracker::new(299, 299, 0);
+        assert!(cost_tracker.try_add(&tx_cost).is_ok());
+        assert_eq!(100, cost_tracker.block_cost);
+        assert_eq!(50, cost_tracker.in_flight_execution_cost_overrun);
+        assert!(cost_tracker.would_fit(&tx_cost).is_err());
+
+        // the actual cost releases the reservation
+        cost_tracker.update_execution_cost(&tx_cost, 120, 0);
+        assert_eq!(120, cost_tracker.block_cost);
+        assert_eq!(0, cost_tracker.in_flight_execution_cost_overrun);
+        assert!(cost_tracker.try_add(&tx_cost).is_ok());
+        assert_eq!(50, cost_tracker.in_flight_execution_cost_overrun);
+
+        // and so does removing the transaction
+        cost_tracker.remove(&tx_cost);
+        assert_eq!(120, cost_tracker.block_cost);
+        assert_eq!(0, cost_tracker.in_flight_execution_cost_overrun);
+        assert!(cost_tracker
+            .in_flight_execution_cost_overrun_by_account
+            .is_empty());
+    }
+
+    #[test]
+    fn test_execution_cost_overrun_disjoint_accounts() {
+        let hot_account = Pubkey::new_unique();
+        let other_account = Pubkey::new_unique();
+        let hot_transaction = WritableKeysTransaction(vec![hot_account]);
+        let mut usage_cost = simple_usage_cost_details(&hot_transaction, 100);
+        usage_cost.max_execution_cost_overrun = 100;
+        let hot_tx_cost = TransactionCost::Transaction(usage_cost);
+        let other_transaction = WritableKeysTransaction(vec![other_account]);
+        let other_tx_cost =
+            TransactionCost::Transaction(simple_usage_cost_details(&other_transaction, 150));
+
+        let mut cost_tracker = CostTracker::new(200, 1_000, 0);
+        assert!(cost_tracker.try_add(&hot_tx_cost).is_ok());
+        assert!(cost_tracker.try_add(&other_tx_cost).is_ok());
+        assert_eq!(
+            Some(&100),
+            cost_tracker
+                .in_flight_execution_cost_overrun_by_account
+                .get(&hot_account)
+        );
+
+        // the overrun of the in-flight transaction only limits the account it
+        // writes, not the unrelated one
+        let other_tx_cost =
+            TransactionCost::Transaction(simple_usage_cost_details(&other_transaction, 50));
+        assert!(cost_tracker.would_fit(&other_tx_cost).is_ok());
+        let hot_tx_cost_without_overrun =
+            TransactionCost::Transaction(simple_usage_cost_details(&hot_transaction, 50));
+        assert_eq!(
+            Err(CostTrackerError::WouldExceedAccountMaxLimit),
+            cost_tracker.would_fit(&hot_tx_cost_without_overrun)
+        );
+
+        // once the actual cost is known the reservation is released
+        cost_tracker.update_execution_cost(&hot_tx_cost, 100, 0);
+        assert!(cost_tracker
+            .in_flight_execution_cost_overrun_by_account
+            .is_empty());
+        assert!(cost_tracker.would_fit(&hot_tx_cost_without_overrun).is_ok());
+    }
 }
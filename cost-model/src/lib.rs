@@ -4,6 +4,7 @@
 pub mod block_cost_limits;
 pub mod cost_model;
 pub mod cost_tracker;
+pub mod program_cost_estimator;
 pub mod transaction_cost;
 
 #[cfg_attr(feature = "frozen-abi", macro_use)]
@@ -0,0 +1,443 @@
+//! `program_cost_estimator` learns how many compute units instructions
+//! actually consume, keyed by program id and instruction discriminator.
+//!
+//! Transactions that do not request a compute unit limit are charged the
+//! default limit per instruction by the cost model, which greatly overstates
+//! the cost of most of them. A leader can use the learned estimates instead
+//! when packing blocks. Estimates are local to the node and never affect the
+//! fees charged to a transaction.
+
+use {
+    ahash::AHashMap,
+    log::*,
+    serde_derive::{Deserialize, Serialize},
+    solana_builtins_default_costs::get_builtin_instruction_cost,
+    solana_compute_budget::compute_budget_limits::MAX_COMPUTE_UNIT_LIMIT,
+    solana_feature_set::FeatureSet,
+    solana_pubkey::Pubkey,
+    solana_svm_transaction::instruction::SVMInstruction,
+    std::{
+        fs::{self, File},
+        io::{self, BufReader, BufWriter, Write},
+        path::Path,
+        sync::RwLock,
+    },
+};
+
+/// Maximum number of leading instruction data bytes used to tell the
+/// instructions of a program apart
+pub const MAX_DISCRIMINATOR_LEN: usize = 8;
+
+/// Default maximum number of tracked (program, discriminator) pairs
+pub const DEFAULT_MAX_ENTRIES: usize = 100_000;
+
+/// Number of samples required before an estimate is used
+const MIN_SAMPLES: u64 = 8;
+
+/// New samples are given a weight of 1 / EWMA_WEIGHT
+const EWMA_WEIGHT: u64 = 16;
+
+/// Estimates are padded by this many mean absolute deviations so that
+/// variable cost instructions are not routinely underestimated
+const DEVIATION_MULTIPLIER: u64 = 2;
+
+/// Bumped whenever the persisted format changes
+const PERSISTED_VERSION: u32 = 1;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+struct InstructionKey {
+    program_id: Pubkey,
+    discriminator_len: u8,
+    discriminator: [u8; MAX_DISCRIMINATOR_LEN],
+}
+
+impl InstructionKey {
+    fn new(program_id: &Pubkey, data: &[u8], discriminator_len: usize) -> Self {
+        let discriminator_len = data.len().min(discriminator_len);
+        let mut discriminator = [0; MAX_DISCRIMINATOR_LEN];
+        discriminator[..discriminator_len].copy_from_slice(&data[..discriminator_len]);
+        Self {
+            program_id: *program_id,
+            discriminator_len: discriminator_len as u8,
+            discriminator,
+        }
+    }
+
+    /// Programs with short discriminators carry arguments in the bytes
+    /// following them (e.g. token amounts), so every instruction is tracked
+    /// under both its full-length key and its one byte key
+    fn keys(program_id: &Pubkey, data: &[u8]) -> impl Iterator<Item = Self> {
+        let full = Self::new(program_id, data, MAX_DISCRIMINATOR_LEN);
+        let short = Self::new(program_id, data, 1);
+        std::iter::once(full).chain((short != full).then_some(short))
+    }
+}
+
+/// Exponentially weighted moving average of the compute units consumed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct UnitsEstimate {
+    mean: u64,
+    mean_deviation: u64,
+    samples: u64,
+}
+
+impl UnitsEstimate {
+    fn record(&mut self, units: u64) {
+        if self.samples == 0 {
+            self.mean = units;
+        } else {
+            let deviation = units.abs_diff(self.mean);
+            self.mean = self
+                .mean
+                .saturating_mul(EWMA_WEIGHT - 1)
+                .saturating_add(units)
+                / EWMA_WEIGHT;
+            self.mean_deviation = self
+                .mean_deviation
+                .saturating_mul(EWMA_WEIGHT - 1)
+                .saturating_add(deviation)
+                / EWMA_WEIGHT;
+        }
+        self.samples = self.samples.saturating_add(1);
+    }
+
+    fn units(&self) -> Option<u64> {
+        (self.samples >= MIN_SAMPLES).then(|| {
+            self.mean
+                .saturating_add(self.mean_deviation.saturating_mul(DEVIATION_MULTIPLIER))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PersistedEstimates {
+    version: u32,
+    entries: Vec<(InstructionKey, UnitsEstimate)>,
+}
+
+#[derive(Debug)]
+pub struct ProgramCostEstimator {
+    estimates: RwLock<AHashMap<InstructionKey, UnitsEstimate>>,
+    max_entries: usize,
+}
+
+impl Default for ProgramCostEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl ProgramCostEstimator {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            estimates: RwLock::default(),
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// Loads estimates previously saved with `save()`, or starts from
+    /// scratch if `path` does not exist or cannot be read.
+    pub fn load_or_default(path: &Path, max_entries: usize) -> Self {
+        let estimator = Self::new(max_entries);
+        if !path.exists() {
+            return estimator;
+        }
+        match estimator.load(path) {
+            Ok(()) => info!(
+                "Loaded {} program cost estimates from {}",
+                estimator.num_entries(),
+                path.display()
+            ),
+            Err(err) => warn!(
+                "Failed to load program cost estimates from {}: {err}",
+                path.display()
+            ),
+        }
+        estimator
+    }
+
+    fn load(&self, path: &Path) -> io::Result<()> {
+        let file = File::open(path)?;
+        let persisted: PersistedEstimates = bincode::deserialize_from(BufReader::new(file))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if persisted.version != PERSISTED_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported version {}", persisted.version),
+            ));
+        }
+        let mut estimates = self.estimates.write().unwrap();
+        estimates.extend(persisted.entries.into_iter().take(self.max_entries));
+        Ok(())
+    }
+
+    /// Writes all estimates to `path`, replacing any existing file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let persisted = PersistedEstimates {
+            version: PERSISTED_VERSION,
+            entries: self
+                .estimates
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(key, estimate)| (*key, *estimate))
+                .collect(),
+        };
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, &persisted)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        writer.flush()?;
+        drop(writer);
+        fs::rename(&tmp_path, path)
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.estimates.read().unwrap().len()
+    }
+
+    /// Returns the estimated execution cost of `instructions`, or None if
+    /// any non-builtin instruction has not been observed often enough.
+    /// Builtin instructions are charged their default cost.
+    pub fn estimate_programs_execution_cost<'a>(
+        &self,
+        instructions: impl Iterator<Item = (&'a Pubkey, SVMInstruction<'a>)>,
+        feature_set: &FeatureSet,
+    ) -> Option<u64> {
+        let estimates = self.estimates.read().unwrap();
+        let mut programs_execution_cost = 0u64;
+        for (program_id, instruction) in instructions {
+            let units = match get_builtin_instruction_cost(program_id, feature_set) {
+                Some(builtin_cost) => builtin_cost,
+                None => InstructionKey::keys(program_id, instruction.data)
+                    .find_map(|key| estimates.get(&key).and_then(UnitsEstimate::units))?,
+            };
+            programs_execution_cost = programs_execution_cost.saturating_add(units);
+        }
+        Some(programs_execution_cost.min(u64::from(MAX_COMPUTE_UNIT_LIMIT)))
+    }
+
+    /// Records the compute units consumed by a successfully executed
+    /// transaction. The units left after subtracting the cost of builtin
+    /// instructions are split evenly across the remaining instructions.
+    pub fn record_executed_transaction<'a>(
+        &self,
+        instructions: impl Iterator<Item = (&'a Pubkey, SVMInstruction<'a>)>,
+        executed_units: u64,
+        feature_set: &FeatureSet,
+    ) {
+        let mut builtin_units = 0u64;
+        let mut non_builtin_instructions = Vec::new();
+        for (program_id, instruction) in instructions {
+            match get_builtin_instruction_cost(program_id, feature_set) {
+                Some(builtin_cost) => builtin_units = builtin_units.saturating_add(builtin_cost),
+                None => non_builtin_instructions.push((program_id, instruction.data)),
+            }
+        }
+        if non_builtin_instructions.is_empty() {
+            return;
+        }
+        let units_per_instruction =
+            executed_units.saturating_sub(builtin_units) / non_builtin_instructions.len() as u64;
+
+        let mut estimates = self.estimates.write().unwrap();
+        for (program_id, data) in non_builtin_instructions {
+            for key in InstructionKey::keys(program_id, data) {
+                if estimates.len() >= self.max_entries && !estimates.contains_key(&key) {
+                    Self::evict(&mut estimates, self.max_entries / 2);
+                }
+                estimates
+                    .entry(key)
+                    .or_default()
+                    .record(units_per_instruction);
+            }
+        }
+    }
+
+    /// Shrinks `estimates` to `target_len` entries, keeping those backed by
+    /// the most samples
+    fn evict(estimates: &mut AHashMap<InstructionKey, UnitsEstimate>, target_len: usize) {
+        let mut entries: Vec<_> = estimates
+            .iter()
+            .map(|(key, estimate)| (estimate.samples, *key))
+            .collect();
+        entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        for (_samples, key) in entries.into_iter().skip(target_len) {
+            estimates.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk_ids::system_program, tempfile::TempDir};
+
+    fn instruction(data: &[u8]) -> SVMInstruction {
+        SVMInstruction {
+            program_id_index: 0,
+            accounts: &[],
+            data,
+        }
+    }
+
+    #[test]
+    fn test_estimate_requires_min_samples() {
+        let estimator = ProgramCostEstimator::default();
+        let feature_set = FeatureSet::default();
+        let program_id = Pubkey::new_unique();
+        let data = [3u8; 12];
+
+        for _ in 0..MIN_SAMPLES - 1 {
+            estimator.record_executed_transaction(
+                [(&program_id, instruction(&data))].into_iter(),
+                10_000,
+                &feature_set,
+            );
+        }
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [(&program_id, instruction(&data))].into_iter(),
+                &feature_set
+            ),
+            None
+        );
+
+        estimator.record_executed_transaction(
+            [(&program_id, instruction(&data))].into_iter(),
+            10_000,
+            &feature_set,
+        );
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [(&program_id, instruction(&data))].into_iter(),
+                &feature_set
+            ),
+            Some(10_000)
+        );
+
+        // unseen discriminators fall back to the one byte key
+        let other_data = [3u8, 1, 2];
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [(&program_id, instruction(&other_data))].into_iter(),
+                &feature_set
+            ),
+            Some(10_000)
+        );
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [(&program_id, instruction(&[4u8]))].into_iter(),
+                &feature_set
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_record_excludes_builtin_units() {
+        let estimator = ProgramCostEstimator::default();
+        let feature_set = FeatureSet::default();
+        let program_id = Pubkey::new_unique();
+        let builtin_cost =
+            get_builtin_instruction_cost(&system_program::id(), &feature_set).unwrap();
+
+        for _ in 0..MIN_SAMPLES {
+            estimator.record_executed_transaction(
+                [
+                    (&system_program::id(), instruction(&[2])),
+                    (&program_id, instruction(&[1])),
+                    (&program_id, instruction(&[1])),
+                ]
+                .into_iter(),
+                builtin_cost + 2 * 5_000,
+                &feature_set,
+            );
+        }
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [
+                    (&system_program::id(), instruction(&[2])),
+                    (&program_id, instruction(&[1])),
+                ]
+                .into_iter(),
+                &feature_set
+            ),
+            Some(builtin_cost + 5_000)
+        );
+    }
+
+    #[test]
+    fn test_units_estimate_pads_variable_costs() {
+        let mut estimate = UnitsEstimate::default();
+        for i in 0..64 {
+            estimate.record(if i % 2 == 0 { 1_000 } else { 3_000 });
+        }
+        let units = estimate.units().unwrap();
+        assert!(units > estimate.mean);
+        assert!(units >= 3_000, "{units}");
+    }
+
+    #[test]
+    fn test_eviction_keeps_most_sampled() {
+        let estimator = ProgramCostEstimator::new(4);
+        let feature_set = FeatureSet::default();
+        let frequent = Pubkey::new_unique();
+        for _ in 0..MIN_SAMPLES {
+            estimator.record_executed_transaction(
+                [(&frequent, instruction(&[0]))].into_iter(),
+                1_000,
+                &feature_set,
+            );
+        }
+        for _ in 0..4 {
+            let program_id = Pubkey::new_unique();
+            estimator.record_executed_transaction(
+                [(&program_id, instruction(&[0]))].into_iter(),
+                1_000,
+                &feature_set,
+            );
+        }
+        assert!(estimator.num_entries() <= 4);
+        assert_eq!(
+            estimator.estimate_programs_execution_cost(
+                [(&frequent, instruction(&[0]))].into_iter(),
+                &feature_set
+            ),
+            Some(1_000)
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let estimator = ProgramCostEstimator::default();
+        let feature_set = FeatureSet::default();
+        let program_id = Pubkey::new_unique();
+        for _ in 0..MIN_SAMPLES {
+            estimator.record_executed_transaction(
+                [(&program_id, instruction(&[7, 7]))].into_iter(),
+                42_000,
+                &feature_set,
+            );
+        }
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("program_cost_estimates.bin");
+        estimator.save(&path).unwrap();
+
+        let loaded = ProgramCostEstimator::load_or_default(&path, DEFAULT_MAX_ENTRIES);
+        assert_eq!(loaded.num_entries(), estimator.num_entries());
+        assert_eq!(
+            loaded.estimate_programs_execution_cost(
+                [(&program_id, instruction(&[7, 7]))].into_iter(),
+                &feature_set
+            ),
+            Some(42_000)
+        );
+
+        let missing = ProgramCostEstimator::load_or_default(
+            &dir.path().join("missing.bin"),
+            DEFAULT_MAX_ENTRIES,
+        );
+        assert_eq!(missing.num_entries(), 0);
+    }
+}
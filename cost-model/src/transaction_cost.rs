@@ -48,6 +48,13 @@ impl<'a, Tx> TransactionCost<'a, Tx> {
         }
     }
 
+    pub fn max_execution_cost_overrun(&self) -> u64 {
+        match self {
+            Self::SimpleVote { .. } => 0,
+            Self::Transaction(usage_cost) => usage_cost.max_execution_cost_overrun,
+        }
+    }
+
     pub fn is_simple_vote(&self) -> bool {
         match self {
             Self::SimpleVote { .. } => true,
@@ -158,6 +165,9 @@ pub struct UsageCostDetails<'a, Tx> {
     pub programs_execution_cost: u64,
     pub loaded_accounts_data_size_cost: u64,
     pub allocated_accounts_data_size: u64,
+    /// How much the actual execution cost can exceed `programs_execution_cost`, which is only
+    /// nonzero when that is an estimate lower than the transaction's compute unit limit
+    pub max_execution_cost_overrun: u64,
 }
 
 impl<'a, Tx> UsageCostDetails<'a, Tx> {
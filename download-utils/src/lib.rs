@@ -68,6 +68,7 @@ pub fn download_snapshot_archive(
 
     for archive_format in [
         ArchiveFormat::TarZstd,
+        ArchiveFormat::TarZstdSeekable,
         ArchiveFormat::TarGzip,
         ArchiveFormat::TarBzip2,
         ArchiveFormat::TarLz4,
@@ -165,6 +165,9 @@ impl AccountsUpdateNotifierImpl {
             return;
         }
         for plugin in plugin_manager.plugins.iter() {
+            if !is_startup && !plugin.account_data_notifications_enabled() {
+                continue;
+            }
             let mut measure = Measure::start("geyser-plugin-update-account");
             match plugin.update_account(
                 ReplicaAccountInfoVersions::V0_0_3(&account),
@@ -22,8 +22,10 @@ pub struct LoadedGeyserPlugin {
     // any code before returning from Drop. This means if the Geyser plugins
     // spawn threads that access the Library, those threads must be `join`ed
     // before the Geyser plugin returns from on_unload / Drop.
+    //
+    // Built-in plugins are linked into the validator and have no library.
     #[allow(dead_code)]
-    library: Library,
+    library: Option<Library>,
 }
 
 impl LoadedGeyserPlugin {
@@ -31,13 +33,27 @@ impl LoadedGeyserPlugin {
         Self {
             name: name.unwrap_or_else(|| plugin.name().to_owned()),
             plugin,
-            library,
+            library: Some(library),
+        }
+    }
+
+    /// Wraps a plugin that is compiled into the validator rather than loaded
+    /// from a shared library
+    pub fn new_builtin(plugin: Box<dyn GeyserPlugin>) -> Self {
+        Self {
+            name: plugin.name().to_owned(),
+            plugin,
+            library: None,
         }
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    pub fn is_builtin(&self) -> bool {
+        self.library.is_none()
+    }
 }
 
 impl Deref for LoadedGeyserPlugin {
@@ -192,6 +208,14 @@ impl GeyserPluginManager {
             });
         };
 
+        if self.plugins[idx].is_builtin() {
+            return Err(jsonrpc_core::error::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("The plugin {name} is built in and can not be unloaded"),
+                data: None,
+            });
+        }
+
         // Unload and drop plugin and lib
         self._drop_plugin(idx);
 
@@ -216,6 +240,14 @@ impl GeyserPluginManager {
             });
         };
 
+        if self.plugins[idx].is_builtin() {
+            return Err(jsonrpc_core::error::Error {
+                code: ErrorCode::InvalidRequest,
+                message: format!("The plugin {name} is built in and can not be reloaded"),
+                data: None,
+            });
+        }
+
         // Unload and drop current plugin first in case plugin requires exclusive access to resource,
         // such as a particular port or database.
         self._drop_plugin(idx);
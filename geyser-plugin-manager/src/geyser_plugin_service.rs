@@ -4,11 +4,14 @@ use {
         block_metadata_notifier::BlockMetadataNotifierImpl,
         block_metadata_notifier_interface::BlockMetadataNotifierArc,
         entry_notifier::EntryNotifierImpl,
-        geyser_plugin_manager::{GeyserPluginManager, GeyserPluginManagerRequest},
+        geyser_plugin_manager::{
+            GeyserPluginManager, GeyserPluginManagerRequest, LoadedGeyserPlugin,
+        },
         slot_status_notifier::SlotStatusNotifierImpl,
         slot_status_observer::SlotStatusObserver,
         transaction_notifier::TransactionNotifierImpl,
     },
+    agave_geyser_plugin_interface::geyser_plugin_interface::GeyserPlugin,
     crossbeam_channel::Receiver,
     log::*,
     solana_accounts_db::accounts_update_notifier_interface::AccountsUpdateNotifier,
@@ -65,14 +68,20 @@ impl GeyserPluginService {
             confirmed_bank_receiver,
             geyser_plugin_always_enabled,
             geyser_plugin_config_files,
+            Vec::default(),
             None,
         )
     }
 
+    /// Same as `new`, additionally accepting plugins compiled into the validator
+    /// (`builtin_plugins`), which are registered ahead of the plugins loaded from
+    /// `geyser_plugin_config_files` and can not be unloaded or reloaded, and the
+    /// receiver for admin RPC requests to the plugin manager.
     pub fn new_with_receiver(
         confirmed_bank_receiver: Receiver<SlotNotification>,
         geyser_plugin_always_enabled: bool,
         geyser_plugin_config_files: &[PathBuf],
+        builtin_plugins: Vec<Box<dyn GeyserPlugin>>,
         rpc_to_plugin_manager_receiver_and_exit: Option<(
             Receiver<GeyserPluginManagerRequest>,
             Arc<AtomicBool>,
@@ -84,16 +93,29 @@ impl GeyserPluginService {
         );
         let mut plugin_manager = GeyserPluginManager::new();
 
+        // Built-in plugins may only want account data and transactions while they have clients,
+        // so their notifiers are always created and ask them again before every notification
+        let builtin_plugins_registered = !builtin_plugins.is_empty();
+        for builtin_plugin in builtin_plugins {
+            info!("Registering built-in plugin {}", builtin_plugin.name());
+            plugin_manager
+                .plugins
+                .push(LoadedGeyserPlugin::new_builtin(builtin_plugin));
+        }
+
         for geyser_plugin_config_file in geyser_plugin_config_files {
             Self::load_plugin(&mut plugin_manager, geyser_plugin_config_file)?;
         }
 
-        let account_data_notifications_enabled =
-            plugin_manager.account_data_notifications_enabled() || geyser_plugin_always_enabled;
+        let account_data_notifications_enabled = plugin_manager
+            .account_data_notifications_enabled()
+            || geyser_plugin_always_enabled
+            || builtin_plugins_registered;
         let account_data_snapshot_notifications_enabled =
             plugin_manager.account_data_snapshot_notifications_enabled();
-        let transaction_notifications_enabled =
-            plugin_manager.transaction_notifications_enabled() || geyser_plugin_always_enabled;
+        let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled()
+            || geyser_plugin_always_enabled
+            || builtin_plugins_registered;
         let entry_notifications_enabled =
             plugin_manager.entry_notifications_enabled() || geyser_plugin_always_enabled;
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
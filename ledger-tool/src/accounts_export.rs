@@ -0,0 +1,503 @@
+//! Streaming export of the accounts in snapshot archives to Parquet or CSV files
+//!
+//! The account storages unpacked from the archives are read directly, without loading a bank or
+//! building an accounts index. A single pass over the storages splits the accounts into pubkey
+//! bins, spilling each bin to disk. The bins are then exported one at a time, each to its own
+//! file, so memory use is bounded by the size of one bin rather than by the number of accounts in
+//! the snapshot.
+
+use {
+    crate::error::{LedgerToolError, Result},
+    base64::{prelude::BASE64_STANDARD, Engine},
+    log::*,
+    parquet::{
+        basic::{Compression, ZstdLevel},
+        data_type::{BoolType, ByteArray, ByteArrayType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    },
+    rayon::prelude::*,
+    solana_accounts_db::{accounts_db::AccountStorageEntry, pubkey_bins::PubkeyBinCalculator24},
+    solana_sdk::{account::ReadableAccount, clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{hash_map::Entry, HashMap, HashSet},
+        fs::{self, File},
+        io::{BufRead, BufReader, BufWriter, Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Number of rows in each Parquet row group
+const PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = LedgerToolError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "parquet" => Ok(Self::Parquet),
+            "csv" => Ok(Self::Csv),
+            _ => Err(LedgerToolError::BadArgument(format!(
+                "unknown export format: {s}"
+            ))),
+        }
+    }
+}
+
+/// Which accounts to export, applied to the latest version of each account
+#[derive(Debug, Default)]
+pub struct ExportFilter {
+    pub owners: Option<HashSet<Pubkey>>,
+    pub min_data_len: Option<usize>,
+    pub max_data_len: Option<usize>,
+}
+
+impl ExportFilter {
+    fn matches(&self, account: &impl ReadableAccount) -> bool {
+        self.owners
+            .as_ref()
+            .map_or(true, |owners| owners.contains(account.owner()))
+            && self
+                .min_data_len
+                .map_or(true, |min_data_len| account.data().len() >= min_data_len)
+            && self
+                .max_data_len
+                .map_or(true, |max_data_len| account.data().len() <= max_data_len)
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportOptions {
+    pub output_dir: PathBuf,
+    pub format: ExportFormat,
+    pub filter: ExportFilter,
+    pub include_data: bool,
+    /// Number of pubkey bins, and so of output files; must be a power of two
+    pub num_bins: usize,
+    /// Directory for the per-bin spill files, which are removed once their bin is exported
+    pub spill_dir: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub num_accounts: usize,
+    pub num_files: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ExportedAccount {
+    pubkey: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data_len: u64,
+    executable: bool,
+    rent_epoch: u64,
+    /// Slot the account was last written in
+    slot: Slot,
+    data: Option<Vec<u8>>,
+}
+
+/// The latest version of each account in a bin, which is None if that version was filtered out
+type BinVersions = HashMap<Pubkey, (Slot, Option<ExportedAccount>)>;
+
+/// Size of the fixed part of a spilled account version: pubkey, slot, and whether it is exported
+const SPILL_HEADER_SIZE: usize = 32 + 8 + 1;
+/// Size of the fixed part of a spilled exported account: owner, lamports, data_len, executable,
+/// rent_epoch, and whether its data is included
+const SPILL_ACCOUNT_SIZE: usize = 32 + 8 + 8 + 1 + 8 + 1;
+
+/// Appends one account version to a bin's spill buffer
+fn spill_version(
+    buffer: &mut Vec<u8>,
+    pubkey: &Pubkey,
+    slot: Slot,
+    account: Option<&ExportedAccount>,
+) {
+    buffer.extend_from_slice(pubkey.as_ref());
+    buffer.extend_from_slice(&slot.to_le_bytes());
+    buffer.push(account.is_some() as u8);
+    if let Some(account) = account {
+        buffer.extend_from_slice(account.owner.as_ref());
+        buffer.extend_from_slice(&account.lamports.to_le_bytes());
+        buffer.extend_from_slice(&account.data_len.to_le_bytes());
+        buffer.push(account.executable as u8);
+        buffer.extend_from_slice(&account.rent_epoch.to_le_bytes());
+        buffer.push(account.data.is_some() as u8);
+        if let Some(data) = &account.data {
+            buffer.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            buffer.extend_from_slice(data);
+        }
+    }
+}
+
+/// Reads back a bin's spill file, keeping the latest version of each account
+///
+/// Versions of an account in the same slot are spilled in the order they were written to the
+/// slot's storage, so the last one wins.
+fn read_bin_spill(path: &Path) -> Result<BinVersions> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut versions = BinVersions::new();
+    let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+    let read_pubkey = |bytes: &[u8]| Pubkey::new_from_array(bytes.try_into().unwrap());
+    let mut header = [0u8; SPILL_HEADER_SIZE];
+    while !reader.fill_buf()?.is_empty() {
+        reader.read_exact(&mut header)?;
+        let pubkey = read_pubkey(&header[0..32]);
+        let slot = read_u64(&header[32..40]);
+        let account = if header[40] != 0 {
+            let mut fields = [0u8; SPILL_ACCOUNT_SIZE];
+            reader.read_exact(&mut fields)?;
+            let data = if fields[57] != 0 {
+                let mut data_len = [0u8; 8];
+                reader.read_exact(&mut data_len)?;
+                let mut data = vec![0u8; u64::from_le_bytes(data_len) as usize];
+                reader.read_exact(&mut data)?;
+                Some(data)
+            } else {
+                None
+            };
+            Some(ExportedAccount {
+                pubkey,
+                owner: read_pubkey(&fields[0..32]),
+                lamports: read_u64(&fields[32..40]),
+                data_len: read_u64(&fields[40..48]),
+                executable: fields[48] != 0,
+                rent_epoch: read_u64(&fields[49..57]),
+                slot,
+                data,
+            })
+        } else {
+            None
+        };
+        match versions.entry(pubkey) {
+            Entry::Occupied(mut entry) => {
+                if slot >= entry.get().0 {
+                    entry.insert((slot, account));
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((slot, account));
+            }
+        }
+    }
+    Ok(versions)
+}
+
+/// Exports the accounts in `storages`, which should cover every slot of a snapshot, to one file
+/// per pubkey bin in `options.output_dir`
+///
+/// The storages are scanned once, appending every account version to the spill file of its bin
+/// in `options.spill_dir`.  Each bin's spill file is then read back on its own to pick the latest
+/// version of its accounts.
+pub fn export_accounts(
+    storages: &[(Slot, Arc<AccountStorageEntry>)],
+    options: &ExportOptions,
+) -> Result<ExportSummary> {
+    fs::create_dir_all(&options.output_dir)?;
+    fs::create_dir_all(&options.spill_dir)?;
+    let bin_calculator = PubkeyBinCalculator24::new(options.num_bins);
+    let spill_paths: Vec<_> = (0..options.num_bins)
+        .map(|bin| options.spill_dir.join(format!("accounts-{bin:06}.spill")))
+        .collect();
+    let spill_writers = spill_paths
+        .iter()
+        .map(|path| Ok(Mutex::new(BufWriter::new(File::create(path)?))))
+        .collect::<Result<Vec<_>>>()?;
+
+    storages.par_iter().try_for_each(|(slot, storage)| {
+        let mut bin_buffers = HashMap::<usize, Vec<u8>>::new();
+        storage.accounts.scan_accounts(|account| {
+            // zero-lamport accounts have been deleted
+            let exported =
+                (account.lamports() != 0 && options.filter.matches(&account)).then(|| {
+                    ExportedAccount {
+                        pubkey: *account.pubkey(),
+                        owner: *account.owner(),
+                        lamports: account.lamports(),
+                        data_len: account.data().len() as u64,
+                        executable: account.executable(),
+                        rent_epoch: account.rent_epoch(),
+                        slot: *slot,
+                        data: options.include_data.then(|| account.data().to_vec()),
+                    }
+                });
+            spill_version(
+                bin_buffers
+                    .entry(bin_calculator.bin_from_pubkey(account.pubkey()))
+                    .or_default(),
+                account.pubkey(),
+                *slot,
+                exported.as_ref(),
+            );
+        });
+        bin_buffers
+            .into_iter()
+            .try_for_each(|(bin, buffer)| spill_writers[bin].lock().unwrap().write_all(&buffer))
+    })?;
+    for writer in spill_writers {
+        writer.into_inner().unwrap().flush()?;
+    }
+
+    let mut summary = ExportSummary::default();
+    for (bin, spill_path) in spill_paths.iter().enumerate() {
+        let versions = read_bin_spill(spill_path)?;
+        fs::remove_file(spill_path)?;
+
+        let mut accounts: Vec<_> = versions
+            .into_values()
+            .filter_map(|(_slot, account)| account)
+            .collect();
+        if accounts.is_empty() {
+            continue;
+        }
+        accounts.par_sort_unstable_by(|a, b| a.pubkey.cmp(&b.pubkey));
+
+        let path = options
+            .output_dir
+            .join(format!("accounts-{bin:06}.{}", options.format.extension()));
+        match options.format {
+            ExportFormat::Parquet => write_parquet(&path, &accounts, options.include_data)?,
+            ExportFormat::Csv => write_csv(&path, &accounts, options.include_data)?,
+        }
+        info!(
+            "Exported {} accounts of bin {bin} to {}",
+            accounts.len(),
+            path.display()
+        );
+        summary.num_accounts += accounts.len();
+        summary.num_files += 1;
+    }
+    Ok(summary)
+}
+
+fn write_parquet(path: &Path, accounts: &[ExportedAccount], include_data: bool) -> Result<()> {
+    let schema = format!(
+        "message account {{
+            REQUIRED BYTE_ARRAY pubkey (STRING);
+            REQUIRED BYTE_ARRAY owner (STRING);
+            REQUIRED INT64 lamports (INTEGER(64,false));
+            REQUIRED INT64 data_len (INTEGER(64,false));
+            REQUIRED BOOLEAN executable;
+            REQUIRED INT64 rent_epoch (INTEGER(64,false));
+            REQUIRED INT64 slot (INTEGER(64,false));
+            {}
+        }}",
+        if include_data {
+            "REQUIRED BYTE_ARRAY data;"
+        } else {
+            ""
+        },
+    );
+    let schema = Arc::new(parse_message_type(&schema)?);
+    let properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build(),
+    );
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+    let pubkey_column = |pubkey: &Pubkey| ByteArray::from(pubkey.to_string().into_bytes());
+    for chunk in accounts.chunks(PARQUET_ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group()?;
+        let mut column_index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            match column_index {
+                0 => column.typed::<ByteArrayType>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| pubkey_column(&account.pubkey))
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                1 => column.typed::<ByteArrayType>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| pubkey_column(&account.owner))
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                2 => column.typed::<Int64Type>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| account.lamports as i64)
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                3 => column.typed::<Int64Type>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| account.data_len as i64)
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                4 => column.typed::<BoolType>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| account.executable)
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                5 => column.typed::<Int64Type>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| account.rent_epoch as i64)
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                6 => column.typed::<Int64Type>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| account.slot as i64)
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                7 => column.typed::<ByteArrayType>().write_batch(
+                    &chunk
+                        .iter()
+                        .map(|account| ByteArray::from(account.data.clone().unwrap_or_default()))
+                        .collect::<Vec<_>>(),
+                    None,
+                    None,
+                )?,
+                _ => unreachable!(),
+            };
+            column.close()?;
+            column_index += 1;
+        }
+        row_group.close()?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+fn write_csv(path: &Path, accounts: &[ExportedAccount], include_data: bool) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    let mut header = vec![
+        "pubkey",
+        "owner",
+        "lamports",
+        "data_len",
+        "executable",
+        "rent_epoch",
+        "slot",
+    ];
+    if include_data {
+        header.push("data");
+    }
+    writer.write_record(&header)?;
+    for account in accounts {
+        let mut record = vec![
+            account.pubkey.to_string(),
+            account.owner.to_string(),
+            account.lamports.to_string(),
+            account.data_len.to_string(),
+            account.executable.to_string(),
+            account.rent_epoch.to_string(),
+            account.slot.to_string(),
+        ];
+        if include_data {
+            record.push(BASE64_STANDARD.encode(account.data.as_deref().unwrap_or_default()));
+        }
+        writer.write_record(&record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::account::AccountSharedData};
+
+    #[test]
+    fn test_export_filter() {
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(1, 10, &owner);
+
+        assert!(ExportFilter::default().matches(&account));
+        let filter = ExportFilter {
+            owners: Some(HashSet::from([owner])),
+            min_data_len: Some(10),
+            max_data_len: Some(10),
+        };
+        assert!(filter.matches(&account));
+        let filter = ExportFilter {
+            owners: Some(HashSet::from([Pubkey::new_unique()])),
+            ..ExportFilter::default()
+        };
+        assert!(!filter.matches(&account));
+        let filter = ExportFilter {
+            min_data_len: Some(11),
+            ..ExportFilter::default()
+        };
+        assert!(!filter.matches(&account));
+        let filter = ExportFilter {
+            max_data_len: Some(9),
+            ..ExportFilter::default()
+        };
+        assert!(!filter.matches(&account));
+    }
+
+    #[test]
+    fn test_bin_spill() {
+        let pubkey = Pubkey::new_unique();
+        let other_pubkey = Pubkey::new_unique();
+        let exported = |pubkey, slot, data: Option<Vec<u8>>| ExportedAccount {
+            pubkey,
+            owner: Pubkey::new_unique(),
+            lamports: 1 + slot,
+            data_len: 3,
+            executable: slot % 2 == 0,
+            rent_epoch: u64::MAX,
+            slot,
+            data,
+        };
+
+        let mut buffer = Vec::new();
+        let newest = exported(pubkey, 3, Some(vec![1, 2, 3]));
+        let other = exported(other_pubkey, 1, None);
+        spill_version(&mut buffer, &pubkey, 3, Some(&exported(pubkey, 3, None)));
+        spill_version(&mut buffer, &pubkey, 1, Some(&exported(pubkey, 1, None)));
+        // a later version in the same slot wins
+        spill_version(&mut buffer, &pubkey, 3, Some(&newest));
+        spill_version(&mut buffer, &other_pubkey, 1, Some(&other));
+        let spill_file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(spill_file.path(), &buffer).unwrap();
+        let versions = read_bin_spill(spill_file.path()).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[&pubkey], (3, Some(newest)));
+        assert_eq!(versions[&other_pubkey], (1, Some(other)));
+
+        // the newest version wins even if it was filtered out
+        spill_version(&mut buffer, &pubkey, 4, None);
+        fs::write(spill_file.path(), &buffer).unwrap();
+        let versions = read_bin_spill(spill_file.path()).unwrap();
+        assert_eq!(versions[&pubkey], (4, None));
+
+        // a truncated spill file is an error
+        fs::write(spill_file.path(), &buffer[..buffer.len() - 1]).unwrap();
+        assert!(read_bin_spill(spill_file.path()).is_err());
+    }
+}
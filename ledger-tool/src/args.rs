@@ -117,7 +117,7 @@ pub fn accounts_db_args<'a, 'b>() -> Box<[Arg<'a, 'b>]> {
             .long("accounts-db-squash-storages-method")
             .value_name("METHOD")
             .takes_value(true)
-            .possible_values(&["pack", "append"])
+            .possible_values(&["pack", "pack-cold", "append"])
             .help("Squash multiple account storage files together using this method")
             .hidden(hidden_unless_forced()),
         Arg::with_name("accounts_db_access_storages_method")
@@ -325,6 +325,7 @@ pub fn get_accounts_db_config(
         .value_of("accounts_db_squash_storages_method")
         .map(|method| match method {
             "pack" => CreateAncientStorage::Pack,
+            "pack-cold" => CreateAncientStorage::PackCold,
             "append" => CreateAncientStorage::Append,
             _ => {
                 // clap will enforce one of the above values is given
@@ -28,6 +28,7 @@ use {
     solana_entry::entry::{create_ticks, Entry},
     solana_ledger::{
         bigtable_upload::ConfirmedBlockUploadConfig,
+        bigtable_verify::VerifyAndRepairStats,
         blockstore::Blockstore,
         blockstore_options::AccessType,
         shred::{ProcessShredsStats, ReedSolomonCache, Shredder},
@@ -98,6 +99,61 @@ async fn upload(
     Ok(())
 }
 
+async fn verify_and_repair(
+    blockstore: Blockstore,
+    starting_slot: Option<Slot>,
+    ending_slot: Option<Slot>,
+    dry_run: bool,
+    config: solana_storage_bigtable::LedgerStorageConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let bigtable = solana_storage_bigtable::LedgerStorage::new_with_config(config)
+        .await
+        .map_err(|err| format!("Failed to connect to storage: {err:?}"))?;
+
+    let starting_slot = match starting_slot {
+        Some(slot) => slot,
+        None => blockstore.get_first_available_block()?,
+    };
+    let ending_slot = ending_slot.unwrap_or_else(|| blockstore.max_root());
+
+    let VerifyAndRepairStats {
+        num_blocks_verified,
+        num_blocks_repaired,
+        mismatched_slots,
+        failed_slots,
+    } = solana_ledger::bigtable_verify::verify_and_repair_confirmed_blocks(
+        Arc::new(blockstore),
+        bigtable,
+        starting_slot,
+        ending_slot,
+        dry_run,
+        Arc::new(AtomicBool::new(false)),
+    )
+    .await?;
+
+    println!(
+        "{}",
+        json!({
+            "starting_slot": json!(starting_slot),
+            "ending_slot": json!(ending_slot),
+            "num_verified_slots": json!(num_blocks_verified),
+            "num_repaired_slots": json!(num_blocks_repaired),
+            "mismatched_slots": json!(mismatched_slots),
+            "failed_slots": json!(failed_slots),
+        })
+    );
+
+    if failed_slots.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} slots could not be verified or repaired",
+            failed_slots.len()
+        )
+        .into())
+    }
+}
+
 async fn delete_slots(
     slots: Vec<Slot>,
     config: solana_storage_bigtable::LedgerStorageConfig,
@@ -635,10 +691,12 @@ struct CopyArgs {
 
     force: bool,
     dry_run: bool,
+
+    object_store_url: Option<String>,
 }
 
 impl CopyArgs {
-    pub fn process(arg_matches: &ArgMatches) -> Self {
+    pub fn process(arg_matches: &ArgMatches, object_store_url: Option<String>) -> Self {
         CopyArgs {
             from_slot: value_t!(arg_matches, "starting_slot", Slot).unwrap_or(0),
             to_slot: value_t!(arg_matches, "ending_slot", Slot).ok(),
@@ -668,6 +726,8 @@ impl CopyArgs {
 
             force: arg_matches.is_present("force"),
             dry_run: arg_matches.is_present("dry_run"),
+
+            object_store_url,
         }
     }
 }
@@ -688,6 +748,7 @@ async fn copy(args: CopyArgs) -> Result<(), Box<dyn std::error::Error>> {
         timeout: None,
         emulated_source: args.emulated_source,
         crediential_path: args.source_credential_path,
+        object_store_url: args.object_store_url.clone(),
     })
     .await?;
 
@@ -698,6 +759,7 @@ async fn copy(args: CopyArgs) -> Result<(), Box<dyn std::error::Error>> {
         timeout: None,
         emulated_source: args.emulated_destination,
         crediential_path: args.destination_credential_path,
+        object_store_url: args.object_store_url,
     })
     .await?;
 
@@ -859,6 +921,7 @@ struct GetBigtableArgs {
     timeout: Option<std::time::Duration>,
     emulated_source: Option<String>,
     crediential_path: Option<String>,
+    object_store_url: Option<String>,
 }
 
 async fn get_bigtable(
@@ -880,6 +943,7 @@ async fn get_bigtable(
                 instance_name: args.instance_name,
                 app_profile_id: args.app_profile_id,
                 max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+                object_store_url: args.object_store_url,
             },
         )
         .await
@@ -915,6 +979,18 @@ impl BigTableSubCommand for App<'_, '_> {
                         .default_value(solana_storage_bigtable::DEFAULT_APP_PROFILE_ID)
                         .help("Bigtable application profile id to use in requests"),
                 )
+                .arg(
+                    Arg::with_name("object_store_url")
+                        .global(true)
+                        .long("object-store-url")
+                        .takes_value(true)
+                        .value_name("URL")
+                        .help(
+                            "Use the ledger storage in a local directory or \
+                             s3://BUCKET[/PREFIX] instead of Bigtable. S3 settings are read \
+                             from the AWS_* environment variables",
+                        ),
+                )
                 .subcommand(
                     SubCommand::with_name("upload")
                         .about("Upload the ledger to BigTable")
@@ -949,6 +1025,39 @@ impl BigTableSubCommand for App<'_, '_> {
                                 ),
                         ),
                 )
+                .subcommand(
+                    SubCommand::with_name("verify-and-repair")
+                        .about(
+                            "Verify that the rooted blocks of the ledger are fully stored in \
+                             BigTable, and re-upload the blocks that are missing or mismatched",
+                        )
+                        .arg(
+                            Arg::with_name("starting_slot")
+                                .long("starting-slot")
+                                .validator(is_slot)
+                                .value_name("START_SLOT")
+                                .takes_value(true)
+                                .index(1)
+                                .help(
+                                    "Start verifying at this slot [default: first available slot]",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("ending_slot")
+                                .long("ending-slot")
+                                .validator(is_slot)
+                                .value_name("END_SLOT")
+                                .takes_value(true)
+                                .index(2)
+                                .help("Stop verifying at this slot [default: last root]"),
+                        )
+                        .arg(
+                            Arg::with_name("dry_run")
+                                .long("dry-run")
+                                .takes_value(false)
+                                .help("Only report the missing and mismatched blocks"),
+                        ),
+                )
                 .subcommand(
                     SubCommand::with_name("delete-slots")
                         .about("Delete ledger information from BigTable")
@@ -1367,6 +1476,10 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
         "rpc_bigtable_app_profile_id",
         solana_storage_bigtable::DEFAULT_APP_PROFILE_ID,
     );
+    let object_store_url = sub_matches
+        .and_then(|sub_matches| sub_matches.value_of("object_store_url"))
+        .or_else(|| matches.value_of("object_store_url"))
+        .map(String::from);
 
     let future = match (subcommand, sub_matches) {
         ("upload", Some(arg_matches)) => {
@@ -1382,6 +1495,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: false,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(upload(
@@ -1392,12 +1506,37 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 config,
             ))
         }
+        ("verify-and-repair", Some(arg_matches)) => {
+            let starting_slot = value_t!(arg_matches, "starting_slot", Slot).ok();
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
+            let dry_run = arg_matches.is_present("dry_run");
+            let blockstore = crate::open_blockstore(
+                &canonicalize_ledger_path(ledger_path),
+                arg_matches,
+                AccessType::Secondary,
+            );
+            let config = solana_storage_bigtable::LedgerStorageConfig {
+                read_only: dry_run,
+                instance_name,
+                app_profile_id,
+                object_store_url: object_store_url.clone(),
+                ..solana_storage_bigtable::LedgerStorageConfig::default()
+            };
+            runtime.block_on(verify_and_repair(
+                blockstore,
+                starting_slot,
+                ending_slot,
+                dry_run,
+                config,
+            ))
+        }
         ("delete-slots", Some(arg_matches)) => {
             let slots = values_t_or_exit!(arg_matches, "slots", Slot);
             let config = solana_storage_bigtable::LedgerStorageConfig {
                 read_only: !arg_matches.is_present("force"),
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(delete_slots(slots, config))
@@ -1407,6 +1546,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(first_available_block(config))
@@ -1418,6 +1558,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(block(slot, output_format, show_entries, config))
@@ -1428,6 +1569,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
             runtime.block_on(entries(slot, output_format, config))
@@ -1472,6 +1614,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1490,6 +1633,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1502,6 +1646,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1535,6 +1680,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1555,6 +1701,7 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 read_only: true,
                 instance_name,
                 app_profile_id,
+                object_store_url: object_store_url.clone(),
                 ..solana_storage_bigtable::LedgerStorageConfig::default()
             };
 
@@ -1569,7 +1716,9 @@ pub fn bigtable_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
                 config,
             ))
         }
-        ("copy", Some(arg_matches)) => runtime.block_on(copy(CopyArgs::process(arg_matches))),
+        ("copy", Some(arg_matches)) => {
+            runtime.block_on(copy(CopyArgs::process(arg_matches, object_store_url)))
+        }
         _ => unreachable!(),
     };
 
@@ -16,6 +16,12 @@ pub enum LedgerToolError {
     #[error("{0}")]
     Io(#[from] std::io::Error),
 
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("{0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
     #[error("{0}")]
     Generic(String),
 
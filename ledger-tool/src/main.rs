@@ -11,6 +11,7 @@ use {
             SlotBankHash,
         },
         program::*,
+        snapshot::*,
     },
     clap::{
         crate_description, crate_name, value_t, value_t_or_exit, values_t_or_exit, App,
@@ -100,6 +101,7 @@ use {
     },
 };
 
+mod accounts_export;
 mod args;
 mod bigtable;
 mod blockstore;
@@ -108,6 +110,7 @@ mod ledger_path;
 mod ledger_utils;
 mod output;
 mod program;
+mod snapshot;
 
 fn parse_encoding_format(matches: &ArgMatches<'_>) -> UiAccountEncoding {
     match matches.value_of("encoding") {
@@ -1424,12 +1427,13 @@ fn main() {
                         .long("minimized")
                         .takes_value(false)
                         .help(
-                            "Create a minimized snapshot instead of a full snapshot. This \
-                             snapshot will only include information needed to replay the ledger \
-                             from the snapshot slot to the ending slot.",
+                            "Create a minimized snapshot instead of a full snapshot. With \
+                             --ending-slot, this snapshot will only include information needed \
+                             to replay the ledger from the snapshot slot to the ending slot. \
+                             With --minimized-program and/or --minimized-account, it will only \
+                             include those accounts plus the accounts needed to boot a bank.",
                         )
-                        .conflicts_with("incremental")
-                        .requires("ending_slot"),
+                        .conflicts_with("incremental"),
                 )
                 .arg(
                     Arg::with_name("ending_slot")
@@ -1438,6 +1442,34 @@ fn main() {
                         .value_name("ENDING_SLOT")
                         .help("Ending slot for minimized snapshot creation"),
                 )
+                .arg(
+                    Arg::with_name("minimized_program")
+                        .long("minimized-program")
+                        .takes_value(true)
+                        .multiple(true)
+                        .value_name("PROGRAM_ID")
+                        .validator(is_pubkey)
+                        .requires("minimized")
+                        .conflicts_with("ending_slot")
+                        .help(
+                            "Keep this program and every account owned by it in the minimized \
+                             snapshot. May be specified multiple times.",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("minimized_account")
+                        .long("minimized-account")
+                        .takes_value(true)
+                        .multiple(true)
+                        .value_name("PUBKEY")
+                        .validator(is_pubkey)
+                        .requires("minimized")
+                        .conflicts_with("ending_slot")
+                        .help(
+                            "Keep this account in the minimized snapshot. May be specified \
+                             multiple times.",
+                        ),
+                )
                 .arg(
                     Arg::with_name("snapshot_archive_format")
                         .long("snapshot-archive-format")
@@ -1537,6 +1569,25 @@ fn main() {
                         .validator(is_pubkey)
                         .conflicts_with("account")
                         .help("Limit output to accounts owned by the provided program pubkey"),
+                )
+                .arg(
+                    Arg::with_name("owner_stats")
+                        .long("owner-stats")
+                        .takes_value(false)
+                        .conflicts_with_all(&["account", "program_accounts"])
+                        .help(
+                            "Instead of the accounts, print the accounts index and storage usage \
+                             broken down by account owner",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("owner_stats_limit")
+                        .long("owner-stats-limit")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .validator(is_parsable::<usize>)
+                        .requires("owner_stats")
+                        .help("Only print the COUNT owners with the most storage bytes"),
                 ),
         )
         .subcommand(
@@ -1614,6 +1665,7 @@ fn main() {
                 .arg(&allow_dead_slots_arg),
         )
         .program_subcommand()
+        .snapshot_subcommand()
         .get_matches();
 
     info!("{} {}", crate_name!(), solana_version::version!());
@@ -1631,6 +1683,7 @@ fn main() {
         ("bigtable", Some(arg_matches)) => bigtable_process_command(&ledger_path, arg_matches),
         ("blockstore", Some(arg_matches)) => blockstore_process_command(&ledger_path, arg_matches),
         ("program", Some(arg_matches)) => program(&ledger_path, arg_matches),
+        ("snapshot", Some(arg_matches)) => snapshot_process_command(&ledger_path, arg_matches),
         // This match case provides legacy support for commands that were previously top level
         // subcommands of the binary, but have been moved under the blockstore subcommand.
         ("analyze-storage", Some(_))
@@ -2010,7 +2063,18 @@ fn main() {
                     }
                     process_options.halt_at_slot = Some(snapshot_slot);
 
-                    let ending_slot = if is_minimized {
+                    let minimized_program_ids = pubkeys_of(arg_matches, "minimized_program");
+                    let minimized_accounts = pubkeys_of(arg_matches, "minimized_account");
+                    let is_minimized_to_accounts =
+                        minimized_program_ids.is_some() || minimized_accounts.is_some();
+                    let ending_slot = if is_minimized && !is_minimized_to_accounts {
+                        if !arg_matches.is_present("ending_slot") {
+                            eprintln!(
+                                "Error: --minimized requires --ending-slot, --minimized-program \
+                                 or --minimized-account"
+                            );
+                            exit(1);
+                        }
                         let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
                         if ending_slot <= snapshot_slot {
                             eprintln!(
@@ -2336,13 +2400,15 @@ fn main() {
                         bank
                     };
 
-                    let minimize_snapshot_possibly_incomplete = if is_minimized {
-                        minimize_bank_for_snapshot(
-                            &blockstore,
+                    let minimize_snapshot_possibly_incomplete = if is_minimized_to_accounts {
+                        SnapshotMinimizer::minimize_to_accounts(
                             &bank,
-                            snapshot_slot,
-                            ending_slot.unwrap(),
-                        )
+                            minimized_accounts.unwrap_or_default().into_iter().collect(),
+                            &minimized_program_ids.unwrap_or_default(),
+                        );
+                        false
+                    } else if let Some(ending_slot) = ending_slot {
+                        minimize_bank_for_snapshot(&blockstore, &bank, snapshot_slot, ending_slot)
                     } else {
                         false
                     };
@@ -2418,10 +2484,9 @@ fn main() {
                             full_snapshot_archive_info.path().display(),
                         );
 
-                        if is_minimized {
+                        if let Some(ending_slot) = ending_slot {
                             let starting_epoch = bank.epoch_schedule().get_epoch(snapshot_slot);
-                            let ending_epoch =
-                                bank.epoch_schedule().get_epoch(ending_slot.unwrap());
+                            let ending_epoch = bank.epoch_schedule().get_epoch(ending_slot);
                             if starting_epoch != ending_epoch {
                                 warn!(
                                     "Minimized snapshot range crosses epoch boundary ({} to \
@@ -2531,39 +2596,56 @@ fn main() {
                         );
                     let bank = bank_forks.read().unwrap().working_bank();
 
-                    let include_sysvars = arg_matches.is_present("include_sysvars");
-                    let include_account_contents = !arg_matches.is_present("no_account_contents");
-                    let include_account_data = !arg_matches.is_present("no_account_data");
-                    let account_data_encoding = parse_encoding_format(arg_matches);
-                    let mode = if let Some(pubkeys) = pubkeys_of(arg_matches, "account") {
-                        info!("Scanning individual accounts: {pubkeys:?}");
-                        AccountsOutputMode::Individual(pubkeys)
-                    } else if let Some(pubkey) = pubkey_of(arg_matches, "program_accounts") {
-                        info!("Scanning program accounts for {pubkey}");
-                        AccountsOutputMode::Program(pubkey)
+                    if arg_matches.is_present("owner_stats") {
+                        let limit = value_t!(arg_matches, "owner_stats_limit", usize).ok();
+                        let output_format =
+                            OutputFormat::from_matches(arg_matches, "output_format", false);
+                        let report = bank.rc.accounts.accounts_db.owner_stats_report(limit);
+                        match output_format {
+                            OutputFormat::Json => {
+                                println!("{}", serde_json::to_string_pretty(&report).unwrap())
+                            }
+                            OutputFormat::JsonCompact => {
+                                println!("{}", serde_json::to_string(&report).unwrap())
+                            }
+                            _ => print!("{report}"),
+                        }
                     } else {
-                        info!("Scanning all accounts");
-                        AccountsOutputMode::All
-                    };
-                    let config = AccountsOutputConfig {
-                        mode,
-                        include_sysvars,
-                        include_account_contents,
-                        include_account_data,
-                        account_data_encoding,
-                    };
-                    let output_format =
-                        OutputFormat::from_matches(arg_matches, "output_format", false);
-
-                    let accounts_streamer =
-                        AccountsOutputStreamer::new(bank, output_format, config);
-                    let (_, scan_time) = measure_time!(
-                        accounts_streamer
-                            .output()
-                            .map_err(|err| error!("Error while outputting accounts: {err}")),
-                        "accounts scan"
-                    );
-                    info!("{scan_time}");
+                        let include_sysvars = arg_matches.is_present("include_sysvars");
+                        let include_account_contents =
+                            !arg_matches.is_present("no_account_contents");
+                        let include_account_data = !arg_matches.is_present("no_account_data");
+                        let account_data_encoding = parse_encoding_format(arg_matches);
+                        let mode = if let Some(pubkeys) = pubkeys_of(arg_matches, "account") {
+                            info!("Scanning individual accounts: {pubkeys:?}");
+                            AccountsOutputMode::Individual(pubkeys)
+                        } else if let Some(pubkey) = pubkey_of(arg_matches, "program_accounts") {
+                            info!("Scanning program accounts for {pubkey}");
+                            AccountsOutputMode::Program(pubkey)
+                        } else {
+                            info!("Scanning all accounts");
+                            AccountsOutputMode::All
+                        };
+                        let config = AccountsOutputConfig {
+                            mode,
+                            include_sysvars,
+                            include_account_contents,
+                            include_account_data,
+                            account_data_encoding,
+                        };
+                        let output_format =
+                            OutputFormat::from_matches(arg_matches, "output_format", false);
+
+                        let accounts_streamer =
+                            AccountsOutputStreamer::new(bank, output_format, config);
+                        let (_, scan_time) = measure_time!(
+                            accounts_streamer
+                                .output()
+                                .map_err(|err| error!("Error while outputting accounts: {err}")),
+                            "accounts scan"
+                        );
+                        info!("{scan_time}");
+                    }
                 }
                 ("capitalization", Some(arg_matches)) => {
                     let process_options = parse_process_options(&ledger_path, arg_matches);
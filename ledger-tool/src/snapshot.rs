@@ -0,0 +1,1042 @@
+//! The `snapshot` subcommand
+
+use {
+    crate::{
+        accounts_export::{export_accounts, ExportFilter, ExportFormat, ExportOptions},
+        args::{accounts_db_args, load_genesis_arg, parse_process_options},
+        error::{LedgerToolError, Result},
+        ledger_path::canonicalize_ledger_path,
+        ledger_utils::open_genesis_config_by,
+        LEDGER_TOOL_DIRECTORY,
+    },
+    clap::{
+        value_t, value_t_or_exit, values_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand,
+    },
+    log::*,
+    serde_derive::Serialize,
+    solana_accounts_db::accounts_file::StorageAccess,
+    solana_clap_utils::{
+        input_parsers::pubkeys_of,
+        input_validators::{is_parsable, is_pow2, is_pubkey, is_within_range},
+    },
+    solana_cli_output::{OutputFormat, QuietDisplay, VerboseDisplay},
+    solana_feature_set::FEATURE_NAMES,
+    solana_ledger::blockstore_processor::ProcessOptions,
+    solana_runtime::{
+        bank::Bank,
+        snapshot_archive_info::{
+            FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
+        },
+        snapshot_bank_utils,
+        snapshot_utils::{
+            self,
+            seekable_archive::{extract_seekable_archive_files, read_seekable_archive_index},
+        },
+    },
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::{Epoch, Slot},
+        genesis_config::GenesisConfig,
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet, HashSet},
+        fmt::{self, Display, Formatter},
+        fs,
+        io::{stdout, Write},
+        path::{Path, PathBuf},
+        sync::{atomic::AtomicBool, Arc},
+    },
+};
+
+pub trait SnapshotSubCommand {
+    fn snapshot_subcommand(self) -> Self;
+}
+
+impl SnapshotSubCommand for App<'_, '_> {
+    fn snapshot_subcommand(self) -> Self {
+        let seekable_snapshot_arg = Arg::with_name("snapshot_archive")
+            .index(1)
+            .value_name("SNAPSHOT_ARCHIVE")
+            .takes_value(true)
+            .required(true)
+            .help("Snapshot archive created with the zstd-seekable archive format");
+
+        self.subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Commands to inspect snapshot archives")
+                .setting(AppSettings::InferSubcommands)
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("diff")
+                        .about(
+                            "Compare the accounts and bank fields of two snapshots. The base \
+                             snapshot must be a full snapshot archive. The other snapshot may be \
+                             either a full snapshot archive or an incremental snapshot archive \
+                             built on top of the base.",
+                        )
+                        .arg(load_genesis_arg())
+                        .args(&accounts_db_args())
+                        .arg(
+                            Arg::with_name("base_snapshot")
+                                .index(1)
+                                .value_name("BASE_SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Full snapshot archive to compare against"),
+                        )
+                        .arg(
+                            Arg::with_name("other_snapshot")
+                                .index(2)
+                                .value_name("OTHER_SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .required(true)
+                                .help(
+                                    "Full snapshot archive, or incremental snapshot archive \
+                                     based on BASE_SNAPSHOT_ARCHIVE, to compare",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("owner")
+                                .long("owner")
+                                .value_name("PUBKEY")
+                                .takes_value(true)
+                                .multiple(true)
+                                .validator(is_pubkey)
+                                .help(
+                                    "Only report accounts owned by this program in either \
+                                     snapshot. May be specified multiple times.",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("summary_only")
+                                .long("summary-only")
+                                .takes_value(false)
+                                .help("Only report the number of changed accounts"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("list-files")
+                        .about("List the files in a seekable snapshot archive")
+                        .arg(&seekable_snapshot_arg),
+                )
+                .subcommand(
+                    SubCommand::with_name("extract")
+                        .about(
+                            "Extract individual files, such as account storages, from a seekable \
+                             snapshot archive without unpacking the whole archive",
+                        )
+                        .arg(&seekable_snapshot_arg)
+                        .arg(
+                            Arg::with_name("file")
+                                .long("file")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .multiple(true)
+                                .required(true)
+                                .help(
+                                    "Path of the file in the archive, e.g. accounts/1234.5. May \
+                                     be specified multiple times.",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("output_directory")
+                                .long("output-directory")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Directory to extract the files into"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("export-accounts")
+                        .about(
+                            "Export the accounts in a snapshot to Parquet or CSV files, reading \
+                             the account storages directly instead of loading a bank",
+                        )
+                        .arg(
+                            Arg::with_name("snapshot_archive")
+                                .index(1)
+                                .value_name("SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Full snapshot archive to export"),
+                        )
+                        .arg(
+                            Arg::with_name("incremental_snapshot_archive")
+                                .long("incremental-snapshot-archive")
+                                .value_name("INCREMENTAL_SNAPSHOT_ARCHIVE")
+                                .takes_value(true)
+                                .help(
+                                    "Incremental snapshot archive based on SNAPSHOT_ARCHIVE to \
+                                     export the accounts as of",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("output_directory")
+                                .long("output-directory")
+                                .value_name("DIR")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Directory to write the exported files into"),
+                        )
+                        .arg(
+                            Arg::with_name("format")
+                                .long("format")
+                                .value_name("FORMAT")
+                                .takes_value(true)
+                                .possible_values(&["parquet", "csv"])
+                                .default_value("parquet")
+                                .help("Format of the exported files"),
+                        )
+                        .arg(
+                            Arg::with_name("owner")
+                                .long("owner")
+                                .value_name("PUBKEY")
+                                .takes_value(true)
+                                .multiple(true)
+                                .validator(is_pubkey)
+                                .help(
+                                    "Only export accounts owned by this program. May be \
+                                     specified multiple times.",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("min_data_len")
+                                .long("min-data-len")
+                                .value_name("BYTES")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Only export accounts with at least this much data"),
+                        )
+                        .arg(
+                            Arg::with_name("max_data_len")
+                                .long("max-data-len")
+                                .value_name("BYTES")
+                                .takes_value(true)
+                                .validator(is_parsable::<usize>)
+                                .help("Only export accounts with at most this much data"),
+                        )
+                        .arg(
+                            Arg::with_name("include_data")
+                                .long("include-data")
+                                .takes_value(false)
+                                .help(
+                                    "Include account data, base64 encoded in CSV files and raw \
+                                     bytes in Parquet files",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("pubkey_bins")
+                                .long("pubkey-bins")
+                                .value_name("NUM_BINS")
+                                .takes_value(true)
+                                .validator(is_pow2)
+                                .default_value("64")
+                                .help(
+                                    "Number of pubkey ranges to export one at a time, each to \
+                                     its own file. Accounts are first split into one spill file \
+                                     per range in a single pass over the snapshot. Memory use is \
+                                     proportional to the number of accounts in one range. Must \
+                                     be a power of two.",
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("workers")
+                                .long("workers")
+                                .value_name("NUM_THREADS")
+                                .takes_value(true)
+                                .validator(|s| is_within_range(s, 1..=num_cpus::get()))
+                                .help(
+                                    "Number of threads reading account storages [default: all \
+                                     cpus]",
+                                ),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn snapshot_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) {
+    do_snapshot_process_command(ledger_path, matches).unwrap_or_else(|err| {
+        eprintln!("Failed to complete command: {err:?}");
+        std::process::exit(1);
+    });
+}
+
+fn do_snapshot_process_command(ledger_path: &Path, matches: &ArgMatches<'_>) -> Result<()> {
+    let ledger_path = canonicalize_ledger_path(ledger_path);
+
+    match matches.subcommand() {
+        ("diff", Some(arg_matches)) => {
+            let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+            let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+            let process_options = parse_process_options(&ledger_path, arg_matches);
+
+            let base_snapshot =
+                PathBuf::from(value_t_or_exit!(arg_matches, "base_snapshot", String));
+            let other_snapshot =
+                PathBuf::from(value_t_or_exit!(arg_matches, "other_snapshot", String));
+            let owners = pubkeys_of(arg_matches, "owner").map(HashSet::from_iter);
+            let summary_only = arg_matches.is_present("summary_only");
+
+            let base_archive =
+                FullSnapshotArchiveInfo::new_from_path(base_snapshot).map_err(|err| {
+                    LedgerToolError::BadArgument(format!(
+                        "base snapshot is not a full snapshot archive: {err}"
+                    ))
+                })?;
+            let (other_full_archive, other_incremental_archive) =
+                match IncrementalSnapshotArchiveInfo::new_from_path(other_snapshot.clone()) {
+                    Ok(incremental_archive) => {
+                        if incremental_archive.base_slot() != base_archive.slot() {
+                            return Err(LedgerToolError::BadArgument(format!(
+                                "incremental snapshot is based on slot {}, but the base snapshot \
+                                 is for slot {}",
+                                incremental_archive.base_slot(),
+                                base_archive.slot(),
+                            )));
+                        }
+                        (base_archive.clone(), Some(incremental_archive))
+                    }
+                    Err(_) => (
+                        FullSnapshotArchiveInfo::new_from_path(other_snapshot).map_err(|err| {
+                            LedgerToolError::BadArgument(format!(
+                                "other snapshot is not a snapshot archive: {err}"
+                            ))
+                        })?,
+                        None,
+                    ),
+                };
+
+            let work_dir = ledger_path
+                .join(LEDGER_TOOL_DIRECTORY)
+                .join("snapshot_diff");
+            let base_bank = load_bank_from_snapshot_archives(
+                &work_dir.join("base"),
+                &base_archive,
+                None,
+                &genesis_config,
+                &process_options,
+            )?;
+            let other_bank = load_bank_from_snapshot_archives(
+                &work_dir.join("other"),
+                &other_full_archive,
+                other_incremental_archive.as_ref(),
+                &genesis_config,
+                &process_options,
+            )?;
+
+            let snapshot_diff = diff_banks(&base_bank, &other_bank, owners.as_ref(), summary_only)?;
+            println!("{}", output_format.formatted_string(&snapshot_diff));
+        }
+        ("list-files", Some(arg_matches)) => {
+            let snapshot_archive = value_t_or_exit!(arg_matches, "snapshot_archive", String);
+            let frames = read_seekable_archive_index(&snapshot_archive)?;
+            let mut stdout = stdout().lock();
+            for frame in frames
+                .iter()
+                .filter(|frame| !frame.path.as_os_str().is_empty())
+            {
+                writeln!(
+                    stdout,
+                    "{}: {} bytes, {} compressed",
+                    frame.path.display(),
+                    frame.decompressed_len,
+                    frame.compressed_len,
+                )?;
+            }
+        }
+        ("extract", Some(arg_matches)) => {
+            let snapshot_archive = value_t_or_exit!(arg_matches, "snapshot_archive", String);
+            let files = values_t_or_exit!(arg_matches, "file", String);
+            let output_directory =
+                PathBuf::from(value_t_or_exit!(arg_matches, "output_directory", String));
+            fs::create_dir_all(&output_directory)?;
+            for path in extract_seekable_archive_files(snapshot_archive, &files, output_directory)?
+            {
+                println!("{}", path.display());
+            }
+        }
+        ("export-accounts", Some(arg_matches)) => {
+            let full_archive = FullSnapshotArchiveInfo::new_from_path(PathBuf::from(
+                value_t_or_exit!(arg_matches, "snapshot_archive", String),
+            ))
+            .map_err(|err| {
+                LedgerToolError::BadArgument(format!(
+                    "snapshot is not a full snapshot archive: {err}"
+                ))
+            })?;
+            let incremental_archive = value_t!(arg_matches, "incremental_snapshot_archive", String)
+                .ok()
+                .map(|path| {
+                    IncrementalSnapshotArchiveInfo::new_from_path(PathBuf::from(path)).map_err(
+                        |err| {
+                            LedgerToolError::BadArgument(format!(
+                                "incremental snapshot is not an incremental snapshot archive: \
+                                 {err}"
+                            ))
+                        },
+                    )
+                })
+                .transpose()?;
+            let work_dir = ledger_path
+                .join(LEDGER_TOOL_DIRECTORY)
+                .join("accounts_export");
+            let options = ExportOptions {
+                output_dir: PathBuf::from(value_t_or_exit!(
+                    arg_matches,
+                    "output_directory",
+                    String
+                )),
+                format: value_t_or_exit!(arg_matches, "format", String).parse()?,
+                filter: ExportFilter {
+                    owners: pubkeys_of(arg_matches, "owner").map(HashSet::from_iter),
+                    min_data_len: value_t!(arg_matches, "min_data_len", usize).ok(),
+                    max_data_len: value_t!(arg_matches, "max_data_len", usize).ok(),
+                },
+                include_data: arg_matches.is_present("include_data"),
+                num_bins: value_t_or_exit!(arg_matches, "pubkey_bins", usize),
+                spill_dir: work_dir.join("spill"),
+            };
+            let workers =
+                value_t!(arg_matches, "workers", usize).unwrap_or_else(|_| num_cpus::get());
+
+            if work_dir.exists() {
+                fs::remove_dir_all(&work_dir)?;
+            }
+            let account_paths = vec![work_dir.join("accounts")];
+            let bank_snapshots_dir = work_dir.join("snapshot");
+            fs::create_dir_all(&account_paths[0])?;
+            fs::create_dir_all(&bank_snapshots_dir)?;
+            let (unarchived_full, unarchived_incremental, _next_append_vec_id) =
+                snapshot_utils::verify_and_unarchive_snapshots(
+                    &bank_snapshots_dir,
+                    &full_archive,
+                    incremental_archive.as_ref(),
+                    &account_paths,
+                    StorageAccess::default(),
+                )
+                .map_err(|err| {
+                    LedgerToolError::Generic(format!("failed to unpack snapshot archives: {err}"))
+                })?;
+            let storages: Vec<_> = unarchived_full
+                .storage
+                .iter()
+                .chain(
+                    unarchived_incremental
+                        .iter()
+                        .flat_map(|unarchived| unarchived.storage.iter()),
+                )
+                .map(|entry| (*entry.key(), entry.value().storage.clone()))
+                .collect();
+
+            let thread_pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(workers)
+                .thread_name(|i| format!("solAcctExport{i:02}"))
+                .build()
+                .unwrap();
+            let summary = thread_pool.install(|| export_accounts(&storages, &options))?;
+            println!(
+                "Exported {} accounts from {} storages to {} files in {}",
+                summary.num_accounts,
+                storages.len(),
+                summary.num_files,
+                options.output_dir.display(),
+            );
+
+            drop(storages);
+            drop(unarchived_incremental);
+            drop(unarchived_full);
+            fs::remove_dir_all(&work_dir)?;
+        }
+        _ => unreachable!(),
+    }
+
+    Ok(())
+}
+
+/// Load a bank from snapshot archives, using `work_dir` for all of the bank's on-disk state so
+/// that multiple banks can be loaded side by side.
+fn load_bank_from_snapshot_archives(
+    work_dir: &Path,
+    full_snapshot_archive_info: &FullSnapshotArchiveInfo,
+    incremental_snapshot_archive_info: Option<&IncrementalSnapshotArchiveInfo>,
+    genesis_config: &GenesisConfig,
+    process_options: &ProcessOptions,
+) -> Result<Bank> {
+    if work_dir.exists() {
+        fs::remove_dir_all(work_dir)?;
+    }
+    let account_paths = vec![work_dir.join("accounts")];
+    let bank_snapshots_dir = work_dir.join("snapshot");
+    fs::create_dir_all(&account_paths[0])?;
+    fs::create_dir_all(&bank_snapshots_dir)?;
+
+    let mut accounts_db_config = process_options.accounts_db_config.clone();
+    if let Some(index) = accounts_db_config
+        .as_mut()
+        .and_then(|config| config.index.as_mut())
+    {
+        index.drives = Some(vec![work_dir.join("accounts_index")]);
+    }
+
+    let (bank, _) = snapshot_bank_utils::bank_from_snapshot_archives(
+        &account_paths,
+        &bank_snapshots_dir,
+        full_snapshot_archive_info,
+        incremental_snapshot_archive_info,
+        genesis_config,
+        &process_options.runtime_config,
+        None,
+        None,
+        None,
+        false,
+        process_options.accounts_db_skip_shrink,
+        false,
+        false,
+        accounts_db_config,
+        None,
+        Arc::new(AtomicBool::new(false)),
+    )
+    .map_err(|err| {
+        LedgerToolError::Generic(format!(
+            "failed to load bank from snapshot archives {} and {:?}: {err}",
+            full_snapshot_archive_info.path().display(),
+            incremental_snapshot_archive_info.map(|archive| archive.path().display().to_string()),
+        ))
+    })?;
+    info!(
+        "Loaded bank for slot {} from {}",
+        bank.slot(),
+        work_dir.display()
+    );
+    Ok(bank)
+}
+
+fn diff_banks(
+    base_bank: &Bank,
+    other_bank: &Bank,
+    owners: Option<&HashSet<Pubkey>>,
+    summary_only: bool,
+) -> Result<CliSnapshotDiff> {
+    let base = CliBankFields::new(base_bank);
+    let other = CliBankFields::new(other_bank);
+    let mut accounts = CliAccountsDiff::default();
+
+    let scan_error = |err| LedgerToolError::Generic(format!("failed to scan accounts: {err}"));
+    // Added and modified accounts are found by walking the other bank, removed accounts by
+    // walking the base bank
+    other_bank
+        .scan_all_accounts(
+            |scanned| {
+                let Some((pubkey, other_account, _slot)) = scanned else {
+                    return;
+                };
+                if other_account.lamports() == 0 {
+                    return;
+                }
+                let base_account = base_bank.get_account_with_fixed_root(pubkey);
+                accounts.add(
+                    pubkey,
+                    base_account.as_ref(),
+                    Some(&other_account),
+                    owners,
+                    summary_only,
+                );
+            },
+            true,
+        )
+        .map_err(scan_error)?;
+    base_bank
+        .scan_all_accounts(
+            |scanned| {
+                let Some((pubkey, base_account, _slot)) = scanned else {
+                    return;
+                };
+                if base_account.lamports() == 0
+                    || other_bank.get_account_with_fixed_root(pubkey).is_some()
+                {
+                    return;
+                }
+                accounts.add(pubkey, Some(&base_account), None, owners, summary_only);
+            },
+            true,
+        )
+        .map_err(scan_error)?;
+
+    Ok(CliSnapshotDiff::new(base, other, accounts))
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CliBankFields {
+    slot: Slot,
+    hash: String,
+    epoch: Epoch,
+    capitalization: u64,
+    #[serde(skip)]
+    epoch_stakes: BTreeMap<Epoch, u64>,
+    #[serde(skip)]
+    active_features: BTreeMap<Pubkey, Slot>,
+}
+
+impl CliBankFields {
+    fn new(bank: &Bank) -> Self {
+        Self {
+            slot: bank.slot(),
+            hash: bank.hash().to_string(),
+            epoch: bank.epoch(),
+            capitalization: bank.capitalization(),
+            epoch_stakes: bank
+                .epoch_stakes_map()
+                .iter()
+                .map(|(epoch, epoch_stakes)| (*epoch, epoch_stakes.total_stake()))
+                .collect(),
+            active_features: bank
+                .feature_set
+                .active
+                .iter()
+                .map(|(feature_id, slot)| (*feature_id, *slot))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct CliEpochStakesDiff {
+    epoch: Epoch,
+    base_total_stake: Option<u64>,
+    other_total_stake: Option<u64>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct CliFeatureDiff {
+    id: String,
+    name: Option<&'static str>,
+    base_activation_slot: Option<Slot>,
+    other_activation_slot: Option<Slot>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct CliAccountState {
+    owner: String,
+    lamports: u64,
+    data_len: usize,
+    executable: bool,
+    rent_epoch: Epoch,
+}
+
+impl CliAccountState {
+    fn new(account: &AccountSharedData) -> Self {
+        Self {
+            owner: account.owner().to_string(),
+            lamports: account.lamports(),
+            data_len: account.data().len(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct CliAccountDiff {
+    pubkey: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<CliAccountState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    other: Option<CliAccountState>,
+    lamports_delta: i128,
+    data_len_delta: i64,
+    data_changed: bool,
+}
+
+#[derive(Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct CliAccountsDiff {
+    num_added: usize,
+    num_removed: usize,
+    num_modified: usize,
+    lamports_delta: i128,
+    data_len_delta: i64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    accounts: Vec<CliAccountDiff>,
+}
+
+impl CliAccountsDiff {
+    /// Record the difference between two versions of an account, if there is one
+    fn add(
+        &mut self,
+        pubkey: &Pubkey,
+        base: Option<&AccountSharedData>,
+        other: Option<&AccountSharedData>,
+        owners: Option<&HashSet<Pubkey>>,
+        summary_only: bool,
+    ) {
+        if let Some(owners) = owners {
+            if !base
+                .into_iter()
+                .chain(other)
+                .any(|account| owners.contains(account.owner()))
+            {
+                return;
+            }
+        }
+        let Some(account_diff) = diff_account(pubkey, base, other) else {
+            return;
+        };
+
+        match (base, other) {
+            (None, Some(_)) => self.num_added += 1,
+            (Some(_), None) => self.num_removed += 1,
+            _ => self.num_modified += 1,
+        }
+        self.lamports_delta += account_diff.lamports_delta;
+        self.data_len_delta += account_diff.data_len_delta;
+        if !summary_only {
+            self.accounts.push(account_diff);
+        }
+    }
+}
+
+fn diff_account(
+    pubkey: &Pubkey,
+    base: Option<&AccountSharedData>,
+    other: Option<&AccountSharedData>,
+) -> Option<CliAccountDiff> {
+    let data_changed = match (base, other) {
+        (None, None) => return None,
+        (Some(base), Some(other)) => {
+            if base.lamports() == other.lamports()
+                && base.owner() == other.owner()
+                && base.executable() == other.executable()
+                && base.rent_epoch() == other.rent_epoch()
+                && base.data() == other.data()
+            {
+                return None;
+            }
+            base.data() != other.data()
+        }
+        _ => true,
+    };
+
+    let lamports = |account: Option<&AccountSharedData>| {
+        account
+            .map(|account| account.lamports())
+            .unwrap_or_default() as i128
+    };
+    let data_len = |account: Option<&AccountSharedData>| {
+        account
+            .map(|account| account.data().len())
+            .unwrap_or_default() as i64
+    };
+    Some(CliAccountDiff {
+        pubkey: pubkey.to_string(),
+        base: base.map(CliAccountState::new),
+        other: other.map(CliAccountState::new),
+        lamports_delta: lamports(other) - lamports(base),
+        data_len_delta: data_len(other) - data_len(base),
+        data_changed,
+    })
+}
+
+fn diff_epoch_stakes(
+    base: &BTreeMap<Epoch, u64>,
+    other: &BTreeMap<Epoch, u64>,
+) -> Vec<CliEpochStakesDiff> {
+    base.keys()
+        .chain(other.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|epoch| {
+            let base_total_stake = base.get(epoch).copied();
+            let other_total_stake = other.get(epoch).copied();
+            (base_total_stake != other_total_stake).then_some(CliEpochStakesDiff {
+                epoch: *epoch,
+                base_total_stake,
+                other_total_stake,
+            })
+        })
+        .collect()
+}
+
+fn diff_features(
+    base: &BTreeMap<Pubkey, Slot>,
+    other: &BTreeMap<Pubkey, Slot>,
+) -> Vec<CliFeatureDiff> {
+    base.keys()
+        .chain(other.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|feature_id| {
+            let base_activation_slot = base.get(feature_id).copied();
+            let other_activation_slot = other.get(feature_id).copied();
+            (base_activation_slot != other_activation_slot).then(|| CliFeatureDiff {
+                id: feature_id.to_string(),
+                name: FEATURE_NAMES.get(feature_id).copied(),
+                base_activation_slot,
+                other_activation_slot,
+            })
+        })
+        .collect()
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CliSnapshotDiff {
+    base: CliBankFields,
+    other: CliBankFields,
+    capitalization_delta: i128,
+    epoch_stakes: Vec<CliEpochStakesDiff>,
+    features: Vec<CliFeatureDiff>,
+    accounts: CliAccountsDiff,
+}
+
+impl CliSnapshotDiff {
+    fn new(base: CliBankFields, other: CliBankFields, accounts: CliAccountsDiff) -> Self {
+        let capitalization_delta = other.capitalization as i128 - base.capitalization as i128;
+        let epoch_stakes = diff_epoch_stakes(&base.epoch_stakes, &other.epoch_stakes);
+        let features = diff_features(&base.active_features, &other.active_features);
+        Self {
+            base,
+            other,
+            capitalization_delta,
+            epoch_stakes,
+            features,
+            accounts,
+        }
+    }
+}
+
+impl VerboseDisplay for CliSnapshotDiff {}
+impl QuietDisplay for CliSnapshotDiff {}
+
+impl Display for CliSnapshotDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (label, bank) in [("Base", &self.base), ("Other", &self.other)] {
+            writeln!(
+                f,
+                "{label}: slot {}, epoch {}, bank hash {}, capitalization {}",
+                bank.slot, bank.epoch, bank.hash, bank.capitalization,
+            )?;
+        }
+        writeln!(f, "Capitalization delta: {:+}", self.capitalization_delta)?;
+
+        if self.epoch_stakes.is_empty() {
+            writeln!(f, "Epoch stakes: unchanged")?;
+        } else {
+            writeln!(f, "Epoch stakes:")?;
+            for diff in &self.epoch_stakes {
+                writeln!(
+                    f,
+                    "  epoch {}: {:?} -> {:?}",
+                    diff.epoch, diff.base_total_stake, diff.other_total_stake
+                )?;
+            }
+        }
+
+        if self.features.is_empty() {
+            writeln!(f, "Feature set: unchanged")?;
+        } else {
+            writeln!(f, "Feature set:")?;
+            for diff in &self.features {
+                writeln!(
+                    f,
+                    "  {} ({}): activated at {:?} -> {:?}",
+                    diff.id,
+                    diff.name.unwrap_or("unknown"),
+                    diff.base_activation_slot,
+                    diff.other_activation_slot,
+                )?;
+            }
+        }
+
+        let accounts = &self.accounts;
+        writeln!(
+            f,
+            "Accounts: {} added, {} removed, {} modified, lamports delta {:+}, data len delta {:+}",
+            accounts.num_added,
+            accounts.num_removed,
+            accounts.num_modified,
+            accounts.lamports_delta,
+            accounts.data_len_delta,
+        )?;
+        for diff in &accounts.accounts {
+            match (&diff.base, &diff.other) {
+                (None, Some(other)) => writeln!(
+                    f,
+                    "  + {} owner {} lamports {} data len {}",
+                    diff.pubkey, other.owner, other.lamports, other.data_len,
+                )?,
+                (Some(base), None) => writeln!(
+                    f,
+                    "  - {} owner {} lamports {} data len {}",
+                    diff.pubkey, base.owner, base.lamports, base.data_len,
+                )?,
+                (Some(base), Some(other)) => {
+                    write!(
+                        f,
+                        "  ~ {} lamports {} -> {} ({:+}), data len {} -> {} ({:+})",
+                        diff.pubkey,
+                        base.lamports,
+                        other.lamports,
+                        diff.lamports_delta,
+                        base.data_len,
+                        other.data_len,
+                        diff.data_len_delta,
+                    )?;
+                    if base.owner != other.owner {
+                        write!(f, ", owner {} -> {}", base.owner, other.owner)?;
+                    }
+                    if base.executable != other.executable {
+                        write!(
+                            f,
+                            ", executable {} -> {}",
+                            base.executable, other.executable
+                        )?;
+                    }
+                    if base.rent_epoch != other.rent_epoch {
+                        write!(
+                            f,
+                            ", rent epoch {} -> {}",
+                            base.rent_epoch, other.rent_epoch
+                        )?;
+                    }
+                    if diff.data_changed {
+                        write!(f, ", data changed")?;
+                    }
+                    writeln!(f)?;
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::account::WritableAccount};
+
+    #[test]
+    fn test_diff_account() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let account = AccountSharedData::new(100, 10, &owner);
+
+        assert_eq!(diff_account(&pubkey, None, None), None);
+        assert_eq!(diff_account(&pubkey, Some(&account), Some(&account)), None);
+
+        let added = diff_account(&pubkey, None, Some(&account)).unwrap();
+        assert_eq!(added.base, None);
+        assert_eq!(added.lamports_delta, 100);
+        assert_eq!(added.data_len_delta, 10);
+
+        let removed = diff_account(&pubkey, Some(&account), None).unwrap();
+        assert_eq!(removed.other, None);
+        assert_eq!(removed.lamports_delta, -100);
+        assert_eq!(removed.data_len_delta, -10);
+
+        let mut modified_account = account.clone();
+        modified_account.set_lamports(50);
+        let modified = diff_account(&pubkey, Some(&account), Some(&modified_account)).unwrap();
+        assert_eq!(modified.lamports_delta, -50);
+        assert_eq!(modified.data_len_delta, 0);
+        assert!(!modified.data_changed);
+
+        modified_account.data_as_mut_slice()[0] = 1;
+        let modified = diff_account(&pubkey, Some(&account), Some(&modified_account)).unwrap();
+        assert!(modified.data_changed);
+    }
+
+    #[test]
+    fn test_accounts_diff_owner_filter() {
+        let owner = Pubkey::new_unique();
+        let other_owner = Pubkey::new_unique();
+        let owners = HashSet::from([owner]);
+        let owned = AccountSharedData::new(1, 0, &owner);
+        let not_owned = AccountSharedData::new(2, 0, &other_owner);
+
+        let mut accounts = CliAccountsDiff::default();
+        accounts.add(
+            &Pubkey::new_unique(),
+            None,
+            Some(&owned),
+            Some(&owners),
+            false,
+        );
+        accounts.add(
+            &Pubkey::new_unique(),
+            None,
+            Some(&not_owned),
+            Some(&owners),
+            false,
+        );
+        // a change of owner away from a filtered program is still reported
+        accounts.add(
+            &Pubkey::new_unique(),
+            Some(&owned),
+            Some(&not_owned),
+            Some(&owners),
+            false,
+        );
+        accounts.add(
+            &Pubkey::new_unique(),
+            Some(&owned),
+            None,
+            Some(&owners),
+            true,
+        );
+
+        assert_eq!(accounts.num_added, 1);
+        assert_eq!(accounts.num_modified, 1);
+        assert_eq!(accounts.num_removed, 1);
+        assert_eq!(accounts.lamports_delta, 1 + 1 - 1);
+        assert_eq!(accounts.accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_diff_bank_fields() {
+        let feature_a = Pubkey::new_unique();
+        let feature_b = Pubkey::new_unique();
+        let base_features = BTreeMap::from([(feature_a, 10)]);
+        let other_features = BTreeMap::from([(feature_a, 10), (feature_b, 20)]);
+        assert_eq!(
+            diff_features(&base_features, &other_features),
+            vec![CliFeatureDiff {
+                id: feature_b.to_string(),
+                name: None,
+                base_activation_slot: None,
+                other_activation_slot: Some(20),
+            }]
+        );
+
+        let base_stakes = BTreeMap::from([(1, 100), (2, 200)]);
+        let other_stakes = BTreeMap::from([(2, 250), (3, 300)]);
+        assert_eq!(
+            diff_epoch_stakes(&base_stakes, &other_stakes),
+            vec![
+                CliEpochStakesDiff {
+                    epoch: 1,
+                    base_total_stake: Some(100),
+                    other_total_stake: None,
+                },
+                CliEpochStakesDiff {
+                    epoch: 2,
+                    base_total_stake: Some(200),
+                    other_total_stake: Some(250),
+                },
+                CliEpochStakesDiff {
+                    epoch: 3,
+                    base_total_stake: None,
+                    other_total_stake: Some(300),
+                },
+            ]
+        );
+    }
+}
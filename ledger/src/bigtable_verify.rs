@@ -0,0 +1,139 @@
+use {
+    crate::blockstore::Blockstore,
+    log::*,
+    solana_measure::measure::Measure,
+    solana_sdk::clock::Slot,
+    std::{
+        result::Result,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    },
+};
+
+// Attempt to verify this many blocks in parallel
+const NUM_BLOCKS_TO_VERIFY_IN_PARALLEL: usize = 32;
+
+#[derive(Debug, Default)]
+pub struct VerifyAndRepairStats {
+    pub num_blocks_verified: usize,
+    pub num_blocks_repaired: usize,
+    /// Slots whose stored ledger data did not match the blockstore
+    pub mismatched_slots: Vec<Slot>,
+    /// Slots that could not be loaded, verified or repaired
+    pub failed_slots: Vec<Slot>,
+}
+
+/// Verifies that every rooted block of a Blockstore in the range `[starting_slot, ending_slot]`
+/// is fully stored in bigtable LedgerStorage, and re-uploads the blocks that are missing or do
+/// not match. A stored block with a different blockhash or transaction count is deleted before
+/// its re-upload, so none of its index rows are left behind.
+///
+/// If `dry_run` is set, nothing is repaired.
+pub async fn verify_and_repair_confirmed_blocks(
+    blockstore: Arc<Blockstore>,
+    bigtable: solana_storage_bigtable::LedgerStorage,
+    starting_slot: Slot,
+    ending_slot: Slot,
+    dry_run: bool,
+    exit: Arc<AtomicBool>,
+) -> Result<VerifyAndRepairStats, Box<dyn std::error::Error>> {
+    let mut measure = Measure::start("entire verify and repair");
+
+    let blockstore_slots: Vec<_> = blockstore
+        .rooted_slot_iterator(starting_slot)
+        .map_err(|err| {
+            format!("Failed to load entries starting from slot {starting_slot}: {err:?}")
+        })?
+        .take_while(|slot| *slot <= ending_slot)
+        .collect();
+    info!(
+        "{} rooted blocks to be verified between slots {} and {}",
+        blockstore_slots.len(),
+        starting_slot,
+        ending_slot
+    );
+
+    let mut stats = VerifyAndRepairStats::default();
+    for slots in blockstore_slots.chunks(NUM_BLOCKS_TO_VERIFY_IN_PARALLEL) {
+        if exit.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let mut measure_verify = Measure::start("Verify");
+        let blocks: Vec<_> = slots
+            .iter()
+            .filter_map(
+                |slot| match blockstore.get_rooted_block_with_entries(*slot, true) {
+                    Ok(confirmed_block_with_entries) => Some((*slot, confirmed_block_with_entries)),
+                    Err(err) => {
+                        warn!(
+                            "Failed to get load confirmed block from slot {}: {:?}",
+                            slot, err
+                        );
+                        stats.failed_slots.push(*slot);
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        let verifications = blocks.into_iter().map(|(slot, confirmed_block)| {
+            let bigtable = bigtable.clone();
+            async move {
+                let mismatches = match bigtable
+                    .verify_confirmed_block(slot, &confirmed_block.block)
+                    .await
+                {
+                    Ok(mismatches) => mismatches,
+                    Err(err) => return (slot, Err(err)),
+                };
+                if mismatches.is_empty() || dry_run {
+                    return (slot, Ok(mismatches));
+                }
+                if mismatches
+                    .iter()
+                    .any(|mismatch| mismatch.is_different_block())
+                {
+                    if let Err(err) = bigtable.delete_confirmed_block(slot, false).await {
+                        return (slot, Err(err));
+                    }
+                }
+                let result = bigtable
+                    .upload_confirmed_block_with_entries(slot, confirmed_block)
+                    .await
+                    .map(|()| mismatches);
+                (slot, result)
+            }
+        });
+
+        for (slot, result) in futures::future::join_all(verifications).await {
+            match result {
+                Ok(mismatches) => {
+                    stats.num_blocks_verified += 1;
+                    if !mismatches.is_empty() {
+                        for mismatch in &mismatches {
+                            warn!("slot {}: {}", slot, mismatch);
+                        }
+                        stats.mismatched_slots.push(slot);
+                        if !dry_run {
+                            stats.num_blocks_repaired += 1;
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!("verify and repair of slot {} failed: {:?}", slot, err);
+                    stats.failed_slots.push(slot);
+                }
+            }
+        }
+
+        measure_verify.stop();
+        info!("{} for {} blocks", measure_verify, slots.len());
+    }
+
+    measure.stop();
+    info!("{}", measure);
+    Ok(stats)
+}
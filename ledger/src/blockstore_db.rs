@@ -9,7 +9,9 @@ use {
             PERF_METRIC_OP_NAME_MULTI_GET, PERF_METRIC_OP_NAME_PUT,
             PERF_METRIC_OP_NAME_WRITE_BATCH,
         },
-        blockstore_options::{AccessType, BlockstoreOptions, LedgerColumnOptions},
+        blockstore_options::{
+            AccessType, BlockstoreCompressionType, BlockstoreOptions, LedgerColumnOptions,
+        },
     },
     bincode::{deserialize, serialize},
     byteorder::{BigEndian, ByteOrder},
@@ -20,8 +22,8 @@ use {
         compaction_filter::CompactionFilter,
         compaction_filter_factory::{CompactionFilterContext, CompactionFilterFactory},
         properties as RocksProperties, ColumnFamily, ColumnFamilyDescriptor, CompactionDecision,
-        DBCompressionType, DBIterator, DBPinnableSlice, DBRawIterator,
-        IteratorMode as RocksIteratorMode, LiveFile, Options, WriteBatch as RWriteBatch, DB,
+        DBIterator, DBPinnableSlice, DBRawIterator, IteratorMode as RocksIteratorMode, LiveFile,
+        Options, WriteBatch as RWriteBatch, DB,
     },
     serde::{de::DeserializeOwned, Serialize},
     solana_accounts_db::hardened_unpack::UnpackError,
@@ -449,6 +451,7 @@ impl Rocks {
                 )?
             }
         };
+
         let rocks = Rocks {
             db,
             path,
@@ -1978,16 +1981,25 @@ fn process_cf_options_advanced<C: 'static + Column + ColumnName>(
     cf_options: &mut Options,
     column_options: &LedgerColumnOptions,
 ) {
-    // Explicitly disable compression on all columns by default
+    // Explicitly set the compression of every column, which is None for the
+    // columns that are not eligible for compression. Every SST file records
+    // its own compression, so files written before a compression change stay
+    // readable and are rewritten in the new compression as they are compacted
     // See https://docs.rs/rocksdb/0.21.0/rocksdb/struct.Options.html#method.set_compression_type
-    cf_options.set_compression_type(DBCompressionType::None);
-
-    if should_enable_compression::<C>() {
-        cf_options.set_compression_type(
-            column_options
-                .compression_type
-                .to_rocksdb_compression_type(),
-        );
+    let compression_type = column_options.get_compression_type(C::NAME);
+    cf_options.set_compression_type(compression_type.to_rocksdb_compression_type());
+    if let BlockstoreCompressionType::Zstd {
+        level,
+        max_dict_bytes,
+    } = compression_type
+    {
+        // Keep the RocksDB defaults for window_bits (-14) and strategy (0)
+        let max_dict_bytes = i32::try_from(*max_dict_bytes).unwrap_or(i32::MAX);
+        cf_options.set_compression_options(-14, *level, 0, max_dict_bytes);
+        if max_dict_bytes > 0 {
+            // zstd recommends training a dictionary on 100x its size of samples
+            cf_options.set_zstd_max_train_bytes(max_dict_bytes.saturating_mul(100));
+        }
     }
 }
 
@@ -2081,15 +2093,15 @@ fn should_enable_cf_compaction(cf_name: &str) -> bool {
     )
 }
 
-// Returns true if the column family enables compression.
-fn should_enable_compression<C: 'static + Column + ColumnName>() -> bool {
-    C::NAME == columns::TransactionStatus::NAME
-}
-
 #[cfg(test)]
 pub mod tests {
     use {
-        super::*, crate::blockstore_db::columns::ShredData, std::path::PathBuf, tempfile::tempdir,
+        super::*,
+        crate::{
+            blockstore_db::columns::ShredData, blockstore_options::DEFAULT_ZSTD_COMPRESSION_LEVEL,
+        },
+        std::path::PathBuf,
+        tempfile::tempdir,
     };
 
     #[test]
@@ -2219,6 +2231,46 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_open_mixed_compression() {
+        solana_logger::setup();
+
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path();
+        let open_with_compression = |compression_type| {
+            let options = BlockstoreOptions {
+                enforce_ulimit_nofile: false,
+                column_options: LedgerColumnOptions {
+                    compression_type,
+                    ..LedgerColumnOptions::default()
+                },
+                ..BlockstoreOptions::default()
+            };
+            Rocks::open(db_path.to_path_buf(), options).unwrap()
+        };
+
+        // Write and flush one SST file per compression of the same column, and
+        // check that every value stays readable by the next compressions
+        let compression_types = [
+            BlockstoreCompressionType::Zstd {
+                level: DEFAULT_ZSTD_COMPRESSION_LEVEL,
+                max_dict_bytes: 16 * 1024,
+            },
+            BlockstoreCompressionType::Lz4,
+            BlockstoreCompressionType::None,
+        ];
+        let value = vec![7u8; 4096];
+        for (i, compression_type) in compression_types.into_iter().enumerate() {
+            let rocks = open_with_compression(compression_type);
+            let cf = rocks.cf_handle(columns::TransactionStatus::NAME);
+            rocks.put_cf(cf, &[i as u8], &value).unwrap();
+            rocks.db.flush_cf(cf).unwrap();
+            for key in 0..=i {
+                assert_eq!(rocks.get_cf(cf, &[key as u8]).unwrap(), Some(value.clone()));
+            }
+        }
+    }
+
     impl<C, const K: usize> LedgerColumn<C, K>
     where
         C: ColumnIndexDeprecation + ProtobufColumn + ColumnName,
@@ -341,7 +341,7 @@ impl BlockstoreRocksDbColumnFamilyMetrics {
             "blockstore_rocksdb_cfs",
             // tags that support group-by operations
             "cf_name" => cf_name,
-            "compression" => column_options.get_compression_type_string(),
+            "compression" => column_options.get_compression_type_string(cf_name),
             // Size related
             (
                 "total_sst_files_size",
@@ -452,7 +452,7 @@ pub(crate) fn report_rocksdb_read_perf(
             // tags that support group-by operations
             "op" => op_name,
             "cf_name" => cf_name,
-            "compression" => column_options.get_compression_type_string(),
+            "compression" => column_options.get_compression_type_string(cf_name),
             // total nanos spent on the entire operation.
             ("total_op_nanos", total_op_duration.as_nanos() as i64, i64),
             (
@@ -627,7 +627,7 @@ pub(crate) fn report_rocksdb_write_perf(
             // tags that support group-by operations
             "op" => op_name,
             "cf_name" => cf_name,
-            "compression" => column_options.get_compression_type_string(),
+            "compression" => column_options.get_compression_type_string(cf_name),
             // total nanos spent on the entire operation.
             ("total_op_nanos", total_op_duration.as_nanos() as i64, i64),
             // total nanos spent on writing to WAL
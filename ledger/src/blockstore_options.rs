@@ -1,5 +1,7 @@
 use {
-    crate::blockstore_db::{default_num_compaction_threads, default_num_flush_threads},
+    crate::blockstore_db::{
+        columns, default_num_compaction_threads, default_num_flush_threads, ColumnName,
+    },
     rocksdb::{DBCompressionType as RocksCompressionType, DBRecoveryMode},
     std::num::NonZeroUsize,
 };
@@ -101,10 +103,15 @@ impl From<BlockstoreRecoveryMode> for DBRecoveryMode {
 /// reporting metrics.
 #[derive(Default, Debug, Clone)]
 pub struct LedgerColumnOptions {
-    // Determine the way to compress column families which are eligible for
-    // compression.
+    // Determine the way to compress the transaction status column family.
     pub compression_type: BlockstoreCompressionType,
 
+    // Determine the way to compress the rewards column family.
+    pub rewards_compression_type: BlockstoreCompressionType,
+
+    // Determine the way to compress the data shred column family.
+    pub data_shred_compression_type: BlockstoreCompressionType,
+
     // Control how often RocksDB read/write performance samples are collected.
     // If the value is greater than 0, then RocksDB read/write perf sample
     // will be collected once for every `rocks_perf_sample_interval` ops.
@@ -112,22 +119,51 @@ pub struct LedgerColumnOptions {
 }
 
 impl LedgerColumnOptions {
-    pub fn get_compression_type_string(&self) -> &'static str {
-        match self.compression_type {
+    /// Returns the compression of the column family `cf_name`. Column families
+    /// which are not eligible for compression are never compressed.
+    pub fn get_compression_type(&self, cf_name: &str) -> &BlockstoreCompressionType {
+        if cf_name == columns::TransactionStatus::NAME {
+            &self.compression_type
+        } else if cf_name == columns::Rewards::NAME {
+            &self.rewards_compression_type
+        } else if cf_name == columns::ShredData::NAME {
+            &self.data_shred_compression_type
+        } else {
+            &BlockstoreCompressionType::None
+        }
+    }
+
+    pub fn get_compression_type_string(&self, cf_name: &str) -> &'static str {
+        match self.get_compression_type(cf_name) {
             BlockstoreCompressionType::None => "None",
             BlockstoreCompressionType::Snappy => "Snappy",
             BlockstoreCompressionType::Lz4 => "Lz4",
             BlockstoreCompressionType::Zlib => "Zlib",
+            BlockstoreCompressionType::Zstd { .. } => "Zstd",
         }
     }
 }
 
-#[derive(Debug, Clone)]
+pub const DEFAULT_ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// The compression of a column family.
+///
+/// Changing the compression of an existing Blockstore is safe: RocksDB records
+/// the compression of each block, so the data written with the previous
+/// compression remains readable until compaction rewrites it.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BlockstoreCompressionType {
     None,
     Snappy,
     Lz4,
     Zlib,
+    Zstd {
+        level: i32,
+        // The size of the dictionary RocksDB trains from samples of each SST
+        // file for a better compression ratio of small values, or 0 to
+        // compress without a dictionary.
+        max_dict_bytes: u32,
+    },
 }
 
 impl Default for BlockstoreCompressionType {
@@ -143,6 +179,7 @@ impl BlockstoreCompressionType {
             Self::Snappy => RocksCompressionType::Snappy,
             Self::Lz4 => RocksCompressionType::Lz4,
             Self::Zlib => RocksCompressionType::Zlib,
+            Self::Zstd { .. } => RocksCompressionType::Zstd,
         }
     }
 }
@@ -6,6 +6,7 @@ pub mod bank_forks_utils;
 pub mod bigtable_delete;
 pub mod bigtable_upload;
 pub mod bigtable_upload_service;
+pub mod bigtable_verify;
 pub mod block_error;
 #[macro_use]
 pub mod blockstore;
@@ -18,6 +18,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         geyser_plugin_always_enabled: config.geyser_plugin_always_enabled,
         rpc_addrs: config.rpc_addrs,
         pubsub_config: config.pubsub_config.clone(),
+        grpc_streaming_addr: config.grpc_streaming_addr,
+        grpc_streaming_config: config.grpc_streaming_config.clone(),
         snapshot_config: config.snapshot_config.clone(),
         max_ledger_shreds: config.max_ledger_shreds,
         blockstore_options: config.blockstore_options.clone(),
@@ -62,6 +64,8 @@ pub fn safe_clone_config(config: &ValidatorConfig) -> ValidatorConfig {
         block_verification_method: config.block_verification_method.clone(),
         block_production_method: config.block_production_method.clone(),
         enable_block_production_forwarding: config.enable_block_production_forwarding,
+        block_production_forwarding_mode: config.block_production_forwarding_mode,
+        enable_program_cost_estimation: config.enable_program_cost_estimation,
         generator_config: config.generator_config.clone(),
         use_snapshot_archives_at_startup: config.use_snapshot_archives_at_startup,
         wen_restart_proto_path: config.wen_restart_proto_path.clone(),
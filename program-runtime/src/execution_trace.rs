@@ -0,0 +1,194 @@
+//! Structured, per-instruction record of a transaction's execution.
+//!
+//! When enabled, the [`ExecutionTraceRecorder`] is handed to the
+//! [`InvokeContext`](crate::invoke_context::InvokeContext) and receives one
+//! [`InstructionTrace`] for every top-level instruction and every CPI, in the
+//! order in which they were invoked.
+
+use {
+    solana_account::ReadableAccount,
+    solana_instruction::error::InstructionError,
+    solana_pubkey::Pubkey,
+    solana_transaction_context::{IndexOfAccount, InstructionContext, TransactionContext},
+    std::{cell::RefCell, rc::Rc},
+};
+
+/// The state change of a single account caused by one instruction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AccountTrace {
+    pub pubkey: Pubkey,
+    pub is_writable: bool,
+    pub pre_lamports: u64,
+    pub post_lamports: u64,
+    pub pre_data_len: usize,
+    pub post_data_len: usize,
+}
+
+impl AccountTrace {
+    /// Change in lamports over the instruction, saturating at the `i64` bounds
+    pub fn lamports_delta(&self) -> i64 {
+        let delta = i128::from(self.post_lamports).saturating_sub(i128::from(self.pre_lamports));
+        i64::try_from(delta).unwrap_or(if delta.is_negative() {
+            i64::MIN
+        } else {
+            i64::MAX
+        })
+    }
+
+    /// Change in account data length over the instruction
+    pub fn data_len_delta(&self) -> i64 {
+        (self.post_data_len as i64).saturating_sub(self.pre_data_len as i64)
+    }
+}
+
+/// Execution record of a single instruction or CPI
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstructionTrace {
+    pub program_id: Pubkey,
+    /// Invocation stack height, top level instructions are height
+    /// `solana_instruction::TRANSACTION_LEVEL_STACK_HEIGHT`
+    pub stack_height: usize,
+    /// Compute units consumed, including those of any nested invocations
+    pub compute_units_consumed: u64,
+    /// The deduplicated instruction accounts, in instruction order
+    pub accounts: Vec<AccountTrace>,
+    pub result: Result<(), InstructionError>,
+}
+
+/// Collects [`InstructionTrace`]s while a transaction is being processed
+#[derive(Debug, Default)]
+pub struct ExecutionTraceRecorder {
+    traces: Vec<InstructionTrace>,
+}
+
+impl ExecutionTraceRecorder {
+    pub fn new_ref() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    /// Reserves a trace entry for the instruction which was just pushed onto
+    /// the invocation stack and returns its index
+    pub(crate) fn begin(
+        &mut self,
+        transaction_context: &TransactionContext,
+        instruction_context: &InstructionContext,
+    ) -> usize {
+        let program_id = instruction_context
+            .get_last_program_key(transaction_context)
+            .copied()
+            .unwrap_or_default();
+        let mut accounts: Vec<AccountTrace> = Vec::new();
+        for instruction_account_index in 0..instruction_context.get_number_of_instruction_accounts()
+        {
+            if !matches!(
+                instruction_context.is_instruction_account_duplicate(instruction_account_index),
+                Ok(None)
+            ) {
+                continue;
+            }
+            let Ok(index_in_transaction) = instruction_context
+                .get_index_of_instruction_account_in_transaction(instruction_account_index)
+            else {
+                continue;
+            };
+            let Some((pubkey, lamports, data_len)) =
+                Self::account_state(transaction_context, index_in_transaction)
+            else {
+                continue;
+            };
+            accounts.push(AccountTrace {
+                pubkey,
+                is_writable: instruction_context
+                    .is_instruction_account_writable(instruction_account_index)
+                    .unwrap_or(false),
+                pre_lamports: lamports,
+                post_lamports: lamports,
+                pre_data_len: data_len,
+                post_data_len: data_len,
+            });
+        }
+        self.traces.push(InstructionTrace {
+            program_id,
+            stack_height: instruction_context.get_stack_height(),
+            compute_units_consumed: 0,
+            accounts,
+            result: Ok(()),
+        });
+        self.traces.len().saturating_sub(1)
+    }
+
+    /// Completes the trace entry at `index` once the instruction has finished
+    pub(crate) fn end(
+        &mut self,
+        index: usize,
+        transaction_context: &TransactionContext,
+        compute_units_consumed: u64,
+        result: &Result<(), InstructionError>,
+    ) {
+        let Some(trace) = self.traces.get_mut(index) else {
+            return;
+        };
+        trace.compute_units_consumed = compute_units_consumed;
+        trace.result = result.clone();
+        for account in trace.accounts.iter_mut() {
+            let Some((_, lamports, data_len)) = transaction_context
+                .find_index_of_account(&account.pubkey)
+                .and_then(|index_in_transaction| {
+                    Self::account_state(transaction_context, index_in_transaction)
+                })
+            else {
+                continue;
+            };
+            account.post_lamports = lamports;
+            account.post_data_len = data_len;
+        }
+    }
+
+    fn account_state(
+        transaction_context: &TransactionContext,
+        index_in_transaction: IndexOfAccount,
+    ) -> Option<(Pubkey, u64, usize)> {
+        let pubkey = *transaction_context
+            .get_key_of_account_at_index(index_in_transaction)
+            .ok()?;
+        let account = transaction_context
+            .accounts()
+            .try_borrow(index_in_transaction)
+            .ok()?;
+        Some((pubkey, account.lamports(), account.data().len()))
+    }
+
+    pub fn get_recorded_traces(&self) -> &[InstructionTrace] {
+        self.traces.as_slice()
+    }
+
+    pub fn into_traces(self) -> Vec<InstructionTrace> {
+        self.traces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_trace_deltas() {
+        let trace = AccountTrace {
+            pubkey: Pubkey::new_unique(),
+            is_writable: true,
+            pre_lamports: 10,
+            post_lamports: 3,
+            pre_data_len: 0,
+            post_data_len: 100,
+        };
+        assert_eq!(trace.lamports_delta(), -7);
+        assert_eq!(trace.data_len_delta(), 100);
+
+        let trace = AccountTrace {
+            pre_lamports: 0,
+            post_lamports: u64::MAX,
+            ..trace
+        };
+        assert_eq!(trace.lamports_delta(), i64::MAX);
+    }
+}
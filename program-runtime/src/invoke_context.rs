@@ -1,5 +1,6 @@
 use {
     crate::{
+        execution_trace::ExecutionTraceRecorder,
         loaded_programs::{
             ProgramCacheEntry, ProgramCacheEntryType, ProgramCacheForTxBatch,
             ProgramRuntimeEnvironments,
@@ -199,6 +200,7 @@ pub struct InvokeContext<'a> {
     /// the designated compute budget during program execution.
     compute_meter: RefCell<u64>,
     log_collector: Option<Rc<RefCell<LogCollector>>>,
+    execution_trace_recorder: Option<Rc<RefCell<ExecutionTraceRecorder>>>,
     /// Latest measurement not yet accumulated in [ExecuteDetailsTimings::execute_us]
     pub execute_time: Option<Measure>,
     pub timings: ExecuteDetailsTimings,
@@ -220,6 +222,7 @@ impl<'a> InvokeContext<'a> {
             program_cache_for_tx_batch,
             environment_config,
             log_collector,
+            execution_trace_recorder: None,
             compute_budget,
             compute_meter: RefCell::new(compute_budget.compute_unit_limit),
             execute_time: None,
@@ -472,10 +475,14 @@ impl<'a> InvokeContext<'a> {
             .get_next_instruction_context()?
             .configure(program_indices, instruction_accounts, instruction_data);
         self.push()?;
-        self.process_executable_chain(compute_units_consumed, timings)
+        let trace_index = self.begin_instruction_trace();
+        let result = self
+            .process_executable_chain(compute_units_consumed, timings)
             // MUST pop if and only if `push` succeeded, independent of `result`.
             // Thus, the `.and()` instead of an `.and_then()`.
-            .and(self.pop())
+            .and(self.pop());
+        self.end_instruction_trace(trace_index, *compute_units_consumed, &result);
+        result
     }
 
     /// Processes a precompile instruction
@@ -491,11 +498,12 @@ impl<'a> InvokeContext<'a> {
             .get_next_instruction_context()?
             .configure(program_indices, instruction_accounts, instruction_data);
         self.push()?;
+        let trace_index = self.begin_instruction_trace();
 
         let feature_set = self.get_feature_set();
         let move_precompile_verification_to_svm =
             feature_set.is_active(&move_precompile_verification_to_svm::id());
-        if move_precompile_verification_to_svm {
+        let result = if move_precompile_verification_to_svm {
             let instruction_datas: Vec<_> = message_instruction_datas_iter.collect();
             precompile
                 .verify(instruction_data, &instruction_datas, feature_set)
@@ -503,6 +511,41 @@ impl<'a> InvokeContext<'a> {
                 .and(self.pop())
         } else {
             self.pop()
+        };
+        self.end_instruction_trace(trace_index, 0, &result);
+        result
+    }
+
+    /// Opens an execution trace entry for the instruction on top of the stack
+    fn begin_instruction_trace(&self) -> Option<usize> {
+        let recorder = self.execution_trace_recorder.as_ref()?;
+        let instruction_context = self
+            .transaction_context
+            .get_current_instruction_context()
+            .ok()?;
+        Some(
+            recorder
+                .borrow_mut()
+                .begin(self.transaction_context, instruction_context),
+        )
+    }
+
+    /// Closes the execution trace entry opened by `begin_instruction_trace()`
+    fn end_instruction_trace(
+        &self,
+        trace_index: Option<usize>,
+        compute_units_consumed: u64,
+        result: &Result<(), InstructionError>,
+    ) {
+        if let (Some(recorder), Some(trace_index)) =
+            (self.execution_trace_recorder.as_ref(), trace_index)
+        {
+            recorder.borrow_mut().end(
+                trace_index,
+                self.transaction_context,
+                compute_units_consumed,
+                result,
+            );
         }
     }
 
@@ -608,6 +651,19 @@ impl<'a> InvokeContext<'a> {
         self.log_collector.clone()
     }
 
+    /// Set the recorder which receives a structured trace of every instruction
+    pub fn set_execution_trace_recorder(
+        &mut self,
+        execution_trace_recorder: Option<Rc<RefCell<ExecutionTraceRecorder>>>,
+    ) {
+        self.execution_trace_recorder = execution_trace_recorder;
+    }
+
+    /// Get this invocation's ExecutionTraceRecorder
+    pub fn get_execution_trace_recorder(&self) -> Option<Rc<RefCell<ExecutionTraceRecorder>>> {
+        self.execution_trace_recorder.clone()
+    }
+
     /// Consume compute units
     pub fn consume_checked(&self, amount: u64) -> Result<(), Box<dyn std::error::Error>> {
         let mut compute_meter = self.compute_meter.borrow_mut();
@@ -7,6 +7,7 @@
 extern crate solana_metrics;
 
 pub use solana_sbpf;
+pub mod execution_trace;
 pub mod invoke_context;
 pub mod loaded_programs;
 pub mod mem_pool;
@@ -121,6 +121,7 @@ fn load_execute_and_commit_transaction(bank: &Bank, tx: Transaction) -> Transact
                 enable_cpi_recording: true,
                 enable_log_recording: true,
                 enable_return_data_recording: false,
+                enable_trace_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -658,7 +659,7 @@ fn test_return_data_and_log_data_syscall() {
         let transaction = Transaction::new(&[&mint_keypair], message, blockhash);
         let sanitized_tx = RuntimeTransaction::from_transaction_for_tests(transaction);
 
-        let result = bank.simulate_transaction(&sanitized_tx, false);
+        let result = bank.simulate_transaction(&sanitized_tx, false, false);
 
         assert!(result.result.is_ok());
 
@@ -5546,6 +5547,7 @@ fn test_function_call_args() {
                 enable_cpi_recording: false,
                 enable_log_recording: false,
                 enable_return_data_recording: true,
+                enable_trace_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -93,7 +93,7 @@ fn test_syscall_get_epoch_stake() {
     let transaction = Transaction::new(&[&mint_keypair], message, blockhash);
     let sanitized_tx = RuntimeTransaction::from_transaction_for_tests(transaction);
 
-    let result = bank.simulate_transaction(&sanitized_tx, false);
+    let result = bank.simulate_transaction(&sanitized_tx, false, false);
 
     assert!(result.result.is_ok());
 
@@ -29,14 +29,16 @@
 //! reliably. For a non-blocking, cancelable API, use the asynchronous client
 //! in [`crate::nonblocking::pubsub_client`].
 //!
-//! By default the [`block_subscribe`] and [`vote_subscribe`] events are
-//! disabled on RPC nodes. They can be enabled by passing
-//! `--rpc-pubsub-enable-block-subscription` and
+//! By default the [`block_subscribe`], [`transaction_subscribe`] and
+//! [`vote_subscribe`] events are disabled on RPC nodes. They can be enabled by
+//! passing `--rpc-pubsub-enable-block-subscription`,
+//! `--rpc-pubsub-enable-transaction-subscription` and
 //! `--rpc-pubsub-enable-vote-subscription` to `agave-validator`. When these
 //! methods are disabled, the RPC server will return a "Method not found" error
 //! message.
 //!
 //! [`block_subscribe`]: https://docs.rs/solana-rpc/latest/solana_rpc/rpc_pubsub/trait.RpcSolPubSub.html#tymethod.block_subscribe
+//! [`transaction_subscribe`]: https://docs.rs/solana-rpc/latest/solana_rpc/rpc_pubsub/trait.RpcSolPubSub.html#tymethod.transaction_subscribe
 //! [`vote_subscribe`]: https://docs.rs/solana-rpc/latest/solana_rpc/rpc_pubsub/trait.RpcSolPubSub.html#tymethod.vote_subscribe
 //!
 //! # Examples
@@ -103,11 +105,11 @@ use {
         config::{
             RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
             RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcTransactionLogsFilter, RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
         },
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+            RpcSignatureResult, RpcTransactionUpdate, RpcVote, SlotInfo, SlotUpdate,
         },
     },
     solana_signature::Signature,
@@ -275,6 +277,13 @@ pub type BlockSubscription = (
     Receiver<RpcResponse<RpcBlockUpdate>>,
 );
 
+pub type PubsubTransactionClientSubscription =
+    PubsubClientSubscription<RpcResponse<RpcTransactionUpdate>>;
+pub type TransactionSubscription = (
+    PubsubTransactionClientSubscription,
+    Receiver<RpcResponse<RpcTransactionUpdate>>,
+);
+
 pub type PubsubProgramClientSubscription = PubsubClientSubscription<RpcResponse<RpcKeyedAccount>>;
 pub type ProgramSubscription = (
     PubsubProgramClientSubscription,
@@ -434,6 +443,60 @@ impl PubsubClient {
         Ok((result, receiver))
     }
 
+    /// Subscribe to transaction events.
+    ///
+    /// Receives messages of type [`RpcTransactionUpdate`] for every transaction
+    /// matching `filter`, with its status meta, when its block reaches the
+    /// requested commitment.
+    /// At processed commitment, transactions of a block that is later
+    /// abandoned for another fork are still notified.
+    ///
+    /// This method is disabled by default. It can be enabled by passing
+    /// `--rpc-pubsub-enable-transaction-subscription` to `agave-validator`.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `transactionSubscribe` RPC method.
+    pub fn transaction_subscribe(
+        url: &str,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> Result<TransactionSubscription, PubsubClientError> {
+        let url = Url::parse(url)?;
+        let socket = connect_with_retry(url)?;
+        let (sender, receiver) = unbounded();
+
+        let socket = Arc::new(RwLock::new(socket));
+        let socket_clone = socket.clone();
+        let exit = Arc::new(AtomicBool::new(false));
+        let exit_clone = exit.clone();
+        let body = json!({
+            "jsonrpc":"2.0",
+            "id":1,
+            "method":"transactionSubscribe",
+            "params":[filter, config]
+        })
+        .to_string();
+
+        let subscription_id =
+            PubsubTransactionClientSubscription::send_subscribe(&socket_clone, body)?;
+
+        let t_cleanup = std::thread::spawn(move || {
+            Self::cleanup_with_sender(exit_clone, &socket_clone, sender)
+        });
+
+        let result = PubsubClientSubscription {
+            message_type: PhantomData,
+            operation: "transaction",
+            socket,
+            subscription_id,
+            t_cleanup: Some(t_cleanup),
+            exit,
+        };
+
+        Ok((result, receiver))
+    }
+
     /// Subscribe to transaction log events.
     ///
     /// Receives messages of type [`RpcLogsResponse`] when a transaction is committed.
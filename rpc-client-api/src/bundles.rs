@@ -1,7 +1,7 @@
 //! Use a separate file for Jito related code to minimize upstream merge conflicts.
 
 use {
-    crate::config::RpcSimulateTransactionAccountsConfig,
+    crate::{config::RpcSimulateTransactionAccountsConfig, response::RpcInstructionTrace},
     solana_account_decoder_client_types::UiAccount,
     solana_bundle::{bundle_execution::LoadAndExecuteBundleError, BundleExecutionError},
     solana_sdk::{
@@ -115,6 +115,8 @@ pub struct RpcSimulateBundleTransactionResult {
     pub post_execution_accounts: Option<Vec<UiAccount>>,
     pub units_consumed: Option<u64>,
     pub return_data: Option<UiTransactionReturnData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<RpcInstructionTrace>>,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -138,6 +140,10 @@ pub struct RpcSimulateBundleConfig {
     /// Replace recent blockhash to simulate old transactions without resigning.
     #[serde(default)]
     pub replace_recent_blockhash: bool,
+
+    /// Return a per-instruction execution trace for each transaction.
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
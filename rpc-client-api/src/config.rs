@@ -44,6 +44,8 @@ pub struct RpcSimulateTransactionConfig {
     pub min_context_slot: Option<Slot>,
     #[serde(default)]
     pub inner_instructions: bool,
+    #[serde(default)]
+    pub trace: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -156,6 +158,16 @@ pub struct RpcAccountInfoConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountHistoryConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Return the accounts as of this rooted slot, which has to be within the account history
+    /// retained by the node
+    pub at_slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcProgramAccountsConfig {
@@ -166,6 +178,55 @@ pub struct RpcProgramAccountsConfig {
     pub sort_results: Option<bool>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAccountsPageConfig {
+    pub filters: Option<Vec<RpcFilterType>>,
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Maximum number of accounts in the page
+    pub limit: Option<usize>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Format of the notifications of an account or program subscription
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcNotificationFormat {
+    /// JSON-RPC notifications, with account data encoded according to `encoding`
+    #[default]
+    Json,
+    /// Versioned, bincode-serialized `RpcBinaryNotification`s, sent as binary WebSocket messages
+    Binary,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountSubscribeConfig {
+    #[serde(flatten)]
+    pub account_config: RpcAccountInfoConfig,
+    /// Notify only these byte ranges of the account data, concatenated in order
+    pub data_slices: Option<Vec<UiDataSliceConfig>>,
+    /// Skip notifications that leave the notified account data unchanged. Requires `confirmed` or
+    /// `finalized` commitment
+    pub changes_only: Option<bool>,
+    pub notification_format: Option<RpcNotificationFormat>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramSubscribeConfig {
+    #[serde(flatten)]
+    pub program_config: RpcProgramAccountsConfig,
+    /// Notify only these byte ranges of the account data, concatenated in order
+    pub data_slices: Option<Vec<UiDataSliceConfig>>,
+    /// Skip notifications that leave the notified account data unchanged. Requires `confirmed` or
+    /// `finalized` commitment
+    pub changes_only: Option<bool>,
+    pub notification_format: Option<RpcNotificationFormat>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum RpcTransactionLogsFilter {
@@ -214,6 +275,33 @@ pub struct RpcBlockSubscribeConfig {
     pub max_supported_transaction_version: Option<u8>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSubscribeFilter {
+    /// Only vote transactions if true, only non-vote transactions if false [default: all]
+    pub vote: Option<bool>,
+    /// Only failed transactions if true, only successful transactions if false [default: all]
+    pub failed: Option<bool>,
+    /// Only transactions signed by this account (base-58 encoded)
+    pub signer: Option<String>,
+    /// Only transactions that mention any of these accounts (base-58 encoded)
+    pub account_include: Option<Vec<String>>,
+    /// Only transactions that mention none of these accounts (base-58 encoded)
+    pub account_exclude: Option<Vec<String>>,
+    /// Only transactions that mention all of these accounts (base-58 encoded)
+    pub account_required: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSubscribeConfig {
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub show_rewards: Option<bool>,
+    pub max_supported_transaction_version: Option<u8>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcSignaturesForAddressConfig {
@@ -232,11 +320,11 @@ pub enum RpcEncodingConfigWrapper<T> {
     Current(Option<T>),
 }
 
-impl<T: EncodingConfig + Default + Copy> RpcEncodingConfigWrapper<T> {
+impl<T: EncodingConfig + Default + Clone> RpcEncodingConfigWrapper<T> {
     pub fn convert_to_current(&self) -> T {
         match self {
             RpcEncodingConfigWrapper::Deprecated(encoding) => T::new_with_encoding(encoding),
-            RpcEncodingConfigWrapper::Current(config) => config.unwrap_or_default(),
+            RpcEncodingConfigWrapper::Current(config) => config.clone().unwrap_or_default(),
         }
     }
 
@@ -246,7 +334,7 @@ impl<T: EncodingConfig + Default + Copy> RpcEncodingConfigWrapper<T> {
                 RpcEncodingConfigWrapper::Deprecated(*encoding)
             }
             RpcEncodingConfigWrapper::Current(config) => {
-                RpcEncodingConfigWrapper::Current(config.map(|config| config.into()))
+                RpcEncodingConfigWrapper::Current(config.clone().map(|config| config.into()))
             }
         }
     }
@@ -256,7 +344,7 @@ pub trait EncodingConfig {
     fn new_with_encoding(encoding: &Option<UiTransactionEncoding>) -> Self;
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcBlockConfig {
     pub encoding: Option<UiTransactionEncoding>,
@@ -265,6 +353,24 @@ pub struct RpcBlockConfig {
     #[serde(flatten)]
     pub commitment: Option<CommitmentConfig>,
     pub max_supported_transaction_version: Option<u8>,
+    /// Only return transactions referencing any of these base-58 encoded accounts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_include: Option<Vec<String>>,
+    /// Only return transactions invoking any of these base-58 encoded programs in a
+    /// top-level instruction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub program_ids: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_failed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_vote: Option<bool>,
+    /// Index of the first transaction of the block to consider, as returned in
+    /// `nextTransactionCursor` of the previous page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_cursor: Option<u64>,
+    /// Maximum number of transactions to return
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_limit: Option<usize>,
 }
 
 impl EncodingConfig for RpcBlockConfig {
@@ -317,6 +423,17 @@ impl EncodingConfig for RpcTransactionConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPrioritizationFeeEstimateConfig {
+    /// Estimate for transactions writing to these accounts; block-wide if empty
+    pub account_keys: Option<Vec<String>>,
+    /// Number of most recent blocks to sample
+    pub lookback_slots: Option<usize>,
+    /// Percentiles to return, in basis points from 0 to 10_000; at most 10
+    pub percentiles: Option<Vec<u16>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcBlocksConfigWrapper {
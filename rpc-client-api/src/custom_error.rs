@@ -27,6 +27,8 @@ pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
 pub const JSON_RPC_SERVER_ERROR_EPOCH_REWARDS_PERIOD_ACTIVE: i64 = -32017;
 pub const JSON_RPC_SERVER_ERROR_SLOT_NOT_EPOCH_BOUNDARY: i64 = -32018;
 pub const JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_UNREACHABLE: i64 = -32019;
+pub const JSON_RPC_SERVER_ERROR_RATE_LIMITED: i64 = -32020;
+pub const JSON_RPC_SERVER_ERROR_INVALID_API_KEY: i64 = -32021;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -78,6 +80,10 @@ pub enum RpcCustomError {
     SlotNotEpochBoundary { slot: Slot },
     #[error("LongTermStorageUnreachable")]
     LongTermStorageUnreachable,
+    #[error("RateLimited")]
+    RateLimited { method: String },
+    #[error("InvalidApiKey")]
+    InvalidApiKey,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -252,6 +258,16 @@ impl From<RpcCustomError> for Error {
                 message: "Failed to query long-term storage; please try again".to_string(),
                 data: None,
             },
+            RpcCustomError::RateLimited { method } => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_RATE_LIMITED),
+                message: format!("Rate limit exceeded for method {method}"),
+                data: None,
+            },
+            RpcCustomError::InvalidApiKey => Self {
+                code: ErrorCode::ServerError(JSON_RPC_SERVER_ERROR_INVALID_API_KEY),
+                message: "Invalid API key".to_string(),
+                data: None,
+            },
         }
     }
 }
@@ -40,6 +40,7 @@ pub enum RpcRequest {
     GetProgramAccounts,
     GetRecentPerformanceSamples,
     GetRecentPrioritizationFees,
+    GetPrioritizationFeeEstimate,
     GetHighestSnapshotSlot,
     GetSignaturesForAddress,
     GetSignatureStatuses,
@@ -106,6 +107,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetProgramAccounts => "getProgramAccounts",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
+            RpcRequest::GetPrioritizationFeeEstimate => "getPrioritizationFeeEstimate",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
             RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
             RpcRequest::GetSignatureStatuses => "getSignatureStatuses",
@@ -149,7 +151,15 @@ pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
+pub const MAX_GET_PROGRAM_ACCOUNTS_PAGE_LIMIT: usize = 10_000;
 pub const MAX_GET_SLOT_LEADERS: usize = 5000;
+pub const MAX_GET_BLOCK_FILTER_ADDRESSES: usize = 256;
+pub const MAX_SUBSCRIPTION_DATA_SLICES: usize = 16;
+// Matches the number of blocks kept by the prioritization fee cache
+pub const MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS: usize = 150;
+pub const MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILES: usize = 10;
+// Percentiles are given in basis points
+pub const MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILE: u16 = 10_000;
 
 // Limit the length of the `epoch_credits` array for each validator in a `get_vote_accounts`
 // response
@@ -5,10 +5,13 @@ use {
     solana_clock::{Epoch, Slot, UnixTimestamp},
     solana_fee_calculator::{FeeCalculator, FeeRateGovernor},
     solana_inflation::Inflation,
+    solana_pubkey::Pubkey,
+    solana_sdk::instruction::InstructionError,
     solana_transaction_error::{TransactionError, TransactionResult as Result},
     solana_transaction_status_client_types::{
-        ConfirmedTransactionStatusWithSignature, TransactionConfirmationStatus, UiConfirmedBlock,
-        UiInnerInstructions, UiTransactionReturnData,
+        ConfirmedTransactionStatusWithSignature, EncodedTransactionWithStatusMeta,
+        TransactionConfirmationStatus, UiConfirmedBlock, UiInnerInstructions,
+        UiTransactionReturnData,
     },
     std::{collections::HashMap, fmt, net::SocketAddr, str::FromStr},
     thiserror::Error,
@@ -166,6 +169,16 @@ pub struct RpcKeyedAccount {
     pub account: UiAccount,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcProgramAccountsPage {
+    pub accounts: Vec<RpcKeyedAccount>,
+    /// Opaque cursor of the next page, absent once the last page was returned. A page may hold
+    /// fewer than `limit` accounts, or none, and still have a next page, since a page only scans
+    /// a bounded number of the program's accounts.
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SlotInfo {
     pub slot: Slot,
@@ -407,6 +420,32 @@ pub struct RpcSimulateTransactionResult {
     pub return_data: Option<UiTransactionReturnData>,
     pub inner_instructions: Option<Vec<UiInnerInstructions>>,
     pub replacement_blockhash: Option<RpcBlockhash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<RpcInstructionTrace>>,
+}
+
+/// Execution record of a single instruction or CPI, in invocation order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcInstructionTrace {
+    pub program_id: String,
+    pub stack_height: u32,
+    /// Compute units consumed, including those of any nested invocations
+    pub compute_units_consumed: u64,
+    pub accounts: Vec<RpcAccountTrace>,
+    pub err: Option<InstructionError>,
+}
+
+/// The state change of a single account caused by one instruction
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcAccountTrace {
+    pub pubkey: String,
+    pub writable: bool,
+    pub lamports_delta: i64,
+    pub data_len_delta: i64,
+    pub post_lamports: u64,
+    pub post_data_len: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -497,6 +536,77 @@ pub struct RpcBlockUpdate {
     pub err: Option<RpcBlockUpdateError>,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionUpdate {
+    pub slot: Slot,
+    pub signature: String,
+    pub transaction: Option<EncodedTransactionWithStatusMeta>,
+    pub err: Option<RpcBlockUpdateError>,
+}
+
+/// Version of the layout of [`RpcBinaryNotification`], which is its first byte
+pub const RPC_BINARY_NOTIFICATION_VERSION: u8 = 1;
+
+/// Notification of a subscription with the binary notification format, sent bincode-serialized
+/// as a binary WebSocket message
+///
+/// Integers are little-endian and fixed-size, and the layout is:
+///
+/// | bytes | field                                             |
+/// |-------|---------------------------------------------------|
+/// | 1     | `version`, [`RPC_BINARY_NOTIFICATION_VERSION`]    |
+/// | 8     | `subscription`, the subscription id               |
+/// | 8     | `slot`, the slot of the notification              |
+/// | ...   | `value`, e.g. an [`RpcBinaryAccount`]             |
+///
+/// Clients should reject notifications with a version they don't know, since a new version may
+/// change everything after the version byte.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RpcBinaryNotification<T> {
+    pub version: u8,
+    pub subscription: u64,
+    pub slot: Slot,
+    pub value: T,
+}
+
+impl<T> RpcBinaryNotification<T> {
+    pub fn new(subscription: u64, slot: Slot, value: T) -> Self {
+        Self {
+            version: RPC_BINARY_NOTIFICATION_VERSION,
+            subscription,
+            slot,
+            value,
+        }
+    }
+}
+
+/// Account of an account or program notification in the binary notification format
+///
+/// The layout follows [`RpcBinaryNotification`]'s conventions:
+///
+/// | bytes | field                                         |
+/// |-------|-----------------------------------------------|
+/// | 32    | `pubkey`                                      |
+/// | 8     | `lamports`                                    |
+/// | 32    | `owner`                                       |
+/// | 1     | `executable`, 0 or 1                          |
+/// | 8     | `rent_epoch`                                  |
+/// | 8     | `space`                                       |
+/// | 8 + n | `data`, its length `n` followed by the bytes  |
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RpcBinaryAccount {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: Epoch,
+    /// Length of the full account data
+    pub space: u64,
+    /// The subscribed data slices, concatenated in order
+    pub data: Vec<u8>,
+}
+
 impl From<ConfirmedTransactionStatusWithSignature> for RpcConfirmedTransactionStatusWithSignature {
     fn from(value: ConfirmedTransactionStatusWithSignature) -> Self {
         let ConfirmedTransactionStatusWithSignature {
@@ -530,6 +640,24 @@ pub struct RpcPrioritizationFee {
     pub prioritization_fee: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPrioritizationFeePercentile {
+    /// In basis points, from 0 to 10_000
+    pub percentile: u16,
+    pub prioritization_fee: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPrioritizationFeeEstimate {
+    /// Number of recent blocks that were sampled
+    pub num_slots: usize,
+    /// Number of transaction fees that were sampled
+    pub num_samples: usize,
+    pub fees: Vec<RpcPrioritizationFeePercentile>,
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -20,7 +20,8 @@ use {
             Response, RpcAccountBalance, RpcBlockProduction, RpcBlockProductionRange, RpcBlockhash,
             RpcConfirmedTransactionStatusWithSignature, RpcContactInfo, RpcIdentity,
             RpcInflationGovernor, RpcInflationRate, RpcInflationReward, RpcKeyedAccount,
-            RpcPerfSample, RpcPrioritizationFee, RpcResponseContext, RpcSimulateTransactionResult,
+            RpcPerfSample, RpcPrioritizationFee, RpcPrioritizationFeeEstimate,
+            RpcPrioritizationFeePercentile, RpcResponseContext, RpcSimulateTransactionResult,
             RpcSnapshotSlotInfo, RpcSupply, RpcVersionInfo, RpcVoteAccountInfo,
             RpcVoteAccountStatus,
         },
@@ -306,7 +307,8 @@ impl RpcSender for MockSender {
                     units_consumed: None,
                     return_data: None,
                     inner_instructions: None,
-                    replacement_blockhash: None
+                    replacement_blockhash: None,
+                    trace: None,
                 },
             })?,
             "getMinimumBalanceForRentExemption" => json![20],
@@ -390,6 +392,14 @@ impl RpcSender for MockSender {
                 slot: 123_456_789,
                 prioritization_fee: 10_000,
             }])?,
+            "getPrioritizationFeeEstimate" => serde_json::to_value(RpcPrioritizationFeeEstimate {
+                num_slots: 150,
+                num_samples: 1_000,
+                fees: vec![RpcPrioritizationFeePercentile {
+                    percentile: 5_000,
+                    prioritization_fee: 10_000,
+                }],
+            })?,
             "getIdentity" => serde_json::to_value(RpcIdentity {
                 identity: PUBKEY.to_string(),
             })?,
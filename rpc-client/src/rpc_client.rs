@@ -2014,6 +2014,7 @@ impl RpcClient {
     ///     rewards: Some(true),
     ///     commitment: None,
     ///     max_supported_transaction_version: Some(0),
+    ///     ..RpcBlockConfig::default()
     /// };
     /// let block = rpc_client.get_block_with_config(
     ///     slot,
@@ -2682,6 +2683,44 @@ impl RpcClient {
         self.invoke((self.rpc_client.as_ref()).get_recent_prioritization_fees(addresses))
     }
 
+    /// Returns percentiles of the prioritization fees paid in recent blocks.
+    ///
+    /// If `config` lists account keys, the percentiles are over the transactions writing to
+    /// each of them, and the highest fee among the accounts is returned for each percentile.
+    /// Otherwise they are over all transactions. Percentiles are given in basis points, so
+    /// `5_000` is the median.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the `getPrioritizationFeeEstimate` RPC method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_rpc_client_api::{
+    /// #     client_error::Error,
+    /// #     config::RpcPrioritizationFeeEstimateConfig,
+    /// # };
+    /// # use solana_rpc_client::rpc_client::RpcClient;
+    /// # use solana_keypair::Keypair;
+    /// # use solana_signer::Signer;
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// let config = RpcPrioritizationFeeEstimateConfig {
+    ///     account_keys: Some(vec![alice.pubkey().to_string()]),
+    ///     lookback_slots: Some(20),
+    ///     percentiles: Some(vec![5_000, 7_500]),
+    /// };
+    /// let estimate = rpc_client.get_prioritization_fee_estimate(config)?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn get_prioritization_fee_estimate(
+        &self,
+        config: RpcPrioritizationFeeEstimateConfig,
+    ) -> ClientResult<RpcPrioritizationFeeEstimate> {
+        self.invoke((self.rpc_client.as_ref()).get_prioritization_fee_estimate(config))
+    }
+
     /// Returns the identity pubkey for the current node.
     ///
     /// # RPC Reference
@@ -8,9 +8,11 @@ pub mod parsed_token_accounts;
 pub mod rpc;
 mod rpc_cache;
 pub mod rpc_completed_slots_service;
+pub mod rpc_grpc_service;
 pub mod rpc_health;
 pub mod rpc_pubsub;
 pub mod rpc_pubsub_service;
+pub mod rpc_rate_limiter;
 pub mod rpc_service;
 pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
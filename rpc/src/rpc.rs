@@ -1,9 +1,13 @@
 //! The `rpc` module implements the Solana RPC interface.
 use {
     crate::{
-        filter::filter_allows, max_slots::MaxSlots,
+        filter::filter_allows,
+        max_slots::MaxSlots,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-        parsed_token_accounts::*, rpc_cache::LargestAccountsCache, rpc_health::*,
+        parsed_token_accounts::*,
+        rpc_cache::LargestAccountsCache,
+        rpc_health::*,
+        rpc_rate_limiter::{RpcClientKey, RpcRateLimiter},
     },
     base64::{prelude::BASE64_STANDARD, Engine},
     bincode::{config::Options, serialize},
@@ -17,12 +21,17 @@ use {
     solana_account_decoder::{
         encode_ui_account,
         parse_account_data::SplTokenAdditionalData,
-        parse_token::{is_known_spl_token_id, token_amount_to_ui_amount_v2, UiTokenAmount},
+        parse_token::{
+            get_token_account_mint, is_known_spl_token_id, token_amount_to_ui_amount_v2,
+            UiTokenAmount,
+        },
         UiAccount, UiAccountEncoding, UiDataSliceConfig, MAX_BASE58_BYTES,
     },
     solana_accounts_db::{
         accounts::AccountAddressFilter,
-        accounts_index::{AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult},
+        accounts_index::{
+            AccountIndex, AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult, ScanStart,
+        },
     },
     solana_client::connection_cache::Protocol,
     solana_entry::entry::Entry,
@@ -41,15 +50,20 @@ use {
     },
     solana_metrics::inc_new_counter_info,
     solana_perf::packet::PACKET_DATA_SIZE,
+    solana_program_runtime::execution_trace::InstructionTrace,
     solana_rpc_client_api::{
         config::*,
         custom_error::RpcCustomError,
         filter::{Memcmp, RpcFilterType},
         request::{
             TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE,
-            MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+            MAX_GET_BLOCK_FILTER_ADDRESSES, MAX_GET_CONFIRMED_BLOCKS_RANGE,
+            MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT, MAX_GET_PROGRAM_ACCOUNTS_PAGE_LIMIT,
             MAX_GET_PROGRAM_ACCOUNT_FILTERS, MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
             MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+            MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS,
+            MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILE,
+            MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILES,
             MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, NUM_LARGEST_ACCOUNTS,
         },
         response::{Response as RpcResponse, *},
@@ -75,7 +89,7 @@ use {
         epoch_schedule::EpochSchedule,
         exit::Exit,
         hash::Hash,
-        message::SanitizedMessage,
+        message::{SanitizedMessage, VersionedMessage},
         pubkey::{Pubkey, PUBKEY_BYTES},
         signature::{Keypair, Signature, Signer},
         system_instruction,
@@ -88,12 +102,13 @@ use {
     solana_send_transaction_service::send_transaction_service::TransactionInfo,
     solana_stake_program,
     solana_storage_bigtable::Error as StorageError,
+    solana_transaction::simple_vote_transaction_checker::is_simple_vote_transaction_impl,
     solana_transaction_status::{
         map_inner_instructions, BlockEncodingOptions, ConfirmedBlock,
         ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta,
         EncodedConfirmedTransactionWithStatusMeta, Reward, RewardType, Rewards,
         TransactionBinaryEncoding, TransactionConfirmationStatus, TransactionStatus,
-        UiConfirmedBlock, UiTransactionEncoding,
+        TransactionWithStatusMeta, UiConfirmedBlock, UiTransactionEncoding,
     },
     solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY,
     spl_token_2022::{
@@ -138,6 +153,9 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 
 pub const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
+/// Maximum number of program accounts a `getProgramAccountsPaginated` page visits, matching its
+/// filters or not, so a page with few matches ends early instead of scanning the whole program
+const MAX_GET_PROGRAM_ACCOUNTS_PAGE_KEYS_VISITED: usize = 100_000;
 
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     RpcResponse {
@@ -146,6 +164,14 @@ fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     }
 }
 
+/// Response for a query that was answered as of `at_slot` rather than at `bank`, if given
+fn new_response_at_slot<T>(bank: &Bank, at_slot: Option<Slot>, value: T) -> RpcResponse<T> {
+    RpcResponse {
+        context: RpcResponseContext::new(at_slot.unwrap_or_else(|| bank.slot())),
+        value,
+    }
+}
+
 fn is_finalized(
     block_commitment_cache: &BlockCommitmentCache,
     bank: &Bank,
@@ -174,6 +200,7 @@ pub struct JsonRpcConfig {
     pub max_request_body_size: Option<usize>,
     /// Disable the health check, used for tests and TestValidator
     pub disable_health_check: bool,
+    pub rate_limiter: Option<Arc<RpcRateLimiter>>,
 }
 
 impl Default for JsonRpcConfig {
@@ -194,6 +221,7 @@ impl Default for JsonRpcConfig {
             rpc_scan_and_fix_roots: Default::default(),
             max_request_body_size: Option::default(),
             disable_health_check: Default::default(),
+            rate_limiter: Option::default(),
         }
     }
 }
@@ -215,6 +243,8 @@ pub struct RpcBigtableConfig {
     pub bigtable_app_profile_id: String,
     pub timeout: Option<Duration>,
     pub max_message_size: usize,
+    /// Use the object storage at this url instead of BigTable
+    pub object_store_url: Option<String>,
 }
 
 impl Default for RpcBigtableConfig {
@@ -227,6 +257,7 @@ impl Default for RpcBigtableConfig {
             bigtable_app_profile_id,
             timeout: None,
             max_message_size: solana_storage_bigtable::DEFAULT_MAX_MESSAGE_SIZE,
+            object_store_url: None,
         }
     }
 }
@@ -253,6 +284,7 @@ pub struct JsonRpcRequestProcessor {
     max_complete_rewards_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
     runtime: Arc<Runtime>,
+    pub(crate) rate_limit_client_key: RpcClientKey,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -435,6 +467,7 @@ impl JsonRpcRequestProcessor {
                 max_complete_rewards_slot,
                 prioritization_fee_cache,
                 runtime,
+                rate_limit_client_key: RpcClientKey::default(),
             },
             transaction_receiver,
         )
@@ -520,19 +553,24 @@ impl JsonRpcRequestProcessor {
             max_complete_rewards_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
             runtime: service_runtime(rpc_threads, rpc_blocking_threads, rpc_niceness_adj),
+            rate_limit_client_key: RpcClientKey::default(),
         }
     }
 
     pub async fn get_account_info(
         &self,
         pubkey: Pubkey,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountHistoryConfig>,
     ) -> Result<RpcResponse<Option<UiAccount>>> {
-        let RpcAccountInfoConfig {
-            encoding,
-            data_slice,
-            commitment,
-            min_context_slot,
+        let RpcAccountHistoryConfig {
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice,
+                    commitment,
+                    min_context_slot,
+                },
+            at_slot,
         } = config.unwrap_or_default();
         let bank = self.get_bank_with_config(RpcContextConfig {
             commitment,
@@ -544,23 +582,32 @@ impl JsonRpcRequestProcessor {
             .runtime
             .spawn_blocking({
                 let bank = Arc::clone(&bank);
-                move || get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
+                move || match at_slot {
+                    Some(slot) => {
+                        get_encoded_account_at_slot(&bank, &pubkey, slot, encoding, data_slice)
+                    }
+                    None => get_encoded_account(&bank, &pubkey, encoding, data_slice, None),
+                }
             })
             .await
             .expect("rpc: get_encoded_account panicked")?;
-        Ok(new_response(&bank, response))
+        Ok(new_response_at_slot(&bank, at_slot, response))
     }
 
     pub async fn get_multiple_accounts(
         &self,
         pubkeys: Vec<Pubkey>,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountHistoryConfig>,
     ) -> Result<RpcResponse<Vec<Option<UiAccount>>>> {
-        let RpcAccountInfoConfig {
-            encoding,
-            data_slice,
-            commitment,
-            min_context_slot,
+        let RpcAccountHistoryConfig {
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice,
+                    commitment,
+                    min_context_slot,
+                },
+            at_slot,
         } = config.unwrap_or_default();
         let bank = self.get_bank_with_config(RpcContextConfig {
             commitment,
@@ -573,14 +620,17 @@ impl JsonRpcRequestProcessor {
             let bank = Arc::clone(&bank);
             accounts.push(
                 self.runtime
-                    .spawn_blocking(move || {
-                        get_encoded_account(&bank, &pubkey, encoding, data_slice, None)
+                    .spawn_blocking(move || match at_slot {
+                        Some(slot) => {
+                            get_encoded_account_at_slot(&bank, &pubkey, slot, encoding, data_slice)
+                        }
+                        None => get_encoded_account(&bank, &pubkey, encoding, data_slice, None),
                     })
                     .await
                     .expect("rpc: get_encoded_account panicked")?,
             );
         }
-        Ok(new_response(&bank, accounts))
+        Ok(new_response_at_slot(&bank, at_slot, accounts))
     }
 
     pub fn get_minimum_balance_for_rent_exemption(
@@ -631,6 +681,20 @@ impl JsonRpcRequestProcessor {
                     sort_results,
                 )
                 .await?
+            } else if let Some(index_key) =
+                get_custom_index_filter(&program_id, &filters, &self.config.account_indexes)
+            {
+                self.get_filtered_indexed_accounts(
+                    &bank,
+                    &index_key,
+                    &program_id,
+                    filters,
+                    sort_results,
+                )
+                .await
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })?
             } else {
                 self.get_filtered_program_accounts(
                     Arc::clone(&bank),
@@ -641,27 +705,87 @@ impl JsonRpcRequestProcessor {
                 .await?
             }
         };
-        let accounts = if is_known_spl_token_id(&program_id)
-            && encoding == UiAccountEncoding::JsonParsed
-        {
-            get_parsed_token_accounts(Arc::clone(&bank), keyed_accounts.into_iter()).collect()
-        } else {
-            keyed_accounts
-                .into_iter()
-                .map(|(pubkey, account)| {
-                    Ok(RpcKeyedAccount {
-                        pubkey: pubkey.to_string(),
-                        account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?
-        };
+        let accounts = encode_program_accounts(
+            &bank,
+            &program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+        )?;
         Ok(match with_context {
             true => OptionalContext::Context(new_response(&bank, accounts)),
             false => OptionalContext::NoContext(accounts),
         })
     }
 
+    pub async fn get_program_accounts_page(
+        &self,
+        program_id: Pubkey,
+        config: Option<RpcProgramAccountsPageConfig>,
+    ) -> Result<RpcResponse<RpcProgramAccountsPage>> {
+        let RpcProgramAccountsPageConfig {
+            filters,
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice: data_slice_config,
+                    commitment,
+                    min_context_slot,
+                },
+            limit,
+            cursor,
+        } = config.unwrap_or_default();
+        let filters = filters.unwrap_or_default();
+        if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+            return Err(Error::invalid_params(format!(
+                "Too many filters provided; max {MAX_GET_PROGRAM_ACCOUNT_FILTERS}"
+            )));
+        }
+        for filter in &filters {
+            verify_filter(filter)?;
+        }
+        let limit = limit.unwrap_or(MAX_GET_PROGRAM_ACCOUNTS_PAGE_LIMIT);
+        if limit == 0 || limit > MAX_GET_PROGRAM_ACCOUNTS_PAGE_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {MAX_GET_PROGRAM_ACCOUNTS_PAGE_LIMIT}"
+            )));
+        }
+        let start_after = cursor
+            .map(|cursor| {
+                Pubkey::from_str(&cursor).map_err(|_| Error::invalid_params("Invalid cursor"))
+            })
+            .transpose()?;
+
+        let bank = self.get_bank_with_config(RpcContextConfig {
+            commitment,
+            min_context_slot,
+        })?;
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        let (keyed_accounts, next_cursor) = self
+            .get_filtered_program_accounts_page(
+                Arc::clone(&bank),
+                program_id,
+                filters,
+                start_after,
+                limit,
+            )
+            .await?;
+        let accounts = encode_program_accounts(
+            &bank,
+            &program_id,
+            keyed_accounts,
+            encoding,
+            data_slice_config,
+        )?;
+        Ok(new_response(
+            &bank,
+            RpcProgramAccountsPage {
+                accounts,
+                next_cursor: next_cursor.map(|pubkey| pubkey.to_string()),
+            },
+        ))
+    }
+
     fn filter_map_rewards<'a, F>(
         rewards: &'a Option<Rewards>,
         slot: Slot,
@@ -1304,6 +1428,7 @@ impl JsonRpcRequestProcessor {
             };
             let commitment = config.commitment.unwrap_or_default();
             check_is_at_least_confirmed(commitment)?;
+            let filter = &BlockTransactionFilter::new(&config)?;
 
             // Block is old enough to be finalized
             if slot
@@ -1323,7 +1448,8 @@ impl JsonRpcRequestProcessor {
                     .await
                     .expect("Failed to spawn blocking task");
                 self.check_blockstore_root(&result, slot)?;
-                let encode_block = |confirmed_block: ConfirmedBlock| async move {
+                let encode_block = |mut confirmed_block: ConfirmedBlock| async move {
+                    let next_transaction_cursor = filter.apply(&mut confirmed_block);
                     let mut encoded_block = self
                         .runtime
                         .spawn_blocking(move || {
@@ -1337,6 +1463,7 @@ impl JsonRpcRequestProcessor {
                         encoded_block.block_time = Some(self.genesis_creation_time());
                         encoded_block.block_height = Some(0);
                     }
+                    encoded_block.next_transaction_cursor = next_transaction_cursor;
                     Ok::<UiConfirmedBlock, Error>(encoded_block)
                 };
                 if result.is_err() {
@@ -1387,7 +1514,8 @@ impl JsonRpcRequestProcessor {
                                     }
                                 }
                             }
-                            let encoded_block = self
+                            let next_transaction_cursor = filter.apply(&mut confirmed_block);
+                            let mut encoded_block = self
                                 .runtime
                                 .spawn_blocking(move || {
                                     confirmed_block
@@ -1396,6 +1524,7 @@ impl JsonRpcRequestProcessor {
                                 })
                                 .await
                                 .expect("Failed to spawn blocking task")?;
+                            encoded_block.next_transaction_cursor = next_transaction_cursor;
 
                             Ok(encoded_block)
                         })
@@ -2277,6 +2406,51 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    /// Get a page of program accounts from the program-id secondary index, in pubkey order,
+    /// along with the key to continue after if the page is full
+    async fn get_filtered_program_accounts_page(
+        &self,
+        bank: Arc<Bank>,
+        program_id: Pubkey,
+        mut filters: Vec<RpcFilterType>,
+        start_after: Option<Pubkey>,
+        limit: usize,
+    ) -> RpcCustomResult<(Vec<(Pubkey, AccountSharedData)>, Option<Pubkey>)> {
+        optimize_filters(&mut filters);
+        if !self
+            .config
+            .account_indexes
+            .contains(&AccountIndex::ProgramId)
+            || !self.config.account_indexes.include_key(&program_id)
+        {
+            return Err(RpcCustomError::KeyExcludedFromSecondaryIndex {
+                index_key: program_id.to_string(),
+            });
+        }
+        self.runtime
+            .spawn_blocking(move || {
+                bank.get_filtered_indexed_accounts_page(
+                    &IndexKey::ProgramId(program_id),
+                    |account| {
+                        // See `get_filtered_indexed_accounts()` for why the owner is checked again
+                        account.owner().eq(&program_id)
+                            && filters
+                                .iter()
+                                .all(|filter_type| filter_allows(filter_type, account))
+                    },
+                    start_after.map_or(ScanStart::First, ScanStart::After),
+                    limit,
+                    MAX_GET_PROGRAM_ACCOUNTS_PAGE_KEYS_VISITED,
+                    bank.byte_limit_for_scans(),
+                )
+                .map_err(|e| RpcCustomError::ScanError {
+                    message: e.to_string(),
+                })
+            })
+            .await
+            .expect("Failed to spawn blocking task")
+    }
+
     /// Get an iterator of spl-token accounts by owner address
     async fn get_filtered_spl_token_accounts_by_owner(
         &self,
@@ -2420,6 +2594,31 @@ impl JsonRpcRequestProcessor {
             })
             .collect())
     }
+
+    fn get_prioritization_fee_estimate(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        lookback_slots: usize,
+        percentiles: Vec<u16>,
+    ) -> Result<RpcPrioritizationFeeEstimate> {
+        let estimate = self
+            .prioritization_fee_cache
+            .get_prioritization_fee_estimate(&pubkeys, lookback_slots, &percentiles);
+        Ok(RpcPrioritizationFeeEstimate {
+            num_slots: estimate.num_blocks,
+            num_samples: estimate.num_samples,
+            fees: estimate
+                .percentile_fees
+                .into_iter()
+                .map(
+                    |(percentile, prioritization_fee)| RpcPrioritizationFeePercentile {
+                        percentile,
+                        prioritization_fee,
+                    },
+                )
+                .collect(),
+        })
+    }
 }
 
 fn optimize_filters(filters: &mut [RpcFilterType]) {
@@ -2513,6 +2712,127 @@ fn verify_and_parse_signatures_for_address_params(
     Ok((address, before, until, limit))
 }
 
+/// Transaction filters and pagination of `getBlock`, applied before the block is encoded
+#[derive(Debug, Default)]
+struct BlockTransactionFilter {
+    account_include: HashSet<Pubkey>,
+    program_ids: HashSet<Pubkey>,
+    exclude_failed: bool,
+    exclude_vote: bool,
+    cursor: usize,
+    limit: Option<usize>,
+}
+
+impl BlockTransactionFilter {
+    fn new(config: &RpcBlockConfig) -> Result<Self> {
+        let account_include = config.account_include.as_deref().unwrap_or_default();
+        let program_ids = config.program_ids.as_deref().unwrap_or_default();
+        if account_include.len() + program_ids.len() > MAX_GET_BLOCK_FILTER_ADDRESSES {
+            return Err(Error::invalid_params(format!(
+                "Too many filter addresses; max {MAX_GET_BLOCK_FILTER_ADDRESSES}"
+            )));
+        }
+        if config.transaction_limit == Some(0) {
+            return Err(Error::invalid_params(
+                "Invalid transactionLimit; must be non-zero",
+            ));
+        }
+        Ok(Self {
+            account_include: account_include
+                .iter()
+                .map(|address| verify_pubkey(address))
+                .collect::<Result<_>>()?,
+            program_ids: program_ids
+                .iter()
+                .map(|address| verify_pubkey(address))
+                .collect::<Result<_>>()?,
+            exclude_failed: config.exclude_failed.unwrap_or_default(),
+            exclude_vote: config.exclude_vote.unwrap_or_default(),
+            cursor: config
+                .transaction_cursor
+                .map(|cursor| usize::try_from(cursor).unwrap_or(usize::MAX))
+                .unwrap_or_default(),
+            limit: config.transaction_limit,
+        })
+    }
+
+    fn allows(&self, transaction: &TransactionWithStatusMeta) -> bool {
+        let (signatures, is_legacy_message, instructions) = match transaction {
+            TransactionWithStatusMeta::MissingMetadata(transaction) => (
+                &transaction.signatures,
+                true,
+                transaction.message.instructions.as_slice(),
+            ),
+            TransactionWithStatusMeta::Complete(transaction_with_meta) => {
+                if self.exclude_failed && transaction_with_meta.meta.status.is_err() {
+                    return false;
+                }
+                let transaction = &transaction_with_meta.transaction;
+                (
+                    &transaction.signatures,
+                    matches!(transaction.message, VersionedMessage::Legacy(_)),
+                    transaction.message.instructions(),
+                )
+            }
+        };
+        let account_keys = transaction.account_keys();
+        let instruction_programs = instructions
+            .iter()
+            .filter_map(|instruction| account_keys.get(usize::from(instruction.program_id_index)));
+        if self.exclude_vote
+            && is_simple_vote_transaction_impl(
+                signatures,
+                is_legacy_message,
+                instruction_programs.clone(),
+            )
+        {
+            return false;
+        }
+        if !self.program_ids.is_empty()
+            && !instruction_programs
+                .clone()
+                .any(|program_id| self.program_ids.contains(program_id))
+        {
+            return false;
+        }
+        self.account_include.is_empty()
+            || account_keys
+                .iter()
+                .any(|key| self.account_include.contains(key))
+    }
+
+    /// Drops the transactions of `block` that are not selected, returning the cursor of the
+    /// next page if the page is full and transactions remain
+    fn apply(&self, block: &mut ConfirmedBlock) -> Option<u64> {
+        if self.cursor == 0
+            && self.limit.is_none()
+            && !self.exclude_failed
+            && !self.exclude_vote
+            && self.program_ids.is_empty()
+            && self.account_include.is_empty()
+        {
+            return None;
+        }
+        let mut next_cursor = None;
+        let mut transactions = Vec::new();
+        for (index, transaction) in std::mem::take(&mut block.transactions)
+            .into_iter()
+            .enumerate()
+            .skip(self.cursor)
+        {
+            if self.limit == Some(transactions.len()) {
+                next_cursor = Some(index as u64);
+                break;
+            }
+            if self.allows(&transaction) {
+                transactions.push(transaction);
+            }
+        }
+        block.transactions = transactions;
+        next_cursor
+    }
+}
+
 pub(crate) fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     if !commitment.is_at_least_confirmed() {
         return Err(Error::invalid_params(
@@ -2545,6 +2865,69 @@ fn get_encoded_account(
     }
 }
 
+/// Like `get_encoded_account()`, but loads the account as of the rooted `slot` from the account
+/// history retained by accounts-db.
+///
+/// Token accounts are parsed with their mint as of `slot` too, so decimals and extensions match
+/// the account. The clock of `bank` is still used for the UI amount of interest-bearing mints,
+/// and the mint at `bank` only when the mint did not exist at `slot`.
+fn get_encoded_account_at_slot(
+    bank: &Bank,
+    pubkey: &Pubkey,
+    slot: Slot,
+    encoding: UiAccountEncoding,
+    data_slice: Option<UiDataSliceConfig>,
+) -> Result<Option<UiAccount>> {
+    let account = bank
+        .accounts()
+        .accounts_db
+        .load_account_at_slot(pubkey, slot)
+        .map_err(|err| Error::invalid_params(err.to_string()))?;
+    match account {
+        Some(account) => {
+            let response = if is_known_spl_token_id(account.owner())
+                && encoding == UiAccountEncoding::JsonParsed
+            {
+                let historical_mint: HashMap<_, _> = get_token_account_mint(account.data())
+                    .and_then(|mint| {
+                        let accounts_db = &bank.accounts().accounts_db;
+                        let mint_account = accounts_db.load_account_at_slot(&mint, slot).ok()??;
+                        Some((mint, mint_account))
+                    })
+                    .into_iter()
+                    .collect();
+                get_parsed_token_account(bank, pubkey, account, Some(&historical_mint))
+            } else {
+                encode_account(&account, pubkey, encoding, data_slice)?
+            };
+            Ok(Some(response))
+        }
+        None => Ok(None),
+    }
+}
+
+fn encode_program_accounts(
+    bank: &Arc<Bank>,
+    program_id: &Pubkey,
+    keyed_accounts: Vec<(Pubkey, AccountSharedData)>,
+    encoding: UiAccountEncoding,
+    data_slice_config: Option<UiDataSliceConfig>,
+) -> Result<Vec<RpcKeyedAccount>> {
+    if is_known_spl_token_id(program_id) && encoding == UiAccountEncoding::JsonParsed {
+        Ok(get_parsed_token_accounts(Arc::clone(bank), keyed_accounts.into_iter()).collect())
+    } else {
+        keyed_accounts
+            .into_iter()
+            .map(|(pubkey, account)| {
+                Ok(RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: encode_account(&account, &pubkey, encoding, data_slice_config)?,
+                })
+            })
+            .collect()
+    }
+}
+
 fn encode_account<T: ReadableAccount>(
     account: &T,
     pubkey: &Pubkey,
@@ -2570,6 +2953,30 @@ fn encode_account<T: ReadableAccount>(
     }
 }
 
+fn rpc_execution_trace(execution_trace: Vec<InstructionTrace>) -> Vec<RpcInstructionTrace> {
+    execution_trace
+        .into_iter()
+        .map(|instruction_trace| RpcInstructionTrace {
+            program_id: instruction_trace.program_id.to_string(),
+            stack_height: u32::try_from(instruction_trace.stack_height).unwrap_or(u32::MAX),
+            compute_units_consumed: instruction_trace.compute_units_consumed,
+            accounts: instruction_trace
+                .accounts
+                .iter()
+                .map(|account_trace| RpcAccountTrace {
+                    pubkey: account_trace.pubkey.to_string(),
+                    writable: account_trace.is_writable,
+                    lamports_delta: account_trace.lamports_delta(),
+                    data_len_delta: account_trace.data_len_delta(),
+                    post_lamports: account_trace.post_lamports,
+                    post_data_len: account_trace.post_data_len as u64,
+                })
+                .collect(),
+            err: instruction_trace.result.err(),
+        })
+        .collect()
+}
+
 /// Analyze custom filters to determine if the result will be a subset of spl-token accounts by
 /// owner.
 /// NOTE: `optimize_filters()` should almost always be called before using this method because of
@@ -2672,6 +3079,31 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
     }
 }
 
+/// Use a user-defined secondary index for `program_id` if one of the memcmp filters starts at the
+/// offset of that index and covers all of its bytes.
+///
+/// NOTE: `optimize_filters()` should almost always be called before using this method because of
+/// the requirement that `Memcmp::raw_bytes_as_ref().is_some()`.
+fn get_custom_index_filter(
+    program_id: &Pubkey,
+    filters: &[RpcFilterType],
+    account_indexes: &AccountSecondaryIndexes,
+) -> Option<IndexKey> {
+    account_indexes
+        .custom_indexes()
+        .filter(|custom_index| custom_index.program_id == *program_id)
+        .find_map(|custom_index| {
+            filters.iter().find_map(|filter| match filter {
+                RpcFilterType::Memcmp(memcmp) if memcmp.offset() == custom_index.offset => {
+                    let bytes = memcmp.raw_bytes_as_ref()?;
+                    let index_key = custom_index.index_key(bytes.get(..custom_index.len)?)?;
+                    Some(IndexKey::Custom(*custom_index, index_key))
+                }
+                _ => None,
+            })
+        })
+}
+
 /// Analyze a passed Pubkey that may be a Token program id or Mint address to determine the program
 /// id and optional Mint
 fn get_token_program_id_and_mint(
@@ -3192,7 +3624,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountHistoryConfig>,
         ) -> BoxFuture<Result<RpcResponse<Option<UiAccount>>>>;
 
         #[rpc(meta, name = "getMultipleAccounts")]
@@ -3200,7 +3632,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountHistoryConfig>,
         ) -> BoxFuture<Result<RpcResponse<Vec<Option<UiAccount>>>>>;
 
         #[rpc(meta, name = "getBlockCommitment")]
@@ -3239,7 +3671,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountHistoryConfig>,
         ) -> BoxFuture<Result<RpcResponse<Option<UiAccount>>>> {
             debug!("get_account_info rpc request received: {:?}", pubkey_str);
             async move {
@@ -3253,7 +3685,7 @@ pub mod rpc_accounts {
             &self,
             meta: Self::Metadata,
             pubkey_strs: Vec<String>,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountHistoryConfig>,
         ) -> BoxFuture<Result<RpcResponse<Vec<Option<UiAccount>>>>> {
             debug!(
                 "get_multiple_accounts rpc request received: {:?}",
@@ -3331,6 +3763,14 @@ pub mod rpc_accounts_scan {
             config: Option<RpcProgramAccountsConfig>,
         ) -> BoxFuture<Result<OptionalContext<Vec<RpcKeyedAccount>>>>;
 
+        #[rpc(meta, name = "getProgramAccountsPaginated")]
+        fn get_program_accounts_paginated(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsPageConfig>,
+        ) -> BoxFuture<Result<RpcResponse<RpcProgramAccountsPage>>>;
+
         #[rpc(meta, name = "getLargestAccounts")]
         fn get_largest_accounts(
             &self,
@@ -3416,6 +3856,23 @@ pub mod rpc_accounts_scan {
             .boxed()
         }
 
+        fn get_program_accounts_paginated(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcProgramAccountsPageConfig>,
+        ) -> BoxFuture<Result<RpcResponse<RpcProgramAccountsPage>>> {
+            debug!(
+                "get_program_accounts_paginated rpc request received: {:?}",
+                program_id_str
+            );
+            async move {
+                let program_id = verify_pubkey(&program_id_str)?;
+                meta.get_program_accounts_page(program_id, config).await
+            }
+            .boxed()
+        }
+
         fn get_largest_accounts(
             &self,
             meta: Self::Metadata,
@@ -3495,7 +3952,7 @@ pub mod rpc_accounts_scan {
 
 pub mod utils {
     use {
-        crate::rpc::encode_account,
+        crate::rpc::{encode_account, rpc_execution_trace},
         jsonrpc_core::Error,
         solana_account_decoder::{UiAccount, UiAccountEncoding},
         solana_bundle::{
@@ -3609,6 +4066,9 @@ pub mod utils {
                                 .map(|data| UiTransactionReturnData::from(data.clone()))
                         })
                         .unwrap_or_default(),
+                    trace: details
+                        .and_then(|d| d.execution_trace.clone())
+                        .map(rpc_execution_trace),
                 });
             }
         }
@@ -3827,6 +4287,13 @@ pub mod rpc_full {
             meta: Self::Metadata,
             pubkey_strs: Option<Vec<String>>,
         ) -> Result<Vec<RpcPrioritizationFee>>;
+
+        #[rpc(meta, name = "getPrioritizationFeeEstimate")]
+        fn get_prioritization_fee_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcPrioritizationFeeEstimateConfig>,
+        ) -> Result<RpcPrioritizationFeeEstimate>;
     }
 
     pub struct FullImpl;
@@ -4104,7 +4571,8 @@ pub mod rpc_full {
                     units_consumed,
                     return_data,
                     inner_instructions: _, // Always `None` due to `enable_cpi_recording = false`
-                } = preflight_bank.simulate_transaction(&transaction, false)
+                    execution_trace: _,    // Always `None` due to `enable_trace_recording = false`
+                } = preflight_bank.simulate_transaction(&transaction, false, false)
                 {
                     match err {
                         TransactionError::BlockhashNotFound => {
@@ -4124,6 +4592,7 @@ pub mod rpc_full {
                             return_data: return_data.map(|return_data| return_data.into()),
                             inner_instructions: None,
                             replacement_blockhash: None,
+                            trace: None,
                         },
                     }
                     .into());
@@ -4155,6 +4624,7 @@ pub mod rpc_full {
                 accounts: config_accounts,
                 min_context_slot,
                 inner_instructions: enable_cpi_recording,
+                trace: enable_trace_recording,
             } = config.unwrap_or_default();
             let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
             let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
@@ -4202,7 +4672,12 @@ pub mod rpc_full {
                 units_consumed,
                 return_data,
                 inner_instructions,
-            } = bank.simulate_transaction(&transaction, enable_cpi_recording);
+                execution_trace,
+            } = bank.simulate_transaction(
+                &transaction,
+                enable_cpi_recording,
+                enable_trace_recording,
+            );
 
             let account_keys = transaction.message().account_keys();
             let number_of_accounts = account_keys.len();
@@ -4269,6 +4744,7 @@ pub mod rpc_full {
                     return_data: return_data.map(|return_data| return_data.into()),
                     inner_instructions,
                     replacement_blockhash: blockhash,
+                    trace: execution_trace.map(rpc_execution_trace),
                 },
             ))
         }
@@ -4369,6 +4845,7 @@ pub mod rpc_full {
                 MAX_PROCESSING_AGE,
                 &MAX_BUNDLE_SIMULATION_TIME,
                 true,
+                config.trace,
                 &None,
                 true,
                 None,
@@ -4617,6 +5094,62 @@ pub mod rpc_full {
                 .collect::<Result<Vec<_>>>()?;
             meta.get_recent_prioritization_fees(pubkeys)
         }
+
+        fn get_prioritization_fee_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcPrioritizationFeeEstimateConfig>,
+        ) -> Result<RpcPrioritizationFeeEstimate> {
+            let RpcPrioritizationFeeEstimateConfig {
+                account_keys,
+                lookback_slots,
+                percentiles,
+            } = config.unwrap_or_default();
+            let pubkey_strs = account_keys.unwrap_or_default();
+            debug!(
+                "get_prioritization_fee_estimate rpc request received: {:?} pubkeys",
+                pubkey_strs.len()
+            );
+            if pubkey_strs.len() > MAX_TX_ACCOUNT_LOCKS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {MAX_TX_ACCOUNT_LOCKS}"
+                )));
+            }
+            let lookback_slots =
+                lookback_slots.unwrap_or(MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS);
+            if lookback_slots == 0
+                || lookback_slots > MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS
+            {
+                return Err(Error::invalid_params(format!(
+                    "Invalid lookbackSlots; must be between 1 and \
+                     {MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS}"
+                )));
+            }
+            let percentiles =
+                percentiles.unwrap_or_else(|| vec![2_500, 5_000, 7_500, 9_000, 10_000]);
+            if percentiles.is_empty()
+                || percentiles.len() > MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILES
+            {
+                return Err(Error::invalid_params(format!(
+                    "Invalid percentiles; must list between 1 and \
+                     {MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILES} percentiles"
+                )));
+            }
+            if percentiles
+                .iter()
+                .any(|percentile| *percentile > MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILE)
+            {
+                return Err(Error::invalid_params(format!(
+                    "Invalid percentiles; must be between 0 and \
+                     {MAX_PRIORITIZATION_FEE_ESTIMATE_PERCENTILE} basis points"
+                )));
+            }
+            let pubkeys = pubkey_strs
+                .into_iter()
+                .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                .collect::<Result<Vec<_>>>()?;
+            meta.get_prioritization_fee_estimate(pubkeys, lookback_slots, percentiles)
+        }
     }
 }
 
@@ -4848,6 +5381,7 @@ pub mod tests {
         solana_rpc_client_api::{
             custom_error::{
                 JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_KEY_EXCLUDED_FROM_SECONDARY_INDEX,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
             },
@@ -4957,9 +5491,9 @@ pub mod tests {
         }
     }
 
-    struct RpcHandler {
+    pub(crate) struct RpcHandler {
         io: MetaIoHandler<JsonRpcRequestProcessor>,
-        meta: JsonRpcRequestProcessor,
+        pub(crate) meta: JsonRpcRequestProcessor,
         identity: Pubkey,
         mint_keypair: Keypair,
         leader_vote_keypair: Arc<Keypair>,
@@ -4978,7 +5512,7 @@ pub mod tests {
             })
         }
 
-        fn start_with_config(config: JsonRpcConfig) -> Self {
+        pub(crate) fn start_with_config(config: JsonRpcConfig) -> Self {
             let (bank_forks, mint_keypair, leader_vote_keypair) =
                 new_bank_forks_with_config(BankTestConfig {
                     accounts_db_config: AccountsDbConfig {
@@ -5265,7 +5799,7 @@ pub mod tests {
             &self.meta.prioritization_fee_cache
         }
 
-        fn working_bank(&self) -> Arc<Bank> {
+        pub(crate) fn working_bank(&self) -> Arc<Bank> {
             self.bank_forks.read().unwrap().working_bank()
         }
 
@@ -5810,6 +6344,14 @@ pub mod tests {
             result["value"]["data"], expected,
             "should use data slice if parsing fails"
         );
+
+        let request = create_test_request("getAccountInfo", Some(json!([address, {"atSlot": 0}])));
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        let expected = (
+            ErrorCode::InvalidParams.code(),
+            String::from("account history is not retained by this node"),
+        );
+        assert_eq!(response, expected);
     }
 
     #[test]
@@ -6159,6 +6701,99 @@ pub mod tests {
         assert_eq!(result.len(), 0);
     }
 
+    #[test]
+    fn test_rpc_get_program_accounts_paginated() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            account_indexes: AccountSecondaryIndexes {
+                keys: None,
+                indexes: HashSet::from([AccountIndex::ProgramId]),
+            },
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+
+        let program_id = Pubkey::new_unique();
+        let mut pubkeys = (0..5)
+            .map(|i| {
+                let pubkey = Pubkey::new_unique();
+                bank.store_account(&pubkey, &AccountSharedData::new(42, i, &program_id));
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        pubkeys.sort_unstable();
+
+        // Pages are returned in pubkey order
+        let mut cursor = None;
+        let mut paged_pubkeys = vec![];
+        loop {
+            let request = create_test_request(
+                "getProgramAccountsPaginated",
+                Some(json!([program_id.to_string(), {"limit": 2, "cursor": cursor}])),
+            );
+            let result: RpcResponse<RpcProgramAccountsPage> =
+                parse_success_result(rpc.handle_request_sync(request));
+            assert!(result.value.accounts.len() <= 2);
+            paged_pubkeys.extend(
+                result
+                    .value
+                    .accounts
+                    .into_iter()
+                    .map(|keyed_account| Pubkey::from_str(&keyed_account.pubkey).unwrap()),
+            );
+            cursor = result.value.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(paged_pubkeys, pubkeys);
+
+        // Filters are applied before the limit
+        let request = create_test_request(
+            "getProgramAccountsPaginated",
+            Some(json!([
+                program_id.to_string(),
+                {"limit": 1, "filters": [{"dataSize": 3}]},
+            ])),
+        );
+        let result: RpcResponse<RpcProgramAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(result.value.accounts.len(), 1);
+        assert_eq!(result.value.accounts[0].account.space, Some(3));
+        let request = create_test_request(
+            "getProgramAccountsPaginated",
+            Some(json!([
+                program_id.to_string(),
+                {"limit": 1, "filters": [{"dataSize": 3}], "cursor": result.value.next_cursor},
+            ])),
+        );
+        let result: RpcResponse<RpcProgramAccountsPage> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert!(result.value.accounts.is_empty());
+        assert_eq!(result.value.next_cursor, None);
+
+        // Invalid limit and cursor
+        for config in [json!({"limit": 0}), json!({"cursor": "not a cursor"})] {
+            let request = create_test_request(
+                "getProgramAccountsPaginated",
+                Some(json!([program_id.to_string(), config])),
+            );
+            let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+            assert_eq!(code, ErrorCode::InvalidParams.code());
+        }
+
+        // The program-id index is required
+        let rpc = RpcHandler::start();
+        let request = create_test_request(
+            "getProgramAccountsPaginated",
+            Some(json!([program_id.to_string()])),
+        );
+        let (code, _) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            code,
+            JSON_RPC_SERVER_ERROR_KEY_EXCLUDED_FROM_SECONDARY_INDEX
+        );
+    }
+
     #[test]
     fn test_rpc_simulate_bundle_happy_path() {
         // 1. setup
@@ -7653,6 +8288,7 @@ pub mod tests {
                     rewards: Some(false),
                     commitment: None,
                     max_supported_transaction_version: None,
+                    ..RpcBlockConfig::default()
                 },
             ])),
         );
@@ -7676,6 +8312,7 @@ pub mod tests {
                     rewards: Some(true),
                     commitment: None,
                     max_supported_transaction_version: None,
+                    ..RpcBlockConfig::default()
                 },
             ])),
         );
@@ -7687,6 +8324,83 @@ pub mod tests {
         assert_eq!(confirmed_block.rewards.unwrap(), vec![]);
     }
 
+    #[test]
+    fn test_get_block_transaction_filters() {
+        let rpc = RpcHandler::start();
+        let confirmed_block_signatures = rpc.create_test_transactions_and_populate_blockstore();
+        let get_block = |config: RpcBlockConfig| {
+            let request = create_test_request(
+                "getBlock",
+                Some(json!([
+                    0u64,
+                    RpcBlockConfig {
+                        transaction_details: Some(TransactionDetails::Signatures),
+                        rewards: Some(false),
+                        ..config
+                    },
+                ])),
+            );
+            let result: Option<UiConfirmedBlock> =
+                parse_success_result(rpc.handle_request_sync(request));
+            result.unwrap()
+        };
+        let successful_signature = confirmed_block_signatures[0].to_string();
+        let failed_signature = confirmed_block_signatures[1].to_string();
+
+        let confirmed_block = get_block(RpcBlockConfig {
+            exclude_failed: Some(true),
+            ..RpcBlockConfig::default()
+        });
+        let signatures = confirmed_block.signatures.unwrap();
+        assert!(signatures.contains(&successful_signature));
+        assert!(!signatures.contains(&failed_signature));
+        assert_eq!(confirmed_block.next_transaction_cursor, None);
+
+        let confirmed_block = get_block(RpcBlockConfig {
+            account_include: Some(vec![rpc.mint_keypair.pubkey().to_string()]),
+            program_ids: Some(vec![system_program::id().to_string()]),
+            ..RpcBlockConfig::default()
+        });
+        assert_eq!(
+            confirmed_block.signatures.unwrap(),
+            vec![successful_signature.clone()]
+        );
+
+        let confirmed_block = get_block(RpcBlockConfig {
+            transaction_limit: Some(1),
+            ..RpcBlockConfig::default()
+        });
+        assert_eq!(
+            confirmed_block.signatures.unwrap(),
+            vec![successful_signature]
+        );
+        assert_eq!(confirmed_block.next_transaction_cursor, Some(1));
+
+        let confirmed_block = get_block(RpcBlockConfig {
+            transaction_cursor: Some(1),
+            transaction_limit: Some(1),
+            ..RpcBlockConfig::default()
+        });
+        assert_eq!(confirmed_block.signatures.unwrap(), vec![failed_signature]);
+
+        let request = create_test_request(
+            "getBlock",
+            Some(json!([
+                0u64,
+                RpcBlockConfig {
+                    transaction_limit: Some(0),
+                    ..RpcBlockConfig::default()
+                },
+            ])),
+        );
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        let expected = (
+            ErrorCode::InvalidParams.code(),
+            String::from("Invalid transactionLimit; must be non-zero"),
+        );
+        assert_eq!(response, expected);
+    }
+
     #[test]
     fn test_get_block_production() {
         let rpc = RpcHandler::start();
@@ -8936,6 +9650,70 @@ pub mod tests {
         .is_none());
     }
 
+    #[test]
+    fn test_get_custom_index_filter() {
+        use solana_accounts_db::accounts_index::CustomAccountIndex;
+
+        let program_id = Pubkey::new_unique();
+        let custom_index = CustomAccountIndex::new(program_id, 8, 32).unwrap();
+        let account_indexes = AccountSecondaryIndexes {
+            keys: None,
+            indexes: HashSet::from([AccountIndex::Custom(custom_index)]),
+        };
+        let market = Pubkey::new_unique();
+
+        // memcmp at the indexed offset
+        assert!(matches!(
+            get_custom_index_filter(
+                &program_id,
+                &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, market.to_bytes().to_vec()))],
+                &account_indexes,
+            ),
+            Some(IndexKey::Custom(index, key)) if index == custom_index && key == market
+        ));
+
+        // memcmp longer than the indexed field still uses the index
+        let mut bytes = market.to_bytes().to_vec();
+        bytes.extend_from_slice(&[1, 2, 3]);
+        assert!(matches!(
+            get_custom_index_filter(
+                &program_id,
+                &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, bytes))],
+                &account_indexes,
+            ),
+            Some(IndexKey::Custom(_, key)) if key == market
+        ));
+
+        // wrong offset, too short, or wrong program
+        assert!(get_custom_index_filter(
+            &program_id,
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                market.to_bytes().to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+        assert!(get_custom_index_filter(
+            &program_id,
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                8,
+                vec![1, 2, 3]
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+        assert!(get_custom_index_filter(
+            &Pubkey::new_unique(),
+            &[RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                8,
+                market.to_bytes().to_vec()
+            ))],
+            &account_indexes,
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_get_spl_token_mint_filter() {
         // Filtering on token-v3 length
@@ -9559,4 +10337,119 @@ pub mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_rpc_get_prioritization_fee_estimate() {
+        let rpc = RpcHandler::start();
+        let slot0 = rpc.working_bank().slot();
+        let bank0_id = rpc.working_bank().bank_id();
+        let account0 = Pubkey::new_unique();
+        let account1 = Pubkey::new_unique();
+        let account2 = Pubkey::new_unique();
+        let price0 = 42;
+        let transactions = vec![
+            Transaction::new_unsigned(Message::new(
+                &[
+                    system_instruction::transfer(&account0, &account1, 1),
+                    ComputeBudgetInstruction::set_compute_unit_price(price0),
+                ],
+                Some(&account0),
+            )),
+            Transaction::new_unsigned(Message::new(
+                &[system_instruction::transfer(&account0, &account2, 1)],
+                Some(&account0),
+            )),
+        ];
+        rpc.update_prioritization_fee_cache(transactions);
+        let cache = rpc.get_prioritization_fee_cache();
+        cache.finalize_priority_fee(slot0, bank0_id);
+        while cache.available_block_count() < 1 {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let request = create_test_request(
+            "getPrioritizationFeeEstimate",
+            Some(json!([{"percentiles": [5_000, 10_000]}])),
+        );
+        let response: RpcPrioritizationFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            response,
+            RpcPrioritizationFeeEstimate {
+                num_slots: 1,
+                num_samples: 2,
+                fees: vec![
+                    RpcPrioritizationFeePercentile {
+                        percentile: 5_000,
+                        prioritization_fee: 0,
+                    },
+                    RpcPrioritizationFeePercentile {
+                        percentile: 10_000,
+                        prioritization_fee: price0,
+                    },
+                ],
+            }
+        );
+
+        let request = create_test_request(
+            "getPrioritizationFeeEstimate",
+            Some(json!([{"accountKeys": [account1.to_string()], "percentiles": [5_000]}])),
+        );
+        let response: RpcPrioritizationFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(response.num_samples, 1);
+        assert_eq!(
+            response.fees,
+            vec![RpcPrioritizationFeePercentile {
+                percentile: 5_000,
+                prioritization_fee: price0,
+            }]
+        );
+
+        // default percentiles
+        let request = create_test_request("getPrioritizationFeeEstimate", None);
+        let response: RpcPrioritizationFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            response
+                .fees
+                .iter()
+                .map(|fee| fee.percentile)
+                .collect::<Vec<_>>(),
+            vec![2_500, 5_000, 7_500, 9_000, 10_000]
+        );
+
+        let request = create_test_request(
+            "getPrioritizationFeeEstimate",
+            Some(json!([{"percentiles": [10_001]}])),
+        );
+        let (_, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            message,
+            "Invalid percentiles; must be between 0 and 10000 basis points"
+        );
+
+        let request = create_test_request(
+            "getPrioritizationFeeEstimate",
+            Some(json!([{"percentiles": vec![5_000; 11]}])),
+        );
+        let (_, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            message,
+            "Invalid percentiles; must list between 1 and 10 percentiles"
+        );
+
+        let request = create_test_request(
+            "getPrioritizationFeeEstimate",
+            Some(json!([{"lookbackSlots": 0}])),
+        );
+        let (_, message) = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            message,
+            format!(
+                "Invalid lookbackSlots; must be between 1 and \
+                 {MAX_PRIORITIZATION_FEE_ESTIMATE_LOOKBACK_SLOTS}"
+            )
+        );
+    }
 }
@@ -0,0 +1,860 @@
+//! The `rpc_grpc_service` module implements an optional gRPC streaming service. It is fed by
+//! `GrpcStreamingPlugin`, a Geyser plugin built into the validator, so it receives the same
+//! account, slot, transaction and block notifications as out-of-tree plugins.
+//!
+//! The service serves plaintext HTTP/2 and does not authenticate clients, so any client that can
+//! reach its port can stream from it. It should only be exposed to trusted networks, or behind a
+//! proxy that terminates TLS and authenticates clients.
+
+use {
+    self::proto::subscribe_update::Update,
+    crate::rpc_subscription_tracker::TransactionSubscriptionFilter,
+    agave_geyser_plugin_interface::geyser_plugin_interface::{
+        GeyserPlugin, ReplicaAccountInfoVersions, ReplicaBlockInfoVersions,
+        ReplicaTransactionInfoVersions, Result as PluginResult, SlotStatus,
+    },
+    prost::Message,
+    solana_pubkey::Pubkey,
+    solana_sdk::clock::Slot,
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::VersionedTransactionWithStatusMeta,
+    std::{
+        collections::{HashMap, HashSet},
+        net::{IpAddr, SocketAddr},
+        pin::Pin,
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+            Arc, RwLock,
+        },
+        task::{Context, Poll},
+        thread::{self, Builder, JoinHandle},
+    },
+    stream_cancel::{Trigger, Tripwire},
+    tokio::sync::mpsc::{self, error::TrySendError},
+    tokio_stream::Stream,
+    tonic::{transport::Server, Request, Response, Status},
+};
+
+pub mod proto {
+    tonic::include_proto!("solana.rpc.streaming");
+}
+
+pub const DEFAULT_GRPC_MAX_CLIENTS: usize = 100;
+pub const DEFAULT_GRPC_MAX_CLIENTS_PER_IP: usize = 10;
+pub const DEFAULT_GRPC_CLIENT_QUEUE_CAPACITY: usize = 10_000;
+pub const DEFAULT_GRPC_MAX_FILTER_KEYS: usize = 1_000;
+pub const DEFAULT_GRPC_WORKER_THREADS: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct GrpcStreamingConfig {
+    /// Maximum number of concurrently connected clients
+    pub max_clients: usize,
+    /// Maximum number of concurrently connected clients from a single IP address
+    pub max_clients_per_ip: usize,
+    /// Number of updates queued for a client before it is disconnected
+    pub client_queue_capacity: usize,
+    /// Maximum number of addresses across all filters of a request
+    pub max_filter_keys: usize,
+    pub worker_threads: usize,
+}
+
+impl Default for GrpcStreamingConfig {
+    fn default() -> Self {
+        Self {
+            max_clients: DEFAULT_GRPC_MAX_CLIENTS,
+            max_clients_per_ip: DEFAULT_GRPC_MAX_CLIENTS_PER_IP,
+            client_queue_capacity: DEFAULT_GRPC_CLIENT_QUEUE_CAPACITY,
+            max_filter_keys: DEFAULT_GRPC_MAX_FILTER_KEYS,
+            worker_threads: DEFAULT_GRPC_WORKER_THREADS,
+        }
+    }
+}
+
+pub struct GrpcStreamingService {
+    thread_hdl: JoinHandle<()>,
+}
+
+impl GrpcStreamingService {
+    /// Starts the service. The returned plugin feeds the service and must be registered
+    /// with the `GeyserPluginService`.
+    pub fn new(
+        config: GrpcStreamingConfig,
+        grpc_addr: SocketAddr,
+    ) -> (Trigger, Self, GrpcStreamingPlugin) {
+        let clients = Arc::<GrpcClients>::default();
+        let plugin = GrpcStreamingPlugin {
+            clients: clients.clone(),
+        };
+        info!("rpc_grpc bound to {:?}", grpc_addr);
+
+        let (trigger, tripwire) = Tripwire::new();
+        let thread_hdl = Builder::new()
+            .name("solRpcGrpc".to_string())
+            .spawn(move || {
+                info!("GrpcStreamingService has started");
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .thread_name("solRpcGrpcRt")
+                    .worker_threads(config.worker_threads)
+                    .enable_all()
+                    .build()
+                    .expect("runtime creation failed");
+                let handler = StreamingHandler {
+                    clients: clients.clone(),
+                    config,
+                };
+                // Streams only end once their clients are gone, so drop them all to let
+                // the server shut down
+                let shutdown = async move {
+                    tripwire.await;
+                    clients.clear();
+                };
+                if let Err(err) = runtime.block_on(
+                    Server::builder()
+                        .add_service(proto::streaming_server::StreamingServer::new(handler))
+                        .serve_with_shutdown(grpc_addr, shutdown),
+                ) {
+                    error!("GrpcStreamingService has stopped due to error: {err}");
+                }
+                info!("GrpcStreamingService has stopped");
+            })
+            .expect("thread spawn failed");
+
+        (trigger, Self { thread_hdl }, plugin)
+    }
+
+    pub fn join(self) -> thread::Result<()> {
+        self.thread_hdl.join()
+    }
+}
+
+#[derive(Debug)]
+struct AccountFilter {
+    accounts: HashSet<Pubkey>,
+    owners: HashSet<Pubkey>,
+}
+
+impl AccountFilter {
+    fn allows(&self, pubkey: &Pubkey, owner: &Pubkey) -> bool {
+        (self.accounts.is_empty() && self.owners.is_empty())
+            || self.accounts.contains(pubkey)
+            || self.owners.contains(owner)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ClientFilter {
+    accounts: Vec<AccountFilter>,
+    slots: bool,
+    transactions: Vec<TransactionSubscriptionFilter>,
+    blocks: bool,
+}
+
+impl ClientFilter {
+    fn new(request: proto::SubscribeRequest, max_filter_keys: usize) -> Result<Self, Status> {
+        let num_keys = request
+            .accounts
+            .iter()
+            .map(|filter| filter.accounts.len() + filter.owners.len())
+            .chain(request.transactions.iter().map(|filter| {
+                usize::from(filter.signer.is_some())
+                    + filter.account_include.len()
+                    + filter.account_exclude.len()
+                    + filter.account_required.len()
+            }))
+            .sum::<usize>();
+        if num_keys > max_filter_keys {
+            return Err(Status::invalid_argument(format!(
+                "Request has {num_keys} filter addresses, the maximum is {max_filter_keys}"
+            )));
+        }
+
+        let accounts = request
+            .accounts
+            .iter()
+            .map(|filter| {
+                Ok(AccountFilter {
+                    accounts: parse_pubkeys(&filter.accounts)?,
+                    owners: parse_pubkeys(&filter.owners)?,
+                })
+            })
+            .collect::<Result<_, Status>>()?;
+        let transactions = request
+            .transactions
+            .iter()
+            .map(|filter| {
+                Ok(TransactionSubscriptionFilter {
+                    vote: filter.vote,
+                    failed: filter.failed,
+                    signer: filter.signer.as_deref().map(parse_pubkey).transpose()?,
+                    account_include: parse_pubkeys(&filter.account_include)?,
+                    account_exclude: parse_pubkeys(&filter.account_exclude)?,
+                    account_required: parse_pubkeys(&filter.account_required)?,
+                })
+            })
+            .collect::<Result<_, Status>>()?;
+        let filter = Self {
+            accounts,
+            slots: request.slots,
+            transactions,
+            blocks: request.blocks,
+        };
+        if filter.accounts.is_empty()
+            && !filter.slots
+            && filter.transactions.is_empty()
+            && !filter.blocks
+        {
+            return Err(Status::invalid_argument(
+                "Request does not subscribe to any updates",
+            ));
+        }
+        Ok(filter)
+    }
+}
+
+fn parse_pubkey(key: &str) -> Result<Pubkey, Status> {
+    Pubkey::from_str(key).map_err(|_| Status::invalid_argument(format!("Invalid address: {key}")))
+}
+
+fn parse_pubkeys<T: FromIterator<Pubkey>>(keys: &[String]) -> Result<T, Status> {
+    keys.iter().map(|key| parse_pubkey(key)).collect()
+}
+
+#[derive(Debug)]
+struct GrpcClient {
+    ip: Option<IpAddr>,
+    filter: ClientFilter,
+    sender: mpsc::Sender<proto::SubscribeUpdate>,
+    lagged: Arc<AtomicBool>,
+}
+
+#[derive(Debug, Default)]
+struct GrpcClients {
+    clients: RwLock<HashMap<u64, GrpcClient>>,
+    next_id: AtomicU64,
+    // Numbers of clients subscribed to account and transaction updates, updated while holding
+    // the write lock of `clients`
+    num_account_clients: AtomicUsize,
+    num_transaction_clients: AtomicUsize,
+}
+
+impl GrpcClients {
+    fn add(&self, client: GrpcClient, config: &GrpcStreamingConfig) -> Result<u64, Status> {
+        let mut clients = self.clients.write().unwrap();
+        if clients.len() >= config.max_clients {
+            return Err(Status::resource_exhausted(format!(
+                "Maximum number of streaming clients ({}) reached",
+                config.max_clients
+            )));
+        }
+        if let Some(ip) = client.ip {
+            let ip_clients = clients
+                .values()
+                .filter(|other| other.ip == Some(ip))
+                .count();
+            if ip_clients >= config.max_clients_per_ip {
+                return Err(Status::resource_exhausted(format!(
+                    "Maximum number of streaming clients per IP address ({}) reached",
+                    config.max_clients_per_ip
+                )));
+            }
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if !client.filter.accounts.is_empty() {
+            self.num_account_clients.fetch_add(1, Ordering::Relaxed);
+        }
+        if !client.filter.transactions.is_empty() {
+            self.num_transaction_clients.fetch_add(1, Ordering::Relaxed);
+        }
+        clients.insert(id, client);
+        Ok(id)
+    }
+
+    fn remove(&self, id: u64) {
+        self.remove_locked(&mut self.clients.write().unwrap(), id);
+    }
+
+    fn remove_locked(&self, clients: &mut HashMap<u64, GrpcClient>, id: u64) {
+        let Some(client) = clients.remove(&id) else {
+            return;
+        };
+        if !client.filter.accounts.is_empty() {
+            self.num_account_clients.fetch_sub(1, Ordering::Relaxed);
+        }
+        if !client.filter.transactions.is_empty() {
+            self.num_transaction_clients.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    fn clear(&self) {
+        let mut clients = self.clients.write().unwrap();
+        clients.clear();
+        self.num_account_clients.store(0, Ordering::Relaxed);
+        self.num_transaction_clients.store(0, Ordering::Relaxed);
+    }
+
+    fn has_account_clients(&self) -> bool {
+        self.num_account_clients.load(Ordering::Relaxed) > 0
+    }
+
+    fn has_transaction_clients(&self) -> bool {
+        self.num_transaction_clients.load(Ordering::Relaxed) > 0
+    }
+
+    /// Queues the update built by `make_update` for every client whose filter satisfies
+    /// `predicate`. The update is only built if at least one client wants it. Clients whose
+    /// queue is full are disconnected.
+    fn broadcast(
+        &self,
+        predicate: impl Fn(&ClientFilter) -> bool,
+        make_update: impl Fn() -> Update,
+    ) {
+        let mut update = None;
+        let mut lagged_clients = vec![];
+        for (id, client) in self.clients.read().unwrap().iter() {
+            if !predicate(&client.filter) {
+                continue;
+            }
+            let update = update.get_or_insert_with(&make_update).clone();
+            match client.sender.try_send(proto::SubscribeUpdate {
+                update: Some(update),
+            }) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    client.lagged.store(true, Ordering::Relaxed);
+                    lagged_clients.push(*id);
+                }
+                // The stream is being dropped and will remove the client itself
+                Err(TrySendError::Closed(_)) => {}
+            }
+        }
+        if !lagged_clients.is_empty() {
+            let mut clients = self.clients.write().unwrap();
+            for id in lagged_clients {
+                warn!("Disconnecting gRPC streaming client {id}: queue is full");
+                self.remove_locked(&mut clients, id);
+            }
+        }
+    }
+}
+
+/// The stream of updates of a single client. Once the client is dropped from `GrpcClients`,
+/// the queued updates are drained and the stream ends, with RESOURCE_EXHAUSTED if the client
+/// was disconnected for not keeping up.
+struct UpdateStream {
+    id: u64,
+    receiver: mpsc::Receiver<proto::SubscribeUpdate>,
+    lagged: Arc<AtomicBool>,
+    clients: Arc<GrpcClients>,
+    finished: bool,
+}
+
+impl Stream for UpdateStream {
+    type Item = Result<proto::SubscribeUpdate, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+        match self.receiver.poll_recv(cx) {
+            Poll::Ready(Some(update)) => Poll::Ready(Some(Ok(update))),
+            Poll::Ready(None) => {
+                self.finished = true;
+                if self.lagged.load(Ordering::Relaxed) {
+                    Poll::Ready(Some(Err(Status::resource_exhausted(
+                        "Client did not keep up with its updates",
+                    ))))
+                } else {
+                    Poll::Ready(None)
+                }
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for UpdateStream {
+    fn drop(&mut self) {
+        self.clients.remove(self.id);
+    }
+}
+
+struct StreamingHandler {
+    clients: Arc<GrpcClients>,
+    config: GrpcStreamingConfig,
+}
+
+#[tonic::async_trait]
+impl proto::streaming_server::Streaming for StreamingHandler {
+    type SubscribeStream = UpdateStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<proto::SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let ip = request.remote_addr().map(|addr| addr.ip());
+        let filter = ClientFilter::new(request.into_inner(), self.config.max_filter_keys)?;
+        let (sender, receiver) = mpsc::channel(self.config.client_queue_capacity);
+        let lagged = Arc::<AtomicBool>::default();
+        let id = self.clients.add(
+            GrpcClient {
+                ip,
+                filter,
+                sender,
+                lagged: lagged.clone(),
+            },
+            &self.config,
+        )?;
+        Ok(Response::new(UpdateStream {
+            id,
+            receiver,
+            lagged,
+            clients: self.clients.clone(),
+            finished: false,
+        }))
+    }
+}
+
+/// The Geyser plugin forwarding validator notifications to the clients of
+/// `GrpcStreamingService`
+#[derive(Debug)]
+pub struct GrpcStreamingPlugin {
+    clients: Arc<GrpcClients>,
+}
+
+impl GeyserPlugin for GrpcStreamingPlugin {
+    fn name(&self) -> &'static str {
+        "rpc_grpc_streaming"
+    }
+
+    fn on_unload(&mut self) {
+        self.clients.clear();
+    }
+
+    fn update_account(
+        &self,
+        account: ReplicaAccountInfoVersions,
+        slot: Slot,
+        is_startup: bool,
+    ) -> PluginResult<()> {
+        if is_startup {
+            return Ok(());
+        }
+        let (pubkey, owner) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(info) => (info.pubkey, info.owner),
+            ReplicaAccountInfoVersions::V0_0_2(info) => (info.pubkey, info.owner),
+            ReplicaAccountInfoVersions::V0_0_3(info) => (info.pubkey, info.owner),
+        };
+        let (Ok(pubkey), Ok(owner)) = (Pubkey::try_from(pubkey), Pubkey::try_from(owner)) else {
+            return Ok(());
+        };
+        self.clients.broadcast(
+            |filter| {
+                filter
+                    .accounts
+                    .iter()
+                    .any(|filter| filter.allows(&pubkey, &owner))
+            },
+            || Update::Account(account_update(&account, slot)),
+        );
+        Ok(())
+    }
+
+    fn update_slot_status(
+        &self,
+        slot: Slot,
+        parent: Option<u64>,
+        status: &SlotStatus,
+    ) -> PluginResult<()> {
+        self.clients.broadcast(
+            |filter| filter.slots,
+            || {
+                let (status, dead_error) = match status {
+                    SlotStatus::Processed => (proto::SlotStatus::Processed, None),
+                    SlotStatus::Rooted => (proto::SlotStatus::Rooted, None),
+                    SlotStatus::Confirmed => (proto::SlotStatus::Confirmed, None),
+                    SlotStatus::FirstShredReceived => (proto::SlotStatus::FirstShredReceived, None),
+                    SlotStatus::Completed => (proto::SlotStatus::Completed, None),
+                    SlotStatus::CreatedBank => (proto::SlotStatus::CreatedBank, None),
+                    SlotStatus::Dead(err) => (proto::SlotStatus::Dead, Some(err.clone())),
+                };
+                Update::Slot(proto::SlotUpdate {
+                    slot,
+                    parent,
+                    status: status as i32,
+                    dead_error,
+                })
+            },
+        );
+        Ok(())
+    }
+
+    fn notify_transaction(
+        &self,
+        transaction: ReplicaTransactionInfoVersions,
+        slot: Slot,
+    ) -> PluginResult<()> {
+        if !self.clients.has_transaction_clients() {
+            return Ok(());
+        }
+        let (signature, is_vote, transaction, meta, index) = match transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => (
+                info.signature,
+                info.is_vote,
+                info.transaction,
+                info.transaction_status_meta,
+                0,
+            ),
+            ReplicaTransactionInfoVersions::V0_0_2(info) => (
+                info.signature,
+                info.is_vote,
+                info.transaction,
+                info.transaction_status_meta,
+                info.index,
+            ),
+        };
+        let transaction_with_meta = VersionedTransactionWithStatusMeta {
+            transaction: transaction.to_versioned_transaction(),
+            meta: meta.clone(),
+        };
+        self.clients.broadcast(
+            |filter| {
+                filter
+                    .transactions
+                    .iter()
+                    .any(|filter| filter.allows(&transaction_with_meta))
+            },
+            || {
+                Update::Transaction(proto::TransactionUpdate {
+                    slot,
+                    signature: signature.as_ref().to_vec(),
+                    is_vote,
+                    index: index as u64,
+                    transaction: generated::ConfirmedTransaction::from(
+                        transaction_with_meta.clone(),
+                    )
+                    .encode_to_vec(),
+                })
+            },
+        );
+        Ok(())
+    }
+
+    fn notify_block_metadata(&self, blockinfo: ReplicaBlockInfoVersions) -> PluginResult<()> {
+        self.clients.broadcast(
+            |filter| filter.blocks,
+            || Update::Block(block_update(&blockinfo)),
+        );
+        Ok(())
+    }
+
+    // As a built-in plugin, this is asked before every account update and transaction rather
+    // than only at startup, so the validator only notifies it while a client wants them
+    fn account_data_notifications_enabled(&self) -> bool {
+        self.clients.has_account_clients()
+    }
+
+    fn account_data_snapshot_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        self.clients.has_transaction_clients()
+    }
+}
+
+fn account_update(account: &ReplicaAccountInfoVersions, slot: Slot) -> proto::AccountUpdate {
+    match account {
+        ReplicaAccountInfoVersions::V0_0_1(info) => proto::AccountUpdate {
+            slot,
+            pubkey: info.pubkey.to_vec(),
+            lamports: info.lamports,
+            owner: info.owner.to_vec(),
+            executable: info.executable,
+            rent_epoch: info.rent_epoch,
+            data: info.data.to_vec(),
+            write_version: info.write_version,
+            txn_signature: None,
+        },
+        ReplicaAccountInfoVersions::V0_0_2(info) => proto::AccountUpdate {
+            slot,
+            pubkey: info.pubkey.to_vec(),
+            lamports: info.lamports,
+            owner: info.owner.to_vec(),
+            executable: info.executable,
+            rent_epoch: info.rent_epoch,
+            data: info.data.to_vec(),
+            write_version: info.write_version,
+            txn_signature: info
+                .txn_signature
+                .map(|signature| signature.as_ref().to_vec()),
+        },
+        ReplicaAccountInfoVersions::V0_0_3(info) => proto::AccountUpdate {
+            slot,
+            pubkey: info.pubkey.to_vec(),
+            lamports: info.lamports,
+            owner: info.owner.to_vec(),
+            executable: info.executable,
+            rent_epoch: info.rent_epoch,
+            data: info.data.to_vec(),
+            write_version: info.write_version,
+            txn_signature: info.txn.map(|txn| txn.signature().as_ref().to_vec()),
+        },
+    }
+}
+
+fn block_update(blockinfo: &ReplicaBlockInfoVersions) -> proto::BlockUpdate {
+    match blockinfo {
+        ReplicaBlockInfoVersions::V0_0_1(info) => proto::BlockUpdate {
+            slot: info.slot,
+            blockhash: info.blockhash.to_string(),
+            block_time: info.block_time,
+            block_height: info.block_height,
+            ..proto::BlockUpdate::default()
+        },
+        ReplicaBlockInfoVersions::V0_0_2(info) => proto::BlockUpdate {
+            slot: info.slot,
+            parent_slot: info.parent_slot,
+            blockhash: info.blockhash.to_string(),
+            parent_blockhash: info.parent_blockhash.to_string(),
+            block_time: info.block_time,
+            block_height: info.block_height,
+            executed_transaction_count: info.executed_transaction_count,
+            entry_count: 0,
+        },
+        ReplicaBlockInfoVersions::V0_0_3(info) => proto::BlockUpdate {
+            slot: info.slot,
+            parent_slot: info.parent_slot,
+            blockhash: info.blockhash.to_string(),
+            parent_blockhash: info.parent_blockhash.to_string(),
+            block_time: info.block_time,
+            block_height: info.block_height,
+            executed_transaction_count: info.executed_transaction_count,
+            entry_count: info.entry_count,
+        },
+        ReplicaBlockInfoVersions::V0_0_4(info) => proto::BlockUpdate {
+            slot: info.slot,
+            parent_slot: info.parent_slot,
+            blockhash: info.blockhash.to_string(),
+            parent_blockhash: info.parent_blockhash.to_string(),
+            block_time: info.block_time,
+            block_height: info.block_height,
+            executed_transaction_count: info.executed_transaction_count,
+            entry_count: info.entry_count,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tokio_stream::StreamExt};
+
+    fn try_subscribe(
+        clients: &Arc<GrpcClients>,
+        request: proto::SubscribeRequest,
+        ip: Option<IpAddr>,
+        config: &GrpcStreamingConfig,
+    ) -> Result<UpdateStream, Status> {
+        let filter = ClientFilter::new(request, config.max_filter_keys).unwrap();
+        let (sender, receiver) = mpsc::channel(config.client_queue_capacity);
+        let lagged = Arc::<AtomicBool>::default();
+        let id = clients.add(
+            GrpcClient {
+                ip,
+                filter,
+                sender,
+                lagged: lagged.clone(),
+            },
+            config,
+        )?;
+        Ok(UpdateStream {
+            id,
+            receiver,
+            lagged,
+            clients: clients.clone(),
+            finished: false,
+        })
+    }
+
+    fn subscribe(
+        clients: &Arc<GrpcClients>,
+        request: proto::SubscribeRequest,
+        queue_capacity: usize,
+    ) -> UpdateStream {
+        let config = GrpcStreamingConfig {
+            client_queue_capacity: queue_capacity,
+            ..GrpcStreamingConfig::default()
+        };
+        try_subscribe(clients, request, None, &config).unwrap()
+    }
+
+    #[test]
+    fn test_client_limits() {
+        let clients = Arc::<GrpcClients>::default();
+        let config = GrpcStreamingConfig {
+            max_clients: 3,
+            max_clients_per_ip: 2,
+            ..GrpcStreamingConfig::default()
+        };
+        let request = proto::SubscribeRequest {
+            slots: true,
+            ..proto::SubscribeRequest::default()
+        };
+        let ip = Some(IpAddr::from([10, 0, 0, 1]));
+        let other_ip = Some(IpAddr::from([10, 0, 0, 2]));
+        let subscribe = |ip| try_subscribe(&clients, request.clone(), ip, &config);
+
+        let stream = subscribe(ip).unwrap();
+        let _other_stream = subscribe(ip).unwrap();
+        let status = subscribe(ip).err().unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+        // A disconnected client frees its slot
+        drop(stream);
+        let _stream = subscribe(ip).unwrap();
+
+        // The overall limit applies across IP addresses
+        let _other_ip_stream = subscribe(other_ip).unwrap();
+        let status = subscribe(other_ip).err().unwrap();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    }
+
+    #[test]
+    fn test_client_filter() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let filter = ClientFilter::new(
+            proto::SubscribeRequest {
+                accounts: vec![proto::AccountFilter {
+                    accounts: vec![],
+                    owners: vec![owner.to_string()],
+                }],
+                ..proto::SubscribeRequest::default()
+            },
+            1,
+        )
+        .unwrap();
+        assert!(filter.accounts[0].allows(&pubkey, &owner));
+        assert!(!filter.accounts[0].allows(&owner, &pubkey));
+
+        let status = ClientFilter::new(proto::SubscribeRequest::default(), 1).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = ClientFilter::new(
+            proto::SubscribeRequest {
+                accounts: vec![proto::AccountFilter {
+                    accounts: vec!["invalid".to_string()],
+                    owners: vec![],
+                }],
+                ..proto::SubscribeRequest::default()
+            },
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let status = ClientFilter::new(
+            proto::SubscribeRequest {
+                transactions: vec![proto::TransactionFilter {
+                    signer: Some(pubkey.to_string()),
+                    account_include: vec![owner.to_string()],
+                    ..proto::TransactionFilter::default()
+                }],
+                ..proto::SubscribeRequest::default()
+            },
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_notifications_enabled_by_clients() {
+        let clients = Arc::<GrpcClients>::default();
+        let plugin = GrpcStreamingPlugin {
+            clients: clients.clone(),
+        };
+        assert!(!plugin.account_data_notifications_enabled());
+        assert!(!plugin.transaction_notifications_enabled());
+
+        let accounts_stream = subscribe(
+            &clients,
+            proto::SubscribeRequest {
+                accounts: vec![proto::AccountFilter::default()],
+                ..proto::SubscribeRequest::default()
+            },
+            1,
+        );
+        assert!(plugin.account_data_notifications_enabled());
+        assert!(!plugin.transaction_notifications_enabled());
+
+        let transactions_stream = subscribe(
+            &clients,
+            proto::SubscribeRequest {
+                transactions: vec![proto::TransactionFilter::default()],
+                ..proto::SubscribeRequest::default()
+            },
+            1,
+        );
+        assert!(plugin.transaction_notifications_enabled());
+
+        drop(accounts_stream);
+        assert!(!plugin.account_data_notifications_enabled());
+        assert!(plugin.transaction_notifications_enabled());
+
+        drop(transactions_stream);
+        assert!(!plugin.transaction_notifications_enabled());
+    }
+
+    #[test]
+    fn test_lagged_client_is_disconnected() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let clients = Arc::<GrpcClients>::default();
+        let plugin = GrpcStreamingPlugin {
+            clients: clients.clone(),
+        };
+        let slots_request = proto::SubscribeRequest {
+            slots: true,
+            ..proto::SubscribeRequest::default()
+        };
+        let mut fast_stream = subscribe(&clients, slots_request.clone(), 2);
+        let mut slow_stream = subscribe(&clients, slots_request, 1);
+
+        plugin
+            .update_slot_status(1, None, &SlotStatus::Rooted)
+            .unwrap();
+        let update = runtime.block_on(fast_stream.next()).unwrap().unwrap();
+        assert_eq!(
+            update.update,
+            Some(Update::Slot(proto::SlotUpdate {
+                slot: 1,
+                parent: None,
+                status: proto::SlotStatus::Rooted as i32,
+                dead_error: None,
+            }))
+        );
+
+        plugin
+            .update_slot_status(2, Some(1), &SlotStatus::Processed)
+            .unwrap();
+        assert_eq!(clients.clients.read().unwrap().len(), 1);
+
+        // The queued update is still delivered before the client is told it lagged
+        let update = runtime.block_on(slow_stream.next()).unwrap().unwrap();
+        assert!(matches!(
+            update.update,
+            Some(Update::Slot(proto::SlotUpdate { slot: 1, .. }))
+        ));
+        let status = runtime.block_on(slow_stream.next()).unwrap().unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(runtime.block_on(slow_stream.next()).is_none());
+
+        let update = runtime.block_on(fast_stream.next()).unwrap().unwrap();
+        assert!(matches!(
+            update.update,
+            Some(Update::Slot(proto::SlotUpdate { slot: 2, .. }))
+        ));
+        drop(fast_stream);
+        assert!(clients.clients.read().unwrap().is_empty());
+    }
+}
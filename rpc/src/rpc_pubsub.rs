@@ -9,28 +9,33 @@ use {
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
             LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
             SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionParams,
-            SubscriptionToken,
+            SubscriptionToken, TransactionSubscriptionFilter, TransactionSubscriptionParams,
         },
     },
     dashmap::DashMap,
     jsonrpc_core::{Error, ErrorCode, Result},
     jsonrpc_derive::rpc,
     jsonrpc_pubsub::{typed::Subscriber, SubscriptionId as PubSubSubscriptionId},
-    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig},
     solana_rpc_client_api::{
         config::{
-            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+            RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcBlockSubscribeConfig,
+            RpcBlockSubscribeFilter, RpcNotificationFormat, RpcProgramSubscribeConfig,
+            RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+            RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
         },
+        request::MAX_SUBSCRIPTION_DATA_SLICES,
         response::{
             Response as RpcResponse, RpcBlockUpdate, RpcKeyedAccount, RpcLogsResponse,
-            RpcSignatureResult, RpcVersionInfo, RpcVote, SlotInfo, SlotUpdate,
+            RpcSignatureResult, RpcTransactionUpdate, RpcVersionInfo, RpcVote, SlotInfo,
+            SlotUpdate,
         },
     },
-    solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature},
+    solana_sdk::{
+        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    },
     solana_transaction_status::UiTransactionEncoding,
-    std::{str::FromStr, sync::Arc},
+    std::{collections::BTreeSet, str::FromStr, sync::Arc},
 };
 
 // We have to keep both of the following traits to not break backwards compatibility.
@@ -61,7 +66,7 @@ pub trait RpcSolPubSub {
         meta: Self::Metadata,
         subscriber: Subscriber<RpcResponse<UiAccount>>,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     );
 
     // Unsubscribe from account notification subscription.
@@ -88,7 +93,7 @@ pub trait RpcSolPubSub {
         meta: Self::Metadata,
         subscriber: Subscriber<RpcResponse<RpcKeyedAccount>>,
         pubkey_str: String,
-        config: Option<RpcProgramAccountsConfig>,
+        config: Option<RpcProgramSubscribeConfig>,
     );
 
     // Unsubscribe from account notification subscription.
@@ -214,6 +219,32 @@ pub trait RpcSolPubSub {
         id: PubSubSubscriptionId,
     ) -> Result<bool>;
 
+    // Subscribe to transactions matching a filter
+    #[pubsub(
+        subscription = "transactionNotification",
+        subscribe,
+        name = "transactionSubscribe"
+    )]
+    fn transaction_subscribe(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<Arc<RpcTransactionUpdate>>,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    );
+
+    // Unsubscribe from transaction notification subscription.
+    #[pubsub(
+        subscription = "transactionNotification",
+        unsubscribe,
+        name = "transactionUnsubscribe"
+    )]
+    fn transaction_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: PubSubSubscriptionId,
+    ) -> Result<bool>;
+
     // Get notification when vote is encountered
     #[pubsub(subscription = "voteNotification", subscribe, name = "voteSubscribe")]
     fn vote_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<RpcVote>);
@@ -261,7 +292,7 @@ mod internal {
         fn account_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcAccountInfoConfig>,
+            config: Option<RpcAccountSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -274,7 +305,7 @@ mod internal {
         fn program_subscribe(
             &self,
             pubkey_str: String,
-            config: Option<RpcProgramAccountsConfig>,
+            config: Option<RpcProgramSubscribeConfig>,
         ) -> Result<SubscriptionId>;
 
         // Unsubscribe from account notification subscription.
@@ -334,6 +365,18 @@ mod internal {
         #[rpc(name = "blockUnsubscribe")]
         fn block_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
 
+        // Subscribe to transactions matching a filter
+        #[rpc(name = "transactionSubscribe")]
+        fn transaction_subscribe(
+            &self,
+            filter: RpcTransactionSubscribeFilter,
+            config: Option<RpcTransactionSubscribeConfig>,
+        ) -> Result<SubscriptionId>;
+
+        // Unsubscribe from transaction notification subscription.
+        #[rpc(name = "transactionUnsubscribe")]
+        fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool>;
+
         // Get notification when vote is encountered
         #[rpc(name = "voteSubscribe")]
         fn vote_subscribe(&self) -> Result<SubscriptionId>;
@@ -419,23 +462,77 @@ fn param<T: FromStr>(param_str: &str, thing: &str) -> Result<T> {
     })
 }
 
+// Combines the `dataSlice` and `dataSlices` of an account or program subscription, checking that
+// they can be applied with the requested encoding
+fn data_slices_param(
+    data_slice: Option<UiDataSliceConfig>,
+    data_slices: Option<Vec<UiDataSliceConfig>>,
+    encoding: UiAccountEncoding,
+    notification_format: RpcNotificationFormat,
+) -> Result<Vec<UiDataSliceConfig>> {
+    let data_slices: Vec<_> = data_slice
+        .into_iter()
+        .chain(data_slices.unwrap_or_default())
+        .collect();
+    if data_slices.len() > MAX_SUBSCRIPTION_DATA_SLICES {
+        return Err(Error::invalid_params(format!(
+            "Invalid Request: at most {MAX_SUBSCRIPTION_DATA_SLICES} data slices are supported"
+        )));
+    }
+    if encoding == UiAccountEncoding::JsonParsed {
+        if notification_format == RpcNotificationFormat::Binary {
+            return Err(Error::invalid_params(
+                "Invalid Request: binary notifications do not support jsonParsed encoding",
+            ));
+        }
+        if data_slices.len() > 1 {
+            return Err(Error::invalid_params(
+                "Invalid Request: jsonParsed encoding does not support multiple data slices",
+            ));
+        }
+    }
+    Ok(data_slices)
+}
+
+// Notified data is only remembered for slots that can't be rolled back, so that `changesOnly`
+// never skips a notification because of data notified on an abandoned fork
+fn changes_only_param(changes_only: Option<bool>, commitment: CommitmentConfig) -> Result<bool> {
+    let changes_only = changes_only.unwrap_or_default();
+    if changes_only {
+        check_is_at_least_confirmed(commitment)?;
+    }
+    Ok(changes_only)
+}
+
 impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn account_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcAccountInfoConfig>,
+        config: Option<RpcAccountSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let RpcAccountInfoConfig {
-            encoding,
-            data_slice,
-            commitment,
-            min_context_slot: _, // ignored
+        let RpcAccountSubscribeConfig {
+            account_config:
+                RpcAccountInfoConfig {
+                    encoding,
+                    data_slice,
+                    commitment,
+                    min_context_slot: _, // ignored
+                },
+            data_slices,
+            changes_only,
+            notification_format,
         } = config.unwrap_or_default();
+        let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
+        let notification_format = notification_format.unwrap_or_default();
+        let commitment = commitment.unwrap_or_default();
+        let changes_only = changes_only_param(changes_only, commitment)?;
         let params = AccountSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
-            commitment: commitment.unwrap_or_default(),
-            data_slice,
-            encoding: encoding.unwrap_or(UiAccountEncoding::Binary),
+            commitment,
+            data_slices: data_slices_param(data_slice, data_slices, encoding, notification_format)?,
+            encoding,
+            changes_only,
+            notification_format,
         };
         self.subscribe(SubscriptionParams::Account(params))
     }
@@ -447,19 +544,35 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
     fn program_subscribe(
         &self,
         pubkey_str: String,
-        config: Option<RpcProgramAccountsConfig>,
+        config: Option<RpcProgramSubscribeConfig>,
     ) -> Result<SubscriptionId> {
-        let config = config.unwrap_or_default();
+        let RpcProgramSubscribeConfig {
+            program_config: config,
+            data_slices,
+            changes_only,
+            notification_format,
+        } = config.unwrap_or_default();
+        let encoding = config
+            .account_config
+            .encoding
+            .unwrap_or(UiAccountEncoding::Binary);
+        let notification_format = notification_format.unwrap_or_default();
+        let commitment = config.account_config.commitment.unwrap_or_default();
+        let changes_only = changes_only_param(changes_only, commitment)?;
         let params = ProgramSubscriptionParams {
             pubkey: param::<Pubkey>(&pubkey_str, "pubkey")?,
             filters: config.filters.unwrap_or_default(),
-            encoding: config
-                .account_config
-                .encoding
-                .unwrap_or(UiAccountEncoding::Binary),
-            data_slice: config.account_config.data_slice,
-            commitment: config.account_config.commitment.unwrap_or_default(),
+            encoding,
+            data_slices: data_slices_param(
+                config.account_config.data_slice,
+                data_slices,
+                encoding,
+                notification_format,
+            )?,
+            commitment,
             with_context: config.with_context.unwrap_or_default(),
+            changes_only,
+            notification_format,
         };
         self.subscribe(SubscriptionParams::Program(params))
     }
@@ -568,6 +681,48 @@ impl RpcSolPubSubInternal for RpcSolPubSubImpl {
         self.unsubscribe(id)
     }
 
+    fn transaction_subscribe(
+        &self,
+        filter: RpcTransactionSubscribeFilter,
+        config: Option<RpcTransactionSubscribeConfig>,
+    ) -> Result<SubscriptionId> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        let config = config.unwrap_or_default();
+        let pubkeys = |keys: Option<Vec<String>>, thing: &'static str| {
+            keys.unwrap_or_default()
+                .iter()
+                .map(|key| param::<Pubkey>(key, thing))
+                .collect::<Result<BTreeSet<_>>>()
+        };
+        let params = TransactionSubscriptionParams {
+            commitment: config.commitment.unwrap_or_default(),
+            encoding: config.encoding.unwrap_or(UiTransactionEncoding::Base64),
+            filter: TransactionSubscriptionFilter {
+                vote: filter.vote,
+                failed: filter.failed,
+                signer: filter
+                    .signer
+                    .map(|signer| param::<Pubkey>(&signer, "signer"))
+                    .transpose()?,
+                account_include: pubkeys(filter.account_include, "account_include")?,
+                account_exclude: pubkeys(filter.account_exclude, "account_exclude")?,
+                account_required: pubkeys(filter.account_required, "account_required")?,
+            },
+            show_rewards: config.show_rewards.unwrap_or_default(),
+            max_supported_transaction_version: config.max_supported_transaction_version,
+        };
+        self.subscribe(SubscriptionParams::Transaction(params))
+    }
+
+    fn transaction_unsubscribe(&self, id: SubscriptionId) -> Result<bool> {
+        if !self.config.enable_transaction_subscription {
+            return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
+        }
+        self.unsubscribe(id)
+    }
+
     fn vote_subscribe(&self) -> Result<SubscriptionId> {
         if !self.config.enable_vote_subscription {
             return Err(Error::new(jsonrpc_core::ErrorCode::MethodNotFound));
@@ -894,11 +1049,14 @@ mod tests {
 
         rpc.account_subscribe(
             stake_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(encoding),
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(encoding),
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                ..RpcAccountSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -1020,11 +1178,14 @@ mod tests {
 
         rpc.account_subscribe(
             nonce_account.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::processed()),
-                encoding: Some(UiAccountEncoding::JsonParsed),
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::processed()),
+                    encoding: Some(UiAccountEncoding::JsonParsed),
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                ..RpcAccountSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -1155,11 +1316,14 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                ..RpcAccountSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -1210,11 +1374,14 @@ mod tests {
 
         rpc.account_subscribe(
             bob.pubkey().to_string(),
-            Some(RpcAccountInfoConfig {
-                commitment: Some(CommitmentConfig::finalized()),
-                encoding: None,
-                data_slice: None,
-                min_context_slot: None,
+            Some(RpcAccountSubscribeConfig {
+                account_config: RpcAccountInfoConfig {
+                    commitment: Some(CommitmentConfig::finalized()),
+                    encoding: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                ..RpcAccountSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -3,13 +3,14 @@
 use {
     crate::{
         rpc_pubsub::{RpcSolPubSubImpl, RpcSolPubSubInternal},
+        rpc_rate_limiter::{RpcRateLimitMiddleware, RpcRateLimiter},
         rpc_subscription_tracker::{
             SubscriptionControl, SubscriptionId, SubscriptionParams, SubscriptionToken,
         },
-        rpc_subscriptions::{RpcNotification, RpcSubscriptions},
+        rpc_subscriptions::{NotificationPayload, RpcNotification, RpcSubscriptions},
     },
     dashmap::{mapref::entry::Entry, DashMap},
-    jsonrpc_core::IoHandler,
+    jsonrpc_core::MetaIoHandler,
     soketto::handshake::{server, Server},
     solana_metrics::TokenCounter,
     solana_rayon_threadlimit::get_thread_count,
@@ -40,24 +41,28 @@ pub const DEFAULT_WORKER_THREADS: usize = 1;
 #[derive(Debug, Clone)]
 pub struct PubSubConfig {
     pub enable_block_subscription: bool,
+    pub enable_transaction_subscription: bool,
     pub enable_vote_subscription: bool,
     pub max_active_subscriptions: usize,
     pub queue_capacity_items: usize,
     pub queue_capacity_bytes: usize,
     pub worker_threads: usize,
     pub notification_threads: Option<NonZeroUsize>,
+    pub rate_limiter: Option<Arc<RpcRateLimiter>>,
 }
 
 impl Default for PubSubConfig {
     fn default() -> Self {
         Self {
             enable_block_subscription: false,
+            enable_transaction_subscription: false,
             enable_vote_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
             queue_capacity_items: DEFAULT_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(get_thread_count()),
+            rate_limiter: None,
         }
     }
 }
@@ -66,12 +71,14 @@ impl PubSubConfig {
     pub fn default_for_tests() -> Self {
         Self {
             enable_block_subscription: false,
+            enable_transaction_subscription: false,
             enable_vote_subscription: false,
             max_active_subscriptions: MAX_ACTIVE_SUBSCRIPTIONS,
             queue_capacity_items: DEFAULT_TEST_QUEUE_CAPACITY_ITEMS,
             queue_capacity_bytes: DEFAULT_QUEUE_CAPACITY_BYTES,
             worker_threads: DEFAULT_WORKER_THREADS,
             notification_threads: NonZeroUsize::new(2),
+            rate_limiter: None,
         }
     }
 }
@@ -137,6 +144,7 @@ struct SentNotificationStats {
     num_root: AtomicUsize,
     num_vote: AtomicUsize,
     num_block: AtomicUsize,
+    num_transaction: AtomicUsize,
     total_creation_to_queue_time_us: AtomicU64,
     last_report: AtomicInterval,
 }
@@ -191,6 +199,11 @@ impl SentNotificationStats {
                     self.num_block.swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "num_transaction",
+                    self.num_transaction.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "total_creation_to_queue_time_us",
                     self.total_creation_to_queue_time_us
@@ -240,6 +253,9 @@ fn increment_sent_notification_stats(
         SubscriptionParams::Block(_) => {
             stats.num_block.fetch_add(1, Ordering::Relaxed);
         }
+        SubscriptionParams::Transaction(_) => {
+            stats.num_transaction.fetch_add(1, Ordering::Relaxed);
+        }
     }
     stats.total_creation_to_queue_time_us.fetch_add(
         notification.created_at.elapsed().as_micros() as u64,
@@ -258,7 +274,10 @@ impl BroadcastHandler {
         }
     }
 
-    fn handle(&self, notification: RpcNotification) -> Result<Option<Arc<String>>, Error> {
+    fn handle(
+        &self,
+        notification: RpcNotification,
+    ) -> Result<Option<Arc<NotificationPayload>>, Error> {
         if let Entry::Occupied(entry) = self
             .current_subscriptions
             .entry(notification.subscription_id)
@@ -273,7 +292,7 @@ impl BroadcastHandler {
                 entry.remove();
             }
             notification
-                .json
+                .payload
                 .upgrade()
                 .ok_or(Error::NotificationIsGone)
                 .map(Some)
@@ -299,6 +318,26 @@ impl TestBroadcastReceiver {
     }
 
     pub fn recv_timeout(&mut self, timeout: std::time::Duration) -> Result<String, String> {
+        match self.recv_payload_timeout(timeout)?.as_ref() {
+            NotificationPayload::Json(json) => Ok(json.clone()),
+            NotificationPayload::Binary(_) => Err("TestBroadcastReceiver: binary payload".into()),
+        }
+    }
+
+    pub fn recv_binary(&mut self) -> Vec<u8> {
+        let payload = self
+            .recv_payload_timeout(std::time::Duration::from_secs(10))
+            .unwrap_or_else(|err| panic!("broadcast receiver error: {err}"));
+        match payload.as_ref() {
+            NotificationPayload::Binary(bytes) => bytes.clone(),
+            NotificationPayload::Json(json) => panic!("unexpected json payload: {json}"),
+        }
+    }
+
+    fn recv_payload_timeout(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<Arc<NotificationPayload>, String> {
         use {std::thread::sleep, tokio::sync::broadcast::error::TryRecvError};
 
         let started = std::time::Instant::now();
@@ -310,8 +349,10 @@ impl TestBroadcastReceiver {
                         "TestBroadcastReceiver: {:?}ms elapsed",
                         started.elapsed().as_millis()
                     );
-                    if let Some(json) = self.handler.handle(notification).expect("handler failed") {
-                        return Ok(json.to_string());
+                    if let Some(payload) =
+                        self.handler.handle(notification).expect("handler failed")
+                    {
+                        return Ok(payload);
                     }
                 }
                 Err(TryRecvError::Empty) => {
@@ -335,6 +376,7 @@ pub fn test_connection(
     let rpc_impl = RpcSolPubSubImpl::new(
         PubSubConfig {
             enable_block_subscription: true,
+            enable_transaction_subscription: true,
             enable_vote_subscription: true,
             queue_capacity_items: 100,
             ..PubSubConfig::default()
@@ -368,8 +410,14 @@ async fn handle_connection(
     config: PubSubConfig,
     mut tripwire: Tripwire,
 ) -> Result<(), Error> {
+    let peer_ip = socket.peer_addr().ok().map(|addr| addr.ip());
     let mut server = Server::new(socket.compat());
     let request = server.receive_request().await?;
+    let rate_limit_client_key = config
+        .rate_limiter
+        .as_ref()
+        .map(|rate_limiter| rate_limiter.websocket_client_key(request.path(), peer_ip))
+        .unwrap_or_default();
     let accept = server::Response::Accept {
         key: request.key(),
         protocol: None,
@@ -381,7 +429,10 @@ async fn handle_connection(
     let mut data = Vec::new();
     let current_subscriptions = Arc::new(DashMap::new());
 
-    let mut json_rpc_handler = IoHandler::new();
+    let mut json_rpc_handler = MetaIoHandler::with_middleware(RpcRateLimitMiddleware::new(
+        config.rate_limiter.clone(),
+        move |_: &()| rate_limit_client_key.clone(),
+    ));
     let rpc_impl = RpcSolPubSubImpl::new(
         config,
         subscription_control,
@@ -406,8 +457,13 @@ async fn handle_connection(
                     result = broadcast_receiver.recv() => {
 
                         // In both possible error cases (closed or lagged) we disconnect the client.
-                        if let Some(json) = broadcast_handler.handle(result?)? {
-                            sender.send_text(&*json).await?;
+                        if let Some(payload) = broadcast_handler.handle(result?)? {
+                            match payload.as_ref() {
+                                NotificationPayload::Json(json) => sender.send_text(json).await?,
+                                NotificationPayload::Binary(bytes) => {
+                                    sender.send_binary(bytes).await?
+                                }
+                            }
                         }
                     },
                     _ = &mut tripwire => {
@@ -424,7 +480,7 @@ async fn handle_connection(
             break;
         };
 
-        if let Some(response) = json_rpc_handler.handle_request(data_str).await {
+        if let Some(response) = json_rpc_handler.handle_request(data_str, ()).await {
             sender.send_text(&response).await?;
         }
         data.clear();
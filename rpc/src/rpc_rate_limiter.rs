@@ -0,0 +1,565 @@
+//! Per-client and per-method request quotas for the JSON RPC and PubSub services
+//!
+//! Clients are identified either by an API key, supplied in a request header or a
+//! query parameter, or by their IP address. Every client gets a set of token
+//! buckets refilled once per second: one for the number of requests, one for the
+//! weighted cost of those requests and one per explicitly limited method.
+
+use {
+    dashmap::DashMap,
+    jsonrpc_core::{
+        futures::future::{self, Either},
+        Call, Metadata, Middleware, Output, Response,
+    },
+    jsonrpc_http_server::hyper,
+    solana_rpc_client_api::custom_error::RpcCustomError,
+    solana_sdk::timing::AtomicInterval,
+    std::{
+        collections::HashMap,
+        fs::File,
+        net::IpAddr,
+        path::Path,
+        sync::Arc,
+        time::{Duration, Instant},
+    },
+};
+
+const METRICS_REPORT_INTERVAL_MS: u64 = 10_000;
+/// IP clients that have not sent a request in this long are forgotten
+const IDLE_CLIENT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Cost of a method not listed in `RpcRateLimitConfig::method_costs`
+pub const DEFAULT_METHOD_COST: u64 = 1;
+/// Carries the peer address of an HTTP request from the front server of the JSON RPC
+/// service to the JSON RPC server itself. Any value sent by the client is overwritten.
+pub const PEER_ADDRESS_HEADER: &str = "x-solana-peer-address";
+
+/// Limits applied to a single client. An unset limit is not enforced.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RpcQuota {
+    pub requests_per_second: Option<u64>,
+    pub cost_per_second: Option<u64>,
+    pub method_requests_per_second: HashMap<String, u64>,
+}
+
+impl RpcQuota {
+    fn is_unlimited(&self) -> bool {
+        self.requests_per_second.is_none()
+            && self.cost_per_second.is_none()
+            && self.method_requests_per_second.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcApiKeyConfig {
+    /// Reported in metrics in place of the key itself
+    pub name: String,
+    pub key: String,
+    #[serde(flatten)]
+    pub quota: RpcQuota,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RpcRateLimitConfig {
+    /// HTTP header carrying the API key
+    pub api_key_header: String,
+    /// Query parameter carrying the API key, for clients that can not set headers
+    pub api_key_query_parameter: String,
+    /// HTTP header carrying the client IP when the node sits behind a proxy, such as
+    /// `x-forwarded-for`. HTTP requests without an API key or this header share a
+    /// single quota. If unset, HTTP clients are identified by their peer address.
+    /// The PubSub service always uses the peer address.
+    pub client_ip_header: Option<String>,
+    /// Number of proxies in front of the node that append the address they received
+    /// a request from to `client_ip_header`. The client IP is taken this many entries
+    /// from the right, so that entries supplied by the client itself are ignored.
+    pub trusted_proxy_count: usize,
+    /// Weighted cost of each method, used against `RpcQuota::cost_per_second`
+    pub method_costs: HashMap<String, u64>,
+    /// Quota applied to each client without an API key
+    pub default_quota: RpcQuota,
+    pub api_keys: Vec<RpcApiKeyConfig>,
+}
+
+impl Default for RpcRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            api_key_header: "x-api-key".to_string(),
+            api_key_query_parameter: "api-key".to_string(),
+            client_ip_header: None,
+            trusted_proxy_count: 1,
+            method_costs: HashMap::default(),
+            default_quota: RpcQuota::default(),
+            api_keys: Vec::default(),
+        }
+    }
+}
+
+impl RpcRateLimitConfig {
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|err| format!("Unable to open {}: {err}", path.display()))?;
+        serde_json::from_reader(file)
+            .map_err(|err| format!("Unable to parse {}: {err}", path.display()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum RpcClientKey {
+    /// Index into `RpcRateLimitConfig::api_keys`
+    ApiKey(usize),
+    Ip(IpAddr),
+    /// A key was supplied but it is not configured
+    InvalidApiKey,
+    #[default]
+    Anonymous,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens_per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(tokens_per_second: u64, now: Instant) -> Self {
+        Self {
+            tokens_per_second: tokens_per_second as f64,
+            tokens: tokens_per_second as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.tokens_per_second).min(self.tokens_per_second);
+        self.last_refill = now;
+    }
+
+    /// A request costing more than a full bucket is let through once the bucket is full
+    fn has(&self, amount: u64) -> bool {
+        self.tokens >= (amount as f64).min(self.tokens_per_second)
+    }
+
+    fn take(&mut self, amount: u64) {
+        self.tokens = (self.tokens - amount as f64).max(0.0);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct ClientStats {
+    num_requests: u64,
+    num_rejected: u64,
+    total_cost: u64,
+}
+
+impl ClientStats {
+    fn add(&mut self, other: &Self) {
+        self.num_requests = self.num_requests.saturating_add(other.num_requests);
+        self.num_rejected = self.num_rejected.saturating_add(other.num_rejected);
+        self.total_cost = self.total_cost.saturating_add(other.total_cost);
+    }
+
+    fn report(&self, api_key: &str) {
+        datapoint_info!(
+            "rpc-rate-limit",
+            "api_key" => api_key,
+            ("num_requests", self.num_requests, i64),
+            ("num_rejected", self.num_rejected, i64),
+            ("total_cost", self.total_cost, i64),
+        );
+    }
+}
+
+#[derive(Debug)]
+struct ClientState {
+    requests: Option<TokenBucket>,
+    cost: Option<TokenBucket>,
+    methods: HashMap<String, TokenBucket>,
+    last_used: Instant,
+    stats: ClientStats,
+}
+
+impl ClientState {
+    fn new(quota: &RpcQuota, now: Instant) -> Self {
+        Self {
+            requests: quota
+                .requests_per_second
+                .map(|rate| TokenBucket::new(rate, now)),
+            cost: quota
+                .cost_per_second
+                .map(|rate| TokenBucket::new(rate, now)),
+            methods: HashMap::default(),
+            last_used: now,
+            stats: ClientStats::default(),
+        }
+    }
+
+    fn try_acquire(&mut self, quota: &RpcQuota, method: &str, cost: u64, now: Instant) -> bool {
+        self.last_used = now;
+        self.stats.num_requests = self.stats.num_requests.saturating_add(1);
+
+        let method_bucket = quota.method_requests_per_second.get(method).map(|rate| {
+            self.methods
+                .entry(method.to_string())
+                .or_insert_with(|| TokenBucket::new(*rate, now))
+        });
+        let mut buckets = [
+            self.requests.as_mut().map(|bucket| (bucket, 1)),
+            self.cost.as_mut().map(|bucket| (bucket, cost)),
+            method_bucket.map(|bucket| (bucket, 1)),
+        ];
+        buckets
+            .iter_mut()
+            .flatten()
+            .for_each(|(bucket, _)| bucket.refill(now));
+        if !buckets
+            .iter()
+            .flatten()
+            .all(|(bucket, amount)| bucket.has(*amount))
+        {
+            self.stats.num_rejected = self.stats.num_rejected.saturating_add(1);
+            return false;
+        }
+        buckets
+            .iter_mut()
+            .flatten()
+            .for_each(|(bucket, amount)| bucket.take(*amount));
+        self.stats.total_cost = self.stats.total_cost.saturating_add(cost);
+        true
+    }
+}
+
+#[derive(Debug)]
+pub struct RpcRateLimiter {
+    config: RpcRateLimitConfig,
+    api_key_indexes: HashMap<String, usize>,
+    clients: DashMap<RpcClientKey, ClientState>,
+    last_report: AtomicInterval,
+}
+
+impl RpcRateLimiter {
+    pub fn new(config: RpcRateLimitConfig) -> Self {
+        let api_key_indexes = config
+            .api_keys
+            .iter()
+            .enumerate()
+            .map(|(index, api_key)| (api_key.key.clone(), index))
+            .collect();
+        Self {
+            config,
+            api_key_indexes,
+            clients: DashMap::default(),
+            last_report: AtomicInterval::default(),
+        }
+    }
+
+    pub fn client_key(&self, api_key: Option<&str>, ip: Option<IpAddr>) -> RpcClientKey {
+        match (api_key, ip) {
+            (Some(api_key), _) => self
+                .api_key_indexes
+                .get(api_key)
+                .map(|index| RpcClientKey::ApiKey(*index))
+                .unwrap_or(RpcClientKey::InvalidApiKey),
+            (None, Some(ip)) => RpcClientKey::Ip(ip),
+            (None, None) => RpcClientKey::Anonymous,
+        }
+    }
+
+    pub fn http_client_key(&self, request: &hyper::Request<hyper::Body>) -> RpcClientKey {
+        let headers = request.headers();
+        let api_key = headers
+            .get(self.config.api_key_header.as_str())
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| self.query_api_key(request.uri().query()));
+        let ip = match &self.config.client_ip_header {
+            // X-Forwarded-For style headers list the originating client first, and each
+            // proxy appends the address it received the request from
+            Some(header) => headers
+                .get(header.as_str())
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| {
+                    value
+                        .rsplit(',')
+                        .nth(self.config.trusted_proxy_count.checked_sub(1)?)
+                })
+                .and_then(|ip| ip.trim().parse().ok()),
+            None => headers
+                .get(PEER_ADDRESS_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|ip| ip.parse().ok()),
+        };
+        self.client_key(api_key, ip)
+    }
+
+    /// Whether HTTP clients are identified by their peer address, which has to be passed
+    /// in `PEER_ADDRESS_HEADER`
+    pub fn keys_on_peer_address(&self) -> bool {
+        self.config.client_ip_header.is_none()
+    }
+
+    /// `path` is the request target of the websocket handshake
+    pub fn websocket_client_key(&self, path: &str, peer_ip: Option<IpAddr>) -> RpcClientKey {
+        let query = path.split_once('?').map(|(_, query)| query);
+        self.client_key(self.query_api_key(query), peer_ip)
+    }
+
+    fn query_api_key<'a>(&self, query: Option<&'a str>) -> Option<&'a str> {
+        query?
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == self.config.api_key_query_parameter)
+            .map(|(_, value)| value)
+    }
+
+    pub fn check(&self, client_key: &RpcClientKey, method: &str) -> Result<(), RpcCustomError> {
+        let quota = match client_key {
+            RpcClientKey::ApiKey(index) => &self.config.api_keys[*index].quota,
+            RpcClientKey::Ip(_) | RpcClientKey::Anonymous => &self.config.default_quota,
+            RpcClientKey::InvalidApiKey => return Err(RpcCustomError::InvalidApiKey),
+        };
+        let now = Instant::now();
+        let allowed = if quota.is_unlimited() && !matches!(client_key, RpcClientKey::ApiKey(_)) {
+            true
+        } else {
+            let cost = self
+                .config
+                .method_costs
+                .get(method)
+                .copied()
+                .unwrap_or(DEFAULT_METHOD_COST);
+            self.clients
+                .entry(client_key.clone())
+                .or_insert_with(|| ClientState::new(quota, now))
+                .try_acquire(quota, method, cost, now)
+        };
+        self.maybe_report(now);
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(RpcCustomError::RateLimited {
+                method: method.to_string(),
+            })
+        }
+    }
+
+    /// Reports usage per API key, with all keyless clients aggregated, and drops
+    /// idle keyless clients
+    fn maybe_report(&self, now: Instant) {
+        if !self.last_report.should_update(METRICS_REPORT_INTERVAL_MS) {
+            return;
+        }
+        let mut keyless_stats = ClientStats::default();
+        self.clients.retain(|client_key, state| {
+            let stats = std::mem::take(&mut state.stats);
+            if let RpcClientKey::ApiKey(index) = client_key {
+                stats.report(&self.config.api_keys[*index].name);
+                return true;
+            }
+            keyless_stats.add(&stats);
+            now.saturating_duration_since(state.last_used) < IDLE_CLIENT_TIMEOUT
+        });
+        keyless_stats.report("none");
+    }
+}
+
+/// Applies an optional `RpcRateLimiter` to every call handled by a `MetaIoHandler`
+pub struct RpcRateLimitMiddleware<M> {
+    rate_limiter: Option<Arc<RpcRateLimiter>>,
+    client_key: Box<dyn Fn(&M) -> RpcClientKey + Send + Sync>,
+}
+
+impl<M> RpcRateLimitMiddleware<M> {
+    pub fn new(
+        rate_limiter: Option<Arc<RpcRateLimiter>>,
+        client_key: impl Fn(&M) -> RpcClientKey + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            rate_limiter,
+            client_key: Box::new(client_key),
+        }
+    }
+}
+
+impl<M: Metadata> Middleware<M> for RpcRateLimitMiddleware<M> {
+    type Future = future::Ready<Option<Response>>;
+    type CallFuture = future::Ready<Option<Output>>;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: std::future::Future<Output = Option<Output>> + Send + 'static,
+    {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Either::Right(next(call, meta));
+        };
+        let method = match &call {
+            Call::MethodCall(method_call) => &method_call.method,
+            Call::Notification(notification) => &notification.method,
+            Call::Invalid { .. } => return Either::Right(next(call, meta)),
+        };
+        let result = rate_limiter.check(&(self.client_key)(&meta), method);
+        match result {
+            Ok(()) => Either::Right(next(call, meta)),
+            Err(err) => {
+                let output = match call {
+                    Call::MethodCall(method_call) => Some(Output::from(
+                        Err(err.into()),
+                        method_call.id,
+                        method_call.jsonrpc,
+                    )),
+                    _ => None,
+                };
+                Either::Left(future::ready(output))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::Ipv4Addr};
+
+    fn quota(requests_per_second: Option<u64>, cost_per_second: Option<u64>) -> RpcQuota {
+        RpcQuota {
+            requests_per_second,
+            cost_per_second,
+            method_requests_per_second: HashMap::default(),
+        }
+    }
+
+    #[test]
+    fn test_config_from_json() {
+        let config: RpcRateLimitConfig = serde_json::from_value(json!({
+            "clientIpHeader": "x-forwarded-for",
+            "methodCosts": {"getProgramAccounts": 100},
+            "defaultQuota": {"requestsPerSecond": 10},
+            "apiKeys": [{
+                "name": "indexer",
+                "key": "secret",
+                "costPerSecond": 1000,
+                "methodRequestsPerSecond": {"getProgramAccounts": 5},
+            }],
+        }))
+        .unwrap();
+        assert_eq!(config.api_key_header, "x-api-key");
+        assert_eq!(config.client_ip_header.as_deref(), Some("x-forwarded-for"));
+        assert_eq!(config.trusted_proxy_count, 1);
+        assert_eq!(config.default_quota, quota(Some(10), None));
+        assert_eq!(config.api_keys[0].name, "indexer");
+        assert_eq!(config.api_keys[0].quota.cost_per_second, Some(1000));
+        assert_eq!(
+            config.api_keys[0].quota.method_requests_per_second["getProgramAccounts"],
+            5
+        );
+    }
+
+    #[test]
+    fn test_client_key() {
+        let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig {
+            api_keys: vec![RpcApiKeyConfig {
+                name: "indexer".to_string(),
+                key: "secret".to_string(),
+                quota: RpcQuota::default(),
+            }],
+            ..RpcRateLimitConfig::default()
+        });
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        assert_eq!(
+            rate_limiter.websocket_client_key("/?foo=bar&api-key=secret", Some(ip)),
+            RpcClientKey::ApiKey(0)
+        );
+        assert_eq!(
+            rate_limiter.websocket_client_key("/?api-key=wrong", Some(ip)),
+            RpcClientKey::InvalidApiKey
+        );
+        assert_eq!(
+            rate_limiter.websocket_client_key("/", Some(ip)),
+            RpcClientKey::Ip(ip)
+        );
+
+        let request = hyper::Request::builder()
+            .header("x-api-key", "secret")
+            .body(hyper::Body::empty())
+            .unwrap();
+        assert_eq!(
+            rate_limiter.http_client_key(&request),
+            RpcClientKey::ApiKey(0)
+        );
+        // Without a client IP header, only the peer address set by the front server is used
+        let request = hyper::Request::builder()
+            .header("x-forwarded-for", "127.0.0.1")
+            .body(hyper::Body::empty())
+            .unwrap();
+        assert_eq!(
+            rate_limiter.http_client_key(&request),
+            RpcClientKey::Anonymous
+        );
+        let request = hyper::Request::builder()
+            .header(PEER_ADDRESS_HEADER, "127.0.0.1")
+            .body(hyper::Body::empty())
+            .unwrap();
+        assert_eq!(rate_limiter.http_client_key(&request), RpcClientKey::Ip(ip));
+
+        // Behind proxies, the client IP is the entry appended by the outermost trusted proxy
+        let request = hyper::Request::builder()
+            .header(PEER_ADDRESS_HEADER, "127.0.0.1")
+            .header("x-forwarded-for", "1.1.1.1, 2.2.2.2,3.3.3.3")
+            .body(hyper::Body::empty())
+            .unwrap();
+        for (trusted_proxy_count, client_key) in [
+            (0, RpcClientKey::Anonymous),
+            (1, RpcClientKey::Ip("3.3.3.3".parse().unwrap())),
+            (3, RpcClientKey::Ip("1.1.1.1".parse().unwrap())),
+            (4, RpcClientKey::Anonymous),
+        ] {
+            let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig {
+                client_ip_header: Some("x-forwarded-for".to_string()),
+                trusted_proxy_count,
+                ..RpcRateLimitConfig::default()
+            });
+            assert_eq!(rate_limiter.http_client_key(&request), client_key);
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig {
+            method_costs: HashMap::from([("getProgramAccounts".to_string(), 10)]),
+            default_quota: RpcQuota {
+                requests_per_second: Some(3),
+                cost_per_second: Some(12),
+                method_requests_per_second: HashMap::from([("getBlock".to_string(), 1)]),
+            },
+            ..RpcRateLimitConfig::default()
+        });
+        let client = RpcClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let other_client = RpcClientKey::Ip(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        // Per method limit
+        assert!(rate_limiter.check(&client, "getBlock").is_ok());
+        assert!(matches!(
+            rate_limiter.check(&client, "getBlock"),
+            Err(RpcCustomError::RateLimited { .. })
+        ));
+        // Cost limit
+        assert!(rate_limiter.check(&client, "getProgramAccounts").is_ok());
+        assert!(rate_limiter.check(&client, "getProgramAccounts").is_err());
+        // Request limit, with a rejected request not consuming tokens
+        assert!(rate_limiter.check(&client, "getSlot").is_ok());
+        assert!(rate_limiter.check(&client, "getSlot").is_err());
+
+        // Clients are limited independently
+        assert!(rate_limiter.check(&other_client, "getBlock").is_ok());
+        assert!(matches!(
+            rate_limiter.check(&RpcClientKey::InvalidApiKey, "getSlot"),
+            Err(RpcCustomError::InvalidApiKey)
+        ));
+    }
+}
@@ -8,11 +8,13 @@ use {
         rpc::{rpc_accounts::*, rpc_accounts_scan::*, rpc_bank::*, rpc_full::*, rpc_minimal::*, *},
         rpc_cache::LargestAccountsCache,
         rpc_health::*,
+        rpc_rate_limiter::{RpcRateLimitMiddleware, RpcRateLimiter, PEER_ADDRESS_HEADER},
     },
     crossbeam_channel::unbounded,
-    jsonrpc_core::{futures::prelude::*, MetaIoHandler},
+    jsonrpc_core::{futures::prelude::*, Error, MetaIoHandler},
     jsonrpc_http_server::{
-        hyper, AccessControlAllowOrigin, CloseHandle, DomainsValidation, RequestMiddleware,
+        hyper::{self, server::conn::AddrStream},
+        AccessControlAllowOrigin, CloseHandle, DomainsValidation, RequestMiddleware,
         RequestMiddlewareAction, ServerBuilder,
     },
     regex::Regex,
@@ -26,6 +28,9 @@ use {
     solana_metrics::inc_new_counter_info,
     solana_perf::thread::renice_this_thread,
     solana_poh::poh_recorder::PohRecorder,
+    solana_rpc_client_api::{
+        config::RpcProgramAccountsPageConfig, response::RpcProgramAccountsPage,
+    },
     solana_runtime::{
         bank_forks::BankForks, commitment::BlockCommitmentCache,
         prioritization_fee_cache::PrioritizationFeeCache,
@@ -42,20 +47,30 @@ use {
     },
     solana_storage_bigtable::CredentialType,
     std::{
-        net::SocketAddr,
+        convert::Infallible,
+        net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, RwLock,
         },
         thread::{self, Builder, JoinHandle},
+        time::{Duration, Instant},
     },
+    tokio::sync::oneshot,
     tokio_util::codec::{BytesCodec, FramedRead},
 };
 
 const FULL_SNAPSHOT_REQUEST_PATH: &str = "/snapshot.tar.bz2";
 const INCREMENTAL_SNAPSHOT_REQUEST_PATH: &str = "/incremental-snapshot.tar.bz2";
 const LARGEST_ACCOUNTS_CACHE_DURATION: u64 = 60 * 60 * 2;
+/// Streams the accounts of a program as JSON lines, fetching them one page at a time
+const PROGRAM_ACCOUNTS_STREAM_PATH: &str = "/v0/program-accounts";
+/// Slot of the bank that the first page of a `PROGRAM_ACCOUNTS_STREAM_PATH` response is read
+/// from. Later pages are read from the latest bank at the same commitment, which may be newer.
+const CONTEXT_SLOT_HEADER: &str = "x-context-slot";
+/// Time after which a `PROGRAM_ACCOUNTS_STREAM_PATH` response stops fetching pages
+const MAX_PROGRAM_ACCOUNTS_STREAM_DURATION: Duration = Duration::from_secs(10 * 60);
 
 pub struct JsonRpcService {
     thread_hdl: JoinHandle<()>,
@@ -66,6 +81,22 @@ pub struct JsonRpcService {
     close_handle: Option<CloseHandle>,
 }
 
+/// Body of a `PROGRAM_ACCOUNTS_STREAM_PATH` request
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProgramAccountsStreamRequest {
+    program_id: String,
+    #[serde(flatten)]
+    config: RpcProgramAccountsPageConfig,
+}
+
+enum ProgramAccountsStreamState {
+    /// The first page, fetched before responding so that errors get a proper status code
+    First(RpcProgramAccountsPage, RpcProgramAccountsPageConfig),
+    Next(RpcProgramAccountsPageConfig),
+    Done,
+}
+
 struct RpcRequestMiddleware {
     ledger_path: PathBuf,
     full_snapshot_archive_path_regex: Regex,
@@ -73,6 +104,10 @@ struct RpcRequestMiddleware {
     snapshot_config: Option<SnapshotConfig>,
     bank_forks: Arc<RwLock<BankForks>>,
     health: Arc<RpcHealth>,
+    /// Serves `PROGRAM_ACCOUNTS_STREAM_PATH` when the full API is enabled
+    request_processor: Option<JsonRpcRequestProcessor>,
+    rate_limiter: Option<Arc<RpcRateLimiter>>,
+    program_accounts_stream_max_duration: Duration,
 }
 
 impl RpcRequestMiddleware {
@@ -81,6 +116,8 @@ impl RpcRequestMiddleware {
         snapshot_config: Option<SnapshotConfig>,
         bank_forks: Arc<RwLock<BankForks>>,
         health: Arc<RpcHealth>,
+        request_processor: Option<JsonRpcRequestProcessor>,
+        rate_limiter: Option<Arc<RpcRateLimiter>>,
     ) -> Self {
         Self {
             ledger_path,
@@ -95,6 +132,9 @@ impl RpcRequestMiddleware {
             snapshot_config,
             bank_forks,
             health,
+            request_processor,
+            rate_limiter,
+            program_accounts_stream_max_duration: MAX_PROGRAM_ACCOUNTS_STREAM_DURATION,
         }
     }
 
@@ -120,6 +160,16 @@ impl RpcRequestMiddleware {
             .unwrap()
     }
 
+    fn json_rpc_error(status: hyper::StatusCode, error: Error) -> hyper::Response<hyper::Body> {
+        hyper::Response::builder()
+            .status(status)
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(hyper::Body::from(
+                serde_json::json!({ "error": error }).to_string(),
+            ))
+            .unwrap()
+    }
+
     fn strip_leading_slash(path: &str) -> Option<&str> {
         path.strip_prefix('/')
     }
@@ -231,6 +281,154 @@ impl RpcRequestMiddleware {
         }
     }
 
+    fn process_program_accounts_stream(
+        &self,
+        request: hyper::Request<hyper::Body>,
+    ) -> RequestMiddlewareAction {
+        let Some(request_processor) = self.request_processor.clone() else {
+            return Self::not_found().into();
+        };
+        inc_new_counter_info!("rpc-program_accounts_stream", 1);
+        // Every page is a separate scan, so each one is charged like a
+        // `getProgramAccountsPaginated` call
+        let rate_limiter = self.rate_limiter.clone().map(|rate_limiter| {
+            let client_key = rate_limiter.http_client_key(&request);
+            (rate_limiter, client_key)
+        });
+        let check_rate_limit = move || match &rate_limiter {
+            Some((rate_limiter, client_key)) => rate_limiter
+                .check(client_key, "getProgramAccountsPaginated")
+                .map_err(Error::from),
+            None => Ok(()),
+        };
+        if let Err(err) = check_rate_limit() {
+            return Self::json_rpc_error(hyper::StatusCode::TOO_MANY_REQUESTS, err).into();
+        }
+        let max_duration = self.program_accounts_stream_max_duration;
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                let body = match hyper::body::to_bytes(request.into_body()).await {
+                    Ok(body) if body.len() <= MAX_REQUEST_BODY_SIZE => body,
+                    Ok(_) => {
+                        return Ok(Self::json_rpc_error(
+                            hyper::StatusCode::PAYLOAD_TOO_LARGE,
+                            Error::invalid_request(),
+                        ))
+                    }
+                    Err(_) => {
+                        return Ok(Self::json_rpc_error(
+                            hyper::StatusCode::BAD_REQUEST,
+                            Error::parse_error(),
+                        ))
+                    }
+                };
+                let first_page = async {
+                    let ProgramAccountsStreamRequest { program_id, config } =
+                        serde_json::from_slice(&body)
+                            .map_err(|err| Error::invalid_params(err.to_string()))?;
+                    let program_id = verify_pubkey(&program_id)?;
+                    let response = request_processor
+                        .get_program_accounts_page(program_id, Some(config.clone()))
+                        .await?;
+                    Ok::<_, Error>((program_id, response, config))
+                }
+                .await;
+                let (program_id, response, mut config) = match first_page {
+                    Ok(first_page) => first_page,
+                    Err(err) => {
+                        return Ok(Self::json_rpc_error(hyper::StatusCode::BAD_REQUEST, err))
+                    }
+                };
+                // Later pages are read from the latest bank, which is never older than the first
+                // page's, so that no bank is held while a slow client reads
+                let context_slot = response.context.slot;
+                config.account_config.min_context_slot = Some(context_slot);
+                let deadline = Instant::now() + max_duration;
+
+                let stream = stream::unfold(
+                    ProgramAccountsStreamState::First(response.value, config),
+                    move |state| {
+                        let request_processor = request_processor.clone();
+                        let check_rate_limit = check_rate_limit.clone();
+                        async move {
+                            let (page, config) = match state {
+                                ProgramAccountsStreamState::First(page, config) => {
+                                    (Ok(page), config)
+                                }
+                                ProgramAccountsStreamState::Next(config) => {
+                                    let page = if Instant::now() >= deadline {
+                                        Err(Error {
+                                            message: format!(
+                                                "Stream reached its maximum duration of {}s",
+                                                max_duration.as_secs()
+                                            ),
+                                            ..Error::invalid_request()
+                                        })
+                                    } else {
+                                        match check_rate_limit() {
+                                            Ok(()) => request_processor
+                                                .get_program_accounts_page(
+                                                    program_id,
+                                                    Some(config.clone()),
+                                                )
+                                                .await
+                                                .map(|response| response.value),
+                                            Err(err) => Err(err),
+                                        }
+                                    };
+                                    (page, config)
+                                }
+                                ProgramAccountsStreamState::Done => return None,
+                            };
+                            let (lines, next_state) = match page {
+                                Ok(RpcProgramAccountsPage {
+                                    accounts,
+                                    next_cursor,
+                                }) => (
+                                    accounts
+                                        .iter()
+                                        .map(|account| serde_json::to_string(account).unwrap())
+                                        .collect::<Vec<_>>(),
+                                    match next_cursor {
+                                        Some(cursor) => ProgramAccountsStreamState::Next(
+                                            RpcProgramAccountsPageConfig {
+                                                cursor: Some(cursor),
+                                                ..config
+                                            },
+                                        ),
+                                        None => ProgramAccountsStreamState::Done,
+                                    },
+                                ),
+                                // The status has already been sent, so report the error inline,
+                                // with the cursor to resume from
+                                Err(err) => (
+                                    vec![serde_json::json!({
+                                        "error": err,
+                                        "nextCursor": config.cursor,
+                                    })
+                                    .to_string()],
+                                    ProgramAccountsStreamState::Done,
+                                ),
+                            };
+                            let chunk = lines
+                                .into_iter()
+                                .map(|line| line + "\n")
+                                .collect::<String>();
+                            Some((Ok::<_, std::io::Error>(chunk), next_state))
+                        }
+                    },
+                );
+
+                Ok(hyper::Response::builder()
+                    .header(hyper::header::CONTENT_TYPE, "application/x-ndjson")
+                    .header(CONTEXT_SLOT_HEADER, context_slot)
+                    .body(hyper::Body::wrap_stream(stream))
+                    .unwrap())
+            }),
+        }
+    }
+
     fn health_check(&self) -> &'static str {
         let response = match self.health.check() {
             RpcHealthStatus::Ok => "ok",
@@ -300,6 +498,10 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 .into()
         } else if self.is_file_get_path(request.uri().path()) {
             self.process_file_get(request.uri().path())
+        } else if request.uri().path() == PROGRAM_ACCOUNTS_STREAM_PATH
+            && request.method() == hyper::Method::POST
+        {
+            self.process_program_accounts_stream(request)
         } else if request.uri().path() == "/health" {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
@@ -390,6 +592,7 @@ impl JsonRpcService {
                 ref bigtable_app_profile_id,
                 timeout,
                 max_message_size,
+                ref object_store_url,
             }) = config.rpc_bigtable_config
             {
                 let bigtable_config = solana_storage_bigtable::LedgerStorageConfig {
@@ -399,6 +602,7 @@ impl JsonRpcService {
                     instance_name: bigtable_instance_name.clone(),
                     app_profile_id: bigtable_app_profile_id.clone(),
                     max_message_size,
+                    object_store_url: object_store_url.clone(),
                 };
                 runtime
                     .block_on(solana_storage_bigtable::LedgerStorage::new_with_config(
@@ -436,6 +640,7 @@ impl JsonRpcService {
             };
 
         let full_api = config.full_api;
+        let rate_limiter = config.rate_limiter.clone();
         let max_request_body_size = config
             .max_request_body_size
             .unwrap_or(MAX_REQUEST_BODY_SIZE);
@@ -488,7 +693,10 @@ impl JsonRpcService {
             .spawn(move || {
                 renice_this_thread(rpc_niceness_adj).unwrap();
 
-                let mut io = MetaIoHandler::default();
+                let mut io = MetaIoHandler::with_middleware(RpcRateLimitMiddleware::new(
+                    rate_limiter.clone(),
+                    |meta: &JsonRpcRequestProcessor| meta.rate_limit_client_key.clone(),
+                ));
 
                 io.extend_with(rpc_minimal::MinimalImpl.to_delegate());
                 if full_api {
@@ -503,27 +711,51 @@ impl JsonRpcService {
                     snapshot_config,
                     bank_forks.clone(),
                     health.clone(),
+                    full_api.then(|| request_processor.clone()),
+                    rate_limiter.clone(),
                 );
-                let server = ServerBuilder::with_meta_extractor(
-                    io,
-                    move |req: &hyper::Request<hyper::Body>| {
-                        let xbigtable = req.headers().get("x-bigtable");
-                        if xbigtable.is_some_and(|v| v == "disabled") {
-                            request_processor.clone_without_bigtable()
-                        } else {
-                            request_processor.clone()
-                        }
-                    },
-                )
-                .event_loop_executor(runtime.handle().clone())
-                .threads(1)
-                .cors(DomainsValidation::AllowOnly(vec![
-                    AccessControlAllowOrigin::Any,
-                ]))
-                .cors_max_age(86400)
-                .request_middleware(request_middleware)
-                .max_request_body_size(max_request_body_size)
-                .start_http(&rpc_addr);
+                // The JSON RPC server doesn't expose the peer address of a request, so when
+                // clients are rate limited by it, a front server accepts the connections instead
+                // and forwards the requests to the JSON RPC server on a loopback port
+                let front_server_listener = rate_limiter
+                    .as_ref()
+                    .filter(|rate_limiter| rate_limiter.keys_on_peer_address())
+                    .map(|_| std::net::TcpListener::bind(rpc_addr))
+                    .transpose();
+                let server = front_server_listener.and_then(|front_server_listener| {
+                    let server_addr = if front_server_listener.is_some() {
+                        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0)
+                    } else {
+                        rpc_addr
+                    };
+                    ServerBuilder::with_meta_extractor(
+                        io,
+                        move |req: &hyper::Request<hyper::Body>| {
+                            let xbigtable = req.headers().get("x-bigtable");
+                            let mut request_processor =
+                                if xbigtable.is_some_and(|v| v == "disabled") {
+                                    request_processor.clone_without_bigtable()
+                                } else {
+                                    request_processor.clone()
+                                };
+                            if let Some(rate_limiter) = &rate_limiter {
+                                request_processor.rate_limit_client_key =
+                                    rate_limiter.http_client_key(req);
+                            }
+                            request_processor
+                        },
+                    )
+                    .event_loop_executor(runtime.handle().clone())
+                    .threads(1)
+                    .cors(DomainsValidation::AllowOnly(vec![
+                        AccessControlAllowOrigin::Any,
+                    ]))
+                    .cors_max_age(86400)
+                    .request_middleware(request_middleware)
+                    .max_request_body_size(max_request_body_size)
+                    .start_http(&server_addr)
+                    .map(|server| (server, front_server_listener))
+                });
 
                 if let Err(e) = server {
                     warn!(
@@ -536,7 +768,17 @@ impl JsonRpcService {
                     return;
                 }
 
-                let server = server.unwrap();
+                let (server, front_server_listener) = server.unwrap();
+                // Dropped to stop the front server once the JSON RPC server is closed
+                let _front_server_exit = front_server_listener.map(|listener| {
+                    let (exit_sender, exit_receiver) = oneshot::channel();
+                    runtime.spawn(serve_front_server(
+                        listener,
+                        *server.address(),
+                        exit_receiver,
+                    ));
+                    exit_sender
+                });
                 close_handle_sender.send(Ok(server.close_handle())).unwrap();
                 server.wait();
                 exit_bigtable_ledger_upload_service.store(true, Ordering::Relaxed);
@@ -571,6 +813,64 @@ impl JsonRpcService {
     }
 }
 
+/// Accepts the connections of the JSON RPC service and forwards every request to the JSON RPC
+/// server at `server_addr`, with the peer address in `PEER_ADDRESS_HEADER`
+async fn serve_front_server(
+    listener: std::net::TcpListener,
+    server_addr: SocketAddr,
+    exit: oneshot::Receiver<()>,
+) {
+    let client = hyper::Client::new();
+    let make_service = hyper::service::make_service_fn(move |conn: &AddrStream| {
+        let client = client.clone();
+        let peer_address =
+            hyper::header::HeaderValue::from_str(&conn.remote_addr().ip().to_string())
+                .expect("IP address is a valid header value");
+        future::ready(Ok::<_, Infallible>(hyper::service::service_fn(
+            move |mut request: hyper::Request<hyper::Body>| {
+                request
+                    .headers_mut()
+                    .insert(PEER_ADDRESS_HEADER, peer_address.clone());
+                let path_and_query = request
+                    .uri()
+                    .path_and_query()
+                    .map_or("/", |path_and_query| path_and_query.as_str());
+                *request.uri_mut() = hyper::Uri::builder()
+                    .scheme("http")
+                    .authority(server_addr.to_string())
+                    .path_and_query(path_and_query)
+                    .build()
+                    .expect("valid request URI");
+                let response = client.request(request);
+                async move {
+                    Ok::<_, Infallible>(response.await.unwrap_or_else(|err| {
+                        debug!("JSON RPC front server request failed: {err}");
+                        hyper::Response::builder()
+                            .status(hyper::StatusCode::BAD_GATEWAY)
+                            .body(hyper::Body::empty())
+                            .unwrap()
+                    }))
+                }
+            },
+        )))
+    });
+    let server = match hyper::Server::from_tcp(listener) {
+        Ok(server) => server.serve(make_service),
+        Err(err) => {
+            warn!("JSON RPC front server unavailable: {err}");
+            return;
+        }
+    };
+    if let Err(err) = server
+        .with_graceful_shutdown(async {
+            let _ = exit.await;
+        })
+        .await
+    {
+        warn!("JSON RPC front server error: {err}");
+    }
+}
+
 pub fn service_runtime(
     rpc_threads: usize,
     rpc_blocking_threads: usize,
@@ -609,20 +909,33 @@ pub fn service_runtime(
 mod tests {
     use {
         super::*,
-        crate::rpc::{create_validator_exit, tests::new_test_cluster_info},
+        crate::{
+            rpc::{
+                create_validator_exit,
+                tests::{new_test_cluster_info, RpcHandler},
+            },
+            rpc_rate_limiter::{RpcQuota, RpcRateLimitConfig},
+        },
+        serde_json::{json, Value},
+        solana_accounts_db::accounts_index::{AccountIndex, AccountSecondaryIndexes},
         solana_ledger::{
             genesis_utils::{create_genesis_config, GenesisConfigInfo},
             get_tmp_ledger_path_auto_delete,
         },
-        solana_rpc_client_api::config::RpcContextConfig,
+        solana_rpc_client_api::{
+            config::RpcContextConfig, custom_error::JSON_RPC_SERVER_ERROR_RATE_LIMITED,
+        },
         solana_runtime::bank::Bank,
         solana_sdk::{
+            account::AccountSharedData,
             genesis_config::{ClusterType, DEFAULT_GENESIS_ARCHIVE},
+            pubkey::Pubkey,
             signature::Signer,
         },
         std::{
+            collections::{HashMap, HashSet},
             io::Write,
-            net::{IpAddr, Ipv4Addr},
+            str::FromStr,
         },
         tokio::runtime::Runtime,
     };
@@ -694,6 +1007,89 @@ mod tests {
         rpc_service.join().unwrap();
     }
 
+    #[test]
+    fn test_rpc_rate_limit_peer_address() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let exit = Arc::new(AtomicBool::new(false));
+        let validator_exit = create_validator_exit(exit.clone());
+        let bank_forks = BankForks::new_rw_arc(Bank::new_for_tests(&genesis_config));
+        let ip_addr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let rpc_addr = SocketAddr::new(
+            ip_addr,
+            solana_net_utils::find_available_port_in_range(ip_addr, (10000, 65535)).unwrap(),
+        );
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig {
+            default_quota: RpcQuota {
+                requests_per_second: Some(2),
+                ..RpcQuota::default()
+            },
+            ..RpcRateLimitConfig::default()
+        });
+        let mut rpc_service = JsonRpcService::new(
+            rpc_addr,
+            JsonRpcConfig {
+                rate_limiter: Some(Arc::new(rate_limiter)),
+                ..JsonRpcConfig::default()
+            },
+            None,
+            bank_forks,
+            Arc::new(RwLock::new(BlockCommitmentCache::default())),
+            blockstore,
+            Arc::new(new_test_cluster_info()),
+            None,
+            Hash::default(),
+            &PathBuf::from("farf"),
+            validator_exit,
+            exit,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(AtomicBool::new(true)),
+            optimistically_confirmed_bank,
+            send_transaction_service::Config::default(),
+            Arc::new(MaxSlots::default()),
+            Arc::new(LeaderScheduleCache::default()),
+            Arc::new(ConnectionCache::new("connection_cache_test")),
+            Arc::new(AtomicU64::default()),
+            Arc::new(AtomicU64::default()),
+            Arc::new(PrioritizationFeeCache::default()),
+        )
+        .expect("assume successful JsonRpcService start");
+
+        let runtime = Runtime::new().unwrap();
+        let client = hyper::Client::new();
+        let get_version = |peer_address: Option<&str>| {
+            let mut request = hyper::Request::post(format!("http://{rpc_addr}"))
+                .header(hyper::header::CONTENT_TYPE, "application/json");
+            if let Some(peer_address) = peer_address {
+                request = request.header(PEER_ADDRESS_HEADER, peer_address);
+            }
+            let request = request
+                .body(hyper::Body::from(
+                    json!({"jsonrpc": "2.0", "id": 1, "method": "getVersion"}).to_string(),
+                ))
+                .unwrap();
+            runtime.block_on(async {
+                let response = client.request(request).await.unwrap();
+                let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                serde_json::from_slice::<Value>(&body).unwrap()
+            })
+        };
+
+        // Requests are limited by the peer address, which the client can not override
+        assert!(get_version(None)["result"].is_object());
+        assert!(get_version(Some("1.1.1.1"))["result"].is_object());
+        assert_eq!(
+            get_version(Some("2.2.2.2"))["error"]["code"],
+            json!(JSON_RPC_SERVER_ERROR_RATE_LIMITED)
+        );
+
+        rpc_service.exit();
+        rpc_service.join().unwrap();
+    }
+
     fn create_bank_forks() -> Arc<RwLock<BankForks>> {
         let GenesisConfigInfo {
             mut genesis_config, ..
@@ -758,12 +1154,16 @@ mod tests {
             None,
             bank_forks.clone(),
             health.clone(),
+            None,
+            None,
         );
         let rrm_with_snapshot_config = RpcRequestMiddleware::new(
             ledger_path.path().to_path_buf(),
             Some(SnapshotConfig::default()),
             bank_forks,
             health,
+            None,
+            None,
         );
 
         assert!(rrm.is_file_get_path(DEFAULT_GENESIS_DOWNLOAD_PATH));
@@ -866,6 +1266,8 @@ mod tests {
             None,
             bank_forks,
             RpcHealth::stub(optimistically_confirmed_bank, blockstore),
+            None,
+            None,
         );
 
         // File does not exist => request should fail.
@@ -910,4 +1312,154 @@ mod tests {
             panic!("Unexpected RequestMiddlewareAction variant");
         }
     }
+
+    #[test]
+    fn test_process_program_accounts_stream() {
+        let runtime = Runtime::new().unwrap();
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            account_indexes: AccountSecondaryIndexes {
+                keys: None,
+                indexes: HashSet::from([AccountIndex::ProgramId]),
+            },
+            ..JsonRpcConfig::default()
+        });
+        let bank = rpc.working_bank();
+        let program_id = Pubkey::new_unique();
+        let mut pubkeys = (0..5)
+            .map(|_| {
+                let pubkey = Pubkey::new_unique();
+                bank.store_account(&pubkey, &AccountSharedData::new(42, 0, &program_id));
+                pubkey
+            })
+            .collect::<Vec<_>>();
+        pubkeys.sort_unstable();
+
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let bank_forks = create_bank_forks();
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let health = RpcHealth::stub(optimistically_confirmed_bank, blockstore);
+        let stream_program_accounts = |rrm: &RpcRequestMiddleware, body: Value| {
+            let request = hyper::Request::post(PROGRAM_ACCOUNTS_STREAM_PATH)
+                .body(hyper::Body::from(body.to_string()))
+                .unwrap();
+            let RequestMiddlewareAction::Respond { response, .. } =
+                rrm.process_program_accounts_stream(request)
+            else {
+                panic!("Unexpected RequestMiddlewareAction variant");
+            };
+            runtime.block_on(async {
+                let response = response.await.unwrap();
+                let status = response.status();
+                let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                (status, String::from_utf8(body.to_vec()).unwrap())
+            })
+        };
+        let line_pubkey = |line: &str| {
+            let line: Value = serde_json::from_str(line).unwrap();
+            Pubkey::from_str(line["pubkey"].as_str().unwrap()).unwrap()
+        };
+
+        // Every account is streamed as a JSON line, in pubkey order across pages
+        let rrm = RpcRequestMiddleware::new(
+            ledger_path.path().to_path_buf(),
+            None,
+            bank_forks.clone(),
+            health.clone(),
+            Some(rpc.meta.clone()),
+            None,
+        );
+        let (status, body) = stream_program_accounts(
+            &rrm,
+            json!({"programId": program_id.to_string(), "limit": 2}),
+        );
+        assert_eq!(status, hyper::StatusCode::OK);
+        assert_eq!(body.lines().map(line_pubkey).collect::<Vec<_>>(), pubkeys);
+
+        // Once the stream reaches its maximum duration it ends with an error line, with the
+        // cursor to resume from
+        let mut expiring_rrm = RpcRequestMiddleware::new(
+            ledger_path.path().to_path_buf(),
+            None,
+            bank_forks.clone(),
+            health.clone(),
+            Some(rpc.meta.clone()),
+            None,
+        );
+        expiring_rrm.program_accounts_stream_max_duration = Duration::ZERO;
+        let (status, body) = stream_program_accounts(
+            &expiring_rrm,
+            json!({"programId": program_id.to_string(), "limit": 2}),
+        );
+        assert_eq!(status, hyper::StatusCode::OK);
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[..2]
+                .iter()
+                .map(|line| line_pubkey(line))
+                .collect::<Vec<_>>(),
+            pubkeys[..2]
+        );
+        let error: Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(
+            error["error"]["code"],
+            json!(jsonrpc_core::ErrorCode::InvalidRequest.code())
+        );
+        assert_eq!(error["nextCursor"], json!(pubkeys[1].to_string()));
+
+        // An error on the first page is returned with an error status
+        let (status, body) = stream_program_accounts(
+            &rrm,
+            json!({"programId": program_id.to_string(), "cursor": "not a cursor"}),
+        );
+        assert_eq!(status, hyper::StatusCode::BAD_REQUEST);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            body["error"]["code"],
+            json!(jsonrpc_core::ErrorCode::InvalidParams.code())
+        );
+
+        // Each page is charged against the rate limit, and an error on a later page ends the
+        // stream with an error line
+        let rate_limiter = RpcRateLimiter::new(RpcRateLimitConfig {
+            default_quota: RpcQuota {
+                method_requests_per_second: HashMap::from([(
+                    "getProgramAccountsPaginated".to_string(),
+                    2,
+                )]),
+                ..RpcQuota::default()
+            },
+            ..RpcRateLimitConfig::default()
+        });
+        let rrm = RpcRequestMiddleware::new(
+            ledger_path.path().to_path_buf(),
+            None,
+            bank_forks,
+            health,
+            Some(rpc.meta.clone()),
+            Some(Arc::new(rate_limiter)),
+        );
+        let (status, body) = stream_program_accounts(
+            &rrm,
+            json!({"programId": program_id.to_string(), "limit": 2}),
+        );
+        assert_eq!(status, hyper::StatusCode::OK);
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[..4]
+                .iter()
+                .map(|line| line_pubkey(line))
+                .collect::<Vec<_>>(),
+            pubkeys[..4]
+        );
+        let error: Value = serde_json::from_str(lines[4]).unwrap();
+        assert_eq!(
+            error["error"]["code"],
+            json!(JSON_RPC_SERVER_ERROR_RATE_LIMITED)
+        );
+        assert_eq!(error["nextCursor"], json!(pubkeys[3].to_string()));
+    }
 }
@@ -1,29 +1,45 @@
 use {
     crate::rpc_subscriptions::{NotificationEntry, RpcNotification, TimestampedNotificationEntry},
     dashmap::{mapref::entry::Entry as DashEntry, DashMap},
+    lru::LruCache,
     solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
     solana_metrics::{CounterToken, TokenCounter},
-    solana_rpc_client_api::filter::RpcFilterType,
+    solana_rpc_client_api::{config::RpcNotificationFormat, filter::RpcFilterType},
     solana_runtime::{
         bank::{TransactionLogCollectorConfig, TransactionLogCollectorFilter},
         bank_forks::BankForks,
     },
     solana_sdk::{
-        clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+        clock::Slot,
+        commitment_config::CommitmentConfig,
+        hash::{hash, Hash},
+        message::VersionedMessage,
+        pubkey::Pubkey,
+        signature::Signature,
+    },
+    solana_transaction::simple_vote_transaction_checker::is_simple_vote_transaction_impl,
+    solana_transaction_status::{
+        TransactionDetails, UiTransactionEncoding, VersionedTransactionWithStatusMeta,
     },
-    solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
     std::{
-        collections::hash_map::{Entry, HashMap},
+        collections::{
+            hash_map::{Entry, HashMap},
+            BTreeSet,
+        },
         fmt,
         sync::{
             atomic::{AtomicU64, Ordering},
-            Arc, RwLock, Weak,
+            Arc, Mutex, RwLock, Weak,
         },
     },
     thiserror::Error,
     tokio::sync::broadcast,
 };
 
+/// Most accounts whose last notified data a `changes_only` subscription remembers. An account
+/// that was forgotten gets notified again even if its data is unchanged.
+const MAX_NOTIFIED_DATA_HASHES: usize = 10_000;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SubscriptionId(u64);
 
@@ -46,6 +62,7 @@ pub enum SubscriptionParams {
     Logs(LogsSubscriptionParams),
     Program(ProgramSubscriptionParams),
     Signature(SignatureSubscriptionParams),
+    Transaction(TransactionSubscriptionParams),
     Slot,
     SlotsUpdates,
     Root,
@@ -59,6 +76,7 @@ impl SubscriptionParams {
             SubscriptionParams::Logs(_) => "logsNotification",
             SubscriptionParams::Program(_) => "programNotification",
             SubscriptionParams::Signature(_) => "signatureNotification",
+            SubscriptionParams::Transaction(_) => "transactionNotification",
             SubscriptionParams::Slot => "slotNotification",
             SubscriptionParams::SlotsUpdates => "slotsUpdatesNotification",
             SubscriptionParams::Block(_) => "blockNotification",
@@ -74,6 +92,7 @@ impl SubscriptionParams {
             SubscriptionParams::Program(params) => Some(params.commitment),
             SubscriptionParams::Signature(params) => Some(params.commitment),
             SubscriptionParams::Block(params) => Some(params.commitment),
+            SubscriptionParams::Transaction(params) => Some(params.commitment),
             SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
             | SubscriptionParams::Root
@@ -88,6 +107,7 @@ impl SubscriptionParams {
             SubscriptionParams::Logs(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
             SubscriptionParams::Root
             | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
@@ -103,6 +123,7 @@ impl SubscriptionParams {
             SubscriptionParams::Logs(params) => &params.commitment,
             SubscriptionParams::Program(params) => &params.commitment,
             SubscriptionParams::Signature(params) => &params.commitment,
+            SubscriptionParams::Transaction(params) => &params.commitment,
             SubscriptionParams::Root
             | SubscriptionParams::Slot
             | SubscriptionParams::SlotsUpdates
@@ -111,6 +132,14 @@ impl SubscriptionParams {
         commitment.is_confirmed()
     }
 
+    fn notification_format(&self) -> RpcNotificationFormat {
+        match self {
+            SubscriptionParams::Account(params) => params.notification_format,
+            SubscriptionParams::Program(params) => params.notification_format,
+            _ => RpcNotificationFormat::Json,
+        }
+    }
+
     fn is_node_progress_watcher(&self) -> bool {
         matches!(
             self,
@@ -126,8 +155,10 @@ impl SubscriptionParams {
 pub struct AccountSubscriptionParams {
     pub pubkey: Pubkey,
     pub encoding: UiAccountEncoding,
-    pub data_slice: Option<UiDataSliceConfig>,
+    pub data_slices: Vec<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
+    pub changes_only: bool,
+    pub notification_format: RpcNotificationFormat,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -164,9 +195,11 @@ pub struct ProgramSubscriptionParams {
     pub pubkey: Pubkey,
     pub filters: Vec<RpcFilterType>,
     pub encoding: UiAccountEncoding,
-    pub data_slice: Option<UiDataSliceConfig>,
+    pub data_slices: Vec<UiDataSliceConfig>,
     pub commitment: CommitmentConfig,
     pub with_context: bool,
+    pub changes_only: bool,
+    pub notification_format: RpcNotificationFormat,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -176,6 +209,75 @@ pub struct SignatureSubscriptionParams {
     pub enable_received_notification: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TransactionSubscriptionParams {
+    pub commitment: CommitmentConfig,
+    pub encoding: UiTransactionEncoding,
+    pub filter: TransactionSubscriptionFilter,
+    pub show_rewards: bool,
+    pub max_supported_transaction_version: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TransactionSubscriptionFilter {
+    pub vote: Option<bool>,
+    pub failed: Option<bool>,
+    pub signer: Option<Pubkey>,
+    pub account_include: BTreeSet<Pubkey>,
+    pub account_exclude: BTreeSet<Pubkey>,
+    pub account_required: BTreeSet<Pubkey>,
+}
+
+impl TransactionSubscriptionFilter {
+    pub fn allows(&self, transaction_with_meta: &VersionedTransactionWithStatusMeta) -> bool {
+        let VersionedTransactionWithStatusMeta { transaction, meta } = transaction_with_meta;
+        if let Some(vote) = self.vote {
+            let message = &transaction.message;
+            let instruction_programs = message.instructions().iter().filter_map(|instruction| {
+                message
+                    .static_account_keys()
+                    .get(instruction.program_id_index as usize)
+            });
+            let is_vote = is_simple_vote_transaction_impl(
+                &transaction.signatures,
+                matches!(message, VersionedMessage::Legacy(_)),
+                instruction_programs,
+            );
+            if is_vote != vote {
+                return false;
+            }
+        }
+        if let Some(failed) = self.failed {
+            if meta.status.is_err() != failed {
+                return false;
+            }
+        }
+        if let Some(signer) = &self.signer {
+            let num_signers = usize::from(transaction.message.header().num_required_signatures);
+            if !transaction
+                .message
+                .static_account_keys()
+                .iter()
+                .take(num_signers)
+                .any(|key| key == signer)
+            {
+                return false;
+            }
+        }
+        if self.account_include.is_empty()
+            && self.account_exclude.is_empty()
+            && self.account_required.is_empty()
+        {
+            return true;
+        }
+        let account_keys = transaction_with_meta.account_keys();
+        let mentions = |key: &Pubkey| account_keys.iter().any(|account_key| account_key == key);
+        (self.account_include.is_empty() || self.account_include.iter().any(mentions))
+            && !self.account_exclude.iter().any(mentions)
+            && self.account_required.iter().all(mentions)
+    }
+}
+
 #[derive(Clone)]
 pub struct SubscriptionControl(Arc<SubscriptionControlInner>);
 pub struct WeakSubscriptionTokenRef(Weak<SubscriptionTokenInner>, SubscriptionId);
@@ -321,6 +423,9 @@ pub struct SubscriptionInfo {
     method: &'static str,
     pub last_notified_slot: RwLock<Slot>,
     commitment: Option<CommitmentConfig>,
+    // Hashes of the last notified data of the most recently notified accounts, for
+    // `changes_only` subscriptions
+    notified_data_hashes: Mutex<LruCache<Pubkey, Hash>>,
 }
 
 impl SubscriptionInfo {
@@ -339,6 +444,22 @@ impl SubscriptionInfo {
     pub fn commitment(&self) -> Option<CommitmentConfig> {
         self.commitment
     }
+
+    pub fn notification_format(&self) -> RpcNotificationFormat {
+        self.params.notification_format()
+    }
+
+    /// Records `data` as the last notified data of `pubkey`, returning whether it differs from
+    /// the data of the previous notification
+    pub fn update_notified_data(&self, pubkey: &Pubkey, data: &[u8]) -> bool {
+        let data_hash = hash(data);
+        let mut notified_data_hashes = self.notified_data_hashes.lock().unwrap();
+        let changed = notified_data_hashes.put(*pubkey, data_hash) != Some(data_hash);
+        if notified_data_hashes.len() > MAX_NOTIFIED_DATA_HASHES {
+            notified_data_hashes.pop_lru();
+        }
+        changed
+    }
 }
 
 #[derive(Debug, Error)]
@@ -453,6 +574,7 @@ impl SubscriptionsTracker {
             commitment: params.commitment(),
             method: params.method(),
             params: params.clone(),
+            notified_data_hashes: Mutex::new(LruCache::unbounded()),
         });
         match &params {
             SubscriptionParams::Logs(params) => {
@@ -598,6 +720,12 @@ mod tests {
         crate::rpc_pubsub_service::PubSubConfig,
         solana_ledger::genesis_utils::{create_genesis_config, GenesisConfigInfo},
         solana_runtime::bank::Bank,
+        solana_sdk::{
+            instruction::{AccountMeta, Instruction},
+            system_program,
+            transaction::{Transaction, TransactionError, VersionedTransaction},
+        },
+        solana_transaction_status::TransactionStatusMeta,
     };
 
     struct ControlWrapper {
@@ -644,6 +772,95 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transaction_subscription_filter() {
+        let payer = Pubkey::new_unique();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let new_transaction = |program_id: Pubkey, accounts: &[Pubkey], failed: bool| {
+            let instruction = Instruction::new_with_bytes(
+                program_id,
+                &[],
+                accounts
+                    .iter()
+                    .map(|account| AccountMeta::new(*account, false))
+                    .collect(),
+            );
+            let transaction = Transaction::new_with_payer(&[instruction], Some(&payer));
+            VersionedTransactionWithStatusMeta {
+                transaction: VersionedTransaction::from(transaction),
+                meta: TransactionStatusMeta {
+                    status: if failed {
+                        Err(TransactionError::AccountNotFound)
+                    } else {
+                        Ok(())
+                    },
+                    ..TransactionStatusMeta::default()
+                },
+            }
+        };
+        let to_alice = new_transaction(system_program::id(), &[alice], false);
+        let to_alice_and_bob = new_transaction(system_program::id(), &[alice, bob], true);
+        let vote = new_transaction(solana_sdk::vote::program::id(), &[], false);
+
+        let allowed = |filter: TransactionSubscriptionFilter| {
+            [&to_alice, &to_alice_and_bob, &vote].map(|transaction| filter.allows(transaction))
+        };
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter::default()),
+            [true, true, true]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                vote: Some(false),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [true, true, false]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                failed: Some(true),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [false, true, false]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                signer: Some(alice),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [false, false, false]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                signer: Some(payer),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [true, true, true]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                account_include: BTreeSet::from([bob, solana_sdk::vote::program::id()]),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [false, true, true]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                account_exclude: BTreeSet::from([bob]),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [true, false, true]
+        );
+        assert_eq!(
+            allowed(TransactionSubscriptionFilter {
+                account_required: BTreeSet::from([alice, bob]),
+                ..TransactionSubscriptionFilter::default()
+            }),
+            [false, true, false]
+        );
+    }
+
     #[test]
     fn notify_subscribe() {
         let control = ControlWrapper::new();
@@ -720,7 +937,9 @@ mod tests {
             pubkey: solana_inline_spl::token::id(),
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
-            data_slice: None,
+            data_slices: vec![],
+            changes_only: false,
+            notification_format: RpcNotificationFormat::Json,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 42);
 
@@ -733,6 +952,18 @@ mod tests {
         assert_eq!(info.method, account_params.method());
         assert_eq!(info.id, SubscriptionId::from(1));
         assert_eq!(*info.last_notified_slot.read().unwrap(), 42);
+
+        // Only the data of the most recently notified accounts is remembered
+        let pubkeys: Vec<_> = (0..=MAX_NOTIFIED_DATA_HASHES)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        for pubkey in &pubkeys {
+            assert!(info.update_notified_data(pubkey, &[1]));
+        }
+        let last_pubkey = &pubkeys[MAX_NOTIFIED_DATA_HASHES];
+        assert!(!info.update_notified_data(last_pubkey, &[1]));
+        assert!(info.update_notified_data(last_pubkey, &[2]));
+        assert!(info.update_notified_data(&pubkeys[0], &[1]));
     }
 
     #[test]
@@ -760,7 +991,9 @@ mod tests {
             pubkey: solana_inline_spl::token::id(),
             commitment: CommitmentConfig::finalized(),
             encoding: UiAccountEncoding::Base64Zstd,
-            data_slice: None,
+            data_slices: vec![],
+            changes_only: false,
+            notification_format: RpcNotificationFormat::Json,
         });
         tracker.subscribe(account_params.clone(), 1.into(), || 0);
         assert_eq!(counts(&tracker), (0, 1, 0, 0));
@@ -771,7 +1004,9 @@ mod tests {
             pubkey: solana_inline_spl::token::id(),
             commitment: CommitmentConfig::confirmed(),
             encoding: UiAccountEncoding::Base64Zstd,
-            data_slice: None,
+            data_slices: vec![],
+            changes_only: false,
+            notification_format: RpcNotificationFormat::Json,
         });
         tracker.subscribe(account_params2.clone(), 2.into(), || 0);
         assert_eq!(counts(&tracker), (0, 0, 1, 0));
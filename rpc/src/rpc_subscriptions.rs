@@ -10,22 +10,28 @@ use {
             AccountSubscriptionParams, BlockSubscriptionKind, BlockSubscriptionParams,
             LogsSubscriptionKind, LogsSubscriptionParams, ProgramSubscriptionParams,
             SignatureSubscriptionParams, SubscriptionControl, SubscriptionId, SubscriptionInfo,
-            SubscriptionParams, SubscriptionsTracker,
+            SubscriptionParams, SubscriptionsTracker, TransactionSubscriptionParams,
         },
     },
     crossbeam_channel::{Receiver, RecvTimeoutError, SendError, Sender},
+    dashmap::DashMap,
     itertools::Either,
     rayon::prelude::*,
     serde::Serialize,
     solana_account_decoder::{
         encode_ui_account, parse_token::is_known_spl_token_id, UiAccount, UiAccountEncoding,
+        UiDataSliceConfig,
     },
     solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path},
     solana_measure::measure::Measure,
-    solana_rpc_client_api::response::{
-        ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse, RpcBlockUpdate,
-        RpcBlockUpdateError, RpcKeyedAccount, RpcLogsResponse, RpcResponseContext,
-        RpcSignatureResult, RpcVote, SlotInfo, SlotUpdate,
+    solana_rpc_client_api::{
+        config::RpcNotificationFormat,
+        response::{
+            ProcessedSignatureResult, ReceivedSignatureResult, Response as RpcResponse,
+            RpcBinaryAccount, RpcBinaryNotification, RpcBlockUpdate, RpcBlockUpdateError,
+            RpcKeyedAccount, RpcLogsResponse, RpcResponseContext, RpcSignatureResult,
+            RpcTransactionUpdate, RpcVote, SlotInfo, SlotUpdate,
+        },
     },
     solana_runtime::{
         bank::{Bank, TransactionLogInfo},
@@ -33,7 +39,7 @@ use {
         commitment::{BlockCommitmentCache, CommitmentSlots},
     },
     solana_sdk::{
-        account::{AccountSharedData, ReadableAccount},
+        account::{Account, AccountSharedData, ReadableAccount},
         clock::Slot,
         pubkey::Pubkey,
         signature::Signature,
@@ -45,13 +51,14 @@ use {
     },
     solana_vote::vote_transaction::VoteTransaction,
     std::{
+        borrow::Cow,
         cell::RefCell,
         collections::{HashMap, VecDeque},
         io::Cursor,
         str,
         sync::{
             atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
-            Arc, Mutex, RwLock, Weak,
+            Arc, Mutex, OnceLock, RwLock, Weak,
         },
         thread::{Builder, JoinHandle},
         time::{Duration, Instant},
@@ -157,14 +164,21 @@ where
         let (filter_results, result_slot) =
             filter_results(results, params, *w_last_notified_slot, bank);
         for result in filter_results {
-            notifier.notify(
-                RpcResponse::from(RpcNotificationResponse {
-                    context: RpcNotificationContext { slot },
-                    value: result,
-                }),
-                subscription,
-                is_final,
-            );
+            match subscription.notification_format() {
+                RpcNotificationFormat::Json => notifier.notify(
+                    RpcResponse::from(RpcNotificationResponse {
+                        context: RpcNotificationContext { slot },
+                        value: result,
+                    }),
+                    subscription,
+                    is_final,
+                ),
+                RpcNotificationFormat::Binary => notifier.notify_binary(
+                    RpcBinaryNotification::new(subscription.id().into(), slot, result),
+                    subscription,
+                    is_final,
+                ),
+            }
             *w_last_notified_slot = result_slot;
             notified = true;
         }
@@ -177,10 +191,26 @@ where
 pub struct RpcNotification {
     pub subscription_id: SubscriptionId,
     pub is_final: bool,
-    pub json: Weak<String>,
+    pub payload: Weak<NotificationPayload>,
     pub created_at: Instant,
 }
 
+/// Serialized notification, sent as a text or a binary WebSocket message
+#[derive(Debug, PartialEq, Eq)]
+pub enum NotificationPayload {
+    Json(String),
+    Binary(Vec<u8>),
+}
+
+impl NotificationPayload {
+    fn len(&self) -> usize {
+        match self {
+            NotificationPayload::Json(json) => json.len(),
+            NotificationPayload::Binary(bytes) => bytes.len(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 struct RpcNotificationResponse<T> {
     context: RpcNotificationContext,
@@ -211,7 +241,7 @@ struct RpcNotificationContext {
 const RPC_NOTIFICATIONS_METRICS_SUBMISSION_INTERVAL_MS: Duration = Duration::from_millis(2_000);
 
 struct RecentItems {
-    queue: VecDeque<Arc<String>>,
+    queue: VecDeque<Arc<NotificationPayload>>,
     total_bytes: usize,
     max_len: usize,
     max_total_bytes: usize,
@@ -229,7 +259,7 @@ impl RecentItems {
         }
     }
 
-    fn push(&mut self, item: Arc<String>) {
+    fn push(&mut self, item: Arc<NotificationPayload>) {
         self.total_bytes = self
             .total_bytes
             .checked_add(item.len())
@@ -304,12 +334,32 @@ impl RpcNotifier {
             serde_json::to_writer(Cursor::new(&mut *buf), &notification)
                 .expect("serialization never fails");
             let buf_str = str::from_utf8(&buf).expect("json is always utf-8");
-            Arc::new(String::from(buf_str))
+            Arc::new(NotificationPayload::Json(String::from(buf_str)))
         });
+        self.send(buf_arc, subscription, is_final);
+    }
+
+    fn notify_binary<T>(&self, value: T, subscription: &SubscriptionInfo, is_final: bool)
+    where
+        T: serde::Serialize,
+    {
+        let bytes = bincode::serialize(&value).expect("serialization never fails");
+        self.send(
+            Arc::new(NotificationPayload::Binary(bytes)),
+            subscription,
+            is_final,
+        );
+    }
 
+    fn send(
+        &self,
+        payload: Arc<NotificationPayload>,
+        subscription: &SubscriptionInfo,
+        is_final: bool,
+    ) {
         let notification = RpcNotification {
             subscription_id: subscription.id(),
-            json: Arc::downgrade(&buf_arc),
+            payload: Arc::downgrade(&payload),
             is_final,
             created_at: Instant::now(),
         };
@@ -318,9 +368,9 @@ impl RpcNotifier {
         let _ = self.sender.send(notification);
 
         inc_new_counter_info!("rpc-pubsub-messages", 1);
-        inc_new_counter_info!("rpc-pubsub-bytes", buf_arc.len());
+        inc_new_counter_info!("rpc-pubsub-bytes", payload.len());
 
-        self.recent_items.lock().unwrap().push(buf_arc);
+        self.recent_items.lock().unwrap().push(payload);
     }
 }
 
@@ -369,11 +419,153 @@ fn filter_block_result_txs(
     }))
 }
 
+// The blocks fetched by one pass of `notify_watchers`, so that the transaction subscriptions
+// notified of a slot share a single blockstore fetch of its block
+#[derive(Default)]
+struct NotifiedBlocks(DashMap<Slot, Arc<OnceLock<Option<Arc<VersionedConfirmedBlock>>>>>);
+
+impl NotifiedBlocks {
+    fn get(&self, blockstore: &Blockstore, slot: Slot) -> Option<Arc<VersionedConfirmedBlock>> {
+        let block = Arc::clone(&self.0.entry(slot).or_default());
+        // concurrent callers for the same slot wait for the first one's fetch
+        block
+            .get_or_init(|| match blockstore.get_complete_block(slot, false) {
+                Ok(block) => Some(Arc::new(block)),
+                Err(e) => {
+                    error!("get_complete_block error: {}", e);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+fn filter_transaction_results<'a>(
+    block: &'a VersionedConfirmedBlock,
+    slot: Slot,
+    params: &'a TransactionSubscriptionParams,
+) -> impl Iterator<Item = RpcTransactionUpdate> + 'a {
+    block
+        .transactions
+        .iter()
+        .filter(|transaction_with_meta| params.filter.allows(transaction_with_meta))
+        .map(move |transaction_with_meta| {
+            let signature = transaction_with_meta.transaction.signatures[0].to_string();
+            match transaction_with_meta.clone().encode(
+                params.encoding,
+                params.max_supported_transaction_version,
+                params.show_rewards,
+            ) {
+                Ok(transaction) => RpcTransactionUpdate {
+                    slot,
+                    signature,
+                    transaction: Some(transaction),
+                    err: None,
+                },
+                Err(EncodeError::UnsupportedTransactionVersion(version)) => RpcTransactionUpdate {
+                    slot,
+                    signature,
+                    transaction: None,
+                    err: Some(RpcBlockUpdateError::UnsupportedTransactionVersion(version)),
+                },
+            }
+        })
+}
+
+// Returns the slots up to `slot` that a block or transaction subscription has not been notified
+// of yet, skipping the slots that are not ancestors of `slot`
+fn get_slots_to_notify(last_unnotified_slot: &mut Slot, bank: &Bank, slot: Slot) -> Vec<Slot> {
+    // would mean it's the first notification for this subscription connection
+    if *last_unnotified_slot == 0 {
+        *last_unnotified_slot = slot;
+    }
+    let mut slots_to_notify: Vec<_> = (*last_unnotified_slot..slot).collect();
+    let ancestors = bank.proper_ancestors_set();
+    slots_to_notify.retain(|slot| ancestors.contains(slot));
+    slots_to_notify.push(slot);
+    slots_to_notify
+}
+
+// Returns the data of an account that a subscription is notified of: the concatenation of the
+// requested data slices, or all of the data if no slices were requested
+fn subscribed_account_data<'a>(data: &'a [u8], data_slices: &[UiDataSliceConfig]) -> Cow<'a, [u8]> {
+    if data_slices.is_empty() {
+        return Cow::Borrowed(data);
+    }
+    let mut subscribed_data = Vec::new();
+    for UiDataSliceConfig { offset, length } in data_slices {
+        let start = (*offset).min(data.len());
+        let end = offset.saturating_add(*length).min(data.len());
+        subscribed_data.extend_from_slice(&data[start..end]);
+    }
+    Cow::Owned(subscribed_data)
+}
+
+// Skips the accounts whose subscribed data is unchanged since their last notification, if the
+// subscription asked for changes only
+fn is_account_data_changed(
+    pubkey: &Pubkey,
+    account: &AccountSharedData,
+    data_slices: &[UiDataSliceConfig],
+    changes_only: bool,
+    subscription: &SubscriptionInfo,
+) -> bool {
+    !changes_only
+        || subscription.update_notified_data(
+            pubkey,
+            &subscribed_account_data(account.data(), data_slices),
+        )
+}
+
+fn encode_subscribed_account(
+    pubkey: &Pubkey,
+    account: &AccountSharedData,
+    encoding: UiAccountEncoding,
+    data_slices: &[UiDataSliceConfig],
+) -> UiAccount {
+    if data_slices.len() <= 1 {
+        return encode_ui_account(
+            pubkey,
+            account,
+            encoding,
+            None,
+            data_slices.first().copied(),
+        );
+    }
+    let sliced_account = Account {
+        lamports: account.lamports(),
+        data: subscribed_account_data(account.data(), data_slices).into_owned(),
+        owner: *account.owner(),
+        executable: account.executable(),
+        rent_epoch: account.rent_epoch(),
+    };
+    let mut ui_account = encode_ui_account(pubkey, &sliced_account, encoding, None, None);
+    ui_account.space = Some(account.data().len() as u64);
+    ui_account
+}
+
+fn binary_subscribed_account(
+    pubkey: &Pubkey,
+    account: &AccountSharedData,
+    data_slices: &[UiDataSliceConfig],
+) -> RpcBinaryAccount {
+    RpcBinaryAccount {
+        pubkey: *pubkey,
+        lamports: account.lamports(),
+        owner: *account.owner(),
+        executable: account.executable(),
+        rent_epoch: account.rent_epoch(),
+        space: account.data().len() as u64,
+        data: subscribed_account_data(account.data(), data_slices).into_owned(),
+    }
+}
+
 fn filter_account_result(
     result: Option<(AccountSharedData, Slot)>,
     params: &AccountSubscriptionParams,
     last_notified_slot: Slot,
     bank: Arc<Bank>,
+    subscription: &SubscriptionInfo,
 ) -> (Option<UiAccount>, Slot) {
     // If the account is not found, `last_modified_slot` will default to zero and
     // we will notify clients that the account no longer exists if we haven't already
@@ -381,18 +573,50 @@ fn filter_account_result(
 
     // If last_modified_slot < last_notified_slot this means that we last notified for a fork
     // and should notify that the account state has been reverted.
-    let account = (last_modified_slot != last_notified_slot).then(|| {
+    let account = (last_modified_slot != last_notified_slot
+        && is_account_data_changed(
+            &params.pubkey,
+            &account,
+            &params.data_slices,
+            params.changes_only,
+            subscription,
+        ))
+    .then(|| {
         if is_known_spl_token_id(account.owner())
             && params.encoding == UiAccountEncoding::JsonParsed
         {
             get_parsed_token_account(&bank, &params.pubkey, account, None)
         } else {
-            encode_ui_account(&params.pubkey, &account, params.encoding, None, None)
+            encode_subscribed_account(
+                &params.pubkey,
+                &account,
+                params.encoding,
+                &params.data_slices,
+            )
         }
     });
     (account, last_modified_slot)
 }
 
+fn filter_binary_account_result(
+    result: Option<(AccountSharedData, Slot)>,
+    params: &AccountSubscriptionParams,
+    last_notified_slot: Slot,
+    subscription: &SubscriptionInfo,
+) -> (Option<RpcBinaryAccount>, Slot) {
+    let (account, last_modified_slot) = result.unwrap_or_default();
+    let account = (last_modified_slot != last_notified_slot
+        && is_account_data_changed(
+            &params.pubkey,
+            &account,
+            &params.data_slices,
+            params.changes_only,
+            subscription,
+        ))
+    .then(|| binary_subscribed_account(&params.pubkey, &account, &params.data_slices));
+    (account, last_modified_slot)
+}
+
 fn filter_signature_result(
     result: Option<transaction::Result<()>>,
     _params: &SignatureSubscriptionParams,
@@ -407,20 +631,33 @@ fn filter_signature_result(
     )
 }
 
-fn filter_program_results(
+fn filter_program_accounts<'a>(
     accounts: Vec<(Pubkey, AccountSharedData)>,
     params: &ProgramSubscriptionParams,
-    last_notified_slot: Slot,
-    bank: Arc<Bank>,
-) -> (impl Iterator<Item = RpcKeyedAccount>, Slot) {
-    let accounts_is_empty = accounts.is_empty();
-    let encoding = params.encoding;
+    subscription: &'a SubscriptionInfo,
+) -> impl Iterator<Item = (Pubkey, AccountSharedData)> + 'a {
     let filters = params.filters.clone();
-    let keyed_accounts = accounts.into_iter().filter(move |(_, account)| {
+    let data_slices = params.data_slices.clone();
+    let changes_only = params.changes_only;
+    accounts.into_iter().filter(move |(pubkey, account)| {
         filters
             .iter()
             .all(|filter_type| filter_allows(filter_type, account))
-    });
+            && is_account_data_changed(pubkey, account, &data_slices, changes_only, subscription)
+    })
+}
+
+fn filter_program_results<'a>(
+    accounts: Vec<(Pubkey, AccountSharedData)>,
+    params: &ProgramSubscriptionParams,
+    last_notified_slot: Slot,
+    bank: Arc<Bank>,
+    subscription: &'a SubscriptionInfo,
+) -> (impl Iterator<Item = RpcKeyedAccount> + 'a, Slot) {
+    let accounts_is_empty = accounts.is_empty();
+    let encoding = params.encoding;
+    let data_slices = params.data_slices.clone();
+    let keyed_accounts = filter_program_accounts(accounts, params, subscription);
     let accounts = if is_known_spl_token_id(&params.pubkey)
         && params.encoding == UiAccountEncoding::JsonParsed
         && !accounts_is_empty
@@ -430,13 +667,25 @@ fn filter_program_results(
     } else {
         let accounts = keyed_accounts.map(move |(pubkey, account)| RpcKeyedAccount {
             pubkey: pubkey.to_string(),
-            account: encode_ui_account(&pubkey, &account, encoding, None, None),
+            account: encode_subscribed_account(&pubkey, &account, encoding, &data_slices),
         });
         Either::Right(accounts)
     };
     (accounts, last_notified_slot)
 }
 
+fn filter_binary_program_results<'a>(
+    accounts: Vec<(Pubkey, AccountSharedData)>,
+    params: &ProgramSubscriptionParams,
+    last_notified_slot: Slot,
+    subscription: &'a SubscriptionInfo,
+) -> (impl Iterator<Item = RpcBinaryAccount> + 'a, Slot) {
+    let data_slices = params.data_slices.clone();
+    let accounts = filter_program_accounts(accounts, params, subscription)
+        .map(move |(pubkey, account)| binary_subscribed_account(&pubkey, &account, &data_slices));
+    (accounts, last_notified_slot)
+}
+
 fn filter_logs_results(
     logs: Option<Vec<TransactionLogInfo>>,
     _params: &LogsSubscriptionParams,
@@ -950,6 +1199,10 @@ impl RpcSubscriptions {
         let num_signatures_found = AtomicUsize::new(0);
         let num_signatures_notified = AtomicUsize::new(0);
 
+        let num_transactions_found = AtomicUsize::new(0);
+        let num_transactions_notified = AtomicUsize::new(0);
+        let notified_blocks = NotifiedBlocks::default();
+
         let subscriptions = subscriptions.into_par_iter();
         subscriptions.for_each(|(_id, subscription)| {
             let slot = if let Some(commitment) = subscription.commitment() {
@@ -968,16 +1221,46 @@ impl RpcSubscriptions {
                 SubscriptionParams::Account(params) => {
                     num_accounts_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
-                        let notified = check_commitment_and_notify(
-                            params,
-                            subscription,
-                            bank_forks,
-                            slot,
-                            |bank, params| bank.get_account_modified_slot(&params.pubkey),
-                            filter_account_result,
-                            notifier,
-                            false,
-                        );
+                        let bank_method = |bank: &Bank, params: &AccountSubscriptionParams| {
+                            bank.get_account_modified_slot(&params.pubkey)
+                        };
+                        let notified = match params.notification_format {
+                            RpcNotificationFormat::Json => check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                bank_method,
+                                |result, params, last_notified_slot, bank| {
+                                    filter_account_result(
+                                        result,
+                                        params,
+                                        last_notified_slot,
+                                        bank,
+                                        subscription,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            ),
+                            RpcNotificationFormat::Binary => check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                bank_method,
+                                |result, params, last_notified_slot, _bank| {
+                                    filter_binary_account_result(
+                                        result,
+                                        params,
+                                        last_notified_slot,
+                                        subscription,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            ),
+                        };
 
                         if notified {
                             num_accounts_notified.fetch_add(1, Ordering::Relaxed);
@@ -1005,15 +1288,8 @@ impl RpcSubscriptions {
                             // as long as they are ancestors of `slot`
                             let mut w_last_unnotified_slot =
                                 subscription.last_notified_slot.write().unwrap();
-                            // would mean it's the first notification for this subscription connection
-                            if *w_last_unnotified_slot == 0 {
-                                *w_last_unnotified_slot = slot;
-                            }
-                            let mut slots_to_notify: Vec<_> =
-                                (*w_last_unnotified_slot..slot).collect();
-                            let ancestors = bank.proper_ancestors_set();
-                            slots_to_notify.retain(|slot| ancestors.contains(slot));
-                            slots_to_notify.push(slot);
+                            let slots_to_notify =
+                                get_slots_to_notify(&mut w_last_unnotified_slot, &bank, slot);
                             for s in slots_to_notify {
                                 // To avoid skipping a slot that fails this condition,
                                 // caused by non-deterministic concurrency accesses, we
@@ -1071,6 +1347,45 @@ impl RpcSubscriptions {
                         }
                     }
                 }
+                SubscriptionParams::Transaction(params) => {
+                    num_transactions_found.fetch_add(1, Ordering::Relaxed);
+                    if let Some(slot) = slot {
+                        let bank = bank_forks.read().unwrap().get(slot);
+                        if let Some(bank) = bank {
+                            // Like block subscriptions, notify the transactions of the slots
+                            // that were potentially missed since the last notification
+                            let mut w_last_unnotified_slot =
+                                subscription.last_notified_slot.write().unwrap();
+                            let slots_to_notify =
+                                get_slots_to_notify(&mut w_last_unnotified_slot, &bank, slot);
+                            for s in slots_to_notify {
+                                if s > max_complete_transaction_status_slot.load(Ordering::SeqCst) {
+                                    break;
+                                }
+
+                                let Some(block) = notified_blocks.get(blockstore, s) else {
+                                    // we don't advance `w_last_unnotified_slot` so that
+                                    // it'll retry on the next notification trigger
+                                    break;
+                                };
+                                for transaction_update in
+                                    filter_transaction_results(&block, s, params)
+                                {
+                                    notifier.notify(
+                                        RpcResponse::from(RpcNotificationResponse {
+                                            context: RpcNotificationContext { slot: s },
+                                            value: transaction_update,
+                                        }),
+                                        subscription,
+                                        false,
+                                    );
+                                    num_transactions_notified.fetch_add(1, Ordering::Relaxed);
+                                }
+                                *w_last_unnotified_slot = s + 1;
+                            }
+                        }
+                    }
+                }
                 SubscriptionParams::Logs(params) => {
                     num_logs_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
@@ -1093,18 +1408,46 @@ impl RpcSubscriptions {
                 SubscriptionParams::Program(params) => {
                     num_programs_found.fetch_add(1, Ordering::Relaxed);
                     if let Some(slot) = slot {
-                        let notified = check_commitment_and_notify(
-                            params,
-                            subscription,
-                            bank_forks,
-                            slot,
-                            |bank, params| {
-                                bank.get_program_accounts_modified_since_parent(&params.pubkey)
-                            },
-                            filter_program_results,
-                            notifier,
-                            false,
-                        );
+                        let bank_method = |bank: &Bank, params: &ProgramSubscriptionParams| {
+                            bank.get_program_accounts_modified_since_parent(&params.pubkey)
+                        };
+                        let notified = match params.notification_format {
+                            RpcNotificationFormat::Json => check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                bank_method,
+                                |accounts, params, last_notified_slot, bank| {
+                                    filter_program_results(
+                                        accounts,
+                                        params,
+                                        last_notified_slot,
+                                        bank,
+                                        subscription,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            ),
+                            RpcNotificationFormat::Binary => check_commitment_and_notify(
+                                params,
+                                subscription,
+                                bank_forks,
+                                slot,
+                                bank_method,
+                                |accounts, params, last_notified_slot, _bank| {
+                                    filter_binary_program_results(
+                                        accounts,
+                                        params,
+                                        last_notified_slot,
+                                        subscription,
+                                    )
+                                },
+                                notifier,
+                                false,
+                            ),
+                        };
 
                         if notified {
                             num_programs_notified.fetch_add(1, Ordering::Relaxed);
@@ -1141,7 +1484,8 @@ impl RpcSubscriptions {
         let total_notified = num_accounts_notified.load(Ordering::Relaxed)
             + num_logs_notified.load(Ordering::Relaxed)
             + num_programs_notified.load(Ordering::Relaxed)
-            + num_signatures_notified.load(Ordering::Relaxed);
+            + num_signatures_notified.load(Ordering::Relaxed)
+            + num_transactions_notified.load(Ordering::Relaxed);
         let total_ms = total_time.as_ms();
         if total_notified > 0 || total_ms > 10 {
             debug!(
@@ -1199,6 +1543,16 @@ impl RpcSubscriptions {
                     num_signatures_notified.load(Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "num_transaction_subscriptions",
+                    num_transactions_found.load(Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "num_transactions_notified",
+                    num_transactions_notified.load(Ordering::Relaxed),
+                    i64
+                ),
                 ("notifications_time", total_time.as_us() as i64, i64),
             );
         }
@@ -1234,13 +1588,18 @@ pub(crate) mod tests {
             rpc::{create_test_transaction_entries, populate_blockstore_for_tests},
             rpc_pubsub::RpcSolPubSubInternal,
             rpc_pubsub_service,
+            rpc_subscription_tracker::TransactionSubscriptionFilter,
         },
         serial_test::serial,
         solana_ledger::get_tmp_ledger_path_auto_delete,
-        solana_rpc_client_api::config::{
-            RpcAccountInfoConfig, RpcBlockSubscribeConfig, RpcBlockSubscribeFilter,
-            RpcProgramAccountsConfig, RpcSignatureSubscribeConfig, RpcTransactionLogsConfig,
-            RpcTransactionLogsFilter,
+        solana_rpc_client_api::{
+            config::{
+                RpcAccountInfoConfig, RpcAccountSubscribeConfig, RpcBlockSubscribeConfig,
+                RpcBlockSubscribeFilter, RpcProgramAccountsConfig, RpcProgramSubscribeConfig,
+                RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+                RpcTransactionSubscribeConfig, RpcTransactionSubscribeFilter,
+            },
+            response::RPC_BINARY_NOTIFICATION_VERSION,
         },
         solana_runtime::{
             commitment::BlockCommitment,
@@ -1256,7 +1615,7 @@ pub(crate) mod tests {
         },
         solana_transaction_status::{TransactionDetails, UiTransactionEncoding},
         std::{
-            collections::HashSet,
+            collections::{BTreeSet, HashSet},
             sync::atomic::{AtomicU64, Ordering::Relaxed},
         },
     };
@@ -1386,11 +1745,14 @@ pub(crate) mod tests {
             let sub_id = rpc
                 .account_subscribe(
                     pubkey.to_string(),
-                    Some(RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        encoding: None,
-                        data_slice: None,
-                        min_context_slot: None,
+                    Some(RpcAccountSubscribeConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            encoding: None,
+                            data_slice: None,
+                            min_context_slot: None,
+                        },
+                        ..RpcAccountSubscribeConfig::default()
                     }),
                 )
                 .unwrap();
@@ -1400,7 +1762,9 @@ pub(crate) mod tests {
                 .assert_subscribed(&SubscriptionParams::Account(AccountSubscriptionParams {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
-                    data_slice: None,
+                    data_slices: vec![],
+                    changes_only: false,
+                    notification_format: RpcNotificationFormat::Json,
                     encoding: UiAccountEncoding::Binary,
                 }));
 
@@ -1431,12 +1795,142 @@ pub(crate) mod tests {
                 .assert_unsubscribed(&SubscriptionParams::Account(AccountSubscriptionParams {
                     pubkey,
                     commitment: CommitmentConfig::processed(),
-                    data_slice: None,
+                    data_slices: vec![],
+                    changes_only: false,
+                    notification_format: RpcNotificationFormat::Json,
                     encoding: UiAccountEncoding::Binary,
                 }));
         }
     }
 
+    #[test]
+    #[serial]
+    fn test_check_account_subscribe_data_slices_changes_only() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(100);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let bank0 = bank_forks.read().unwrap().get(0).unwrap();
+        let bank1 = Bank::new_from_parent(bank0, &Pubkey::default(), 1);
+        bank_forks.write().unwrap().insert(bank1);
+        let alice = Pubkey::new_unique();
+
+        let exit = Arc::new(AtomicBool::new(false));
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let max_complete_rewards_slot = Arc::new(AtomicU64::default());
+        let block_commitment_cache = Arc::new(RwLock::new(
+            BlockCommitmentCache::new_for_tests_with_slots(1, 1),
+        ));
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests(
+            exit,
+            max_complete_transaction_status_slot,
+            max_complete_rewards_slot,
+            bank_forks.clone(),
+            block_commitment_cache,
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks),
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+
+        let data_slices = vec![
+            UiDataSliceConfig {
+                offset: 0,
+                length: 2,
+            },
+            UiDataSliceConfig {
+                offset: 6,
+                length: 10,
+            },
+        ];
+        let config = RpcAccountSubscribeConfig {
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            data_slices: Some(data_slices.clone()),
+            changes_only: Some(true),
+            notification_format: Some(RpcNotificationFormat::Binary),
+        };
+        // Processed slots can be rolled back, so they can't be compared against
+        let processed_config = RpcAccountSubscribeConfig {
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(CommitmentConfig::processed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..config.clone()
+        };
+        assert!(rpc
+            .account_subscribe(alice.to_string(), Some(processed_config))
+            .is_err());
+        let sub_id = rpc
+            .account_subscribe(alice.to_string(), Some(config))
+            .unwrap();
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Account(AccountSubscriptionParams {
+                pubkey: alice,
+                commitment: CommitmentConfig::confirmed(),
+                data_slices,
+                changes_only: true,
+                notification_format: RpcNotificationFormat::Binary,
+                encoding: UiAccountEncoding::Binary,
+            }));
+        rpc.block_until_processed(&subscriptions);
+
+        let store_and_notify = |slot: Slot, data: Vec<u8>| {
+            let bank = bank_forks.read().unwrap().get(slot - 1).unwrap();
+            let bank = Bank::new_from_parent(bank, &Pubkey::default(), slot);
+            let bank = bank_forks
+                .write()
+                .unwrap()
+                .insert(bank)
+                .clone_without_scheduler();
+            let account = Account {
+                lamports: 1,
+                data,
+                owner: system_program::id(),
+                ..Account::default()
+            };
+            bank.store_account(&alice, &AccountSharedData::from(account));
+            subscriptions.notify_gossip_subscribers(slot);
+        };
+        let expected_notification = |slot: Slot, data: Vec<u8>| {
+            RpcBinaryNotification::new(
+                u64::from(sub_id),
+                slot,
+                RpcBinaryAccount {
+                    pubkey: alice,
+                    lamports: 1,
+                    owner: system_program::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                    space: 8,
+                    data,
+                },
+            )
+        };
+
+        store_and_notify(2, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        let bytes = receiver.recv_binary();
+        // the version byte is followed by the little-endian subscription id and slot
+        assert_eq!(bytes[0], RPC_BINARY_NOTIFICATION_VERSION);
+        assert_eq!(bytes[1..9], u64::from(sub_id).to_le_bytes());
+        assert_eq!(bytes[9..17], 2u64.to_le_bytes());
+        let notification: RpcBinaryNotification<RpcBinaryAccount> =
+            bincode::deserialize(&bytes).unwrap();
+        assert_eq!(notification, expected_notification(2, vec![1, 2, 7, 8]));
+
+        // Only data outside of the subscribed slices changed
+        store_and_notify(3, vec![1, 2, 0, 0, 0, 0, 7, 8]);
+        assert!(receiver
+            .recv_timeout(Duration::from_millis(500))
+            .unwrap_err()
+            .contains("timeout"));
+
+        store_and_notify(4, vec![1, 2, 0, 0, 0, 0, 7, 9]);
+        let notification: RpcBinaryNotification<RpcBinaryAccount> =
+            bincode::deserialize(&receiver.recv_binary()).unwrap();
+        assert_eq!(notification, expected_notification(4, vec![1, 2, 7, 9]));
+    }
+
     #[test]
     #[serial]
     fn test_check_confirmed_block_subscribe() {
@@ -1675,6 +2169,154 @@ pub(crate) mod tests {
             .assert_unsubscribed(&SubscriptionParams::Block(params));
     }
 
+    #[test]
+    #[serial]
+    fn test_check_transaction_subscribe() {
+        let exit = Arc::new(AtomicBool::new(false));
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(10_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+        let rent_exempt_amount = bank.get_minimum_balance_for_rent_exemption(0);
+        let bank_forks = BankForks::new_rw_arc(bank);
+        let optimistically_confirmed_bank =
+            OptimisticallyConfirmedBank::locked_from_bank_forks_root(&bank_forks);
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let blockstore = Arc::new(blockstore);
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::default());
+        let max_complete_rewards_slot = Arc::new(AtomicU64::default());
+        let subscriptions = Arc::new(RpcSubscriptions::new_for_tests_with_blockstore(
+            exit,
+            max_complete_transaction_status_slot,
+            max_complete_rewards_slot,
+            blockstore.clone(),
+            bank_forks.clone(),
+            Arc::new(RwLock::new(BlockCommitmentCache::new_for_tests())),
+            optimistically_confirmed_bank,
+        ));
+        let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
+        let keypair1 = Keypair::new();
+        let keypair2 = Keypair::new();
+        let keypair3 = Keypair::new();
+        let filter = RpcTransactionSubscribeFilter {
+            vote: Some(false),
+            failed: Some(true),
+            signer: Some(keypair2.pubkey().to_string()),
+            account_include: None,
+            account_exclude: Some(vec![keypair1.pubkey().to_string()]),
+            account_required: Some(vec![keypair3.pubkey().to_string()]),
+        };
+        let config = RpcTransactionSubscribeConfig {
+            commitment: Some(CommitmentConfig::confirmed()),
+            encoding: Some(UiTransactionEncoding::Json),
+            show_rewards: None,
+            max_supported_transaction_version: None,
+        };
+        let params = TransactionSubscriptionParams {
+            commitment: config.commitment.unwrap(),
+            encoding: config.encoding.unwrap(),
+            filter: TransactionSubscriptionFilter {
+                vote: Some(false),
+                failed: Some(true),
+                signer: Some(keypair2.pubkey()),
+                account_include: BTreeSet::new(),
+                account_exclude: BTreeSet::from([keypair1.pubkey()]),
+                account_required: BTreeSet::from([keypair3.pubkey()]),
+            },
+            show_rewards: false,
+            max_supported_transaction_version: None,
+        };
+        let processed_config = RpcTransactionSubscribeConfig {
+            commitment: Some(CommitmentConfig::processed()),
+            ..config.clone()
+        };
+        let processed_params = TransactionSubscriptionParams {
+            commitment: CommitmentConfig::processed(),
+            ..params.clone()
+        };
+        let sub_id = rpc
+            .transaction_subscribe(filter.clone(), Some(config))
+            .unwrap();
+        let processed_sub_id = rpc
+            .transaction_subscribe(filter, Some(processed_config))
+            .unwrap();
+
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Transaction(params.clone()));
+        subscriptions
+            .control
+            .assert_subscribed(&SubscriptionParams::Transaction(processed_params.clone()));
+
+        let bank = bank_forks.read().unwrap().working_bank();
+        let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
+        bank.transfer(rent_exempt_amount, &mint_keypair, &keypair2.pubkey())
+            .unwrap();
+        populate_blockstore_for_tests(
+            create_test_transaction_entries(
+                vec![&mint_keypair, &keypair1, &keypair2, &keypair3],
+                bank.clone(),
+            )
+            .0,
+            bank,
+            blockstore.clone(),
+            max_complete_transaction_status_slot,
+        );
+
+        let slot = 0;
+        subscriptions.notify_gossip_subscribers(slot);
+        let actual_resp = receiver.recv();
+        let actual_resp = serde_json::from_str::<serde_json::Value>(&actual_resp).unwrap();
+
+        // only the failed transfer from keypair2 to keypair3 matches the filter
+        let block = blockstore.get_complete_block(slot, false).unwrap();
+        let mut transactions: Vec<_> = block
+            .transactions
+            .into_iter()
+            .filter(|tx_with_meta| tx_with_meta.meta.status.is_err())
+            .collect();
+        assert_eq!(transactions.len(), 1);
+        let transaction = transactions.pop().unwrap();
+        let expected_resp = RpcTransactionUpdate {
+            slot,
+            signature: transaction.transaction.signatures[0].to_string(),
+            transaction: Some(transaction.encode(params.encoding, None, false).unwrap()),
+            err: None,
+        };
+        let expected_resp = |subscription: u64| {
+            json!({
+               "jsonrpc": "2.0",
+               "method": "transactionNotification",
+               "params": {
+                   "result": {
+                       "context": { "slot": slot },
+                       "value": expected_resp,
+                   },
+                   "subscription": subscription,
+               }
+            })
+        };
+        assert_eq!(expected_resp(0), actual_resp);
+
+        // the processed subscription is notified of the same transaction from the bank
+        subscriptions.notify_subscribers(CommitmentSlots::default());
+        let actual_resp = receiver.recv();
+        let actual_resp = serde_json::from_str::<serde_json::Value>(&actual_resp).unwrap();
+        assert_eq!(expected_resp(1), actual_resp);
+
+        rpc.transaction_unsubscribe(sub_id).unwrap();
+        rpc.transaction_unsubscribe(processed_sub_id).unwrap();
+        subscriptions
+            .control
+            .assert_unsubscribed(&SubscriptionParams::Transaction(params));
+        subscriptions
+            .control
+            .assert_unsubscribed(&SubscriptionParams::Transaction(processed_params));
+    }
+
     #[test]
     #[serial]
     fn test_check_finalized_block_subscribe() {
@@ -1839,12 +2481,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::processed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::processed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -1855,7 +2500,9 @@ pub(crate) mod tests {
                 pubkey: stake::program::id(),
                 filters: Vec::new(),
                 commitment: CommitmentConfig::processed(),
-                data_slice: None,
+                data_slices: vec![],
+                changes_only: false,
+                notification_format: RpcNotificationFormat::Json,
                 encoding: UiAccountEncoding::Binary,
                 with_context: false,
             }));
@@ -1895,7 +2542,9 @@ pub(crate) mod tests {
                 pubkey: stake::program::id(),
                 filters: Vec::new(),
                 commitment: CommitmentConfig::processed(),
-                data_slice: None,
+                data_slices: vec![],
+                changes_only: false,
+                notification_format: RpcNotificationFormat::Json,
                 encoding: UiAccountEncoding::Binary,
                 with_context: false,
             }));
@@ -1993,12 +2642,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -2009,7 +2661,9 @@ pub(crate) mod tests {
                 pubkey: stake::program::id(),
                 filters: Vec::new(),
                 encoding: UiAccountEncoding::Binary,
-                data_slice: None,
+                data_slices: vec![],
+                changes_only: false,
+                notification_format: RpcNotificationFormat::Json,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
             }));
@@ -2168,12 +2822,15 @@ pub(crate) mod tests {
         let (rpc, mut receiver) = rpc_pubsub_service::test_connection(&subscriptions);
         rpc.program_subscribe(
             stake::program::id().to_string(),
-            Some(RpcProgramAccountsConfig {
-                account_config: RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    ..RpcAccountInfoConfig::default()
+            Some(RpcProgramSubscribeConfig {
+                program_config: RpcProgramAccountsConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
                 },
-                ..RpcProgramAccountsConfig::default()
+                ..RpcProgramSubscribeConfig::default()
             }),
         )
         .unwrap();
@@ -2184,7 +2841,9 @@ pub(crate) mod tests {
                 pubkey: stake::program::id(),
                 filters: Vec::new(),
                 encoding: UiAccountEncoding::Binary,
-                data_slice: None,
+                data_slices: vec![],
+                changes_only: false,
+                notification_format: RpcNotificationFormat::Json,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
             }));
@@ -2285,12 +2944,15 @@ pub(crate) mod tests {
         let sub_id = rpc
             .program_subscribe(
                 stake::program::id().to_string(),
-                Some(RpcProgramAccountsConfig {
-                    account_config: RpcAccountInfoConfig {
-                        commitment: Some(CommitmentConfig::confirmed()),
-                        ..RpcAccountInfoConfig::default()
+                Some(RpcProgramSubscribeConfig {
+                    program_config: RpcProgramAccountsConfig {
+                        account_config: RpcAccountInfoConfig {
+                            commitment: Some(CommitmentConfig::confirmed()),
+                            ..RpcAccountInfoConfig::default()
+                        },
+                        ..RpcProgramAccountsConfig::default()
                     },
-                    ..RpcProgramAccountsConfig::default()
+                    ..RpcProgramSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -2301,7 +2963,9 @@ pub(crate) mod tests {
                 pubkey: stake::program::id(),
                 filters: Vec::new(),
                 encoding: UiAccountEncoding::Binary,
-                data_slice: None,
+                data_slices: vec![],
+                changes_only: false,
+                notification_format: RpcNotificationFormat::Json,
                 commitment: CommitmentConfig::confirmed(),
                 with_context: false,
             }));
@@ -2767,11 +3431,14 @@ pub(crate) mod tests {
         let sub_id0 = rpc0
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    ..RpcAccountSubscribeConfig::default()
                 }),
             )
             .unwrap();
@@ -2863,11 +3530,14 @@ pub(crate) mod tests {
         let sub_id1 = rpc1
             .account_subscribe(
                 alice.pubkey().to_string(),
-                Some(RpcAccountInfoConfig {
-                    commitment: Some(CommitmentConfig::confirmed()),
-                    encoding: None,
-                    data_slice: None,
-                    min_context_slot: None,
+                Some(RpcAccountSubscribeConfig {
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(CommitmentConfig::confirmed()),
+                        encoding: None,
+                        data_slice: None,
+                        min_context_slot: None,
+                    },
+                    ..RpcAccountSubscribeConfig::default()
                 }),
             )
             .unwrap();
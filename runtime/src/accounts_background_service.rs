@@ -686,6 +686,7 @@ impl AccountsBackgroundService {
                         // was in the snapshot itself.
                         if bank.is_startup_verification_complete() {
                             bank.shrink_candidate_slots();
+                            bank.process_shrink_requests();
                         }
                     }
                     stats.record_and_maybe_submit(start_time.elapsed());
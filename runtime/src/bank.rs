@@ -81,7 +81,7 @@ use {
             AccountHash, AccountsHash, AccountsLtHash, CalcAccountsHashConfig, HashStats,
             IncrementalAccountsHash, MerkleOrLatticeAccountsHash,
         },
-        accounts_index::{IndexKey, ScanConfig, ScanResult},
+        accounts_index::{IndexKey, ScanConfig, ScanResult, ScanStart},
         accounts_partition::{self, Partition, PartitionIndex},
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         ancestors::{Ancestors, AncestorsForSerialization},
@@ -104,7 +104,8 @@ use {
     solana_lattice_hash::lt_hash::LtHash,
     solana_measure::{meas_dur, measure::Measure, measure_time, measure_us},
     solana_program_runtime::{
-        invoke_context::BuiltinFunctionWithContext, loaded_programs::ProgramCacheEntry,
+        execution_trace::InstructionTrace, invoke_context::BuiltinFunctionWithContext,
+        loaded_programs::ProgramCacheEntry,
     },
     solana_runtime_transaction::{
         runtime_transaction::RuntimeTransaction, transaction_with_meta::TransactionWithMeta,
@@ -360,6 +361,7 @@ pub struct TransactionSimulationResult {
     pub units_consumed: u64,
     pub return_data: Option<TransactionReturnData>,
     pub inner_instructions: Option<Vec<InnerInstructions>>,
+    pub execution_trace: Option<Vec<InstructionTrace>>,
 }
 
 #[derive(Clone)]
@@ -3268,10 +3270,15 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+        enable_trace_recording: bool,
     ) -> TransactionSimulationResult {
         assert!(self.is_frozen(), "simulation bank must be frozen");
 
-        self.simulate_transaction_unchecked(transaction, enable_cpi_recording)
+        self.simulate_transaction_unchecked(
+            transaction,
+            enable_cpi_recording,
+            enable_trace_recording,
+        )
     }
 
     /// Run transactions against a bank without committing the results; does not check if the bank
@@ -3280,6 +3287,7 @@ impl Bank {
         &self,
         transaction: &impl TransactionWithMeta,
         enable_cpi_recording: bool,
+        enable_trace_recording: bool,
     ) -> TransactionSimulationResult {
         let account_keys = transaction.account_keys();
         let number_of_accounts = account_keys.len();
@@ -3308,6 +3316,7 @@ impl Bank {
                     enable_cpi_recording,
                     enable_log_recording: true,
                     enable_return_data_recording: true,
+                    enable_trace_recording,
                 },
                 transaction_account_lock_limit: Some(self.get_transaction_account_lock_limit()),
             },
@@ -3330,31 +3339,38 @@ impl Bank {
         let processing_result = processing_results
             .pop()
             .unwrap_or(Err(TransactionError::InvalidProgramForExecution));
-        let (post_simulation_accounts, result, logs, return_data, inner_instructions) =
-            match processing_result {
-                Ok(processed_tx) => match processed_tx {
-                    ProcessedTransaction::Executed(executed_tx) => {
-                        let details = executed_tx.execution_details;
-                        let post_simulation_accounts = executed_tx
-                            .loaded_transaction
-                            .accounts
-                            .into_iter()
-                            .take(number_of_accounts)
-                            .collect::<Vec<_>>();
-                        (
-                            post_simulation_accounts,
-                            details.status,
-                            details.log_messages,
-                            details.return_data,
-                            details.inner_instructions,
-                        )
-                    }
-                    ProcessedTransaction::FeesOnly(fees_only_tx) => {
-                        (vec![], Err(fees_only_tx.load_error), None, None, None)
-                    }
-                },
-                Err(error) => (vec![], Err(error), None, None, None),
-            };
+        let (
+            post_simulation_accounts,
+            result,
+            logs,
+            return_data,
+            inner_instructions,
+            execution_trace,
+        ) = match processing_result {
+            Ok(processed_tx) => match processed_tx {
+                ProcessedTransaction::Executed(executed_tx) => {
+                    let details = executed_tx.execution_details;
+                    let post_simulation_accounts = executed_tx
+                        .loaded_transaction
+                        .accounts
+                        .into_iter()
+                        .take(number_of_accounts)
+                        .collect::<Vec<_>>();
+                    (
+                        post_simulation_accounts,
+                        details.status,
+                        details.log_messages,
+                        details.return_data,
+                        details.inner_instructions,
+                        details.execution_trace,
+                    )
+                }
+                ProcessedTransaction::FeesOnly(fees_only_tx) => {
+                    (vec![], Err(fees_only_tx.load_error), None, None, None, None)
+                }
+            },
+            Err(error) => (vec![], Err(error), None, None, None, None),
+        };
         let logs = logs.unwrap_or_default();
 
         TransactionSimulationResult {
@@ -3364,6 +3380,7 @@ impl Bank {
             units_consumed,
             return_data,
             inner_instructions,
+            execution_trace,
         }
     }
 
@@ -4688,6 +4705,7 @@ impl Bank {
                 enable_cpi_recording: false,
                 enable_log_recording: true,
                 enable_return_data_recording: true,
+                enable_trace_recording: false,
             },
             &mut ExecuteTimings::default(),
             Some(1000 * 1000),
@@ -5096,6 +5114,29 @@ impl Bank {
         )
     }
 
+    /// Returns a page of the accounts in a secondary index, in pubkey order, and the key to
+    /// continue after if more accounts may follow it
+    pub fn get_filtered_indexed_accounts_page<F: Fn(&AccountSharedData) -> bool>(
+        &self,
+        index_key: &IndexKey,
+        filter: F,
+        start: ScanStart,
+        limit: usize,
+        visit_limit: usize,
+        byte_limit_for_scan: Option<usize>,
+    ) -> ScanResult<(Vec<TransactionAccount>, Option<Pubkey>)> {
+        self.rc.accounts.load_page_by_index_key_with_filter(
+            &self.ancestors,
+            self.bank_id,
+            index_key,
+            filter,
+            start,
+            limit,
+            visit_limit,
+            byte_limit_for_scan,
+        )
+    }
+
     pub fn account_indexes_include_key(&self, key: &Pubkey) -> bool {
         self.rc.accounts.account_indexes_include_key(key)
     }
@@ -6350,6 +6391,14 @@ impl Bank {
         }
     }
 
+    /// Runs the shrink and ancient pack requests queued in the accounts db's `shrink_control`
+    pub fn process_shrink_requests(&self) {
+        self.rc
+            .accounts
+            .accounts_db
+            .process_shrink_requests(self.epoch_schedule(), self.are_ancient_storages_enabled())
+    }
+
     /// Returns if ancient storages are enabled or not
     pub fn are_ancient_storages_enabled(&self) -> bool {
         let can_skip_rewrites = self.bank_hash_skips_rent_rewrites();
@@ -253,6 +253,7 @@ fn new_executed_processing_result(
                 log_messages: None,
                 inner_instructions: None,
                 return_data: None,
+                execution_trace: None,
                 executed_units: 0,
                 accounts_data_len_delta: 0,
             },
@@ -9495,6 +9496,7 @@ fn test_tx_log_order() {
                 enable_cpi_recording: false,
                 enable_log_recording: true,
                 enable_return_data_recording: false,
+                enable_trace_recording: false,
             },
             &mut ExecuteTimings::default(),
             None,
@@ -9603,6 +9605,7 @@ fn test_tx_return_data() {
                     enable_cpi_recording: false,
                     enable_log_recording: false,
                     enable_return_data_recording: true,
+                    enable_trace_recording: false,
                 },
                 &mut ExecuteTimings::default(),
                 None,
@@ -13178,7 +13181,7 @@ fn test_failed_simulation_compute_units() {
 
     bank.freeze();
     let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
-    let simulation = bank.simulate_transaction(&sanitized, false);
+    let simulation = bank.simulate_transaction(&sanitized, false, false);
     assert_eq!(expected_consumed_units, simulation.units_consumed);
 }
 
@@ -13201,7 +13204,7 @@ fn test_failed_simulation_load_error() {
 
     bank.freeze();
     let sanitized = RuntimeTransaction::from_transaction_for_tests(transaction);
-    let simulation = bank.simulate_transaction(&sanitized, false);
+    let simulation = bank.simulate_transaction(&sanitized, false, false);
     assert_eq!(
         simulation,
         TransactionSimulationResult {
@@ -13211,6 +13214,7 @@ fn test_failed_simulation_load_error() {
             units_consumed: 0,
             return_data: None,
             inner_instructions: None,
+            execution_trace: None,
         }
     );
 }
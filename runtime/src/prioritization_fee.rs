@@ -144,6 +144,14 @@ pub struct PrioritizationFee {
     // The minimum prioritization fee of each writable account in transactions in this block.
     min_writable_account_fees: HashMap<Pubkey, u64>,
 
+    // The prioritization fee of every transaction in this block, sorted once the block is
+    // finalized.
+    transaction_fee_samples: Vec<u64>,
+
+    // The prioritization fees of the transactions writing to each account in this block, sorted
+    // once the block is finalized.
+    writable_account_fee_samples: HashMap<Pubkey, Vec<u64>>,
+
     // Default to `false`, set to `true` when a block is completed, therefore the minimum fees recorded
     // are finalized, and can be made available for use (e.g., RPC query)
     is_finalized: bool,
@@ -157,6 +165,8 @@ impl Default for PrioritizationFee {
         PrioritizationFee {
             min_transaction_fee: u64::MAX,
             min_writable_account_fees: HashMap::new(),
+            transaction_fee_samples: Vec::new(),
+            writable_account_fee_samples: HashMap::new(),
             is_finalized: false,
             metrics: PrioritizationFeeMetrics::default(),
         }
@@ -171,8 +181,13 @@ impl PrioritizationFee {
                 if transaction_fee < self.min_transaction_fee {
                     self.min_transaction_fee = transaction_fee;
                 }
+                self.transaction_fee_samples.push(transaction_fee);
 
                 for write_account in writable_accounts {
+                    self.writable_account_fee_samples
+                        .entry(write_account)
+                        .or_default()
+                        .push(transaction_fee);
                     self.min_writable_account_fees
                         .entry(write_account)
                         .and_modify(|write_lock_fee| {
@@ -207,6 +222,12 @@ impl PrioritizationFee {
             return Err(PrioritizationFeeError::BlockIsAlreadyFinalized);
         }
         self.prune_irrelevant_writable_accounts();
+        self.transaction_fee_samples.sort_unstable();
+        self.transaction_fee_samples.shrink_to_fit();
+        for fees in self.writable_account_fee_samples.values_mut() {
+            fees.sort_unstable();
+            fees.shrink_to_fit();
+        }
         self.is_finalized = true;
         Ok(())
     }
@@ -223,6 +244,19 @@ impl PrioritizationFee {
         self.min_writable_account_fees.iter()
     }
 
+    /// The prioritization fee of every transaction in the block, sorted if the block is finalized
+    pub fn get_transaction_fee_samples(&self) -> &[u64] {
+        &self.transaction_fee_samples
+    }
+
+    /// The prioritization fees of the transactions writing to `key`, sorted if the block is
+    /// finalized
+    pub fn get_writable_account_fee_samples(&self, key: &Pubkey) -> Option<&[u64]> {
+        self.writable_account_fee_samples
+            .get(key)
+            .map(Vec::as_slice)
+    }
+
     pub fn get_writable_accounts_count(&self) -> usize {
         self.min_writable_account_fees.len()
     }
@@ -367,6 +401,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fee_samples() {
+        let write_account_a = Pubkey::new_unique();
+        let write_account_b = Pubkey::new_unique();
+
+        let mut prioritization_fee = PrioritizationFee::default();
+        prioritization_fee.update(9, vec![write_account_a]);
+        prioritization_fee.update(0, vec![write_account_a, write_account_b]);
+        prioritization_fee.update(5, vec![write_account_b]);
+        prioritization_fee.update(2, vec![write_account_a]);
+        assert!(prioritization_fee.mark_block_completed().is_ok());
+
+        // samples are kept even for accounts pruned from the minimum fees
+        assert_eq!(
+            prioritization_fee.get_transaction_fee_samples(),
+            &[0, 2, 5, 9]
+        );
+        assert_eq!(
+            prioritization_fee.get_writable_account_fee_samples(&write_account_a),
+            Some(&[0, 2, 9][..])
+        );
+        assert_eq!(
+            prioritization_fee.get_writable_account_fee_samples(&write_account_b),
+            Some(&[0, 5][..])
+        );
+        assert!(prioritization_fee
+            .get_writable_account_fee_samples(&Pubkey::new_unique())
+            .is_none());
+
+        // updates after finalization are ignored
+        prioritization_fee.update(1, vec![write_account_a]);
+        assert_eq!(prioritization_fee.get_transaction_fee_samples().len(), 4);
+    }
+
     #[test]
     fn test_mark_block_completed() {
         let mut prioritization_fee = PrioritizationFee::default();
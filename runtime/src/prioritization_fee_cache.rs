@@ -123,6 +123,27 @@ impl PrioritizationFeeCacheMetrics {
     }
 }
 
+/// Prioritization fee percentiles over recent blocks, see `get_prioritization_fee_estimate()`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrioritizationFeeEstimate {
+    /// Number of finalized blocks the estimate was made from
+    pub num_blocks: usize,
+    /// Number of transaction fees the estimate was made from
+    pub num_samples: usize,
+    /// The fee at each requested percentile, as `(percentile, fee)`
+    pub percentile_fees: Vec<(u16, u64)>,
+}
+
+/// Nearest-rank percentile of `sorted_fees`, with `percentile` in basis points, 0 if there are
+/// none
+fn fee_at_percentile(sorted_fees: &[u64], percentile: u16) -> u64 {
+    let rank = (sorted_fees.len() * percentile as usize).div_ceil(10_000);
+    sorted_fees
+        .get(rank.saturating_sub(1))
+        .copied()
+        .unwrap_or_default()
+}
+
 #[derive(Debug)]
 enum CacheServiceUpdate {
     TransactionUpdate {
@@ -429,6 +450,64 @@ impl PrioritizationFeeCache {
             })
             .collect()
     }
+
+    /// Returns the `percentiles` (in basis points, 0 to 10_000) of the prioritization fees paid in
+    /// the last `lookback_blocks` finalized blocks.
+    /// Without `account_keys` the percentiles are over all transactions. Otherwise they are
+    /// computed over the transactions writing to each account, and the highest fee among the
+    /// accounts is returned for each percentile, since a transaction competes for every account
+    /// it writes to. Accounts that were not written to are ignored, and if none were, the
+    /// percentiles over all transactions are returned.
+    pub fn get_prioritization_fee_estimate(
+        &self,
+        account_keys: &[Pubkey],
+        lookback_blocks: usize,
+        percentiles: &[u16],
+    ) -> PrioritizationFeeEstimate {
+        let (num_blocks, sample_sets) = {
+            let cache = self.cache.read().unwrap();
+            let blocks: Vec<_> = cache.values().rev().take(lookback_blocks).collect();
+            let mut sample_sets: Vec<Vec<u64>> = account_keys
+                .iter()
+                .map(|account_key| {
+                    blocks
+                        .iter()
+                        .filter_map(|block| block.get_writable_account_fee_samples(account_key))
+                        .flatten()
+                        .copied()
+                        .collect::<Vec<_>>()
+                })
+                .filter(|samples| !samples.is_empty())
+                .collect();
+            if sample_sets.is_empty() {
+                sample_sets.push(
+                    blocks
+                        .iter()
+                        .flat_map(|block| block.get_transaction_fee_samples())
+                        .copied()
+                        .collect(),
+                );
+            }
+            (blocks.len(), sample_sets)
+        };
+
+        let mut estimate = PrioritizationFeeEstimate {
+            num_blocks,
+            num_samples: 0,
+            percentile_fees: percentiles
+                .iter()
+                .map(|percentile| (*percentile, 0))
+                .collect(),
+        };
+        for mut samples in sample_sets {
+            samples.sort_unstable();
+            estimate.num_samples += samples.len();
+            for (percentile, fee) in estimate.percentile_fees.iter_mut() {
+                *fee = (*fee).max(fee_at_percentile(&samples, *percentile));
+            }
+        }
+        estimate
+    }
 }
 
 #[cfg(test)]
@@ -859,6 +938,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fee_at_percentile() {
+        assert_eq!(fee_at_percentile(&[], 5_000), 0);
+        assert_eq!(fee_at_percentile(&[4], 0), 4);
+        assert_eq!(fee_at_percentile(&[4], 10_000), 4);
+        let fees = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(fee_at_percentile(&fees, 0), 1);
+        assert_eq!(fee_at_percentile(&fees, 2_500), 3);
+        assert_eq!(fee_at_percentile(&fees, 5_000), 5);
+        assert_eq!(fee_at_percentile(&fees, 9_000), 9);
+        assert_eq!(fee_at_percentile(&fees, 10_000), 10);
+    }
+
+    #[test]
+    fn test_get_prioritization_fee_estimate() {
+        let write_account_a = Pubkey::new_unique();
+        let write_account_b = Pubkey::new_unique();
+        let write_account_c = Pubkey::new_unique();
+        let write_account_d = Pubkey::new_unique();
+
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank0 = Bank::new_for_benches(&genesis_config);
+        let bank_forks = BankForks::new_rw_arc(bank0);
+        let bank = bank_forks.read().unwrap().working_bank();
+        let collector = solana_pubkey::new_rand();
+        let bank1 = Arc::new(Bank::new_from_parent(bank.clone(), &collector, 1));
+        let bank2 = Arc::new(Bank::new_from_parent(bank, &collector, 2));
+
+        let prioritization_fee_cache = PrioritizationFeeCache::default();
+        let percentiles = [2_500, 5_000, 10_000];
+        let estimate =
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[], 150, &percentiles);
+        assert_eq!(estimate.num_blocks, 0);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 0), (5_000, 0), (10_000, 0)]
+        );
+
+        // Fees per block, in format of [fee, write-accounts...]:
+        // slot 1: [1, a, b], [3, a, c]
+        // slot 2: [5, c, d], [7, c, d]
+        let txs = vec![
+            build_sanitized_transaction_for_test(1, &write_account_a, &write_account_b),
+            build_sanitized_transaction_for_test(3, &write_account_a, &write_account_c),
+        ];
+        sync_update(&prioritization_fee_cache, bank1.clone(), txs.iter());
+        sync_finalize_priority_fee_for_test(&prioritization_fee_cache, 1, bank1.bank_id());
+        let txs = vec![
+            build_sanitized_transaction_for_test(5, &write_account_c, &write_account_d),
+            build_sanitized_transaction_for_test(7, &write_account_c, &write_account_d),
+        ];
+        sync_update(&prioritization_fee_cache, bank2.clone(), txs.iter());
+        sync_finalize_priority_fee_for_test(&prioritization_fee_cache, 2, bank2.bank_id());
+
+        // block-wide
+        let estimate =
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[], 150, &percentiles);
+        assert_eq!(estimate.num_blocks, 2);
+        assert_eq!(estimate.num_samples, 4);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 1), (5_000, 3), (10_000, 7)]
+        );
+
+        // only the latest block
+        let estimate =
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[], 1, &percentiles);
+        assert_eq!(estimate.num_blocks, 1);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 5), (5_000, 5), (10_000, 7)]
+        );
+
+        // one account
+        let estimate = prioritization_fee_cache.get_prioritization_fee_estimate(
+            &[write_account_a],
+            150,
+            &percentiles,
+        );
+        assert_eq!(estimate.num_samples, 2);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 1), (5_000, 1), (10_000, 3)]
+        );
+
+        // the highest fee of the accounts at each percentile
+        let estimate = prioritization_fee_cache.get_prioritization_fee_estimate(
+            &[write_account_a, write_account_c],
+            150,
+            &percentiles,
+        );
+        assert_eq!(estimate.num_samples, 5);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 3), (5_000, 5), (10_000, 7)]
+        );
+
+        // accounts that were not written to fall back to block-wide fees
+        let estimate = prioritization_fee_cache.get_prioritization_fee_estimate(
+            &[Pubkey::new_unique()],
+            150,
+            &percentiles,
+        );
+        assert_eq!(estimate.num_samples, 4);
+        assert_eq!(
+            estimate.percentile_fees,
+            vec![(2_500, 1), (5_000, 3), (10_000, 7)]
+        );
+    }
+
     #[test]
     fn test_purge_duplicated_bank() {
         // duplicated bank can exists for same slot before OC.
@@ -46,6 +46,10 @@ pub struct SnapshotConfig {
 
     // Thread niceness adjustment for snapshot packager service
     pub packager_thread_niceness_adj: i8,
+
+    /// Whether snapshot archives may contain tiered storages. Validators
+    /// without support for tiered storages cannot unpack such archives.
+    pub archive_tiered_storages: bool,
 }
 
 impl Default for SnapshotConfig {
@@ -67,6 +71,7 @@ impl Default for SnapshotConfig {
                 snapshot_utils::DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             accounts_hash_debug_verify: false,
             packager_thread_niceness_adj: 0,
+            archive_tiered_storages: false,
         }
     }
 }
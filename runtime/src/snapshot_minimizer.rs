@@ -61,25 +61,58 @@ impl<'a> SnapshotMinimizer<'a> {
             minimized_account_set: transaction_account_set,
         };
 
-        minimizer.add_accounts(Self::get_active_bank_features, "active bank features");
-        minimizer.add_accounts(Self::get_inactive_bank_features, "inactive bank features");
-        minimizer.add_accounts(Self::get_static_runtime_accounts, "static runtime accounts");
-        minimizer.add_accounts(Self::get_reserved_accounts, "reserved accounts");
-
         minimizer.add_accounts(
             Self::get_rent_collection_accounts,
             "rent collection accounts",
         );
-        minimizer.add_accounts(Self::get_vote_accounts, "vote accounts");
-        minimizer.add_accounts(Self::get_stake_accounts, "stake accounts");
-        minimizer.add_accounts(Self::get_owner_accounts, "owner accounts");
-        minimizer.add_accounts(Self::get_programdata_accounts, "programdata accounts");
+        minimizer.add_required_accounts_and_minimize();
+    }
 
-        minimizer.minimize_accounts_db();
+    /// Removes all accounts except those in `account_set`, those owned by any of `program_ids`,
+    /// and those needed to boot a bank from the snapshot (features, sysvars, builtins, vote and
+    /// stake accounts, and the owners and programdata of every kept account).
+    ///
+    /// Unlike `minimize`, the result is not meant for replaying a slot range, but for creating
+    /// small snapshots with realistic state for specific programs.  Accounts stored in the bank's
+    /// own slot are always kept.
+    ///
+    /// This function will modify accounts_db by removing all other accounts, and update the
+    /// bank's capitalization.
+    pub fn minimize_to_accounts(
+        bank: &'a Bank,
+        account_set: DashSet<Pubkey>,
+        program_ids: &[Pubkey],
+    ) {
+        let minimizer = SnapshotMinimizer {
+            bank,
+            starting_slot: bank.slot(),
+            ending_slot: bank.slot(),
+            minimized_account_set: account_set,
+        };
+
+        minimizer.add_accounts(
+            |minimizer| minimizer.get_program_owned_accounts(program_ids),
+            "program owned accounts",
+        );
+        minimizer.add_required_accounts_and_minimize();
+    }
+
+    /// Adds the accounts every minimized snapshot needs, then removes all other accounts
+    fn add_required_accounts_and_minimize(self) {
+        self.add_accounts(Self::get_active_bank_features, "active bank features");
+        self.add_accounts(Self::get_inactive_bank_features, "inactive bank features");
+        self.add_accounts(Self::get_static_runtime_accounts, "static runtime accounts");
+        self.add_accounts(Self::get_reserved_accounts, "reserved accounts");
+        self.add_accounts(Self::get_vote_accounts, "vote accounts");
+        self.add_accounts(Self::get_stake_accounts, "stake accounts");
+        self.add_accounts(Self::get_owner_accounts, "owner accounts");
+        self.add_accounts(Self::get_programdata_accounts, "programdata accounts");
+
+        self.minimize_accounts_db();
 
         // Update accounts_cache and capitalization
-        minimizer.bank.force_flush_accounts_cache();
-        minimizer.bank.set_capitalization();
+        self.bank.force_flush_accounts_cache();
+        self.bank.set_capitalization();
     }
 
     /// Helper function to measure time and number of accounts added
@@ -171,6 +204,29 @@ impl<'a> SnapshotMinimizer<'a> {
         self.bank.get_stake_accounts(&self.minimized_account_set);
     }
 
+    /// Used to get program owned accounts in `minimize_to_accounts`
+    /// Add each program in `program_ids`, and every account it owns, to `minimized_account_set`
+    fn get_program_owned_accounts(&self, program_ids: &[Pubkey]) {
+        let program_ids: HashSet<_> = program_ids.iter().copied().collect();
+        for program_id in &program_ids {
+            self.minimized_account_set.insert(*program_id);
+        }
+        // a single scan keeping only the pubkeys, rather than collecting every
+        // account of each program
+        self.bank
+            .scan_all_accounts(
+                |item| {
+                    if let Some((pubkey, account, _slot)) = item {
+                        if account.lamports() != 0 && program_ids.contains(account.owner()) {
+                            self.minimized_account_set.insert(*pubkey);
+                        }
+                    }
+                },
+                false,
+            )
+            .expect("scanning accounts is never aborted here");
+    }
+
     /// Used to get owner accounts in `minimize`
     /// For each account in `minimized_account_set` adds the owner account's pubkey to `minimized_account_set`.
     fn get_owner_accounts(&self) {
@@ -561,6 +617,44 @@ mod tests {
         assert!(minimizer.minimized_account_set.contains(&owner_pubkey));
     }
 
+    #[test]
+    fn test_minimization_get_program_owned_accounts() {
+        solana_logger::setup();
+
+        let (genesis_config, _) = create_genesis_config(1_000_000);
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+
+        let program_id = solana_pubkey::new_rand();
+        let other_program_id = solana_pubkey::new_rand();
+        let owned_pubkeys: Vec<_> = (0..3).map(|_| solana_pubkey::new_rand()).collect();
+        let other_pubkey = solana_pubkey::new_rand();
+        for pubkey in &owned_pubkeys {
+            bank.store_account(pubkey, &AccountSharedData::new(1, 0, &program_id));
+        }
+        bank.store_account(
+            &other_pubkey,
+            &AccountSharedData::new(1, 0, &other_program_id),
+        );
+
+        let minimizer = SnapshotMinimizer {
+            bank: &bank,
+            starting_slot: 0,
+            ending_slot: 0,
+            minimized_account_set: DashSet::new(),
+        };
+        minimizer.get_program_owned_accounts(&[program_id]);
+
+        assert_eq!(
+            minimizer.minimized_account_set.len(),
+            owned_pubkeys.len() + 1
+        );
+        assert!(minimizer.minimized_account_set.contains(&program_id));
+        for pubkey in &owned_pubkeys {
+            assert!(minimizer.minimized_account_set.contains(pubkey));
+        }
+        assert!(!minimizer.minimized_account_set.contains(&other_pubkey));
+    }
+
     #[test]
     fn test_minimization_add_programdata_accounts() {
         solana_logger::setup();
@@ -12,8 +12,9 @@ use {
         snapshot_config::SnapshotConfig,
         snapshot_hash::SnapshotHash,
         snapshot_package::{SnapshotKind, SnapshotPackage},
-        snapshot_utils::snapshot_storage_rebuilder::{
-            RebuiltSnapshotStorage, SnapshotStorageRebuilder,
+        snapshot_utils::{
+            seekable_archive::SeekableZstdEncoder,
+            snapshot_storage_rebuilder::{RebuiltSnapshotStorage, SnapshotStorageRebuilder},
         },
     },
     bzip2::bufread::BzDecoder,
@@ -62,6 +63,7 @@ use {
 };
 
 mod archive_format;
+pub mod seekable_archive;
 pub mod snapshot_storage_rebuilder;
 pub use archive_format::*;
 
@@ -84,8 +86,8 @@ pub const DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN: NonZeroUsize =
     unsafe { NonZeroUsize::new_unchecked(2) };
 pub const DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN: NonZeroUsize =
     unsafe { NonZeroUsize::new_unchecked(4) };
-pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
-pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
+pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4|seekable\.tar\.zst)$";
+pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4|seekable\.tar\.zst)$";
 
 #[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
@@ -501,6 +503,9 @@ pub enum ArchiveSnapshotPackageError {
     #[error("failed to archive account storage file '{1}': {0}")]
     ArchiveAccountStorageFile(#[source] IoError, PathBuf),
 
+    #[error("account storage file '{0}' is a tiered storage, and archiving those is not enabled")]
+    ArchiveTieredStorage(PathBuf),
+
     #[error("failed to archive snapshot: {0}")]
     FinishArchive(#[source] IoError),
 
@@ -810,6 +815,20 @@ pub fn serialize_and_archive_snapshot_package(
         }
     };
 
+    // Other validators may not be able to unpack tiered storages, so they are
+    // only archived when explicitly enabled
+    if !snapshot_config.archive_tiered_storages {
+        if let Some(storage) = snapshot_storages
+            .iter()
+            .find(|storage| matches!(storage.accounts, AccountsFile::TieredStorage(_)))
+        {
+            return Err(ArchiveSnapshotPackageError::ArchiveTieredStorage(
+                storage.accounts.path().to_path_buf(),
+            )
+            .into());
+        }
+    }
+
     let snapshot_archive_info = archive_snapshot(
         snapshot_kind,
         snapshot_slot,
@@ -1038,24 +1057,36 @@ fn archive_snapshot(
         let mut archive_file = fs::File::create(&staging_archive_path)
             .map_err(|err| E::CreateArchiveFile(err, staging_archive_path.clone()))?;
 
-        let do_archive_files = |encoder: &mut dyn Write| -> std::result::Result<(), E> {
-            let mut archive = tar::Builder::new(encoder);
+        // Appends all the snapshot files to `archive`, calling `end_entry` with the path in the
+        // archive after each of the version file, the snapshots dir, and every account storage
+        fn append_snapshot_files<W: Write>(
+            archive: &mut tar::Builder<W>,
+            staging_version_file: &Path,
+            staging_snapshots_dir: &Path,
+            snapshot_storages: &[Arc<AccountStorageEntry>],
+            mut end_entry: impl FnMut(&mut W, &Path) -> std::result::Result<(), E>,
+        ) -> std::result::Result<(), E> {
             // Serialize the version and snapshots files before accounts so we can quickly determine the version
             // and other bank fields. This is necessary if we want to interleave unpacking with reconstruction
             archive
-                .append_path_with_name(&staging_version_file, SNAPSHOT_VERSION_FILENAME)
+                .append_path_with_name(staging_version_file, SNAPSHOT_VERSION_FILENAME)
                 .map_err(E::ArchiveVersionFile)?;
+            end_entry(archive.get_mut(), Path::new(SNAPSHOT_VERSION_FILENAME))?;
             archive
-                .append_dir_all(SNAPSHOTS_DIR, &staging_snapshots_dir)
+                .append_dir_all(SNAPSHOTS_DIR, staging_snapshots_dir)
                 .map_err(E::ArchiveSnapshotsDir)?;
+            end_entry(archive.get_mut(), Path::new(SNAPSHOTS_DIR))?;
 
             for storage in snapshot_storages {
-                let path_in_archive = Path::new(ACCOUNTS_DIR)
-                    .join(AccountsFile::file_name(storage.slot(), storage.id()));
+                let path_in_archive = Path::new(ACCOUNTS_DIR).join(
+                    storage
+                        .accounts
+                        .storage_file_name(storage.slot(), storage.id()),
+                );
                 match storage.accounts.internals_for_archive() {
                     InternalsForArchive::Mmap(data) => {
                         let mut header = tar::Header::new_gnu();
-                        header.set_path(path_in_archive).map_err(|err| {
+                        header.set_path(&path_in_archive).map_err(|err| {
                             E::ArchiveAccountStorageFile(err, storage.path().to_path_buf())
                         })?;
                         header.set_size(storage.capacity());
@@ -1063,12 +1094,24 @@ fn archive_snapshot(
                         archive.append(&header, data)
                     }
                     InternalsForArchive::FileIo(path) => {
-                        archive.append_path_with_name(path, path_in_archive)
+                        archive.append_path_with_name(path, &path_in_archive)
                     }
                 }
                 .map_err(|err| E::ArchiveAccountStorageFile(err, storage.path().to_path_buf()))?;
+                end_entry(archive.get_mut(), &path_in_archive)?;
             }
+            Ok(())
+        }
 
+        let do_archive_files = |encoder: &mut dyn Write| -> std::result::Result<(), E> {
+            let mut archive = tar::Builder::new(encoder);
+            append_snapshot_files(
+                &mut archive,
+                &staging_version_file,
+                &staging_snapshots_dir,
+                snapshot_storages,
+                |_, _| Ok(()),
+            )?;
             archive.into_inner().map_err(E::FinishArchive)?;
             Ok(())
         };
@@ -1105,6 +1148,22 @@ fn archive_snapshot(
             ArchiveFormat::Tar => {
                 do_archive_files(&mut archive_file)?;
             }
+            ArchiveFormat::TarZstdSeekable => {
+                // Every file is compressed into its own frame so that the archive can be unpacked
+                // in parallel and individual account storages can be extracted.
+                let encoder =
+                    SeekableZstdEncoder::new(archive_file, 1).map_err(E::CreateEncoder)?;
+                let mut archive = tar::Builder::new(encoder);
+                append_snapshot_files(
+                    &mut archive,
+                    &staging_version_file,
+                    &staging_snapshots_dir,
+                    snapshot_storages,
+                    |encoder, path| encoder.end_frame(path).map_err(E::FinishEncoder),
+                )?;
+                let encoder = archive.into_inner().map_err(E::FinishArchive)?;
+                encoder.finish().map_err(E::FinishEncoder)?;
+            }
         };
     }
 
@@ -1485,7 +1544,9 @@ pub fn hard_link_storages_to_snapshot(
         )?;
         // The appendvec could be recycled, so its filename may not be consistent to the slot and id.
         // Use the storage slot and id to compose a consistent file name for the hard-link file.
-        let hardlink_filename = AccountsFile::file_name(storage.slot(), storage.id());
+        let hardlink_filename = storage
+            .accounts
+            .storage_file_name(storage.slot(), storage.id());
         let hard_link_path = snapshot_hardlink_dir.join(hardlink_filename);
         fs::hard_link(storage_path, &hard_link_path).map_err(|err| {
             HardLinkStoragesToSnapshotError::HardLinkStorage(
@@ -1601,7 +1662,17 @@ fn streaming_unarchive_snapshot(
     snapshot_archive_path: PathBuf,
     archive_format: ArchiveFormat,
     num_threads: usize,
-) -> Vec<JoinHandle<()>> {
+) -> Result<Vec<JoinHandle<()>>> {
+    if archive_format == ArchiveFormat::TarZstdSeekable {
+        return streaming_unarchive_seekable_snapshot(
+            file_sender,
+            account_paths,
+            ledger_dir,
+            snapshot_archive_path,
+            num_threads,
+        );
+    }
+
     let account_paths = Arc::new(account_paths);
     let ledger_dir = Arc::new(ledger_dir);
     let shared_buffer = untar_snapshot_create_shared_buffer(&snapshot_archive_path, archive_format);
@@ -1614,7 +1685,7 @@ fn streaming_unarchive_snapshot(
         })
         .collect();
 
-    archives
+    let handles = archives
         .into_iter()
         .enumerate()
         .map(|(thread_index, archive)| {
@@ -1632,7 +1703,77 @@ fn streaming_unarchive_snapshot(
                 thread_index,
             )
         })
-        .collect()
+        .collect();
+    Ok(handles)
+}
+
+/// Streams unpacked files across channel, decompressing the frames of a seekable archive in
+/// parallel
+///
+/// Unlike the other archive formats, no thread needs to read the whole archive.  Each thread
+/// decompresses and unpacks its share of the frames.  The unpack limits are shared by all the
+/// frames, so they apply to the snapshot as a whole.
+fn streaming_unarchive_seekable_snapshot(
+    file_sender: Sender<PathBuf>,
+    account_paths: Vec<PathBuf>,
+    ledger_dir: PathBuf,
+    snapshot_archive_path: PathBuf,
+    num_threads: usize,
+) -> Result<Vec<JoinHandle<()>>> {
+    let frames =
+        seekable_archive::read_seekable_archive_index(&snapshot_archive_path).map_err(|err| {
+            IoError::other(format!(
+                "failed to read index of seekable snapshot archive '{}': {err}",
+                snapshot_archive_path.display(),
+            ))
+        })?;
+    let frames = Arc::new(frames);
+    let totals = Arc::new(hardened_unpack::UnpackTotals::default());
+    let account_paths = Arc::new(account_paths);
+    let ledger_dir = Arc::new(ledger_dir);
+    let snapshot_archive_path = Arc::new(snapshot_archive_path);
+
+    let handles = (0..num_threads)
+        .map(|thread_index| {
+            let parallel_selector = ParallelSelector {
+                index: thread_index,
+                divisions: num_threads,
+            };
+            let file_sender = file_sender.clone();
+            let frames = frames.clone();
+            let account_paths = account_paths.clone();
+            let ledger_dir = ledger_dir.clone();
+            let snapshot_archive_path = snapshot_archive_path.clone();
+            let totals = totals.clone();
+            Builder::new()
+                .name(format!("solUnpkSnpsht{thread_index:02}"))
+                .spawn(move || {
+                    for frame in frames
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| parallel_selector.select_index(*index))
+                        .map(|(_, frame)| frame)
+                    {
+                        let reader = seekable_archive::open_seekable_archive_frame(
+                            snapshot_archive_path.as_path(),
+                            frame,
+                        )
+                        .unwrap();
+                        hardened_unpack::streaming_unpack_snapshot_with_totals(
+                            &mut Archive::new(reader),
+                            ledger_dir.as_path(),
+                            &account_paths,
+                            None,
+                            &file_sender,
+                            &totals,
+                        )
+                        .unwrap();
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    Ok(handles)
 }
 
 /// BankSnapshotInfo::new_from_dir() requires a few meta files to accept a snapshot dir
@@ -1687,6 +1828,14 @@ fn unarchive_snapshot(
         .tempdir_in(bank_snapshots_dir)?;
     let unpacked_snapshots_dir = unpack_dir.path().join("snapshots");
 
+    // The frames of a seekable archive are decompressed independently, so unpacking it scales
+    // with the number of threads instead of being bound by a single decompression stream.
+    let parallel_divisions = if archive_format == ArchiveFormat::TarZstdSeekable {
+        (num_cpus::get_physical() / 2).max(parallel_divisions)
+    } else {
+        parallel_divisions
+    };
+
     let (file_sender, file_receiver) = crossbeam_channel::unbounded();
     streaming_unarchive_snapshot(
         file_sender,
@@ -1695,7 +1844,7 @@ fn unarchive_snapshot(
         snapshot_archive_path.as_ref().to_path_buf(),
         archive_format,
         parallel_divisions,
-    );
+    )?;
 
     let num_rebuilder_threads = num_cpus::get_physical()
         .saturating_sub(parallel_divisions)
@@ -2282,7 +2431,7 @@ fn untar_snapshot_create_shared_buffer(
     match archive_format {
         ArchiveFormat::TarBzip2 => SharedBuffer::new(BzDecoder::new(BufReader::new(open_file()))),
         ArchiveFormat::TarGzip => SharedBuffer::new(GzDecoder::new(BufReader::new(open_file()))),
-        ArchiveFormat::TarZstd => SharedBuffer::new(
+        ArchiveFormat::TarZstd | ArchiveFormat::TarZstdSeekable => SharedBuffer::new(
             zstd::stream::read::Decoder::new(BufReader::new(open_file())).unwrap(),
         ),
         ArchiveFormat::TarLz4 => {
@@ -2765,6 +2914,18 @@ mod tests {
             .unwrap(),
             (45, SnapshotHash(Hash::default()), ArchiveFormat::TarLz4)
         );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-46-{}.seekable.tar.zst",
+                Hash::default()
+            ))
+            .unwrap(),
+            (
+                46,
+                SnapshotHash(Hash::default()),
+                ArchiveFormat::TarZstdSeekable
+            )
+        );
 
         assert!(parse_full_snapshot_archive_filename("invalid").is_err());
         assert!(
@@ -0,0 +1,546 @@
+//! Seekable zstd snapshot archives
+//!
+//! A seekable archive is a regular tar stream compressed into many independent zstd frames,
+//! where every frame holds whole tar entries (normally a single account storage file).  The
+//! archive ends with a zstd skippable frame that holds an index of all the data frames: their
+//! location in the archive, their decompressed size, and the path of the tar entry they hold.
+//!
+//! Since skippable frames are ignored by zstd decoders, a seekable archive is still a valid
+//! `.tar.zst` stream and can be unpacked sequentially.  With the index, frames can instead be
+//! decompressed in parallel, or individual files can be pulled out without reading the rest of
+//! the archive.
+//!
+//! Layout of the index frame (all integers are little endian):
+//!
+//! | field                                                    | size     |
+//! |----------------------------------------------------------|----------|
+//! | skippable frame magic                                    | 4        |
+//! | skippable frame content size                             | 4        |
+//! | entries: compressed offset, compressed len, decompressed |          |
+//! |          len, path len, path                             | 26 + len |
+//! | number of entries                                        | 4        |
+//! | size of entries                                          | 4        |
+//! | index magic                                              | 4        |
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+/// The zstd skippable frame magic used for the index frame
+const SKIPPABLE_FRAME_MAGIC: u32 = 0x184D_2A5E;
+/// Identifies the skippable frame at the end of the archive as a seekable archive index
+const SEEKABLE_INDEX_MAGIC: u32 = 0x5EEC_A1DE;
+const SKIPPABLE_FRAME_HEADER_SIZE: u64 = 8;
+const INDEX_FOOTER_SIZE: u64 = 12;
+/// The fixed size part of each index entry, excluding the path
+const INDEX_ENTRY_FIXED_SIZE: usize = 3 * std::mem::size_of::<u64>() + std::mem::size_of::<u16>();
+
+/// The location and contents of one zstd frame in a seekable archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeekableArchiveFrame {
+    /// Path, in the tar archive, of the first entry in this frame
+    ///
+    /// Empty for the frame that holds the end-of-archive marker.
+    pub path: PathBuf,
+    /// Offset of the frame from the start of the archive
+    pub compressed_offset: u64,
+    /// Size of the compressed frame
+    pub compressed_len: u64,
+    /// Size of the frame's tar data once decompressed
+    pub decompressed_len: u64,
+}
+
+/// Writes a tar stream as a seekable zstd archive
+///
+/// Callers must call `end_frame()` after each tar entry that should be addressable on its own,
+/// and `finish()` once the whole tar stream has been written.
+pub struct SeekableZstdEncoder<W: Write> {
+    state: Option<EncoderState<W>>,
+    level: i32,
+    frames: Vec<SeekableArchiveFrame>,
+    frame_offset: u64,
+    frame_decompressed_len: u64,
+}
+
+enum EncoderState<W: Write> {
+    Idle(CountingWriter<W>),
+    InFrame(zstd::stream::write::Encoder<'static, CountingWriter<W>>),
+}
+
+impl<W: Write> SeekableZstdEncoder<W> {
+    pub fn new(writer: W, level: i32) -> io::Result<Self> {
+        Ok(Self {
+            state: Some(EncoderState::Idle(CountingWriter {
+                inner: writer,
+                count: 0,
+            })),
+            level,
+            frames: Vec::new(),
+            frame_offset: 0,
+            frame_decompressed_len: 0,
+        })
+    }
+
+    /// Ends the current frame, if anything has been written to it
+    ///
+    /// `path` is the path of the first tar entry in the frame, and is recorded in the index.
+    pub fn end_frame(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let writer = match self.state.take() {
+            Some(EncoderState::InFrame(encoder)) => encoder.finish()?,
+            state => {
+                // nothing has been written since the last frame ended
+                self.state = state;
+                return Ok(());
+            }
+        };
+
+        self.frames.push(SeekableArchiveFrame {
+            path: path.as_ref().to_path_buf(),
+            compressed_offset: self.frame_offset,
+            compressed_len: writer.count - self.frame_offset,
+            decompressed_len: self.frame_decompressed_len,
+        });
+        self.frame_offset = writer.count;
+        self.frame_decompressed_len = 0;
+        self.state = Some(EncoderState::Idle(writer));
+        Ok(())
+    }
+
+    /// Ends the last frame, writes the index, and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.end_frame("")?;
+        let Some(EncoderState::Idle(mut writer)) = self.state.take() else {
+            unreachable!("the encoder is idle after ending a frame");
+        };
+
+        let mut entries = Vec::new();
+        for frame in &self.frames {
+            let path = frame.path.to_str().ok_or_else(|| {
+                io::Error::other(format!("invalid path in archive: {}", frame.path.display()))
+            })?;
+            let path_len = u16::try_from(path.len())
+                .map_err(|_| io::Error::other(format!("path in archive is too long: {path}")))?;
+            entries.extend_from_slice(&frame.compressed_offset.to_le_bytes());
+            entries.extend_from_slice(&frame.compressed_len.to_le_bytes());
+            entries.extend_from_slice(&frame.decompressed_len.to_le_bytes());
+            entries.extend_from_slice(&path_len.to_le_bytes());
+            entries.extend_from_slice(path.as_bytes());
+        }
+        let index_too_large = || io::Error::other("seekable archive index is too large");
+        let num_entries = u32::try_from(self.frames.len()).map_err(|_| index_too_large())?;
+        let entries_len = u32::try_from(entries.len()).map_err(|_| index_too_large())?;
+        let content_size = entries_len
+            .checked_add(INDEX_FOOTER_SIZE as u32)
+            .ok_or_else(index_too_large)?;
+
+        writer.write_all(&SKIPPABLE_FRAME_MAGIC.to_le_bytes())?;
+        writer.write_all(&content_size.to_le_bytes())?;
+        writer.write_all(&entries)?;
+        writer.write_all(&num_entries.to_le_bytes())?;
+        writer.write_all(&entries_len.to_le_bytes())?;
+        writer.write_all(&SEEKABLE_INDEX_MAGIC.to_le_bytes())?;
+        writer.flush()?;
+        Ok(writer.inner)
+    }
+}
+
+impl<W: Write> Write for SeekableZstdEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut encoder = match self.state.take() {
+            Some(EncoderState::InFrame(encoder)) => encoder,
+            Some(EncoderState::Idle(writer)) => {
+                zstd::stream::write::Encoder::new(writer, self.level)?
+            }
+            None => return Err(io::Error::other("seekable archive encoder is poisoned")),
+        };
+        let result = encoder.write(buf);
+        self.state = Some(EncoderState::InFrame(encoder));
+        let written = result?;
+        self.frame_decompressed_len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.state {
+            Some(EncoderState::InFrame(encoder)) => encoder.flush(),
+            Some(EncoderState::Idle(writer)) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Reads the frame index of a seekable archive
+pub fn read_seekable_archive_index(
+    archive_path: impl AsRef<Path>,
+) -> io::Result<Vec<SeekableArchiveFrame>> {
+    let archive_path = archive_path.as_ref();
+    let invalid = |reason: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "invalid seekable archive '{}': {reason}",
+                archive_path.display()
+            ),
+        )
+    };
+
+    let mut file = File::open(archive_path)?;
+    let archive_len = file.metadata()?.len();
+    if archive_len < SKIPPABLE_FRAME_HEADER_SIZE + INDEX_FOOTER_SIZE {
+        return Err(invalid("too small"));
+    }
+
+    let mut footer = [0u8; INDEX_FOOTER_SIZE as usize];
+    file.seek(SeekFrom::End(-(INDEX_FOOTER_SIZE as i64)))?;
+    file.read_exact(&mut footer)?;
+    let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+    let num_entries = read_u32(&footer[0..4]);
+    let entries_len = u64::from(read_u32(&footer[4..8]));
+    if read_u32(&footer[8..12]) != SEEKABLE_INDEX_MAGIC {
+        return Err(invalid("missing index"));
+    }
+
+    let index_frame_len = SKIPPABLE_FRAME_HEADER_SIZE + entries_len + INDEX_FOOTER_SIZE;
+    let index_frame_offset = archive_len
+        .checked_sub(index_frame_len)
+        .ok_or_else(|| invalid("index is larger than the archive"))?;
+    let mut index = vec![0u8; (SKIPPABLE_FRAME_HEADER_SIZE + entries_len) as usize];
+    file.seek(SeekFrom::Start(index_frame_offset))?;
+    file.read_exact(&mut index)?;
+    if read_u32(&index[0..4]) != SKIPPABLE_FRAME_MAGIC
+        || u64::from(read_u32(&index[4..8])) != entries_len + INDEX_FOOTER_SIZE
+    {
+        return Err(invalid("malformed index frame header"));
+    }
+
+    // every entry takes at least its fixed size, so this bounds the untrusted entry count
+    if u64::from(num_entries) > entries_len / INDEX_ENTRY_FIXED_SIZE as u64 {
+        return Err(invalid("too many index entries"));
+    }
+    let mut entries = &index[SKIPPABLE_FRAME_HEADER_SIZE as usize..];
+    let mut frames = Vec::with_capacity(num_entries as usize);
+    let mut expected_offset = 0;
+    for _ in 0..num_entries {
+        if entries.len() < INDEX_ENTRY_FIXED_SIZE {
+            return Err(invalid("truncated index entry"));
+        }
+        let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+        let compressed_offset = read_u64(&entries[0..8]);
+        let compressed_len = read_u64(&entries[8..16]);
+        let decompressed_len = read_u64(&entries[16..24]);
+        let path_len = u16::from_le_bytes(entries[24..26].try_into().unwrap()) as usize;
+        let path = entries
+            .get(INDEX_ENTRY_FIXED_SIZE..INDEX_ENTRY_FIXED_SIZE + path_len)
+            .ok_or_else(|| invalid("truncated index entry path"))?;
+        let path = std::str::from_utf8(path).map_err(|_| invalid("index entry path"))?;
+        // frames must follow each other in order, so none of them is unpacked twice
+        if compressed_offset != expected_offset || compressed_len == 0 {
+            return Err(invalid("frames overlap, repeat, or are out of order"));
+        }
+        expected_offset = compressed_offset
+            .checked_add(compressed_len)
+            .filter(|frame_end| *frame_end <= index_frame_offset)
+            .ok_or_else(|| invalid("frame is out of bounds"))?;
+        frames.push(SeekableArchiveFrame {
+            path: PathBuf::from(path),
+            compressed_offset,
+            compressed_len,
+            decompressed_len,
+        });
+        entries = &entries[INDEX_ENTRY_FIXED_SIZE + path_len..];
+    }
+    if !entries.is_empty() {
+        return Err(invalid("trailing bytes in index"));
+    }
+    if expected_offset != index_frame_offset {
+        return Err(invalid("frames do not cover the archive"));
+    }
+
+    Ok(frames)
+}
+
+/// Returns true if `archive_path` is a seekable archive, i.e. it ends with a frame index
+pub fn is_seekable_archive(archive_path: impl AsRef<Path>) -> bool {
+    read_seekable_archive_index(archive_path).is_ok()
+}
+
+/// Opens a reader over the decompressed tar data of a single frame
+pub fn open_seekable_archive_frame(
+    archive_path: impl AsRef<Path>,
+    frame: &SeekableArchiveFrame,
+) -> io::Result<impl Read> {
+    let mut file = File::open(archive_path)?;
+    file.seek(SeekFrom::Start(frame.compressed_offset))?;
+    let decoder =
+        zstd::stream::read::Decoder::with_buffer(BufReader::new(file.take(frame.compressed_len)))?
+            .single_frame();
+    Ok(decoder)
+}
+
+/// Extracts the frames holding each of `paths` from a seekable archive into `output_dir`
+///
+/// Only the requested frames are read from the archive.  Files keep their path within the
+/// archive, relative to `output_dir`.  Returns the paths of all the unpacked entries.
+pub fn extract_seekable_archive_files(
+    archive_path: impl AsRef<Path>,
+    paths: &[impl AsRef<Path>],
+    output_dir: impl AsRef<Path>,
+) -> io::Result<Vec<PathBuf>> {
+    let archive_path = archive_path.as_ref();
+    let output_dir = output_dir.as_ref();
+    let frames = read_seekable_archive_index(archive_path)?;
+
+    let mut unpacked = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let frame = frames
+            .iter()
+            .find(|frame| frame.path == path)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "'{}' not found in seekable archive '{}'",
+                        path.display(),
+                        archive_path.display()
+                    ),
+                )
+            })?;
+        let mut archive = tar::Archive::new(open_seekable_archive_frame(archive_path, frame)?);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let entry_path = entry.path()?.into_owned();
+            // unpack_in() refuses paths that would escape `output_dir`
+            if entry.unpack_in(output_dir)? {
+                unpacked.push(output_dir.join(entry_path));
+            }
+        }
+    }
+    Ok(unpacked)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Cursor, tempfile::NamedTempFile};
+
+    fn write_test_archive(entries: &[(&str, Vec<u8>)]) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        let encoder = SeekableZstdEncoder::new(file.reopen().unwrap(), 1).unwrap();
+        let mut archive = tar::Builder::new(encoder);
+        for (path, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_cksum();
+            archive
+                .append_data(&mut header, path, data.as_slice())
+                .unwrap();
+            archive.get_mut().end_frame(path).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap();
+        file
+    }
+
+    fn test_entries() -> Vec<(&'static str, Vec<u8>)> {
+        vec![
+            ("version", b"1.2.0".to_vec()),
+            ("accounts/1.1", vec![1; 10_000]),
+            ("accounts/2.2", vec![]),
+            ("accounts/3.3", (0..100_000).map(|i| i as u8).collect()),
+        ]
+    }
+
+    fn read_entries(reader: impl Read) -> Vec<(String, Vec<u8>)> {
+        tar::Archive::new(reader)
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_str().unwrap().to_string();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                (path, data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_seekable_archive_index() {
+        let entries = test_entries();
+        let archive = write_test_archive(&entries);
+
+        let frames = read_seekable_archive_index(archive.path()).unwrap();
+        // one frame per entry, plus one for the end-of-archive marker
+        assert_eq!(frames.len(), entries.len() + 1);
+        for (frame, (path, _)) in frames.iter().zip(&entries) {
+            assert_eq!(frame.path, Path::new(path));
+        }
+        assert_eq!(frames.last().unwrap().path, Path::new(""));
+
+        let mut expected_offset = 0;
+        for frame in &frames {
+            assert_eq!(frame.compressed_offset, expected_offset);
+            assert_eq!(frame.decompressed_len % 512, 0);
+            expected_offset += frame.compressed_len;
+        }
+        assert!(is_seekable_archive(archive.path()));
+    }
+
+    #[test]
+    fn test_seekable_archive_frames() {
+        let entries = test_entries();
+        let archive = write_test_archive(&entries);
+        let frames = read_seekable_archive_index(archive.path()).unwrap();
+
+        // each frame can be unpacked on its own, in any order
+        for (frame, (path, data)) in frames.iter().zip(&entries).rev() {
+            let unpacked =
+                read_entries(open_seekable_archive_frame(archive.path(), frame).unwrap());
+            assert_eq!(unpacked, vec![(path.to_string(), data.clone())]);
+        }
+    }
+
+    #[test]
+    fn test_extract_seekable_archive_files() {
+        let entries = test_entries();
+        let archive = write_test_archive(&entries);
+        let output_dir = tempfile::TempDir::new().unwrap();
+
+        let unpacked = extract_seekable_archive_files(
+            archive.path(),
+            &["accounts/3.3", "version"],
+            output_dir.path(),
+        )
+        .unwrap();
+        assert_eq!(
+            unpacked,
+            vec![
+                output_dir.path().join("accounts/3.3"),
+                output_dir.path().join("version"),
+            ]
+        );
+        assert_eq!(std::fs::read(&unpacked[0]).unwrap(), entries[3].1);
+        assert_eq!(std::fs::read(&unpacked[1]).unwrap(), entries[0].1);
+        assert!(!output_dir.path().join("accounts/1.1").exists());
+
+        assert!(
+            extract_seekable_archive_files(archive.path(), &["missing"], output_dir.path())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_seekable_archive_is_zstd_stream() {
+        let entries = test_entries();
+        let archive = write_test_archive(&entries);
+
+        let decoder =
+            zstd::stream::read::Decoder::new(File::open(archive.path()).unwrap()).unwrap();
+        let unpacked = read_entries(decoder);
+        let expected: Vec<_> = entries
+            .into_iter()
+            .map(|(path, data)| (path.to_string(), data))
+            .collect();
+        assert_eq!(unpacked, expected);
+    }
+
+    /// Replaces the index of `archive`, which starts at `data_len`, with one listing `frames`
+    /// but claiming `num_entries`
+    fn rewrite_index(
+        archive: &NamedTempFile,
+        data_len: u64,
+        frames: &[SeekableArchiveFrame],
+        num_entries: u32,
+    ) {
+        let mut entries = Vec::new();
+        for frame in frames {
+            let path = frame.path.to_str().unwrap();
+            entries.extend_from_slice(&frame.compressed_offset.to_le_bytes());
+            entries.extend_from_slice(&frame.compressed_len.to_le_bytes());
+            entries.extend_from_slice(&frame.decompressed_len.to_le_bytes());
+            entries.extend_from_slice(&(path.len() as u16).to_le_bytes());
+            entries.extend_from_slice(path.as_bytes());
+        }
+        let entries_len = entries.len() as u32;
+
+        let mut file = archive.reopen().unwrap();
+        file.set_len(data_len).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file.write_all(&SKIPPABLE_FRAME_MAGIC.to_le_bytes())
+            .unwrap();
+        file.write_all(&(entries_len + INDEX_FOOTER_SIZE as u32).to_le_bytes())
+            .unwrap();
+        file.write_all(&entries).unwrap();
+        file.write_all(&num_entries.to_le_bytes()).unwrap();
+        file.write_all(&entries_len.to_le_bytes()).unwrap();
+        file.write_all(&SEEKABLE_INDEX_MAGIC.to_le_bytes()).unwrap();
+    }
+
+    #[test]
+    fn test_seekable_archive_untrusted_index() {
+        let archive = write_test_archive(&test_entries());
+        let frames = read_seekable_archive_index(archive.path()).unwrap();
+        let num_frames = frames.len() as u32;
+        let data_len = frames.iter().map(|frame| frame.compressed_len).sum();
+
+        // rewriting the same index is fine
+        rewrite_index(&archive, data_len, &frames, num_frames);
+        assert_eq!(read_seekable_archive_index(archive.path()).unwrap(), frames);
+
+        // more entries than could fit in the index
+        rewrite_index(&archive, data_len, &frames, u32::MAX);
+        assert!(read_seekable_archive_index(archive.path()).is_err());
+
+        // a repeated frame
+        let mut repeated = frames.clone();
+        repeated.insert(1, frames[1].clone());
+        rewrite_index(&archive, data_len, &repeated, num_frames + 1);
+        assert!(read_seekable_archive_index(archive.path()).is_err());
+
+        // frames out of order
+        let mut swapped = frames.clone();
+        swapped.swap(1, 2);
+        rewrite_index(&archive, data_len, &swapped, num_frames);
+        assert!(read_seekable_archive_index(archive.path()).is_err());
+
+        // overlapping frames
+        let mut overlapping = frames.clone();
+        overlapping[1].compressed_len += 1;
+        rewrite_index(&archive, data_len, &overlapping, num_frames);
+        assert!(read_seekable_archive_index(archive.path()).is_err());
+
+        // a frame missing from the index
+        let mut missing = frames.clone();
+        missing.remove(1);
+        rewrite_index(&archive, data_len, &missing, num_frames - 1);
+        assert!(read_seekable_archive_index(archive.path()).is_err());
+    }
+
+    #[test]
+    fn test_not_seekable_archive() {
+        let mut file = NamedTempFile::new().unwrap();
+        let mut encoder = zstd::stream::write::Encoder::new(file.as_file_mut(), 1).unwrap();
+        io::copy(&mut Cursor::new(vec![7u8; 4096]), &mut encoder).unwrap();
+        encoder.finish().unwrap();
+        assert!(!is_seekable_archive(file.path()));
+        assert!(read_seekable_archive_index(file.path()).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+//! Storage backends for the ledger tables
+//!
+//! LedgerStorage keeps the ledger in the `blocks`, `entries`, `tx` and `tx-by-addr` tables, whose
+//! rows hold compressed bincode or protobuf cells. A backend only stores, lists and deletes rows,
+//! so the encoding of the ledger and its indexes is shared by every backend.
+
+use {
+    crate::{
+        bigtable::{
+            deserialize_bincode_cell_data, deserialize_protobuf_cell_data,
+            deserialize_protobuf_or_bincode_cell_data, CellData, RowData, RowKey,
+        },
+        compression::compress_best,
+        Result,
+    },
+    async_trait::async_trait,
+    backoff::{future::retry, ExponentialBackoff},
+};
+
+#[async_trait]
+pub trait LedgerStorageBackend: Send + Sync {
+    /// Get up to `rows_limit` row keys of `table`, in ascending order.
+    ///
+    /// If `start_at` is provided, the listing starts with that key, or the next key if it does
+    /// not exist. If `end_at` is provided, the listing ends at that key. If `rows_limit` is zero,
+    /// no keys are returned.
+    async fn get_row_keys(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>>;
+
+    /// Check whether a row key exists in `table`
+    async fn row_key_exists(&self, table: &str, row_key: RowKey) -> Result<bool>;
+
+    /// Get the rows of `table` in the range `get_row_keys()` would list
+    async fn get_row_data(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>>;
+
+    /// Get the rows of `table` with the given keys, leaving out the keys that do not exist
+    async fn get_multi_row_data(
+        &self,
+        table: &str,
+        row_keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, RowData)>>;
+
+    /// Get a single row of `table`, or `None` if it does not exist
+    async fn get_single_row_data(&self, table: &str, row_key: RowKey) -> Result<Option<RowData>>;
+
+    /// Store the cells of one or more `table` rows
+    async fn put_row_data(&self, table: &str, row_data: &[(RowKey, RowData)]) -> Result<()>;
+
+    /// Delete one or more `table` rows
+    async fn delete_rows(&self, table: &str, row_keys: &[RowKey]) -> Result<()>;
+}
+
+impl dyn LedgerStorageBackend {
+    pub(crate) async fn get_bincode_cell<T>(&self, table: &str, key: RowKey) -> Result<Option<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let Some(row_data) = self.get_single_row_data(table, key.clone()).await? else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_bincode_cell_data(&row_data, table, key)?))
+    }
+
+    pub(crate) async fn get_bincode_cells<T>(
+        &self,
+        table: &str,
+        keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, crate::bigtable::Result<T>)>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Ok(self
+            .get_multi_row_data(table, keys)
+            .await?
+            .into_iter()
+            .map(|(key, row_data)| {
+                let key_str = key.to_string();
+                (
+                    key,
+                    deserialize_bincode_cell_data(&row_data, table, key_str),
+                )
+            })
+            .collect())
+    }
+
+    /// Like `get_bincode_cells()`, but retries failed reads with exponential backoff
+    pub(crate) async fn get_bincode_cells_with_retry<T>(
+        &self,
+        table: &str,
+        keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, crate::bigtable::Result<T>)>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        retry(ExponentialBackoff::default(), || async {
+            Ok(self.get_bincode_cells(table, keys).await?)
+        })
+        .await
+    }
+
+    pub(crate) async fn get_protobuf_cell<P>(&self, table: &str, key: RowKey) -> Result<Option<P>>
+    where
+        P: prost::Message + Default,
+    {
+        let Some(row_data) = self.get_single_row_data(table, key.clone()).await? else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_protobuf_cell_data(&row_data, table, key)?))
+    }
+
+    pub(crate) async fn get_protobuf_or_bincode_cell<B, P>(
+        &self,
+        table: &str,
+        key: RowKey,
+    ) -> Result<Option<CellData<B, P>>>
+    where
+        B: serde::de::DeserializeOwned,
+        P: prost::Message + Default,
+    {
+        let Some(row_data) = self.get_single_row_data(table, key.clone()).await? else {
+            return Ok(None);
+        };
+        Ok(Some(deserialize_protobuf_or_bincode_cell_data(
+            &row_data, table, key,
+        )?))
+    }
+
+    pub(crate) async fn get_protobuf_or_bincode_cells<'a, B, P>(
+        &self,
+        table: &'a str,
+        row_keys: &[RowKey],
+    ) -> Result<impl Iterator<Item = (RowKey, CellData<B, P>)> + 'a>
+    where
+        B: serde::de::DeserializeOwned,
+        P: prost::Message + Default,
+    {
+        Ok(self
+            .get_multi_row_data(table, row_keys)
+            .await?
+            .into_iter()
+            .map(|(key, row_data)| {
+                let key_str = key.to_string();
+                (
+                    key,
+                    deserialize_protobuf_or_bincode_cell_data(&row_data, table, key_str).unwrap(),
+                )
+            }))
+    }
+
+    pub(crate) async fn put_bincode_cells<T>(
+        &self,
+        table: &str,
+        cells: &[(RowKey, T)],
+    ) -> Result<usize>
+    where
+        T: serde::ser::Serialize,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let data = compress_best(&bincode::serialize(&data).unwrap())?;
+            bytes_written += data.len();
+            new_row_data.push((row_key.clone(), vec![("bin".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data).await?;
+        Ok(bytes_written)
+    }
+
+    pub(crate) async fn put_protobuf_cells<T>(
+        &self,
+        table: &str,
+        cells: &[(RowKey, T)],
+    ) -> Result<usize>
+    where
+        T: prost::Message,
+    {
+        let mut bytes_written = 0;
+        let mut new_row_data = vec![];
+        for (row_key, data) in cells {
+            let mut buf = Vec::with_capacity(data.encoded_len());
+            data.encode(&mut buf).unwrap();
+            let data = compress_best(&buf)?;
+            bytes_written += data.len();
+            new_row_data.push((row_key.clone(), vec![("proto".to_string(), data)]));
+        }
+
+        self.put_row_data(table, &new_row_data).await?;
+        Ok(bytes_written)
+    }
+}
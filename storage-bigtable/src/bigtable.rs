@@ -3,9 +3,11 @@
 use {
     crate::{
         access_token::{AccessToken, Scope},
-        compression::{compress_best, decompress},
+        backend::LedgerStorageBackend,
+        compression::decompress,
         root_ca_certificate, CredentialType,
     },
+    async_trait::async_trait,
     backoff::{future::retry, Error as BackoffError, ExponentialBackoff},
     log::*,
     std::{
@@ -276,61 +278,6 @@ impl BigTableConnection {
             timeout: self.timeout,
         }
     }
-
-    pub async fn put_bincode_cells_with_retry<T>(
-        &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            let result = client.put_bincode_cells(table, cells).await;
-            result.map_err(to_backoff_err)
-        })
-        .await
-    }
-
-    pub async fn delete_rows_with_retry(&self, table: &str, row_keys: &[RowKey]) -> Result<()> {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.delete_rows(table, row_keys).await?)
-        })
-        .await
-    }
-
-    pub async fn get_bincode_cells_with_retry<T>(
-        &self,
-        table: &str,
-        row_keys: &[RowKey],
-    ) -> Result<Vec<(RowKey, Result<T>)>>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            Ok(client.get_bincode_cells(table, row_keys).await?)
-        })
-        .await
-    }
-
-    pub async fn put_protobuf_cells_with_retry<T>(
-        &self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        retry(ExponentialBackoff::default(), || async {
-            let mut client = self.client();
-            let result = client.put_protobuf_cells(table, cells).await;
-            result.map_err(to_backoff_err)
-        })
-        .await
-    }
 }
 
 pub struct BigTable<F: FnMut(Request<()>) -> InterceptedRequestResult> {
@@ -702,7 +649,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         &mut self,
         table_name: &str,
         family_name: &str,
-        row_data: &[(&RowKey, RowData)],
+        row_data: &[(RowKey, RowData)],
     ) -> Result<()> {
         self.refresh_access_token();
 
@@ -721,7 +668,7 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
                 .collect();
 
             entries.push(mutate_rows_request::Entry {
-                row_key: (*row_key).clone().into_bytes(),
+                row_key: row_key.clone().into_bytes(),
                 mutations,
             });
         }
@@ -751,124 +698,6 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
         Ok(())
     }
 
-    pub async fn get_bincode_cell<T>(&mut self, table: &str, key: RowKey) -> Result<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_bincode_cell_data(&row_data, table, key.to_string())
-    }
-
-    pub async fn get_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        keys: &[RowKey],
-    ) -> Result<Vec<(RowKey, Result<T>)>>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        Ok(self
-            .get_multi_row_data(table, keys)
-            .await?
-            .into_iter()
-            .map(|(key, row_data)| {
-                let key_str = key.to_string();
-                (
-                    key,
-                    deserialize_bincode_cell_data(&row_data, table, key_str),
-                )
-            })
-            .collect())
-    }
-
-    pub async fn get_protobuf_cell<P>(&mut self, table: &str, key: RowKey) -> Result<P>
-    where
-        P: prost::Message + Default,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_protobuf_cell_data(&row_data, table, key.to_string())
-    }
-
-    pub async fn get_protobuf_or_bincode_cell<B, P>(
-        &mut self,
-        table: &str,
-        key: RowKey,
-    ) -> Result<CellData<B, P>>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        let row_data = self.get_single_row_data(table, key.clone()).await?;
-        deserialize_protobuf_or_bincode_cell_data(&row_data, table, key)
-    }
-
-    pub async fn get_protobuf_or_bincode_cells<'a, B, P>(
-        &mut self,
-        table: &'a str,
-        row_keys: impl IntoIterator<Item = RowKey>,
-    ) -> Result<impl Iterator<Item = (RowKey, CellData<B, P>)> + 'a>
-    where
-        B: serde::de::DeserializeOwned,
-        P: prost::Message + Default,
-    {
-        Ok(self
-            .get_multi_row_data(
-                table,
-                row_keys.into_iter().collect::<Vec<RowKey>>().as_slice(),
-            )
-            .await?
-            .into_iter()
-            .map(|(key, row_data)| {
-                let key_str = key.to_string();
-                (
-                    key,
-                    deserialize_protobuf_or_bincode_cell_data(&row_data, table, key_str).unwrap(),
-                )
-            }))
-    }
-
-    pub async fn put_bincode_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: serde::ser::Serialize,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let data = compress_best(&bincode::serialize(&data).unwrap())?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("bin".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
-
-    pub async fn put_protobuf_cells<T>(
-        &mut self,
-        table: &str,
-        cells: &[(RowKey, T)],
-    ) -> Result<usize>
-    where
-        T: prost::Message,
-    {
-        let mut bytes_written = 0;
-        let mut new_row_data = vec![];
-        for (row_key, data) in cells {
-            let mut buf = Vec::with_capacity(data.encoded_len());
-            data.encode(&mut buf).unwrap();
-            let data = compress_best(&buf)?;
-            bytes_written += data.len();
-            new_row_data.push((row_key, vec![("proto".to_string(), data)]));
-        }
-
-        self.put_row_data(table, "x", &new_row_data).await?;
-        Ok(bytes_written)
-    }
-
     async fn read_rows(
         &mut self,
         table_name: &str,
@@ -899,6 +728,81 @@ impl<F: FnMut(Request<()>) -> InterceptedRequestResult> BigTable<F> {
     }
 }
 
+#[async_trait]
+impl LedgerStorageBackend for BigTableConnection {
+    async fn get_row_keys(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> crate::Result<Vec<RowKey>> {
+        let mut client = self.client();
+        Ok(client
+            .get_row_keys(table, start_at, end_at, rows_limit)
+            .await?)
+    }
+
+    async fn row_key_exists(&self, table: &str, row_key: RowKey) -> crate::Result<bool> {
+        let mut client = self.client();
+        Ok(client.row_key_exists(table, row_key).await?)
+    }
+
+    async fn get_row_data(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> crate::Result<Vec<(RowKey, RowData)>> {
+        let mut client = self.client();
+        Ok(client
+            .get_row_data(table, start_at, end_at, rows_limit)
+            .await?)
+    }
+
+    async fn get_multi_row_data(
+        &self,
+        table: &str,
+        row_keys: &[RowKey],
+    ) -> crate::Result<Vec<(RowKey, RowData)>> {
+        let mut client = self.client();
+        Ok(client.get_multi_row_data(table, row_keys).await?)
+    }
+
+    async fn get_single_row_data(
+        &self,
+        table: &str,
+        row_key: RowKey,
+    ) -> crate::Result<Option<RowData>> {
+        let mut client = self.client();
+        match client.get_single_row_data(table, row_key).await {
+            Ok(row_data) => Ok(Some(row_data)),
+            Err(Error::RowNotFound) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn put_row_data(&self, table: &str, row_data: &[(RowKey, RowData)]) -> crate::Result<()> {
+        retry(ExponentialBackoff::default(), || async {
+            let mut client = self.client();
+            let result = client.put_row_data(table, "x", row_data).await;
+            result.map_err(to_backoff_err)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_rows(&self, table: &str, row_keys: &[RowKey]) -> crate::Result<()> {
+        retry(ExponentialBackoff::default(), || async {
+            let mut client = self.client();
+            Ok(client.delete_rows(table, row_keys).await?)
+        })
+        .await?;
+        Ok(())
+    }
+}
+
 pub(crate) fn deserialize_protobuf_or_bincode_cell_data<B, P>(
     row_data: RowDataSlice,
     table: &str,
@@ -964,7 +868,7 @@ where
 mod tests {
     use {
         super::*,
-        crate::StoredConfirmedBlock,
+        crate::{compression::compress_best, StoredConfirmedBlock},
         prost::Message,
         solana_sdk::{
             hash::Hash, message::v0::LoadedAddresses, signature::Keypair, system_transaction,
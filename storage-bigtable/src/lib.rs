@@ -43,10 +43,14 @@ extern crate solana_metrics;
 extern crate serde_derive;
 
 mod access_token;
+mod backend;
 mod bigtable;
 mod compression;
+mod object_storage;
 mod root_ca_certificate;
 
+pub use {backend::LedgerStorageBackend, object_storage::ObjectStorage};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("BigTable: {0}")]
@@ -66,6 +70,12 @@ pub enum Error {
 
     #[error("tokio error")]
     TokioJoinError(JoinError),
+
+    #[error("Object store: {0}")]
+    ObjectStoreError(object_store::Error),
+
+    #[error("Ledger storage is read-only")]
+    ReadOnly,
 }
 
 impl std::convert::From<bigtable::Error> for Error {
@@ -80,6 +90,12 @@ impl std::convert::From<std::io::Error> for Error {
     }
 }
 
+impl std::convert::From<object_store::Error> for Error {
+    fn from(err: object_store::Error) -> Self {
+        Self::ObjectStoreError(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 // Convert a slot to its bucket representation whereby lower slots are always lexically ordered
@@ -381,6 +397,39 @@ impl From<LegacyTransactionByAddrInfo> for TransactionByAddrInfo {
     }
 }
 
+/// A difference between a confirmed block and the ledger data stored for its slot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoredBlockMismatch {
+    MissingBlock,
+    Blockhash { expected: String, stored: String },
+    TransactionCount { expected: usize, stored: usize },
+    MissingTransactions(usize),
+    MissingTxByAddr(usize),
+}
+
+impl StoredBlockMismatch {
+    /// Whether the stored block itself differs, so its rows must be deleted before a re-upload
+    pub fn is_different_block(&self) -> bool {
+        matches!(self, Self::Blockhash { .. } | Self::TransactionCount { .. })
+    }
+}
+
+impl std::fmt::Display for StoredBlockMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingBlock => write!(f, "block is missing"),
+            Self::Blockhash { expected, stored } => {
+                write!(f, "blockhash is {stored}, expected {expected}")
+            }
+            Self::TransactionCount { expected, stored } => {
+                write!(f, "block has {stored} transactions, expected {expected}")
+            }
+            Self::MissingTransactions(count) => write!(f, "{count} tx rows are missing"),
+            Self::MissingTxByAddr(count) => write!(f, "{count} tx-by-addr rows are missing"),
+        }
+    }
+}
+
 pub const DEFAULT_INSTANCE_NAME: &str = "solana-ledger";
 pub const DEFAULT_APP_PROFILE_ID: &str = "default";
 pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; // 64MB
@@ -399,6 +448,8 @@ pub struct LedgerStorageConfig {
     pub instance_name: String,
     pub app_profile_id: String,
     pub max_message_size: usize,
+    /// Use the object storage at this url instead of Bigtable, see `ObjectStorage::new()`
+    pub object_store_url: Option<String>,
 }
 
 impl Default for LedgerStorageConfig {
@@ -410,6 +461,7 @@ impl Default for LedgerStorageConfig {
             instance_name: DEFAULT_INSTANCE_NAME.to_string(),
             app_profile_id: DEFAULT_APP_PROFILE_ID.to_string(),
             max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            object_store_url: None,
         }
     }
 }
@@ -444,7 +496,7 @@ impl LedgerStorageStats {
 
 #[derive(Clone)]
 pub struct LedgerStorage {
-    connection: bigtable::BigTableConnection,
+    backend: Arc<dyn LedgerStorageBackend>,
     stats: Arc<LedgerStorageStats>,
 }
 
@@ -469,21 +521,17 @@ impl LedgerStorage {
         endpoint: &str,
         timeout: Option<Duration>,
     ) -> Result<Self> {
-        let stats = Arc::new(LedgerStorageStats::default());
-        Ok(Self {
-            connection: bigtable::BigTableConnection::new_for_emulator(
-                instance_name,
-                app_profile_id,
-                endpoint,
-                timeout,
-                LedgerStorageConfig::default().max_message_size,
-            )?,
-            stats,
-        })
+        let connection = bigtable::BigTableConnection::new_for_emulator(
+            instance_name,
+            app_profile_id,
+            endpoint,
+            timeout,
+            LedgerStorageConfig::default().max_message_size,
+        )?;
+        Ok(Self::new_with_backend(Arc::new(connection)))
     }
 
     pub async fn new_with_config(config: LedgerStorageConfig) -> Result<Self> {
-        let stats = Arc::new(LedgerStorageStats::default());
         let LedgerStorageConfig {
             read_only,
             timeout,
@@ -491,7 +539,12 @@ impl LedgerStorage {
             app_profile_id,
             credential_type,
             max_message_size,
+            object_store_url,
         } = config;
+        if let Some(object_store_url) = object_store_url {
+            let object_storage = ObjectStorage::new(&object_store_url, read_only)?;
+            return Ok(Self::new_with_backend(Arc::new(object_storage)));
+        }
         let connection = bigtable::BigTableConnection::new(
             instance_name.as_str(),
             app_profile_id.as_str(),
@@ -501,7 +554,14 @@ impl LedgerStorage {
             max_message_size,
         )
         .await?;
-        Ok(Self { stats, connection })
+        Ok(Self::new_with_backend(Arc::new(connection)))
+    }
+
+    pub fn new_with_backend(backend: Arc<dyn LedgerStorageBackend>) -> Self {
+        Self {
+            backend,
+            stats: Arc::new(LedgerStorageStats::default()),
+        }
     }
 
     pub async fn new_with_stringified_credential(credential: String) -> Result<Self> {
@@ -516,8 +576,7 @@ impl LedgerStorage {
     pub async fn get_first_available_block(&self) -> Result<Option<Slot>> {
         trace!("LedgerStorage::get_first_available_block request received");
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable.get_row_keys("blocks", None, None, 1).await?;
+        let blocks = self.backend.get_row_keys("blocks", None, None, 1).await?;
         if blocks.is_empty() {
             return Ok(None);
         }
@@ -535,8 +594,8 @@ impl LedgerStorage {
             limit
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let blocks = bigtable
+        let blocks = self
+            .backend
             .get_row_keys(
                 "blocks",
                 Some(slot_to_blocks_key(start_slot)),
@@ -557,10 +616,10 @@ impl LedgerStorage {
             slots
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let row_keys = slots.iter().copied().map(slot_to_blocks_key);
-        let data = bigtable
-            .get_protobuf_or_bincode_cells("blocks", row_keys)
+        let row_keys: Vec<_> = slots.iter().copied().map(slot_to_blocks_key).collect();
+        let data = self
+            .backend
+            .get_protobuf_or_bincode_cells("blocks", &row_keys)
             .await?
             .filter_map(
                 |(row_key, block_cell_data): (
@@ -584,17 +643,14 @@ impl LedgerStorage {
             slot
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let block_cell_data = bigtable
+        let block_cell_data = self
+            .backend
             .get_protobuf_or_bincode_cell::<StoredConfirmedBlock, generated::ConfirmedBlock>(
                 "blocks",
                 slot_to_blocks_key(slot),
             )
-            .await
-            .map_err(|err| match err {
-                bigtable::Error::RowNotFound => Error::BlockNotFound(slot),
-                _ => err.into(),
-            })?;
+            .await?
+            .ok_or(Error::BlockNotFound(slot))?;
         Ok(match block_cell_data {
             bigtable::CellData::Bincode(block) => block.into(),
             bigtable::CellData::Protobuf(block) => block.try_into().map_err(|_err| {
@@ -610,9 +666,8 @@ impl LedgerStorage {
             slot
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-
-        let block_exists = bigtable
+        let block_exists = self
+            .backend
             .row_key_exists("blocks", slot_to_blocks_key(slot))
             .await?;
 
@@ -626,14 +681,11 @@ impl LedgerStorage {
             slot
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let entry_cell_data = bigtable
+        let entry_cell_data = self
+            .backend
             .get_protobuf_cell::<entries::Entries>("entries", slot_to_entries_key(slot))
-            .await
-            .map_err(|err| match err {
-                bigtable::Error::RowNotFound => Error::BlockNotFound(slot),
-                _ => err.into(),
-            })?;
+            .await?
+            .ok_or(Error::BlockNotFound(slot))?;
         let entries = entry_cell_data.entries.into_iter().map(Into::into);
         Ok(entries)
     }
@@ -644,14 +696,11 @@ impl LedgerStorage {
             signature
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-        let transaction_info = bigtable
+        let transaction_info = self
+            .backend
             .get_bincode_cell::<TransactionInfo>("tx", signature.to_string())
-            .await
-            .map_err(|err| match err {
-                bigtable::Error::RowNotFound => Error::SignatureNotFound,
-                _ => err.into(),
-            })?;
+            .await?
+            .ok_or(Error::SignatureNotFound)?;
         Ok(transaction_info.into())
     }
 
@@ -665,11 +714,10 @@ impl LedgerStorage {
             signatures
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-
         // Fetch transactions info
         let keys = signatures.iter().map(|s| s.to_string()).collect::<Vec<_>>();
-        let cells = bigtable
+        let cells = self
+            .backend
             .get_bincode_cells::<TransactionInfo>("tx", &keys)
             .await?;
 
@@ -727,16 +775,12 @@ impl LedgerStorage {
             signature
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
-
         // Figure out which block the transaction is located in
-        let TransactionInfo { slot, index, .. } = bigtable
+        let TransactionInfo { slot, index, .. } = self
+            .backend
             .get_bincode_cell("tx", signature.to_string())
-            .await
-            .map_err(|err| match err {
-                bigtable::Error::RowNotFound => Error::SignatureNotFound,
-                _ => err.into(),
-            })?;
+            .await?
+            .ok_or(Error::SignatureNotFound)?;
 
         // Load the block and return the transaction
         let block = self.get_confirmed_block(slot).await?;
@@ -787,20 +831,17 @@ impl LedgerStorage {
             address
         );
         self.stats.increment_num_queries();
-        let mut bigtable = self.connection.client();
         let address_prefix = format!("{address}/");
 
         // Figure out where to start listing from based on `before_signature`
         let (first_slot, before_transaction_index) = match before_signature {
             None => (Slot::MAX, 0),
             Some(before_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = self
+                    .backend
                     .get_bincode_cell("tx", before_signature.to_string())
-                    .await
-                    .map_err(|err| match err {
-                        bigtable::Error::RowNotFound => Error::SignatureNotFound,
-                        _ => err.into(),
-                    })?;
+                    .await?
+                    .ok_or(Error::SignatureNotFound)?;
 
                 (slot, index)
             }
@@ -810,13 +851,11 @@ impl LedgerStorage {
         let (last_slot, until_transaction_index) = match until_signature {
             None => (0, u32::MAX),
             Some(until_signature) => {
-                let TransactionInfo { slot, index, .. } = bigtable
+                let TransactionInfo { slot, index, .. } = self
+                    .backend
                     .get_bincode_cell("tx", until_signature.to_string())
-                    .await
-                    .map_err(|err| match err {
-                        bigtable::Error::RowNotFound => Error::SignatureNotFound,
-                        _ => err.into(),
-                    })?;
+                    .await?
+                    .ok_or(Error::SignatureNotFound)?;
 
                 (slot, index)
             }
@@ -824,12 +863,15 @@ impl LedgerStorage {
 
         let mut infos = vec![];
 
-        let starting_slot_tx_len = bigtable
+        let starting_slot_tx_len = self
+            .backend
             .get_protobuf_or_bincode_cell::<Vec<LegacyTransactionByAddrInfo>, tx_by_addr::TransactionByAddr>(
                 "tx-by-addr",
                 format!("{}{}", address_prefix, slot_to_tx_by_addr_key(first_slot)),
             )
             .await
+            .ok()
+            .flatten()
             .map(|cell_data| {
                 match cell_data {
                     bigtable::CellData::Bincode(tx_by_addr) => tx_by_addr.len(),
@@ -840,7 +882,8 @@ impl LedgerStorage {
 
         // Return the next tx-by-addr data of amount `limit` plus extra to account for the largest
         // number that might be filtered out
-        let tx_by_addr_data = bigtable
+        let tx_by_addr_data = self
+            .backend
             .get_row_data(
                 "tx-by-addr",
                 Some(format!(
@@ -1013,28 +1056,31 @@ impl LedgerStorage {
         let mut tasks = vec![];
 
         if !tx_cells.is_empty() {
-            let conn = self.connection.clone();
+            let backend = self.backend.clone();
             tasks.push(tokio::spawn(async move {
-                conn.put_bincode_cells_with_retry::<TransactionInfo>("tx", &tx_cells)
+                backend
+                    .put_bincode_cells::<TransactionInfo>("tx", &tx_cells)
                     .await
             }));
         }
 
         if !tx_by_addr_cells.is_empty() {
-            let conn = self.connection.clone();
+            let backend = self.backend.clone();
             tasks.push(tokio::spawn(async move {
-                conn.put_protobuf_cells_with_retry::<tx_by_addr::TransactionByAddr>(
-                    "tx-by-addr",
-                    &tx_by_addr_cells,
-                )
-                .await
+                backend
+                    .put_protobuf_cells::<tx_by_addr::TransactionByAddr>(
+                        "tx-by-addr",
+                        &tx_by_addr_cells,
+                    )
+                    .await
             }));
         }
 
         if num_entries > 0 {
-            let conn = self.connection.clone();
+            let backend = self.backend.clone();
             tasks.push(tokio::spawn(async move {
-                conn.put_protobuf_cells_with_retry::<entries::Entries>("entries", &[entry_cell])
+                backend
+                    .put_protobuf_cells::<entries::Entries>("entries", &[entry_cell])
                     .await
             }));
         }
@@ -1052,7 +1098,7 @@ impl LedgerStorage {
                 }
                 Ok(Err(err)) => {
                     if maybe_first_err.is_none() {
-                        maybe_first_err = Some(Error::BigTableError(err));
+                        maybe_first_err = Some(err);
                     }
                 }
                 Ok(Ok(bytes)) => {
@@ -1072,8 +1118,8 @@ impl LedgerStorage {
         // `get_confirmed_block()` and `get_confirmed_blocks()`
         let blocks_cells = [(slot_to_blocks_key(slot), confirmed_block.into())];
         bytes_written += self
-            .connection
-            .put_protobuf_cells_with_retry::<generated::ConfirmedBlock>("blocks", &blocks_cells)
+            .backend
+            .put_protobuf_cells::<generated::ConfirmedBlock>("blocks", &blocks_cells)
             .await?;
         datapoint_info!(
             "storage-bigtable-upload-block",
@@ -1085,6 +1131,100 @@ impl LedgerStorage {
         Ok(())
     }
 
+    /// Check the ledger data stored for a slot against its confirmed block: the block, its
+    /// blockhash and transaction count, and the tx and tx-by-addr rows of its transactions.
+    /// Returns the differences found, or an empty vec if the block is fully stored.
+    pub async fn verify_confirmed_block(
+        &self,
+        slot: Slot,
+        confirmed_block: &VersionedConfirmedBlock,
+    ) -> Result<Vec<StoredBlockMismatch>> {
+        trace!(
+            "LedgerStorage::verify_confirmed_block request received: {:?}",
+            slot
+        );
+        let stored_block = match self.get_confirmed_block(slot).await {
+            Ok(stored_block) => stored_block,
+            Err(Error::BlockNotFound(_)) => return Ok(vec![StoredBlockMismatch::MissingBlock]),
+            Err(err) => return Err(err),
+        };
+
+        let mut mismatches = vec![];
+        if stored_block.blockhash != confirmed_block.blockhash {
+            mismatches.push(StoredBlockMismatch::Blockhash {
+                expected: confirmed_block.blockhash.clone(),
+                stored: stored_block.blockhash,
+            });
+        }
+        if stored_block.transactions.len() != confirmed_block.transactions.len() {
+            mismatches.push(StoredBlockMismatch::TransactionCount {
+                expected: confirmed_block.transactions.len(),
+                stored: stored_block.transactions.len(),
+            });
+        }
+
+        // Expect the same rows that `upload_confirmed_block_with_entries()` writes
+        let reserved_account_keys = ReservedAccountKeys::new_all_activated();
+        let mut addresses: HashSet<&Pubkey> = HashSet::new();
+        let mut expected_tx_infos: HashMap<String, UploadedTransaction> = HashMap::new();
+        for (index, transaction_with_meta) in confirmed_block.transactions.iter().enumerate() {
+            let VersionedTransactionWithStatusMeta { meta, transaction } = transaction_with_meta;
+            for address in transaction_with_meta.account_keys().iter() {
+                if !reserved_account_keys.is_reserved(address) {
+                    addresses.insert(address);
+                }
+            }
+            expected_tx_infos.insert(
+                transaction.signatures[0].to_string(),
+                UploadedTransaction {
+                    slot,
+                    index: index as u32,
+                    err: meta.status.clone().err(),
+                },
+            );
+        }
+
+        if !expected_tx_infos.is_empty() {
+            self.stats.increment_num_queries();
+            let signatures = expected_tx_infos.keys().cloned().collect::<Vec<_>>();
+            let stored_tx_infos = self
+                .backend
+                .get_bincode_cells::<TransactionInfo>("tx", &signatures)
+                .await?
+                .into_iter()
+                .filter_map(|(signature, tx_info_res)| Some((signature, tx_info_res.ok()?.into())))
+                .collect::<HashMap<_, UploadedTransaction>>();
+            let num_missing = expected_tx_infos
+                .iter()
+                .filter(|(signature, expected_tx_info)| {
+                    stored_tx_infos.get(*signature) != Some(expected_tx_info)
+                })
+                .count();
+            if num_missing > 0 {
+                mismatches.push(StoredBlockMismatch::MissingTransactions(num_missing));
+            }
+        }
+
+        if !addresses.is_empty() {
+            self.stats.increment_num_queries();
+            let address_slot_rows: Vec<_> = addresses
+                .into_iter()
+                .map(|address| format!("{}/{}", address, slot_to_tx_by_addr_key(slot)))
+                .collect();
+            let num_stored = self
+                .backend
+                .get_multi_row_data("tx-by-addr", &address_slot_rows)
+                .await?
+                .len();
+            let num_missing = address_slot_rows.len().saturating_sub(num_stored);
+            if num_missing > 0 {
+                mismatches.push(StoredBlockMismatch::MissingTxByAddr(num_missing));
+            }
+        }
+
+        Ok(mismatches)
+    }
+
     // Delete a confirmed block and associated meta data.
     pub async fn delete_confirmed_block(&self, slot: Slot, dry_run: bool) -> Result<()> {
         let mut addresses: HashSet<&Pubkey> = HashSet::new();
@@ -1144,7 +1284,7 @@ impl LedgerStorage {
         let tx_deletion_rows = if !expected_tx_infos.is_empty() {
             let signatures = expected_tx_infos.keys().cloned().collect::<Vec<_>>();
             let fetched_tx_infos: HashMap<String, std::result::Result<UploadedTransaction, _>> =
-                self.connection
+                self.backend
                     .get_bincode_cells_with_retry::<TransactionInfo>("tx", &signatures)
                     .await?
                     .into_iter()
@@ -1182,33 +1322,30 @@ impl LedgerStorage {
         };
 
         let entries_exist = self
-            .connection
-            .client()
+            .backend
             .row_key_exists("entries", slot_to_entries_key(slot))
             .await
             .is_ok_and(|x| x);
 
         if !dry_run {
             if !address_slot_rows.is_empty() {
-                self.connection
-                    .delete_rows_with_retry("tx-by-addr", &address_slot_rows)
+                self.backend
+                    .delete_rows("tx-by-addr", &address_slot_rows)
                     .await?;
             }
 
             if !tx_deletion_rows.is_empty() {
-                self.connection
-                    .delete_rows_with_retry("tx", &tx_deletion_rows)
-                    .await?;
+                self.backend.delete_rows("tx", &tx_deletion_rows).await?;
             }
 
             if entries_exist {
-                self.connection
-                    .delete_rows_with_retry("entries", &[slot_to_entries_key(slot)])
+                self.backend
+                    .delete_rows("entries", &[slot_to_entries_key(slot)])
                     .await?;
             }
 
-            self.connection
-                .delete_rows_with_retry("blocks", &[slot_to_blocks_key(slot)])
+            self.backend
+                .delete_rows("blocks", &[slot_to_blocks_key(slot)])
                 .await?;
         }
 
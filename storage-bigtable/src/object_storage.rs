@@ -0,0 +1,641 @@
+//! A LedgerStorageBackend for a local directory or an S3-compatible bucket
+//!
+//! Each row is an object named `{table}/{row_key}` holding the bincode-serialized cells of the
+//! row, so blocks, entries and the tx-by-addr index are stored with the same compressed
+//! storage-proto encoding as in Bigtable.
+//!
+//! Range reads list the objects under the start key's prefix up to its last `/`, which is how
+//! LedgerStorage reads its tables: whole tables for `blocks` and `entries`, and one address at a
+//! time for `tx-by-addr`.
+//!
+//! S3 lists objects in key order starting from any key. A local directory can only be listed
+//! whole, so in a local directory the slot keys that these tables are read by are nested under
+//! two levels of directories named after their leading digits, e.g. `blocks/0000000000/
+//! 0000000000001/0000000000001234`. A range read then only lists the directories that can hold
+//! keys in the range, and stops once it has found enough keys.
+
+use {
+    crate::{
+        backend::LedgerStorageBackend,
+        bigtable::{self, RowData, RowKey},
+        Error, Result,
+    },
+    async_trait::async_trait,
+    futures::{stream, StreamExt, TryStreamExt},
+    log::*,
+    object_store::{
+        aws::AmazonS3Builder, local::LocalFileSystem, path::Path, prefix::PrefixStore, ObjectStore,
+    },
+    std::sync::Arc,
+};
+
+// Maximum number of object requests a single read or write keeps in flight
+const MAX_CONCURRENT_REQUESTS: usize = 64;
+
+pub struct ObjectStorage {
+    store: Arc<dyn ObjectStore>,
+    read_only: bool,
+    // Whether slot keys are nested under directories named after their leading digits, which is
+    // how rows are stored in a local directory
+    nest_slot_keys: bool,
+}
+
+impl ObjectStorage {
+    /// Open the ledger storage at `url`, either `s3://BUCKET[/PREFIX]` or a local directory given
+    /// as a path or a `file://` url.
+    ///
+    /// S3 credentials, region and endpoint are read from the `AWS_*` environment variables, so
+    /// S3-compatible services can be used by setting `AWS_ENDPOINT`.
+    pub fn new(url: &str, read_only: bool) -> Result<Self> {
+        let (store, nest_slot_keys): (Arc<dyn ObjectStore>, _) =
+            if let Some(bucket_and_prefix) = url.strip_prefix("s3://") {
+                let (bucket, prefix) = bucket_and_prefix
+                    .split_once('/')
+                    .unwrap_or((bucket_and_prefix, ""));
+                let s3 = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .build()?;
+                let prefix = prefix.trim_matches('/');
+                if prefix.is_empty() {
+                    (Arc::new(s3), false)
+                } else {
+                    (Arc::new(PrefixStore::new(s3, prefix)), false)
+                }
+            } else {
+                let directory = url.strip_prefix("file://").unwrap_or(url);
+                if !read_only {
+                    std::fs::create_dir_all(directory)?;
+                }
+                (Arc::new(LocalFileSystem::new_with_prefix(directory)?), true)
+            };
+        info!("Using object storage at {url} for ledger storage");
+        Ok(Self {
+            store,
+            read_only,
+            nest_slot_keys,
+        })
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::ReadOnly);
+        }
+        Ok(())
+    }
+
+    fn row_path(&self, table: &str, row_key: &str) -> Path {
+        let (directory, name) = split_row_key(row_key);
+        let directory = directory
+            .map(|directory| format!("{directory}/"))
+            .unwrap_or_default();
+        match slot_key_directories(name).filter(|_| self.nest_slot_keys) {
+            Some((outer, inner)) => {
+                Path::from(format!("{table}/{directory}{outer}/{inner}/{name}"))
+            }
+            None => Path::from(format!("{table}/{row_key}")),
+        }
+    }
+
+    async fn get_row(&self, table: &str, row_key: &str) -> Result<Option<RowData>> {
+        let bytes = match self.store.get(&self.row_path(table, row_key)).await {
+            Ok(result) => result.bytes().await?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let row_data = bincode::deserialize(&bytes).map_err(|err| {
+            warn!("Failed to deserialize {}/{}: {}", table, row_key, err);
+            bigtable::Error::ObjectCorrupt(format!("{table}/{row_key}"))
+        })?;
+        Ok(Some(row_data))
+    }
+
+    async fn get_rows(&self, table: &str, row_keys: &[RowKey]) -> Result<Vec<(RowKey, RowData)>> {
+        let rows: Vec<_> = stream::iter(row_keys)
+            .map(|row_key| async move {
+                Ok::<_, Error>(
+                    self.get_row(table, row_key)
+                        .await?
+                        .map(|row_data| (row_key.clone(), row_data)),
+                )
+            })
+            .buffered(MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+        Ok(rows.into_iter().flatten().collect())
+    }
+
+    /// Lists the row keys in `prefix` in key order, for a store with nested slot keys
+    ///
+    /// Only the directories of slot keys that can hold keys in the range are listed, and the
+    /// listing stops once `rows_limit` keys are found.
+    async fn list_nested_row_keys(
+        &self,
+        prefix: &Path,
+        start_at: Option<&str>,
+        end_at: Option<&str>,
+        rows_limit: usize,
+    ) -> Result<Vec<RowKey>> {
+        let directory = start_at.and_then(|start_at| split_row_key(start_at).0);
+        let to_row_key = |name: &str| match directory {
+            Some(directory) => format!("{directory}/{name}"),
+            None => name.to_string(),
+        };
+        let in_range = |row_key: &str| {
+            start_at.map_or(true, |start_at| row_key >= start_at)
+                && end_at.map_or(true, |end_at| row_key <= end_at)
+        };
+        // A directory holds the keys starting with its name, so it can be skipped if its name
+        // sorts before the same length prefix of the start key
+        let start_name = start_at.map(|start_at| split_row_key(start_at).1);
+        let before_start = |directory: &Path| {
+            let name = directory.filename().unwrap_or_default();
+            start_name
+                .and_then(|start_name| start_name.get(..name.len()))
+                .is_some_and(|start_prefix| name < start_prefix)
+        };
+        let sorted_directories = |mut directories: Vec<Path>| {
+            directories.retain(|directory| !before_start(directory));
+            directories.sort_unstable();
+            directories
+        };
+
+        let listing = self.store.list_with_delimiter(Some(prefix)).await?;
+        // keys that are not nested, such as transaction signatures
+        let mut row_keys: Vec<_> = listing
+            .objects
+            .iter()
+            .filter_map(|object| object.location.filename())
+            .map(to_row_key)
+            .filter(|row_key| in_range(row_key))
+            .collect();
+
+        let mut nested_row_keys = vec![];
+        'listing: for outer in sorted_directories(listing.common_prefixes) {
+            let outer_listing = self.store.list_with_delimiter(Some(&outer)).await?;
+            for inner in sorted_directories(outer_listing.common_prefixes) {
+                let mut names: Vec<_> = self
+                    .store
+                    .list_with_delimiter(Some(&inner))
+                    .await?
+                    .objects
+                    .iter()
+                    .filter_map(|object| object.location.filename().map(str::to_string))
+                    .collect();
+                names.sort_unstable();
+                for name in names {
+                    let row_key = to_row_key(&name);
+                    if end_at.is_some_and(|end_at| row_key.as_str() > end_at) {
+                        break 'listing;
+                    }
+                    if in_range(&row_key) {
+                        nested_row_keys.push(row_key);
+                        if nested_row_keys.len() >= rows_limit {
+                            break 'listing;
+                        }
+                    }
+                }
+            }
+        }
+
+        row_keys.append(&mut nested_row_keys);
+        row_keys.sort_unstable();
+        row_keys.truncate(rows_limit);
+        Ok(row_keys)
+    }
+}
+
+/// Splits a row key into the part up to its last `/`, if any, and the rest
+fn split_row_key(row_key: &str) -> (Option<&str>, &str) {
+    match row_key.rsplit_once('/') {
+        Some((directory, name)) => (Some(directory), name),
+        None => (None, row_key),
+    }
+}
+
+/// Returns the names of the two directories a slot key is nested under, or None if `name` is not
+/// a slot key
+fn slot_key_directories(name: &str) -> Option<(&str, &str)> {
+    (name.len() == 16 && name.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .then(|| (&name[..10], &name[..13]))
+}
+
+#[async_trait]
+impl LedgerStorageBackend for ObjectStorage {
+    async fn get_row_keys(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<RowKey>> {
+        if rows_limit <= 0 {
+            return Ok(vec![]);
+        }
+        let rows_limit = rows_limit as usize;
+        let prefix = match start_at
+            .as_deref()
+            .and_then(|start_at| split_row_key(start_at).0)
+        {
+            Some(directory) => Path::from(format!("{table}/{directory}")),
+            None => Path::from(table),
+        };
+        if self.nest_slot_keys {
+            return self
+                .list_nested_row_keys(&prefix, start_at.as_deref(), end_at.as_deref(), rows_limit)
+                .await;
+        }
+        let table_prefix = format!("{table}/");
+
+        // The listing offset is exclusive, so the start key is looked up separately
+        let mut row_keys = vec![];
+        if let Some(start_at) = &start_at {
+            if self.row_key_exists(table, start_at.clone()).await? {
+                row_keys.push(start_at.clone());
+            }
+        }
+        let mut listing = match &start_at {
+            Some(start_at) => self
+                .store
+                .list_with_offset(Some(&prefix), &self.row_path(table, start_at)),
+            None => self.store.list(Some(&prefix)),
+        };
+        while let Some(object) = listing.next().await {
+            let object = object?;
+            let Some(row_key) = object.location.as_ref().strip_prefix(&table_prefix) else {
+                continue;
+            };
+            if end_at.as_deref().is_some_and(|end_at| row_key > end_at) {
+                break;
+            }
+            row_keys.push(row_key.to_string());
+            if row_keys.len() >= rows_limit {
+                break;
+            }
+        }
+        Ok(row_keys)
+    }
+
+    async fn row_key_exists(&self, table: &str, row_key: RowKey) -> Result<bool> {
+        match self.store.head(&self.row_path(table, &row_key)).await {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn get_row_data(
+        &self,
+        table: &str,
+        start_at: Option<RowKey>,
+        end_at: Option<RowKey>,
+        rows_limit: i64,
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        let row_keys = self
+            .get_row_keys(table, start_at, end_at, rows_limit)
+            .await?;
+        self.get_rows(table, &row_keys).await
+    }
+
+    async fn get_multi_row_data(
+        &self,
+        table: &str,
+        row_keys: &[RowKey],
+    ) -> Result<Vec<(RowKey, RowData)>> {
+        self.get_rows(table, row_keys).await
+    }
+
+    async fn get_single_row_data(&self, table: &str, row_key: RowKey) -> Result<Option<RowData>> {
+        self.get_row(table, &row_key).await
+    }
+
+    async fn put_row_data(&self, table: &str, row_data: &[(RowKey, RowData)]) -> Result<()> {
+        self.check_writable()?;
+        stream::iter(row_data)
+            .map(|(row_key, row_data)| async move {
+                let data = bincode::serialize(row_data).unwrap();
+                self.store
+                    .put(&self.row_path(table, row_key), data.into())
+                    .await
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .try_collect::<()>()
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_rows(&self, table: &str, row_keys: &[RowKey]) -> Result<()> {
+        self.check_writable()?;
+        stream::iter(row_keys)
+            .map(|row_key| async move {
+                match self.store.delete(&self.row_path(table, row_key)).await {
+                    Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+                    Err(err) => Err(err),
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+            .try_collect::<()>()
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{slot_to_blocks_key, slot_to_tx_by_addr_key, LedgerStorage, StoredBlockMismatch},
+        solana_sdk::{
+            clock::Slot,
+            hash::Hash,
+            pubkey::Pubkey,
+            signature::{Keypair, Signer},
+            system_transaction,
+            transaction::VersionedTransaction,
+        },
+        solana_transaction_status::{
+            ConfirmedTransactionStatusWithSignature, TransactionStatusMeta,
+            VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+        },
+    };
+
+    fn new_test_block(slot: Slot, from: &Keypair, to: &Pubkey) -> VersionedConfirmedBlock {
+        let transaction = system_transaction::transfer(from, to, 1, Hash::new_unique());
+        VersionedConfirmedBlock {
+            previous_blockhash: Hash::new_unique().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: vec![VersionedTransactionWithStatusMeta {
+                transaction: VersionedTransaction::from(transaction),
+                meta: TransactionStatusMeta {
+                    pre_balances: vec![10, 0, 1],
+                    post_balances: vec![9, 1, 1],
+                    ..TransactionStatusMeta::default()
+                },
+            }],
+            rewards: vec![],
+            num_partitions: None,
+            block_time: Some(slot as i64),
+            block_height: Some(slot),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_object_storage() {
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let url = ledger_dir.path().to_str().unwrap();
+        let storage =
+            LedgerStorage::new_with_backend(Arc::new(ObjectStorage::new(url, false).unwrap()));
+        assert_eq!(storage.get_first_available_block().await.unwrap(), None);
+
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let blocks: Vec<_> = [5, 7, 300]
+            .into_iter()
+            .map(|slot| (slot, new_test_block(slot, &from, &to)))
+            .collect();
+        for (slot, block) in &blocks {
+            storage
+                .upload_confirmed_block(*slot, block.clone())
+                .await
+                .unwrap();
+        }
+        let signatures: Vec<_> = blocks
+            .iter()
+            .map(|(_slot, block)| block.transactions[0].transaction.signatures[0])
+            .collect();
+
+        assert_eq!(storage.get_first_available_block().await.unwrap(), Some(5));
+        assert_eq!(
+            storage.get_confirmed_blocks(6, 10).await.unwrap(),
+            vec![7, 300]
+        );
+        assert_eq!(
+            storage.get_confirmed_blocks(0, 2).await.unwrap(),
+            vec![5, 7]
+        );
+        assert!(storage.confirmed_block_exists(300).await.unwrap());
+        assert_eq!(
+            storage.get_confirmed_block(7).await.unwrap().blockhash,
+            blocks[1].1.blockhash
+        );
+        assert!(matches!(
+            storage.get_confirmed_block(8).await,
+            Err(Error::BlockNotFound(8))
+        ));
+
+        let transaction = storage
+            .get_confirmed_transaction(&signatures[2])
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(transaction.slot, 300);
+        assert_eq!(transaction.block_time, Some(300));
+
+        // the tx-by-addr index is read newest first
+        let address_signatures = |infos: Vec<(ConfirmedTransactionStatusWithSignature, u32)>| {
+            infos
+                .into_iter()
+                .map(|(info, _index)| (info.slot, info.signature))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            address_signatures(
+                storage
+                    .get_confirmed_signatures_for_address(&to, None, None, 10)
+                    .await
+                    .unwrap()
+            ),
+            vec![(300, signatures[2]), (7, signatures[1]), (5, signatures[0])]
+        );
+        assert_eq!(
+            address_signatures(
+                storage
+                    .get_confirmed_signatures_for_address(
+                        &from.pubkey(),
+                        Some(&signatures[2]),
+                        None,
+                        1
+                    )
+                    .await
+                    .unwrap()
+            ),
+            vec![(7, signatures[1])]
+        );
+
+        storage.delete_confirmed_block(7, false).await.unwrap();
+        assert_eq!(
+            storage.get_confirmed_blocks(0, 10).await.unwrap(),
+            vec![5, 300]
+        );
+        assert!(matches!(
+            storage.get_signature_status(&signatures[1]).await,
+            Err(Error::SignatureNotFound)
+        ));
+
+        // a read-only storage serves the same data but rejects writes
+        let storage =
+            LedgerStorage::new_with_backend(Arc::new(ObjectStorage::new(url, true).unwrap()));
+        assert_eq!(storage.get_first_available_block().await.unwrap(), Some(5));
+        assert!(matches!(
+            storage
+                .upload_confirmed_block(8, new_test_block(8, &from, &to))
+                .await,
+            Err(Error::ReadOnly)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_nested_slot_keys() {
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let storage = ObjectStorage::new(ledger_dir.path().to_str().unwrap(), false).unwrap();
+        let slots = [1, 0xfff, 0x1000, 0x1001, 0xff_ffff, 0x100_0000, 0x100_0001];
+        let row_data: Vec<_> = slots
+            .iter()
+            .map(|slot| (slot_to_blocks_key(*slot), vec![]))
+            .collect();
+        storage.put_row_data("blocks", &row_data).await.unwrap();
+        let address = Pubkey::new_unique();
+        let tx_by_addr_key = format!("{address}/{}", slot_to_tx_by_addr_key(5));
+        storage
+            .put_row_data("tx-by-addr", &[(tx_by_addr_key.clone(), vec![])])
+            .await
+            .unwrap();
+        storage
+            .put_row_data("tx", &[("signature".to_string(), vec![])])
+            .await
+            .unwrap();
+
+        assert!(ledger_dir
+            .path()
+            .join("blocks/0000000000/0000000000001/0000000000001001")
+            .is_file());
+        assert!(ledger_dir.path().join("tx/signature").is_file());
+
+        let list = |start_slot: Option<Slot>, end_slot: Option<Slot>, rows_limit| {
+            storage.get_row_keys(
+                "blocks",
+                start_slot.map(slot_to_blocks_key),
+                end_slot.map(slot_to_blocks_key),
+                rows_limit,
+            )
+        };
+        let keys = |slots: &[Slot]| {
+            slots
+                .iter()
+                .copied()
+                .map(slot_to_blocks_key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(list(None, None, 100).await.unwrap(), keys(&slots));
+        assert_eq!(list(None, None, 2).await.unwrap(), keys(&slots[..2]));
+        assert_eq!(
+            list(Some(0x1000), None, 3).await.unwrap(),
+            keys(&[0x1000, 0x1001, 0xff_ffff])
+        );
+        assert_eq!(
+            list(Some(0x1002), Some(0x100_0000), 100).await.unwrap(),
+            keys(&[0xff_ffff, 0x100_0000])
+        );
+        assert_eq!(list(Some(0x100_0002), None, 100).await.unwrap(), keys(&[]));
+
+        assert_eq!(
+            storage
+                .get_row_keys("tx-by-addr", Some(tx_by_addr_key.clone()), None, 10)
+                .await
+                .unwrap(),
+            vec![tx_by_addr_key.clone()]
+        );
+        assert!(storage
+            .row_key_exists("tx-by-addr", tx_by_addr_key)
+            .await
+            .unwrap());
+        assert!(storage
+            .row_key_exists("tx", "signature".to_string())
+            .await
+            .unwrap());
+        storage
+            .delete_rows("blocks", &keys(&[0x1001]))
+            .await
+            .unwrap();
+        assert_eq!(
+            list(Some(0x1001), None, 1).await.unwrap(),
+            keys(&[0xff_ffff])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_confirmed_block() {
+        let ledger_dir = tempfile::TempDir::new().unwrap();
+        let backend =
+            Arc::new(ObjectStorage::new(ledger_dir.path().to_str().unwrap(), false).unwrap());
+        let storage = LedgerStorage::new_with_backend(backend.clone());
+
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let block = new_test_block(5, &from, &to);
+        assert_eq!(
+            storage.verify_confirmed_block(5, &block).await.unwrap(),
+            vec![StoredBlockMismatch::MissingBlock]
+        );
+
+        storage
+            .upload_confirmed_block(5, block.clone())
+            .await
+            .unwrap();
+        assert!(storage
+            .verify_confirmed_block(5, &block)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // a partial upload leaves index rows missing
+        let signature = block.transactions[0].transaction.signatures[0];
+        backend
+            .delete_rows("tx", &[signature.to_string()])
+            .await
+            .unwrap();
+        backend
+            .delete_rows(
+                "tx-by-addr",
+                &[format!("{}/{}", to, slot_to_tx_by_addr_key(5))],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.verify_confirmed_block(5, &block).await.unwrap(),
+            vec![
+                StoredBlockMismatch::MissingTransactions(1),
+                StoredBlockMismatch::MissingTxByAddr(1)
+            ]
+        );
+
+        // a different block stored for the slot
+        let other_block = new_test_block(5, &from, &to);
+        let mismatches = storage
+            .verify_confirmed_block(5, &other_block)
+            .await
+            .unwrap();
+        assert_eq!(
+            mismatches,
+            vec![
+                StoredBlockMismatch::Blockhash {
+                    expected: other_block.blockhash.clone(),
+                    stored: block.blockhash.clone(),
+                },
+                StoredBlockMismatch::MissingTransactions(1),
+                StoredBlockMismatch::MissingTxByAddr(1),
+            ]
+        );
+        assert!(mismatches[0].is_different_block());
+
+        storage
+            .upload_confirmed_block(5, other_block.clone())
+            .await
+            .unwrap();
+        assert!(storage
+            .verify_confirmed_block(5, &other_block)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
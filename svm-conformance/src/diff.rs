@@ -0,0 +1,579 @@
+//! Comparison of locally observed instruction effects and transaction results
+//! against a fixture's expected output.
+
+use {
+    crate::{
+        fixture::{Fixture, FixtureError},
+        instr::{execute_instr, pubkey_from_bytes},
+        proto::{AcctState, InstrEffects, InstrFixture, ResultingState, TxnFixture, TxnResult},
+        txn::execute_txn,
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::fmt,
+};
+
+/// A single difference between expected and actual effects
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EffectsDiff {
+    Result {
+        expected: i32,
+        actual: i32,
+    },
+    CustomError {
+        expected: u32,
+        actual: u32,
+    },
+    ComputeUnits {
+        expected: u64,
+        actual: u64,
+    },
+    ReturnData {
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+    /// An account the fixture expects to be modified is not writable locally
+    MissingAccount(Pubkey),
+    /// A writable account was modified locally but not listed by the fixture
+    UnexpectedAccount(Pubkey),
+    Account {
+        pubkey: Pubkey,
+        field: AccountField,
+        expected: String,
+        actual: String,
+    },
+    /// The program logs differ, starting at `line` (zero-based); a missing
+    /// line means that side of the logs ended first
+    Logs {
+        line: usize,
+        expected: Option<String>,
+        actual: Option<String>,
+    },
+    Txn {
+        field: TxnField,
+        expected: String,
+        actual: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccountField {
+    Lamports,
+    Data,
+    Owner,
+    Executable,
+    RentEpoch,
+}
+
+impl fmt::Display for AccountField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Lamports => "lamports",
+            Self::Data => "data",
+            Self::Owner => "owner",
+            Self::Executable => "executable",
+            Self::RentEpoch => "rent_epoch",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Fields of a transaction result which are compared outside of the
+/// resulting account states
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TxnField {
+    Executed,
+    SanitizationError,
+    IsOk,
+    Status,
+    InstructionError,
+    InstructionErrorIndex,
+    CustomError,
+    ExecutedUnits,
+    TransactionFee,
+    PrioritizationFee,
+    Rent,
+    TransactionRent,
+    RentDebits,
+}
+
+impl fmt::Display for TxnField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Executed => "executed",
+            Self::SanitizationError => "sanitization_error",
+            Self::IsOk => "is_ok",
+            Self::Status => "status",
+            Self::InstructionError => "instruction_error",
+            Self::InstructionErrorIndex => "instruction_error_index",
+            Self::CustomError => "custom_error",
+            Self::ExecutedUnits => "executed_units",
+            Self::TransactionFee => "transaction_fee",
+            Self::PrioritizationFee => "prioritization_fee",
+            Self::Rent => "rent",
+            Self::TransactionRent => "transaction_rent",
+            Self::RentDebits => "rent_debits",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for EffectsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Result { expected, actual } => {
+                write!(f, "result: expected {expected}, actual {actual}")
+            }
+            Self::CustomError { expected, actual } => {
+                write!(f, "custom error: expected {expected}, actual {actual}")
+            }
+            Self::ComputeUnits { expected, actual } => {
+                write!(
+                    f,
+                    "remaining compute units: expected {expected}, actual {actual}"
+                )
+            }
+            Self::ReturnData { expected, actual } => write!(
+                f,
+                "return data: expected {}, actual {}",
+                hex_summary(expected),
+                hex_summary(actual)
+            ),
+            Self::MissingAccount(pubkey) => {
+                write!(
+                    f,
+                    "account {pubkey}: expected to be modified, but is not writable"
+                )
+            }
+            Self::UnexpectedAccount(pubkey) => {
+                write!(
+                    f,
+                    "account {pubkey}: modified, but not expected by the fixture"
+                )
+            }
+            Self::Account {
+                pubkey,
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "account {pubkey} {field}: expected {expected}, actual {actual}"
+            ),
+            Self::Logs {
+                line,
+                expected,
+                actual,
+            } => {
+                let describe = |log: &Option<String>| {
+                    log.as_ref()
+                        .map_or_else(|| "end of logs".to_string(), |log| format!("{log:?}"))
+                };
+                write!(
+                    f,
+                    "program log line {line}: expected {}, actual {}",
+                    describe(expected),
+                    describe(actual)
+                )
+            }
+            Self::Txn {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "transaction {field}: expected {expected}, actual {actual}"
+            ),
+        }
+    }
+}
+
+/// Maximum number of bytes printed when reporting a data mismatch
+const MAX_HEX_BYTES: usize = 32;
+
+fn hex_summary(bytes: &[u8]) -> String {
+    let mut summary: String = bytes
+        .iter()
+        .take(MAX_HEX_BYTES)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    if bytes.len() > MAX_HEX_BYTES {
+        summary.push_str("..");
+    }
+    format!("[{} bytes] {summary}", bytes.len())
+}
+
+fn first_mismatch(expected: &[u8], actual: &[u8]) -> Option<usize> {
+    expected
+        .iter()
+        .zip(actual)
+        .position(|(expected, actual)| expected != actual)
+        .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())))
+}
+
+fn diff_account(expected: &AcctState, actual: &AcctState, diffs: &mut Vec<EffectsDiff>) {
+    let pubkey = Pubkey::try_from(expected.address.as_slice()).unwrap_or_default();
+    let mut push = |field, expected: String, actual: String| {
+        diffs.push(EffectsDiff::Account {
+            pubkey,
+            field,
+            expected,
+            actual,
+        })
+    };
+    if expected.lamports != actual.lamports {
+        push(
+            AccountField::Lamports,
+            expected.lamports.to_string(),
+            actual.lamports.to_string(),
+        );
+    }
+    if let Some(offset) = first_mismatch(&expected.data, &actual.data) {
+        push(
+            AccountField::Data,
+            format!(
+                "{} (first difference at offset {offset})",
+                hex_summary(&expected.data)
+            ),
+            hex_summary(&actual.data),
+        );
+    }
+    if expected.owner != actual.owner {
+        let to_string = |owner: &[u8]| {
+            Pubkey::try_from(owner)
+                .map(|owner| owner.to_string())
+                .unwrap_or_else(|_| hex_summary(owner))
+        };
+        push(
+            AccountField::Owner,
+            to_string(&expected.owner),
+            to_string(&actual.owner),
+        );
+    }
+    if expected.executable != actual.executable {
+        push(
+            AccountField::Executable,
+            expected.executable.to_string(),
+            actual.executable.to_string(),
+        );
+    }
+    if expected.rent_epoch != actual.rent_epoch {
+        push(
+            AccountField::RentEpoch,
+            expected.rent_epoch.to_string(),
+            actual.rent_epoch.to_string(),
+        );
+    }
+}
+
+/// Compares `actual` against `expected`, returning every difference found.
+///
+/// Account state and compute units are only compared when both executions
+/// succeeded, as fixtures do not record them for failed instructions.
+pub fn diff_effects(expected: &InstrEffects, actual: &InstrEffects) -> Vec<EffectsDiff> {
+    let mut diffs = Vec::new();
+    if expected.result != actual.result {
+        diffs.push(EffectsDiff::Result {
+            expected: expected.result,
+            actual: actual.result,
+        });
+    }
+    if expected.custom_err != actual.custom_err {
+        diffs.push(EffectsDiff::CustomError {
+            expected: expected.custom_err,
+            actual: actual.custom_err,
+        });
+    }
+    if expected.result != 0 || actual.result != 0 {
+        return diffs;
+    }
+
+    if expected.cu_avail != actual.cu_avail {
+        diffs.push(EffectsDiff::ComputeUnits {
+            expected: expected.cu_avail,
+            actual: actual.cu_avail,
+        });
+    }
+    if expected.return_data != actual.return_data {
+        diffs.push(EffectsDiff::ReturnData {
+            expected: expected.return_data.clone(),
+            actual: actual.return_data.clone(),
+        });
+    }
+
+    diff_accounts(
+        &expected.modified_accounts,
+        &actual.modified_accounts,
+        &mut diffs,
+    );
+    diffs
+}
+
+fn diff_accounts(expected: &[AcctState], actual: &[AcctState], diffs: &mut Vec<EffectsDiff>) {
+    for expected_account in expected {
+        match actual
+            .iter()
+            .find(|actual_account| actual_account.address == expected_account.address)
+        {
+            Some(actual_account) => diff_account(expected_account, actual_account, diffs),
+            None => diffs.push(EffectsDiff::MissingAccount(
+                Pubkey::try_from(expected_account.address.as_slice()).unwrap_or_default(),
+            )),
+        }
+    }
+    // Fixtures may omit writable accounts which were left untouched, so only
+    // those that were actually modified are reported
+    for actual_account in actual {
+        let is_expected = expected
+            .iter()
+            .any(|expected_account| expected_account.address == actual_account.address);
+        if !is_expected {
+            diffs.push(EffectsDiff::UnexpectedAccount(
+                Pubkey::try_from(actual_account.address.as_slice()).unwrap_or_default(),
+            ));
+        }
+    }
+}
+
+/// Compares a transaction result against `expected`, returning every
+/// difference found.
+///
+/// Execution details and account state are only compared when both sides
+/// executed the transaction, as fixtures do not record them otherwise.
+pub fn diff_txn_result(expected: &TxnResult, actual: &TxnResult) -> Vec<EffectsDiff> {
+    let mut diffs = Vec::new();
+    let mut push = |field, expected: String, actual: String| {
+        if expected != actual {
+            diffs.push(EffectsDiff::Txn {
+                field,
+                expected,
+                actual,
+            });
+        }
+    };
+    push(
+        TxnField::Executed,
+        expected.executed.to_string(),
+        actual.executed.to_string(),
+    );
+    push(
+        TxnField::SanitizationError,
+        expected.sanitization_error.to_string(),
+        actual.sanitization_error.to_string(),
+    );
+    push(
+        TxnField::IsOk,
+        expected.is_ok.to_string(),
+        actual.is_ok.to_string(),
+    );
+    push(
+        TxnField::Status,
+        expected.status.to_string(),
+        actual.status.to_string(),
+    );
+    push(
+        TxnField::InstructionError,
+        expected.instruction_error.to_string(),
+        actual.instruction_error.to_string(),
+    );
+    push(
+        TxnField::InstructionErrorIndex,
+        expected.instruction_error_index.to_string(),
+        actual.instruction_error_index.to_string(),
+    );
+    push(
+        TxnField::CustomError,
+        expected.custom_error.to_string(),
+        actual.custom_error.to_string(),
+    );
+    if !expected.executed || !actual.executed {
+        return diffs;
+    }
+
+    let expected_fees = expected.fee_details.clone().unwrap_or_default();
+    let actual_fees = actual.fee_details.clone().unwrap_or_default();
+    push(
+        TxnField::TransactionFee,
+        expected_fees.transaction_fee.to_string(),
+        actual_fees.transaction_fee.to_string(),
+    );
+    push(
+        TxnField::PrioritizationFee,
+        expected_fees.prioritization_fee.to_string(),
+        actual_fees.prioritization_fee.to_string(),
+    );
+    push(
+        TxnField::ExecutedUnits,
+        expected.executed_units.to_string(),
+        actual.executed_units.to_string(),
+    );
+    push(
+        TxnField::Rent,
+        expected.rent.to_string(),
+        actual.rent.to_string(),
+    );
+
+    let expected_state = expected.resulting_state.clone().unwrap_or_default();
+    let actual_state = actual.resulting_state.clone().unwrap_or_default();
+    push(
+        TxnField::TransactionRent,
+        expected_state.transaction_rent.to_string(),
+        actual_state.transaction_rent.to_string(),
+    );
+    let rent_debits = |state: &ResultingState| {
+        let mut rent_debits = state
+            .rent_debits
+            .iter()
+            .filter(|rent_debit| rent_debit.rent_collected != 0)
+            .map(|rent_debit| {
+                let pubkey = Pubkey::try_from(rent_debit.pubkey.as_slice()).unwrap_or_default();
+                (pubkey.to_string(), rent_debit.rent_collected)
+            })
+            .collect::<Vec<_>>();
+        rent_debits.sort_unstable();
+        format!("{rent_debits:?}")
+    };
+    push(
+        TxnField::RentDebits,
+        rent_debits(&expected_state),
+        rent_debits(&actual_state),
+    );
+
+    if expected.return_data != actual.return_data {
+        diffs.push(EffectsDiff::ReturnData {
+            expected: expected.return_data.clone(),
+            actual: actual.return_data.clone(),
+        });
+    }
+    diff_accounts(
+        &expected_state.acct_states,
+        &actual_state.acct_states,
+        &mut diffs,
+    );
+    diffs
+}
+
+/// Compares program logs line by line, reporting the first difference
+pub fn diff_logs(expected: &[String], actual: &[String]) -> Option<EffectsDiff> {
+    (0..expected.len().max(actual.len()))
+        .find(|&line| expected.get(line) != actual.get(line))
+        .map(|line| EffectsDiff::Logs {
+            line,
+            expected: expected.get(line).cloned(),
+            actual: actual.get(line).cloned(),
+        })
+}
+
+/// The outcome of running a single fixture
+#[derive(Debug)]
+pub struct FixtureReport {
+    /// Program logs emitted while running the fixture
+    pub logs: Vec<String>,
+    pub diffs: Vec<EffectsDiff>,
+}
+
+impl FixtureReport {
+    pub fn passed(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+/// Writable accounts whose state is unchanged are not part of a fixture's
+/// expected accounts, so drop them from `actual` before comparing
+fn retain_changed_accounts(
+    input: &[AcctState],
+    expected: &[AcctState],
+    actual: &mut Vec<AcctState>,
+) -> Result<(), FixtureError> {
+    let mut unchanged = Vec::new();
+    for account in actual.iter() {
+        let address = pubkey_from_bytes(&account.address)?;
+        let is_unchanged = input
+            .iter()
+            .find(|input_account| input_account.address == account.address)
+            .is_some_and(|input_account| {
+                input_account.lamports == account.lamports
+                    && input_account.data == account.data
+                    && input_account.owner == account.owner
+                    && input_account.executable == account.executable
+                    && input_account.rent_epoch == account.rent_epoch
+            });
+        let is_expected = expected
+            .iter()
+            .any(|expected_account| expected_account.address == account.address);
+        if is_unchanged && !is_expected {
+            unchanged.push(address);
+        }
+    }
+    actual.retain(|account| {
+        Pubkey::try_from(account.address.as_slice())
+            .is_ok_and(|address| !unchanged.contains(&address))
+    });
+    Ok(())
+}
+
+/// Executes `fixture` and compares the observed effects with its expected
+/// output. Program logs are compared only if `expected_logs` is given.
+pub fn run_fixture(
+    fixture: &Fixture,
+    expected_logs: Option<&[String]>,
+) -> Result<FixtureReport, FixtureError> {
+    let mut report = match fixture {
+        Fixture::Instr(fixture) => run_instr_fixture(fixture)?,
+        Fixture::Txn(fixture) => run_txn_fixture(fixture)?,
+    };
+    if let Some(expected_logs) = expected_logs {
+        report.diffs.extend(diff_logs(expected_logs, &report.logs));
+    }
+    Ok(report)
+}
+
+fn run_instr_fixture(fixture: &InstrFixture) -> Result<FixtureReport, FixtureError> {
+    let input = fixture
+        .input
+        .as_ref()
+        .ok_or(FixtureError::MissingField("input"))?;
+    let expected = fixture
+        .output
+        .as_ref()
+        .ok_or(FixtureError::MissingField("output"))?;
+    let mut outcome = execute_instr(input)?;
+    retain_changed_accounts(
+        &input.accounts,
+        &expected.modified_accounts,
+        &mut outcome.effects.modified_accounts,
+    )?;
+    Ok(FixtureReport {
+        diffs: diff_effects(expected, &outcome.effects),
+        logs: outcome.logs,
+    })
+}
+
+fn run_txn_fixture(fixture: &TxnFixture) -> Result<FixtureReport, FixtureError> {
+    let input = fixture
+        .input
+        .as_ref()
+        .ok_or(FixtureError::MissingField("input"))?;
+    let expected = fixture
+        .output
+        .as_ref()
+        .ok_or(FixtureError::MissingField("output"))?;
+    let mut outcome = execute_txn(input)?;
+    let input_accounts = input
+        .tx
+        .as_ref()
+        .and_then(|tx| tx.message.as_ref())
+        .map(|message| message.account_shared_data.as_slice())
+        .unwrap_or_default();
+    let expected_accounts = expected
+        .resulting_state
+        .as_ref()
+        .map(|state| state.acct_states.as_slice())
+        .unwrap_or_default();
+    if let Some(state) = outcome.result.resulting_state.as_mut() {
+        retain_changed_accounts(input_accounts, expected_accounts, &mut state.acct_states)?;
+    }
+    Ok(FixtureReport {
+        diffs: diff_txn_result(expected, &outcome.result),
+        logs: outcome.logs,
+    })
+}
@@ -0,0 +1,136 @@
+//! Loading of protobuf-encoded instruction and transaction fixtures from disk.
+//!
+//! Fixtures do not carry program logs, so expected logs are read from an
+//! optional file next to the fixture with the same name and the `logs`
+//! extension, one log line per line.
+
+use {
+    crate::proto::{FixtureMetadata, InstrFixture, TxnFixture},
+    prost::Message,
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+    },
+    thiserror::Error,
+};
+
+/// File extension used for serialized fixtures
+pub const FIXTURE_EXTENSION: &str = "fix";
+
+/// File extension of the optional expected program logs for a fixture
+pub const EXPECTED_LOGS_EXTENSION: &str = "logs";
+
+/// Entrypoint recorded in the metadata of instruction fixtures
+pub const INSTR_FIXTURE_ENTRYPOINT: &str = "sol_compat_instr_execute_v1";
+
+/// Entrypoint recorded in the metadata of transaction fixtures
+pub const TXN_FIXTURE_ENTRYPOINT: &str = "sol_compat_txn_execute_v1";
+
+#[derive(Error, Debug)]
+pub enum FixtureError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("failed to decode fixture: {0}")]
+    Decode(#[from] prost::DecodeError),
+
+    #[error("fixture is missing its {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid pubkey of length {0}")]
+    InvalidPubkey(usize),
+
+    #[error("invalid hash of length {0}")]
+    InvalidHash(usize),
+
+    #[error("invalid signature of length {0}")]
+    InvalidSignature(usize),
+
+    #[error("instruction account index {0} is out of bounds")]
+    InvalidAccountIndex(u32),
+
+    #[error("failed to create program runtime environment: {0}")]
+    Environment(String),
+
+    #[error("unsupported fixture entrypoint {0}")]
+    UnsupportedFixture(String),
+}
+
+/// A decoded fixture of either kind
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fixture {
+    Instr(InstrFixture),
+    Txn(TxnFixture),
+}
+
+/// The metadata shared by every fixture kind; other fields are skipped when
+/// decoding
+#[derive(Clone, PartialEq, Message)]
+struct FixtureHeader {
+    #[prost(message, optional, tag = "1")]
+    metadata: Option<FixtureMetadata>,
+}
+
+/// Decodes the fixture stored at `path`.
+///
+/// Instruction and transaction fixtures share the same field layout, so they
+/// are told apart by the entrypoint in their metadata. Fixtures without an
+/// entrypoint are assumed to be instruction fixtures.
+pub fn load_fixture(path: &Path) -> Result<Fixture, FixtureError> {
+    let buffer = fs::read(path)?;
+    let entrypoint = FixtureHeader::decode(buffer.as_slice())?
+        .metadata
+        .map(|metadata| metadata.fn_entrypoint)
+        .unwrap_or_default();
+    match entrypoint.as_str() {
+        "" | INSTR_FIXTURE_ENTRYPOINT => {
+            Ok(Fixture::Instr(InstrFixture::decode(buffer.as_slice())?))
+        }
+        TXN_FIXTURE_ENTRYPOINT => Ok(Fixture::Txn(TxnFixture::decode(buffer.as_slice())?)),
+        _ => Err(FixtureError::UnsupportedFixture(entrypoint)),
+    }
+}
+
+/// Reads the expected program logs of the fixture at `fixture_path`, or
+/// returns `None` if the fixture has no logs file
+pub fn load_expected_logs(fixture_path: &Path) -> Result<Option<Vec<String>>, FixtureError> {
+    match fs::read_to_string(fixture_path.with_extension(EXPECTED_LOGS_EXTENSION)) {
+        Ok(logs) => Ok(Some(logs.lines().map(String::from).collect())),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Expands `paths` into the list of fixture files to run, descending into
+/// directories and keeping only files with the fixture extension. Paths named
+/// explicitly are always kept. The result is sorted so that runs are
+/// reproducible.
+pub fn collect_fixture_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<PathBuf>, FixtureError> {
+    let mut fixture_paths = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            collect_from_dir(path, &mut fixture_paths)?;
+        } else {
+            fixture_paths.push(path.to_path_buf());
+        }
+    }
+    fixture_paths.sort();
+    fixture_paths.dedup();
+    Ok(fixture_paths)
+}
+
+fn collect_from_dir(dir: &Path, fixture_paths: &mut Vec<PathBuf>) -> Result<(), FixtureError> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_from_dir(&path, fixture_paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == FIXTURE_EXTENSION)
+        {
+            fixture_paths.push(path);
+        }
+    }
+    Ok(())
+}
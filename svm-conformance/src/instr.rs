@@ -0,0 +1,388 @@
+//! Executes an instruction fixture's `InstrContext` through the program
+//! runtime's `InvokeContext`, producing the `InstrEffects` observed locally.
+
+use {
+    crate::{
+        fixture::FixtureError,
+        proto::{AcctState, EpochContext, InstrContext, InstrEffects},
+    },
+    solana_bpf_loader_program::syscalls::{
+        create_program_runtime_environment_v1, create_program_runtime_environment_v2,
+    },
+    solana_builtins::{prototype::BuiltinPrototype, BUILTINS},
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_feature_set::{remove_accounts_executable_flag_checks, FeatureSet, FEATURE_NAMES},
+    solana_log_collector::LogCollector,
+    solana_program_runtime::{
+        invoke_context::{EnvironmentConfig, InvokeContext},
+        loaded_programs::{ProgramCacheEntry, ProgramCacheForTxBatch, ProgramRuntimeEnvironments},
+        sysvar_cache::SysvarCache,
+    },
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable,
+        clock::{Clock, Slot},
+        epoch_schedule::EpochSchedule,
+        hash::Hash,
+        instruction::InstructionError,
+        loader_v4, native_loader,
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar::{last_restart_slot::LastRestartSlot, SysvarId},
+        transaction_context::{
+            IndexOfAccount, InstructionAccount, TransactionAccount, TransactionContext,
+        },
+    },
+    solana_svm::{program_loader, transaction_processing_callback::TransactionProcessingCallback},
+    solana_timings::ExecuteTimings,
+    std::{collections::HashMap, rc::Rc, sync::Arc},
+};
+
+/// Slot used when a fixture does not provide a `SlotContext`
+pub(crate) const DEFAULT_FIXTURE_SLOT: Slot = 10;
+
+/// The locally observed result of executing an `InstrContext`
+#[derive(Debug)]
+pub struct InstrOutcome {
+    pub effects: InstrEffects,
+    /// Program logs emitted during execution. Fixtures do not carry logs, so
+    /// these are only compared when a fixture has an expected logs file.
+    pub logs: Vec<String>,
+}
+
+/// Maps the first 8 bytes of every known feature id to that feature
+fn indexed_features() -> HashMap<u64, Pubkey> {
+    FEATURE_NAMES
+        .keys()
+        .map(|feature_id| (feature_u64(feature_id), *feature_id))
+        .collect()
+}
+
+fn feature_u64(feature_id: &Pubkey) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&feature_id.as_ref()[..8]);
+    u64::from_le_bytes(bytes)
+}
+
+/// Activates every known feature listed by the fixture's `EpochContext`
+pub(crate) fn feature_set_from_context(epoch_context: Option<&EpochContext>) -> FeatureSet {
+    let mut feature_set = FeatureSet::default();
+    if let Some(features) = epoch_context.and_then(|epoch_context| epoch_context.features.as_ref())
+    {
+        let indexed_features = indexed_features();
+        for feature in &features.features {
+            if let Some(feature_id) = indexed_features.get(feature) {
+                feature_set.activate(feature_id, 0);
+            }
+        }
+    }
+    feature_set
+}
+
+/// Builtin programs that are enabled and not yet migrated to Core BPF under
+/// `feature_set`
+pub(crate) fn enabled_builtins(
+    feature_set: &FeatureSet,
+) -> impl Iterator<Item = &'static BuiltinPrototype> + '_ {
+    BUILTINS.iter().filter(|builtin| {
+        let is_enabled = builtin
+            .enable_feature_id
+            .map_or(true, |feature_id| feature_set.is_active(&feature_id));
+        let is_migrated = builtin
+            .core_bpf_migration_config
+            .as_ref()
+            .is_some_and(|config| feature_set.is_active(&config.feature_id));
+        is_enabled && !is_migrated
+    })
+}
+
+pub(crate) fn pubkey_from_bytes(bytes: &[u8]) -> Result<Pubkey, FixtureError> {
+    Pubkey::try_from(bytes).map_err(|_| FixtureError::InvalidPubkey(bytes.len()))
+}
+
+pub(crate) fn account_from_state(state: &AcctState) -> Result<AccountSharedData, FixtureError> {
+    let mut account = AccountSharedData::new(state.lamports, 0, &pubkey_from_bytes(&state.owner)?);
+    account.set_data_from_slice(&state.data);
+    account.set_executable(state.executable);
+    account.set_rent_epoch(state.rent_epoch);
+    Ok(account)
+}
+
+pub(crate) fn state_from_account(pubkey: &Pubkey, account: &AccountSharedData) -> AcctState {
+    AcctState {
+        address: pubkey.to_bytes().to_vec(),
+        lamports: account.lamports(),
+        data: account.data().to_vec(),
+        executable: account.executable(),
+        rent_epoch: account.rent_epoch(),
+        owner: account.owner().to_bytes().to_vec(),
+        seed_addr: None,
+    }
+}
+
+/// Encodes an instruction error the same way fixtures do: one plus the
+/// variant index of the error, with custom errors also reporting their code
+pub(crate) fn encode_instruction_error(error: &InstructionError) -> (i32, u32) {
+    let variant = bincode::serialize(error)
+        .ok()
+        .and_then(|bytes| bytes.get(..4).map(|prefix| prefix.try_into().unwrap()))
+        .map(i32::from_le_bytes)
+        .unwrap_or_default();
+    let custom_err = match error {
+        InstructionError::Custom(code) => *code,
+        _ => 0,
+    };
+    (variant.saturating_add(1), custom_err)
+}
+
+/// Account lookups for loading the fixture's on-chain programs
+struct FixtureAccounts<'a>(&'a [TransactionAccount]);
+
+impl TransactionProcessingCallback for FixtureAccounts<'_> {
+    fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
+        self.get_account_shared_data(account)
+            .filter(|account| account.lamports() > 0)
+            .and_then(|account| owners.iter().position(|owner| account.owner() == owner))
+    }
+
+    fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.0
+            .iter()
+            .find(|(key, _)| key == pubkey)
+            .map(|(_, account)| account.clone())
+    }
+}
+
+fn is_loader(owner: &Pubkey) -> bool {
+    bpf_loader::check_id(owner)
+        || bpf_loader_deprecated::check_id(owner)
+        || bpf_loader_upgradeable::check_id(owner)
+        || loader_v4::check_id(owner)
+}
+
+/// Contents of the sysvars a fixture may omit from its accounts
+pub(crate) fn default_sysvar_data(pubkey: &Pubkey, slot: Slot) -> Option<Vec<u8>> {
+    if *pubkey == Clock::id() {
+        let clock = Clock {
+            slot,
+            ..Clock::default()
+        };
+        Some(bincode::serialize(&clock).unwrap())
+    } else if *pubkey == EpochSchedule::id() {
+        Some(bincode::serialize(&EpochSchedule::default()).unwrap())
+    } else if *pubkey == Rent::id() {
+        Some(bincode::serialize(&Rent::default()).unwrap())
+    } else if *pubkey == LastRestartSlot::id() {
+        Some(bincode::serialize(&LastRestartSlot::default()).unwrap())
+    } else {
+        None
+    }
+}
+
+fn new_sysvar_cache(transaction_accounts: &[TransactionAccount], slot: Slot) -> SysvarCache {
+    let accounts = FixtureAccounts(transaction_accounts);
+    let mut sysvar_cache = SysvarCache::default();
+    sysvar_cache.fill_missing_entries(|pubkey, set_sysvar| {
+        if let Some(account) = accounts.get_account_shared_data(pubkey) {
+            if account.lamports() > 0 {
+                set_sysvar(account.data());
+                return;
+            }
+        }
+        if let Some(data) = default_sysvar_data(pubkey, slot) {
+            set_sysvar(&data);
+        }
+    });
+    sysvar_cache
+}
+
+/// Executes `input` and returns the locally observed effects
+pub fn execute_instr(input: &InstrContext) -> Result<InstrOutcome, FixtureError> {
+    let program_id = pubkey_from_bytes(&input.program_id)?;
+    let slot = input
+        .slot_context
+        .as_ref()
+        .map(|slot_context| slot_context.slot)
+        .unwrap_or(DEFAULT_FIXTURE_SLOT);
+
+    let feature_set = feature_set_from_context(input.epoch_context.as_ref());
+
+    let mut transaction_accounts = input
+        .accounts
+        .iter()
+        .map(|state| {
+            Ok((
+                pubkey_from_bytes(&state.address)?,
+                account_from_state(state)?,
+            ))
+        })
+        .collect::<Result<Vec<TransactionAccount>, FixtureError>>()?;
+    let program_index = match transaction_accounts
+        .iter()
+        .position(|(key, _)| *key == program_id)
+    {
+        Some(program_index) => program_index,
+        None => {
+            // Builtin programs are commonly omitted from the account list
+            let mut program_account = AccountSharedData::new(1, 0, &native_loader::id());
+            program_account.set_executable(true);
+            transaction_accounts.push((program_id, program_account));
+            transaction_accounts.len().saturating_sub(1)
+        }
+    };
+
+    let mut instruction_accounts = Vec::with_capacity(input.instr_accounts.len());
+    for (instruction_account_index, instr_acct) in input.instr_accounts.iter().enumerate() {
+        let index_in_transaction = instr_acct.index as usize;
+        if index_in_transaction >= input.accounts.len() {
+            return Err(FixtureError::InvalidAccountIndex(instr_acct.index));
+        }
+        let index_in_callee = input
+            .instr_accounts
+            .iter()
+            .position(|other| other.index == instr_acct.index)
+            .unwrap_or(instruction_account_index);
+        instruction_accounts.push(InstructionAccount {
+            index_in_transaction: index_in_transaction as IndexOfAccount,
+            index_in_caller: index_in_transaction as IndexOfAccount,
+            index_in_callee: index_in_callee as IndexOfAccount,
+            is_signer: instr_acct.is_signer,
+            is_writable: instr_acct.is_writable,
+        });
+    }
+
+    let compute_budget = ComputeBudget {
+        compute_unit_limit: input.cu_avail,
+        ..ComputeBudget::default()
+    };
+    let environments = ProgramRuntimeEnvironments {
+        program_runtime_v1: Arc::new(
+            create_program_runtime_environment_v1(&feature_set, &compute_budget, false, false)
+                .map_err(|err| FixtureError::Environment(err.to_string()))?,
+        ),
+        program_runtime_v2: Arc::new(create_program_runtime_environment_v2(
+            &compute_budget,
+            false,
+        )),
+    };
+
+    let sysvar_cache = new_sysvar_cache(&transaction_accounts, slot);
+    let epoch = sysvar_cache
+        .get_epoch_schedule()
+        .map(|epoch_schedule| epoch_schedule.get_epoch(slot))
+        .unwrap_or_default();
+    let rent = sysvar_cache
+        .get_rent()
+        .map(|rent| (*rent).clone())
+        .unwrap_or_default();
+
+    let mut program_cache_for_tx_batch =
+        ProgramCacheForTxBatch::new(slot, environments.clone(), None, epoch);
+    for builtin in enabled_builtins(&feature_set) {
+        program_cache_for_tx_batch.replenish(
+            builtin.program_id,
+            Arc::new(ProgramCacheEntry::new_builtin(
+                0,
+                builtin.name.len(),
+                builtin.entrypoint,
+            )),
+        );
+    }
+    let fixture_accounts = FixtureAccounts(&transaction_accounts);
+    for (pubkey, account) in &transaction_accounts {
+        if !is_loader(account.owner()) || program_cache_for_tx_batch.find(pubkey).is_some() {
+            continue;
+        }
+        if let Some(program) = program_loader::load_program_with_pubkey(
+            &fixture_accounts,
+            &environments,
+            pubkey,
+            slot,
+            &mut ExecuteTimings::default(),
+            false,
+        ) {
+            program_cache_for_tx_batch.replenish(*pubkey, program);
+        }
+    }
+
+    let mut transaction_context = TransactionContext::new(
+        transaction_accounts,
+        rent,
+        compute_budget.max_instruction_stack_depth,
+        compute_budget.max_instruction_trace_length,
+    );
+    transaction_context.set_remove_accounts_executable_flag_checks(
+        feature_set.is_active(&remove_accounts_executable_flag_checks::id()),
+    );
+
+    let log_collector = LogCollector::new_ref();
+    let mut compute_units_consumed = 0u64;
+    let result = {
+        let mut invoke_context = InvokeContext::new(
+            &mut transaction_context,
+            &mut program_cache_for_tx_batch,
+            EnvironmentConfig::new(
+                Hash::default(),
+                0,
+                0,
+                &|_| 0,
+                Arc::new(feature_set),
+                &sysvar_cache,
+            ),
+            Some(log_collector.clone()),
+            compute_budget,
+        );
+        invoke_context.process_instruction(
+            &input.data,
+            &instruction_accounts,
+            &[program_index as IndexOfAccount],
+            &mut compute_units_consumed,
+            &mut ExecuteTimings::default(),
+        )
+    };
+
+    let (result, custom_err) = match &result {
+        Ok(()) => (0, 0),
+        Err(err) => encode_instruction_error(err),
+    };
+    let return_data = transaction_context.get_return_data().1.to_vec();
+
+    let mut modified_accounts: Vec<AcctState> = Vec::new();
+    if result == 0 {
+        for instruction_account in instruction_accounts.iter().filter(|account| {
+            account.is_writable
+                && account.index_in_callee as usize
+                    == instruction_accounts
+                        .iter()
+                        .position(|other| {
+                            other.index_in_transaction == account.index_in_transaction
+                        })
+                        .unwrap_or_default()
+        }) {
+            let index_in_transaction = instruction_account.index_in_transaction;
+            let pubkey = transaction_context
+                .get_key_of_account_at_index(index_in_transaction)
+                .map_err(|_| FixtureError::InvalidAccountIndex(index_in_transaction as u32))?;
+            let account = transaction_context
+                .accounts()
+                .try_borrow(index_in_transaction)
+                .map_err(|_| FixtureError::InvalidAccountIndex(index_in_transaction as u32))?;
+            modified_accounts.push(state_from_account(pubkey, &account));
+        }
+    }
+
+    let logs = Rc::try_unwrap(log_collector)
+        .map(|log_collector| log_collector.into_inner().into_messages())
+        .unwrap_or_default();
+
+    Ok(InstrOutcome {
+        effects: InstrEffects {
+            result,
+            custom_err,
+            modified_accounts,
+            cu_avail: input.cu_avail.saturating_sub(compute_units_consumed),
+            return_data,
+        },
+        logs,
+    })
+}
@@ -1,3 +1,8 @@
+pub mod diff;
+pub mod fixture;
+pub mod instr;
+pub mod txn;
+
 pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/org.solana.sealevel.v1.rs"));
 }
@@ -0,0 +1,92 @@
+use {
+    clap::{crate_description, crate_name, App, Arg},
+    log::*,
+    solana_svm_conformance::{
+        diff::run_fixture,
+        fixture::{collect_fixture_paths, load_expected_logs, load_fixture},
+    },
+    std::process::exit,
+};
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("fixtures")
+                .value_name("PATH")
+                .takes_value(true)
+                .multiple(true)
+                .required(true)
+                .help(
+                    "Instruction or transaction fixture files, or directories to search \
+                     for *.fix files. Program logs are compared only for fixtures with a \
+                     matching *.logs file",
+                ),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .long("verbose")
+                .short("v")
+                .help("Print program logs for failing fixtures"),
+        )
+        .arg(
+            Arg::with_name("fail_fast")
+                .long("fail-fast")
+                .help("Stop at the first failing fixture"),
+        )
+        .get_matches();
+
+    let paths: Vec<&str> = matches.values_of("fixtures").unwrap().collect();
+    let verbose = matches.is_present("verbose");
+    let fail_fast = matches.is_present("fail_fast");
+
+    let fixture_paths = collect_fixture_paths(&paths).unwrap_or_else(|err| {
+        error!("Failed to collect fixtures: {err}");
+        exit(1);
+    });
+
+    let mut passed = 0usize;
+    let mut failed = 0usize;
+    for path in &fixture_paths {
+        let report = load_fixture(path).and_then(|fixture| {
+            let expected_logs = load_expected_logs(path)?;
+            run_fixture(&fixture, expected_logs.as_deref())
+        });
+        match report {
+            Ok(report) if report.passed() => {
+                passed += 1;
+                println!("ok      {}", path.display());
+            }
+            Ok(report) => {
+                failed += 1;
+                println!("FAILED  {}", path.display());
+                for diff in &report.diffs {
+                    println!("    {diff}");
+                }
+                if verbose {
+                    for log in &report.logs {
+                        println!("    | {log}");
+                    }
+                }
+            }
+            Err(err) => {
+                failed += 1;
+                println!("ERROR   {}: {err}", path.display());
+            }
+        }
+        if fail_fast && failed > 0 {
+            break;
+        }
+    }
+
+    println!(
+        "\n{} fixtures: {passed} passed, {failed} failed",
+        passed + failed
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,576 @@
+//! Executes a transaction fixture's `TxnContext` through the SVM's
+//! `TransactionBatchProcessor`, producing the `TxnResult` observed locally.
+
+use {
+    crate::{
+        fixture::FixtureError,
+        instr::{
+            account_from_state, default_sysvar_data, enabled_builtins, encode_instruction_error,
+            feature_set_from_context, pubkey_from_bytes, state_from_account, DEFAULT_FIXTURE_SLOT,
+        },
+        proto::{self, AcctState, FeeDetails, RentDebits, ResultingState, TxnContext, TxnResult},
+    },
+    solana_bpf_loader_program::syscalls::create_program_runtime_environment_v1,
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_program_runtime::loaded_programs::{BlockRelation, ForkGraph, ProgramCacheEntry},
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount, WritableAccount},
+        account_utils::StateMut,
+        address_lookup_table::{self, error::AddressLookupError, state::AddressLookupTable},
+        clock::{Clock, Slot, MAX_PROCESSING_AGE},
+        epoch_schedule::EpochSchedule,
+        fee::FeeStructure,
+        hash::Hash,
+        message::{
+            compiled_instruction::CompiledInstruction,
+            legacy,
+            v0::{self, LoadedAddresses, MessageAddressTableLookup},
+            AddressLoaderError, MessageHeader, SimpleAddressLoader, VersionedMessage,
+        },
+        native_loader,
+        nonce::{
+            state::{DurableNonce, State as NonceState, Versions as NonceVersions},
+            NONCED_TX_MARKER_IX_INDEX,
+        },
+        pubkey::Pubkey,
+        rent::Rent,
+        rent_collector::RentCollector,
+        reserved_account_keys::ReservedAccountKeys,
+        signature::Signature,
+        slot_hashes::SlotHashes,
+        system_program,
+        sysvar::{self, last_restart_slot::LastRestartSlot, SysvarId},
+        transaction::{MessageHash, SanitizedTransaction, TransactionError, VersionedTransaction},
+    },
+    solana_svm::{
+        account_loader::{CheckedTransactionDetails, TransactionCheckResult},
+        nonce_info::NonceInfo,
+        rollback_accounts::RollbackAccounts,
+        transaction_processing_callback::TransactionProcessingCallback,
+        transaction_processing_result::ProcessedTransaction,
+        transaction_processor::{
+            ExecutionRecordingConfig, TransactionBatchProcessor, TransactionProcessingConfig,
+            TransactionProcessingEnvironment,
+        },
+    },
+    std::{
+        cmp::Ordering,
+        collections::HashMap,
+        sync::{Arc, RwLock},
+    },
+};
+
+/// The locally observed result of executing a `TxnContext`
+#[derive(Debug)]
+pub struct TxnOutcome {
+    pub result: TxnResult,
+    /// Program logs emitted during execution. Fixtures do not carry logs, so
+    /// these are only compared when a fixture has an expected logs file.
+    pub logs: Vec<String>,
+}
+
+/// Fixtures execute a single transaction on a single fork, so every slot is
+/// an ancestor of the later ones
+struct FixtureForkGraph;
+
+impl ForkGraph for FixtureForkGraph {
+    fn relationship(&self, a: Slot, b: Slot) -> BlockRelation {
+        match a.cmp(&b) {
+            Ordering::Less => BlockRelation::Ancestor,
+            Ordering::Equal => BlockRelation::Equal,
+            Ordering::Greater => BlockRelation::Descendant,
+        }
+    }
+}
+
+/// The accounts of a transaction fixture, plus the builtin program accounts
+/// registered with the processor
+struct FixtureBank {
+    accounts: RwLock<HashMap<Pubkey, AccountSharedData>>,
+}
+
+impl FixtureBank {
+    fn account(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.accounts.read().unwrap().get(pubkey).cloned()
+    }
+}
+
+impl TransactionProcessingCallback for FixtureBank {
+    fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
+        self.account(account)
+            .filter(|account| account.lamports() > 0)
+            .and_then(|account| owners.iter().position(|owner| account.owner() == owner))
+    }
+
+    fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.account(pubkey)
+    }
+
+    fn add_builtin_account(&self, name: &str, program_id: &Pubkey) {
+        self.accounts
+            .write()
+            .unwrap()
+            .entry(*program_id)
+            .or_insert_with(|| native_loader::create_loadable_account_with_fields(name, (1, 0)));
+    }
+}
+
+fn hash_from_bytes(bytes: &[u8]) -> Result<Hash, FixtureError> {
+    <[u8; 32]>::try_from(bytes)
+        .map(Hash::new_from_array)
+        .map_err(|_| FixtureError::InvalidHash(bytes.len()))
+}
+
+fn account_index(index: u32) -> Result<u8, FixtureError> {
+    u8::try_from(index).map_err(|_| FixtureError::InvalidAccountIndex(index))
+}
+
+fn account_indexes(indexes: &[u32]) -> Result<Vec<u8>, FixtureError> {
+    indexes.iter().copied().map(account_index).collect()
+}
+
+/// Rebuilds the versioned transaction described by the fixture
+fn versioned_transaction(
+    tx: &proto::SanitizedTransaction,
+) -> Result<VersionedTransaction, FixtureError> {
+    let message = tx
+        .message
+        .as_ref()
+        .ok_or(FixtureError::MissingField("transaction message"))?;
+    let default_header = proto::MessageHeader::default();
+    let header = message.header.as_ref().unwrap_or(&default_header);
+    let header = MessageHeader {
+        num_required_signatures: account_index(header.num_required_signatures)?,
+        num_readonly_signed_accounts: account_index(header.num_readonly_signed_accounts)?,
+        num_readonly_unsigned_accounts: account_index(header.num_readonly_unsigned_accounts)?,
+    };
+    let account_keys = message
+        .account_keys
+        .iter()
+        .map(|key| pubkey_from_bytes(key))
+        .collect::<Result<Vec<_>, _>>()?;
+    let recent_blockhash = hash_from_bytes(&message.recent_blockhash)?;
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|instruction| {
+            Ok(CompiledInstruction {
+                program_id_index: account_index(instruction.program_id_index)?,
+                accounts: account_indexes(&instruction.accounts)?,
+                data: instruction.data.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, FixtureError>>()?;
+
+    let message = if message.is_legacy {
+        VersionedMessage::Legacy(legacy::Message {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    } else {
+        let address_table_lookups = message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| {
+                Ok(MessageAddressTableLookup {
+                    account_key: pubkey_from_bytes(&lookup.account_key)?,
+                    writable_indexes: account_indexes(&lookup.writable_indexes)?,
+                    readonly_indexes: account_indexes(&lookup.readonly_indexes)?,
+                })
+            })
+            .collect::<Result<Vec<_>, FixtureError>>()?;
+        VersionedMessage::V0(v0::Message {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+            address_table_lookups,
+        })
+    };
+
+    let signatures = tx
+        .signatures
+        .iter()
+        .map(|signature| {
+            Signature::try_from(signature.as_slice())
+                .map_err(|_| FixtureError::InvalidSignature(signature.len()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(VersionedTransaction {
+        signatures,
+        message,
+    })
+}
+
+/// Resolves the message's address table lookups the same way the bank does
+fn load_addresses(
+    bank: &FixtureBank,
+    lookups: &[MessageAddressTableLookup],
+    slot: Slot,
+    slot_hashes: &SlotHashes,
+) -> Result<LoadedAddresses, AddressLoaderError> {
+    let mut loaded_addresses = LoadedAddresses::default();
+    for lookup in lookups {
+        let table_account = bank
+            .account(&lookup.account_key)
+            .filter(|account| account.lamports() > 0)
+            .ok_or(AddressLoaderError::LookupTableAccountNotFound)?;
+        if table_account.owner() != &address_lookup_table::program::id() {
+            return Err(AddressLoaderError::InvalidAccountOwner);
+        }
+        let table = AddressLookupTable::deserialize(table_account.data())
+            .map_err(|_| AddressLoaderError::InvalidAccountData)?;
+        let lookup_error = |err| match err {
+            AddressLookupError::LookupTableAccountNotFound => {
+                AddressLoaderError::LookupTableAccountNotFound
+            }
+            AddressLookupError::InvalidAccountOwner => AddressLoaderError::InvalidAccountOwner,
+            AddressLookupError::InvalidAccountData => AddressLoaderError::InvalidAccountData,
+            AddressLookupError::InvalidLookupIndex => AddressLoaderError::InvalidLookupIndex,
+        };
+        loaded_addresses.writable.extend(
+            table
+                .lookup(slot, &lookup.writable_indexes, slot_hashes)
+                .map_err(lookup_error)?,
+        );
+        loaded_addresses.readonly.extend(
+            table
+                .lookup(slot, &lookup.readonly_indexes, slot_hashes)
+                .map_err(lookup_error)?,
+        );
+    }
+    Ok(loaded_addresses)
+}
+
+/// Checks the transaction's age against the fixture's blockhash queue,
+/// accepting a durable nonce in place of a recent blockhash like the bank does
+fn check_transaction_age(
+    bank: &FixtureBank,
+    tx: &SanitizedTransaction,
+    blockhash_queue: &[Hash],
+    lamports_per_signature: u64,
+) -> TransactionCheckResult {
+    let recent_blockhash = tx.message().recent_blockhash();
+    let is_recent = blockhash_queue
+        .iter()
+        .rev()
+        .take(MAX_PROCESSING_AGE.saturating_add(1))
+        .any(|hash| hash == recent_blockhash);
+    if is_recent {
+        return Ok(CheckedTransactionDetails {
+            nonce: None,
+            lamports_per_signature,
+        });
+    }
+
+    let last_blockhash = blockhash_queue.last().copied().unwrap_or_default();
+    let next_durable_nonce = DurableNonce::from_blockhash(&last_blockhash);
+    if recent_blockhash == next_durable_nonce.as_hash() {
+        return Err(TransactionError::BlockhashNotFound);
+    }
+    let nonce = tx.get_durable_nonce().and_then(|nonce_address| {
+        let mut nonce_account = bank.account(nonce_address)?;
+        if nonce_account.owner() != &system_program::id() {
+            return None;
+        }
+        let versions = StateMut::<NonceVersions>::state(&nonce_account).ok()?;
+        let nonce_data = versions.verify_recent_blockhash(recent_blockhash)?.clone();
+        let is_authorized = tx
+            .message()
+            .get_ix_signers(NONCED_TX_MARKER_IX_INDEX as usize)
+            .any(|signer| *signer == nonce_data.authority);
+        if !is_authorized {
+            return None;
+        }
+        let next_nonce_state = NonceState::new_initialized(
+            &nonce_data.authority,
+            next_durable_nonce,
+            lamports_per_signature,
+        );
+        nonce_account
+            .set_state(&NonceVersions::new(next_nonce_state))
+            .ok()?;
+        Some(CheckedTransactionDetails {
+            nonce: Some(NonceInfo::new(*nonce_address, nonce_account)),
+            lamports_per_signature: nonce_data.get_lamports_per_signature(),
+        })
+    });
+    nonce.ok_or(TransactionError::BlockhashNotFound)
+}
+
+/// Encodes a transaction error the same way fixtures do: one plus the variant
+/// index of the error, with instruction errors also reporting the failing
+/// instruction and their own encoding
+fn encode_transaction_error(result: &mut TxnResult, error: &TransactionError) {
+    let variant = bincode::serialize(error)
+        .ok()
+        .and_then(|bytes| bytes.get(..4).map(|prefix| prefix.try_into().unwrap()))
+        .map(u32::from_le_bytes)
+        .unwrap_or_default();
+    result.status = variant.saturating_add(1);
+    if let TransactionError::InstructionError(index, instruction_error) = error {
+        let (instruction_error, custom_error) = encode_instruction_error(instruction_error);
+        result.instruction_error = instruction_error as u32;
+        result.instruction_error_index = u32::from(*index);
+        result.custom_error = custom_error;
+    }
+}
+
+fn rollback_states(fee_payer: &Pubkey, rollback_accounts: &RollbackAccounts) -> Vec<AcctState> {
+    match rollback_accounts {
+        RollbackAccounts::FeePayerOnly { fee_payer_account } => {
+            vec![state_from_account(fee_payer, fee_payer_account)]
+        }
+        RollbackAccounts::SameNonceAndFeePayer { nonce } => {
+            vec![state_from_account(nonce.address(), nonce.account())]
+        }
+        RollbackAccounts::SeparateNonceAndFeePayer {
+            nonce,
+            fee_payer_account,
+        } => vec![
+            state_from_account(nonce.address(), nonce.account()),
+            state_from_account(fee_payer, fee_payer_account),
+        ],
+    }
+}
+
+/// Executes `input` and returns the locally observed result
+pub fn execute_txn(input: &TxnContext) -> Result<TxnOutcome, FixtureError> {
+    let tx = input
+        .tx
+        .as_ref()
+        .ok_or(FixtureError::MissingField("transaction"))?;
+    let message = tx
+        .message
+        .as_ref()
+        .ok_or(FixtureError::MissingField("transaction message"))?;
+    let slot = input
+        .slot_ctx
+        .as_ref()
+        .map(|slot_context| slot_context.slot)
+        .unwrap_or(DEFAULT_FIXTURE_SLOT);
+    let feature_set = Arc::new(feature_set_from_context(input.epoch_ctx.as_ref()));
+    let blockhash_queue = input
+        .blockhash_queue
+        .iter()
+        .map(|hash| hash_from_bytes(hash))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut accounts = message
+        .account_shared_data
+        .iter()
+        .map(|state| {
+            Ok((
+                pubkey_from_bytes(&state.address)?,
+                account_from_state(state)?,
+            ))
+        })
+        .collect::<Result<HashMap<_, _>, FixtureError>>()?;
+    for sysvar_id in [
+        Clock::id(),
+        EpochSchedule::id(),
+        Rent::id(),
+        LastRestartSlot::id(),
+    ] {
+        if let Some(data) = default_sysvar_data(&sysvar_id, slot) {
+            accounts.entry(sysvar_id).or_insert_with(|| {
+                let mut account = AccountSharedData::new(1, 0, &sysvar::id());
+                account.set_data_from_slice(&data);
+                account
+            });
+        }
+    }
+    let bank = FixtureBank {
+        accounts: RwLock::new(accounts),
+    };
+
+    let slot_hashes = bank
+        .account(&SlotHashes::id())
+        .and_then(|account| bincode::deserialize::<SlotHashes>(account.data()).ok())
+        .unwrap_or_default();
+    let mut reserved_account_keys = ReservedAccountKeys::default();
+    reserved_account_keys.update_active_set(&feature_set);
+    let versioned_transaction = versioned_transaction(tx)?;
+    let loaded_addresses = match &versioned_transaction.message {
+        VersionedMessage::Legacy(_) => Ok(LoadedAddresses::default()),
+        VersionedMessage::V0(message) => {
+            load_addresses(&bank, &message.address_table_lookups, slot, &slot_hashes)
+        }
+    };
+    let message_hash = match <[u8; 32]>::try_from(tx.message_hash.as_slice()) {
+        Ok(hash) => MessageHash::Precomputed(Hash::new_from_array(hash)),
+        Err(_) => MessageHash::Compute,
+    };
+    let sanitized_transaction =
+        loaded_addresses
+            .map_err(TransactionError::from)
+            .and_then(|loaded_addresses| {
+                SanitizedTransaction::try_create(
+                    versioned_transaction,
+                    message_hash,
+                    Some(tx.is_simple_vote_tx),
+                    SimpleAddressLoader::Enabled(loaded_addresses),
+                    &reserved_account_keys.active,
+                )
+            });
+    let sanitized_transaction = match sanitized_transaction {
+        Ok(sanitized_transaction) => sanitized_transaction,
+        Err(err) => {
+            let mut result = TxnResult {
+                sanitization_error: true,
+                ..TxnResult::default()
+            };
+            encode_transaction_error(&mut result, &err);
+            return Ok(TxnOutcome {
+                result,
+                logs: Vec::new(),
+            });
+        }
+    };
+
+    let epoch_schedule = bank
+        .account(&EpochSchedule::id())
+        .and_then(|account| bincode::deserialize::<EpochSchedule>(account.data()).ok())
+        .unwrap_or_default();
+    let rent = bank
+        .account(&Rent::id())
+        .and_then(|account| bincode::deserialize::<Rent>(account.data()).ok())
+        .unwrap_or_default();
+    let epoch = epoch_schedule.get_epoch(slot);
+    let compute_budget = ComputeBudget::default();
+    let program_runtime_environment_v1 =
+        create_program_runtime_environment_v1(&feature_set, &compute_budget, false, false)
+            .map_err(|err| FixtureError::Environment(err.to_string()))?;
+
+    let fork_graph = Arc::new(RwLock::new(FixtureForkGraph));
+    let batch_processor = TransactionBatchProcessor::new(
+        slot,
+        epoch,
+        Arc::downgrade(&fork_graph),
+        Some(Arc::new(program_runtime_environment_v1)),
+        None,
+    );
+    for builtin in enabled_builtins(&feature_set) {
+        batch_processor.add_builtin(
+            &bank,
+            builtin.program_id,
+            builtin.name,
+            ProgramCacheEntry::new_builtin(0, builtin.name.len(), builtin.entrypoint),
+        );
+    }
+    batch_processor.fill_missing_sysvar_cache_entries(&bank);
+
+    let lamports_per_signature = FeeStructure::default().lamports_per_signature;
+    let check_result = check_transaction_age(
+        &bank,
+        &sanitized_transaction,
+        &blockhash_queue,
+        lamports_per_signature,
+    );
+    let rent_collector = RentCollector {
+        epoch,
+        epoch_schedule,
+        rent,
+        ..RentCollector::default()
+    };
+    let environment = TransactionProcessingEnvironment {
+        blockhash: blockhash_queue.last().copied().unwrap_or_default(),
+        blockhash_lamports_per_signature: lamports_per_signature,
+        feature_set,
+        fee_lamports_per_signature: lamports_per_signature,
+        rent_collector: Some(&rent_collector),
+        ..TransactionProcessingEnvironment::default()
+    };
+    let config = TransactionProcessingConfig {
+        recording_config: ExecutionRecordingConfig {
+            enable_log_recording: true,
+            enable_return_data_recording: true,
+            ..ExecutionRecordingConfig::default()
+        },
+        ..TransactionProcessingConfig::default()
+    };
+    let output = batch_processor.load_and_execute_sanitized_transactions(
+        &bank,
+        std::slice::from_ref(&sanitized_transaction),
+        vec![check_result],
+        &environment,
+        &config,
+    );
+
+    let mut result = TxnResult::default();
+    let mut logs = Vec::new();
+    let processed_transaction = match output.processing_results.into_iter().next() {
+        Some(Ok(processed_transaction)) => processed_transaction,
+        Some(Err(err)) => {
+            encode_transaction_error(&mut result, &err);
+            return Ok(TxnOutcome { result, logs });
+        }
+        None => return Err(FixtureError::MissingField("transaction result")),
+    };
+
+    result.executed = true;
+    let fee_details = processed_transaction.fee_details();
+    result.fee_details = Some(FeeDetails {
+        transaction_fee: fee_details.transaction_fee(),
+        prioritization_fee: fee_details.prioritization_fee(),
+    });
+    match processed_transaction.status() {
+        Ok(()) => result.is_ok = true,
+        Err(err) => encode_transaction_error(&mut result, &err),
+    }
+
+    let fee_payer = *sanitized_transaction.message().fee_payer();
+    let resulting_state = match processed_transaction {
+        ProcessedTransaction::Executed(executed_transaction) => {
+            let details = executed_transaction.execution_details;
+            let loaded_transaction = executed_transaction.loaded_transaction;
+            result.executed_units = details.executed_units;
+            result.return_data = details
+                .return_data
+                .map(|return_data| return_data.data)
+                .unwrap_or_default();
+            result.rent = loaded_transaction.rent;
+            logs = details.log_messages.unwrap_or_default();
+
+            let writable_accounts = loaded_transaction
+                .accounts
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| sanitized_transaction.message().is_writable(*index))
+                .map(|(_, (pubkey, account))| (pubkey, account))
+                .collect::<Vec<_>>();
+            let rent_debits = writable_accounts
+                .iter()
+                .map(|(pubkey, _)| RentDebits {
+                    pubkey: pubkey.to_bytes().to_vec(),
+                    rent_collected: loaded_transaction
+                        .rent_debits
+                        .get_account_rent_debit(pubkey) as i64,
+                })
+                .filter(|rent_debit| rent_debit.rent_collected != 0)
+                .collect();
+            let acct_states = if result.is_ok {
+                writable_accounts
+                    .iter()
+                    .map(|(pubkey, account)| state_from_account(pubkey, account))
+                    .collect()
+            } else {
+                rollback_states(&fee_payer, &loaded_transaction.rollback_accounts)
+            };
+            ResultingState {
+                acct_states,
+                rent_debits,
+                transaction_rent: loaded_transaction.rent,
+            }
+        }
+        ProcessedTransaction::FeesOnly(fees_only_transaction) => ResultingState {
+            acct_states: rollback_states(&fee_payer, &fees_only_transaction.rollback_accounts),
+            ..ResultingState::default()
+        },
+    };
+    result.resulting_state = Some(resulting_state);
+
+    Ok(TxnOutcome { result, logs })
+}
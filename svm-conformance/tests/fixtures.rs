@@ -0,0 +1,379 @@
+use {
+    prost::Message,
+    solana_sdk::{
+        hash::Hash, pubkey::Pubkey, system_instruction::SystemInstruction, system_program,
+    },
+    solana_svm_conformance::{
+        diff::{diff_effects, run_fixture, AccountField, EffectsDiff},
+        fixture::{
+            collect_fixture_paths, load_expected_logs, load_fixture, Fixture, FixtureError,
+            INSTR_FIXTURE_ENTRYPOINT, TXN_FIXTURE_ENTRYPOINT,
+        },
+        instr::execute_instr,
+        proto::{
+            AcctState, CompiledInstruction, FixtureMetadata, InstrAcct, InstrContext, InstrFixture,
+            MessageHeader, SanitizedTransaction, TransactionMessage, TxnContext, TxnFixture,
+        },
+        txn::execute_txn,
+    },
+    std::fs,
+};
+
+fn system_account(pubkey: &Pubkey, lamports: u64) -> AcctState {
+    AcctState {
+        address: pubkey.to_bytes().to_vec(),
+        lamports,
+        data: vec![],
+        executable: false,
+        rent_epoch: u64::MAX,
+        owner: system_program::id().to_bytes().to_vec(),
+        seed_addr: None,
+    }
+}
+
+fn transfer_context(from_lamports: u64, lamports: u64) -> (InstrContext, Pubkey, Pubkey) {
+    let from = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let input = InstrContext {
+        program_id: system_program::id().to_bytes().to_vec(),
+        accounts: vec![system_account(&from, from_lamports), system_account(&to, 0)],
+        instr_accounts: vec![
+            InstrAcct {
+                index: 0,
+                is_writable: true,
+                is_signer: true,
+            },
+            InstrAcct {
+                index: 1,
+                is_writable: true,
+                is_signer: false,
+            },
+        ],
+        data: bincode::serialize(&SystemInstruction::Transfer { lamports }).unwrap(),
+        cu_avail: 10_000,
+        slot_context: None,
+        epoch_context: None,
+    };
+    (input, from, to)
+}
+
+fn txn_transfer_context(payer_lamports: u64, lamports: u64) -> (TxnContext, Pubkey, Pubkey) {
+    let payer = Pubkey::new_unique();
+    let to = Pubkey::new_unique();
+    let blockhash = Hash::new_unique();
+    let message = TransactionMessage {
+        is_legacy: true,
+        header: Some(MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        }),
+        account_keys: vec![
+            payer.to_bytes().to_vec(),
+            to.to_bytes().to_vec(),
+            system_program::id().to_bytes().to_vec(),
+        ],
+        account_shared_data: vec![
+            system_account(&payer, payer_lamports),
+            system_account(&to, 0),
+        ],
+        recent_blockhash: blockhash.to_bytes().to_vec(),
+        instructions: vec![CompiledInstruction {
+            program_id_index: 2,
+            accounts: vec![0, 1],
+            data: bincode::serialize(&SystemInstruction::Transfer { lamports }).unwrap(),
+        }],
+        address_table_lookups: vec![],
+    };
+    let input = TxnContext {
+        tx: Some(SanitizedTransaction {
+            message: Some(message),
+            message_hash: vec![],
+            is_simple_vote_tx: false,
+            signatures: vec![vec![0; 64]],
+        }),
+        blockhash_queue: vec![blockhash.to_bytes().to_vec()],
+        epoch_ctx: None,
+        slot_ctx: None,
+    };
+    (input, payer, to)
+}
+
+#[test]
+fn test_transfer_effects() {
+    let (input, from, to) = transfer_context(100, 40);
+    let outcome = execute_instr(&input).unwrap();
+
+    assert_eq!(outcome.effects.result, 0);
+    assert!(outcome.effects.cu_avail < input.cu_avail);
+    let lamports_of = |pubkey: &Pubkey| {
+        outcome
+            .effects
+            .modified_accounts
+            .iter()
+            .find(|account| account.address == pubkey.to_bytes())
+            .unwrap()
+            .lamports
+    };
+    assert_eq!(lamports_of(&from), 60);
+    assert_eq!(lamports_of(&to), 40);
+}
+
+#[test]
+fn test_failed_transfer_effects() {
+    let (input, _, _) = transfer_context(10, 40);
+    let outcome = execute_instr(&input).unwrap();
+
+    assert_ne!(outcome.effects.result, 0);
+    // SystemError::ResultWithNegativeLamports
+    assert_eq!(outcome.effects.custom_err, 1);
+    assert!(outcome.effects.modified_accounts.is_empty());
+
+    let expected = outcome.effects;
+    let (ok_input, _, _) = transfer_context(100, 40);
+    let actual = execute_instr(&ok_input).unwrap().effects;
+    let diffs = diff_effects(&expected, &actual);
+    assert_eq!(
+        diffs,
+        vec![
+            EffectsDiff::Result {
+                expected: expected.result,
+                actual: 0,
+            },
+            EffectsDiff::CustomError {
+                expected: 1,
+                actual: 0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_run_fixture_reports_diffs() {
+    let (input, from, _) = transfer_context(100, 40);
+    let output = execute_instr(&input).unwrap().effects;
+    let mut fixture = InstrFixture {
+        metadata: None,
+        input: Some(input),
+        output: Some(output),
+    };
+    let report = run_fixture(&Fixture::Instr(fixture.clone()), None).unwrap();
+    assert!(report.passed(), "{:?}", report.diffs);
+
+    let output = fixture.output.as_mut().unwrap();
+    output.cu_avail += 1;
+    output
+        .modified_accounts
+        .iter_mut()
+        .find(|account| account.address == from.to_bytes())
+        .unwrap()
+        .lamports = 61;
+    let report = run_fixture(&Fixture::Instr(fixture), None).unwrap();
+    assert!(!report.passed());
+    assert!(matches!(
+        report.diffs[0],
+        EffectsDiff::ComputeUnits { expected, actual } if expected == actual + 1
+    ));
+    assert_eq!(
+        report.diffs[1],
+        EffectsDiff::Account {
+            pubkey: from,
+            field: AccountField::Lamports,
+            expected: "61".to_string(),
+            actual: "60".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_load_fixtures_from_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let nested = dir.path().join("system");
+    fs::create_dir(&nested).unwrap();
+
+    let (input, _, _) = transfer_context(100, 40);
+    let output = execute_instr(&input).unwrap().effects;
+    let fixture = InstrFixture {
+        metadata: None,
+        input: Some(input),
+        output: Some(output),
+    };
+    let fixture_path = nested.join("transfer.fix");
+    fs::write(&fixture_path, fixture.encode_to_vec()).unwrap();
+    fs::write(nested.join("README.md"), b"not a fixture").unwrap();
+
+    let paths = collect_fixture_paths(&[dir.path()]).unwrap();
+    assert_eq!(paths, vec![fixture_path.clone()]);
+    assert_eq!(
+        load_fixture(&fixture_path).unwrap(),
+        Fixture::Instr(fixture)
+    );
+}
+
+#[test]
+fn test_run_fixture_compares_logs() {
+    let (input, _, _) = transfer_context(100, 40);
+    let outcome = execute_instr(&input).unwrap();
+    let fixture = Fixture::Instr(InstrFixture {
+        metadata: None,
+        input: Some(input),
+        output: Some(outcome.effects),
+    });
+    let report = run_fixture(&fixture, Some(outcome.logs.as_slice())).unwrap();
+    assert!(report.passed(), "{:?}", report.diffs);
+
+    let mut expected_logs = outcome.logs.clone();
+    expected_logs.push("Program log: extra".to_string());
+    let report = run_fixture(&fixture, Some(expected_logs.as_slice())).unwrap();
+    assert_eq!(
+        report.diffs,
+        vec![EffectsDiff::Logs {
+            line: outcome.logs.len(),
+            expected: Some("Program log: extra".to_string()),
+            actual: None,
+        }]
+    );
+}
+
+#[test]
+fn test_load_fixture_metadata_and_logs() {
+    let dir = tempfile::tempdir().unwrap();
+    let (input, _, _) = transfer_context(100, 40);
+    let output = execute_instr(&input).unwrap().effects;
+    let fixture = InstrFixture {
+        metadata: Some(FixtureMetadata {
+            fn_entrypoint: INSTR_FIXTURE_ENTRYPOINT.to_string(),
+        }),
+        input: Some(input),
+        output: Some(output),
+    };
+    let fixture_path = dir.path().join("transfer.fix");
+    fs::write(&fixture_path, fixture.encode_to_vec()).unwrap();
+    assert_eq!(
+        load_fixture(&fixture_path).unwrap(),
+        Fixture::Instr(fixture)
+    );
+    assert_eq!(load_expected_logs(&fixture_path).unwrap(), None);
+
+    fs::write(
+        dir.path().join("transfer.logs"),
+        "Program 11111111111111111111111111111111 invoke [1]\n\
+         Program 11111111111111111111111111111111 success\n",
+    )
+    .unwrap();
+    assert_eq!(
+        load_expected_logs(&fixture_path).unwrap().unwrap(),
+        vec![
+            "Program 11111111111111111111111111111111 invoke [1]",
+            "Program 11111111111111111111111111111111 success",
+        ]
+    );
+
+    // Instruction and transaction fixtures share a field layout, so they are
+    // told apart by their entrypoint
+    let (input, _, _) = txn_transfer_context(10_000_000, 1_000_000);
+    let txn_fixture = TxnFixture {
+        metadata: Some(FixtureMetadata {
+            fn_entrypoint: TXN_FIXTURE_ENTRYPOINT.to_string(),
+        }),
+        input: Some(input),
+        output: None,
+    };
+    let txn_fixture_path = dir.path().join("txn.fix");
+    fs::write(&txn_fixture_path, txn_fixture.encode_to_vec()).unwrap();
+    assert_eq!(
+        load_fixture(&txn_fixture_path).unwrap(),
+        Fixture::Txn(txn_fixture)
+    );
+
+    let unknown_fixture = InstrFixture {
+        metadata: Some(FixtureMetadata {
+            fn_entrypoint: "sol_compat_elf_loader_v1".to_string(),
+        }),
+        input: None,
+        output: None,
+    };
+    fs::write(&txn_fixture_path, unknown_fixture.encode_to_vec()).unwrap();
+    assert!(matches!(
+        load_fixture(&txn_fixture_path),
+        Err(FixtureError::UnsupportedFixture(entrypoint)) if entrypoint == "sol_compat_elf_loader_v1"
+    ));
+}
+
+#[test]
+fn test_txn_transfer_result() {
+    let (input, payer, to) = txn_transfer_context(10_000_000, 1_000_000);
+    let outcome = execute_txn(&input).unwrap();
+    let result = &outcome.result;
+
+    assert!(result.executed);
+    assert!(result.is_ok);
+    assert_eq!(result.fee_details.as_ref().unwrap().transaction_fee, 5_000);
+    let lamports_of = |pubkey: &Pubkey| {
+        result
+            .resulting_state
+            .as_ref()
+            .unwrap()
+            .acct_states
+            .iter()
+            .find(|account| account.address == pubkey.to_bytes())
+            .unwrap()
+            .lamports
+    };
+    assert_eq!(lamports_of(&payer), 8_995_000);
+    assert_eq!(lamports_of(&to), 1_000_000);
+}
+
+#[test]
+fn test_txn_failed_transfer_result() {
+    let (input, payer, _) = txn_transfer_context(1_000_000, 2_000_000);
+    let result = execute_txn(&input).unwrap().result;
+
+    // The fee is charged even though the transfer fails
+    assert!(result.executed);
+    assert!(!result.is_ok);
+    assert_eq!(result.instruction_error_index, 0);
+    // SystemError::ResultWithNegativeLamports
+    assert_eq!(result.custom_error, 1);
+    let acct_states = &result.resulting_state.as_ref().unwrap().acct_states;
+    assert_eq!(acct_states.len(), 1);
+    assert_eq!(acct_states[0].address, payer.to_bytes());
+    assert_eq!(acct_states[0].lamports, 995_000);
+}
+
+#[test]
+fn test_run_txn_fixture_reports_diffs() {
+    let (input, _, to) = txn_transfer_context(10_000_000, 1_000_000);
+    let output = execute_txn(&input).unwrap().result;
+    let mut fixture = TxnFixture {
+        metadata: None,
+        input: Some(input),
+        output: Some(output),
+    };
+    let report = run_fixture(&Fixture::Txn(fixture.clone()), None).unwrap();
+    assert!(report.passed(), "{:?}", report.diffs);
+
+    let output = fixture.output.as_mut().unwrap();
+    output.fee_details.as_mut().unwrap().transaction_fee = 10_000;
+    output
+        .resulting_state
+        .as_mut()
+        .unwrap()
+        .acct_states
+        .iter_mut()
+        .find(|account| account.address == to.to_bytes())
+        .unwrap()
+        .lamports = 999_999;
+    let report = run_fixture(&Fixture::Txn(fixture), None).unwrap();
+    assert_eq!(
+        report
+            .diffs
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![
+            "transaction transaction_fee: expected 10000, actual 5000".to_string(),
+            format!("account {to} lamports: expected 999999, actual 1000000"),
+        ]
+    );
+}
@@ -331,6 +331,7 @@ impl JsonRpcRequestProcessor {
                     enable_cpi_recording,
                     enable_log_recording: true,
                     enable_return_data_recording: true,
+                    enable_trace_recording: false,
                 },
                 transaction_account_lock_limit: Some(64),
             },
@@ -659,6 +660,7 @@ pub mod rpc {
                 accounts: config_accounts,
                 min_context_slot: _,
                 inner_instructions: enable_cpi_recording,
+                trace: _,
             } = config.unwrap_or_default();
             let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
             let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
@@ -749,6 +751,7 @@ pub mod rpc {
                     return_data: return_data.map(|return_data| return_data.into()),
                     inner_instructions,
                     replacement_blockhash: None,
+                    trace: None,
                 },
             ))
         }
@@ -6,7 +6,9 @@
 pub use solana_sdk::inner_instruction::{InnerInstruction, InnerInstructionsList};
 use {
     crate::account_loader::LoadedTransaction,
-    solana_program_runtime::loaded_programs::ProgramCacheEntry,
+    solana_program_runtime::{
+        execution_trace::InstructionTrace, loaded_programs::ProgramCacheEntry,
+    },
     solana_pubkey::Pubkey,
     solana_transaction_context::TransactionReturnData,
     solana_transaction_error::TransactionResult,
@@ -38,6 +40,9 @@ pub struct TransactionExecutionDetails {
     pub log_messages: Option<Vec<String>>,
     pub inner_instructions: Option<InnerInstructionsList>,
     pub return_data: Option<TransactionReturnData>,
+    /// Per-instruction trace, in invocation order, when trace recording is
+    /// enabled.
+    pub execution_trace: Option<Vec<InstructionTrace>>,
     pub executed_units: u64,
     /// The change in accounts data len for this transaction.
     /// NOTE: This value is valid IFF `status` is `Ok`.
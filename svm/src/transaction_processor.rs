@@ -42,6 +42,7 @@ use {
         versions::Versions as NonceVersions,
     },
     solana_program_runtime::{
+        execution_trace::ExecutionTraceRecorder,
         invoke_context::{EnvironmentConfig, InvokeContext},
         loaded_programs::{
             ForkGraph, ProgramCache, ProgramCacheEntry, ProgramCacheForTxBatch,
@@ -95,6 +96,10 @@ pub struct ExecutionRecordingConfig {
     pub enable_cpi_recording: bool,
     pub enable_log_recording: bool,
     pub enable_return_data_recording: bool,
+    /// Record a structured per-instruction execution trace. This is more
+    /// expensive than the other settings and is therefore never enabled by
+    /// `new_single_setting()`.
+    pub enable_trace_recording: bool,
 }
 
 impl ExecutionRecordingConfig {
@@ -103,6 +108,7 @@ impl ExecutionRecordingConfig {
             enable_return_data_recording: option,
             enable_log_recording: option,
             enable_cpi_recording: option,
+            enable_trace_recording: false,
         }
     }
 }
@@ -1006,6 +1012,11 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
             log_collector.clone(),
             compute_budget,
         );
+        let execution_trace_recorder = config
+            .recording_config
+            .enable_trace_recording
+            .then(ExecutionTraceRecorder::new_ref);
+        invoke_context.set_execution_trace_recorder(execution_trace_recorder.clone());
 
         let mut process_message_time = Measure::start("process_message_time");
         let process_result = MessageProcessor::process_message(
@@ -1056,6 +1067,12 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                     .ok()
             });
 
+        let execution_trace = execution_trace_recorder.and_then(|execution_trace_recorder| {
+            Rc::try_unwrap(execution_trace_recorder)
+                .map(|execution_trace_recorder| execution_trace_recorder.into_inner().into_traces())
+                .ok()
+        });
+
         let inner_instructions = if config.recording_config.enable_cpi_recording {
             Some(Self::inner_instructions_list_from_instruction_trace(
                 &transaction_context,
@@ -1098,6 +1115,7 @@ impl<FG: ForkGraph> TransactionBatchProcessor<FG> {
                 log_messages,
                 inner_instructions,
                 return_data,
+                execution_trace,
                 executed_units,
                 accounts_data_len_delta,
             },
@@ -1444,8 +1462,9 @@ mod tests {
             false,
         );
 
+        let program_id = Pubkey::new_unique();
         let loaded_transaction = LoadedTransaction {
-            accounts: vec![(Pubkey::new_unique(), AccountSharedData::default())],
+            accounts: vec![(program_id, AccountSharedData::default())],
             program_indices: vec![vec![0]],
             fee_details: FeeDetails::default(),
             rollback_accounts: RollbackAccounts::default(),
@@ -1496,7 +1515,7 @@ mod tests {
         let executed_tx = batch_processor.execute_loaded_transaction(
             &mock_bank,
             &sanitized_transaction,
-            loaded_transaction,
+            loaded_transaction.clone(),
             &mut ExecuteTimings::default(),
             &mut TransactionErrorMetrics::default(),
             &mut program_cache_for_tx_batch,
@@ -1506,6 +1525,31 @@ mod tests {
 
         assert!(executed_tx.execution_details.log_messages.is_none());
         assert!(executed_tx.execution_details.inner_instructions.is_some());
+        assert!(executed_tx.execution_details.execution_trace.is_none());
+
+        processing_config.recording_config.enable_cpi_recording = false;
+        processing_config.recording_config.enable_trace_recording = true;
+
+        let executed_tx = batch_processor.execute_loaded_transaction(
+            &mock_bank,
+            &sanitized_transaction,
+            loaded_transaction,
+            &mut ExecuteTimings::default(),
+            &mut TransactionErrorMetrics::default(),
+            &mut program_cache_for_tx_batch,
+            &processing_environment,
+            &processing_config,
+        );
+
+        assert!(executed_tx.execution_details.inner_instructions.is_none());
+        let execution_trace = executed_tx.execution_details.execution_trace.unwrap();
+        assert_eq!(execution_trace.len(), 1);
+        assert_eq!(
+            execution_trace[0].stack_height,
+            TRANSACTION_LEVEL_STACK_HEIGHT
+        );
+        assert_eq!(execution_trace[0].program_id, program_id);
+        assert!(execution_trace[0].result.is_err());
     }
 
     #[test]
@@ -250,6 +250,7 @@ fn svm_concurrent() {
                     enable_log_recording: true,
                     enable_return_data_recording: false,
                     enable_cpi_recording: false,
+                    enable_trace_recording: false,
                 },
                 ..Default::default()
             };
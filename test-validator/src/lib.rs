@@ -4,7 +4,8 @@ use {
     crossbeam_channel::Receiver,
     log::*,
     solana_accounts_db::{
-        accounts_db::AccountsDbConfig, accounts_index::AccountsIndexConfig,
+        accounts_db::{AccountsDbConfig, CalcAccountsHashDataSource},
+        accounts_index::{AccountsIndexConfig, ScanConfig},
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
         utils::create_accounts_run_and_snapshot_dirs,
     },
@@ -34,10 +35,14 @@ use {
     solana_rpc_client::{nonblocking, rpc_client::RpcClient},
     solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS,
     solana_runtime::{
+        bank::Bank,
         bank_forks::BankForks,
         genesis_utils::{self, create_genesis_config_with_leader_ex_no_features},
         runtime_config::RuntimeConfig,
+        snapshot_archive_info::{FullSnapshotArchiveInfo, SnapshotArchiveInfoGetter},
+        snapshot_bank_utils,
         snapshot_config::SnapshotConfig,
+        snapshot_utils::ArchiveFormat,
     },
     solana_sdk::{
         account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
@@ -48,6 +53,7 @@ use {
         exit::Exit,
         feature_set::FeatureSet,
         fee_calculator::FeeRateGovernor,
+        genesis_config::GenesisConfig,
         instruction::{AccountMeta, Instruction},
         message::Message,
         native_token::sol_to_lamports,
@@ -69,7 +75,7 @@ use {
         net::{IpAddr, Ipv4Addr, SocketAddr},
         path::{Path, PathBuf},
         str::FromStr,
-        sync::{Arc, RwLock},
+        sync::{atomic::AtomicBool, Arc, RwLock},
         time::Duration,
     },
     tokio::time::sleep,
@@ -121,6 +127,7 @@ pub struct TestValidatorGenesis {
     pubsub_config: PubSubConfig,
     rpc_ports: Option<(u16, u16)>, // (JsonRpc, JsonRpcPubSub), None == random ports
     warp_slot: Option<Slot>,
+    snapshot_archive: Option<PathBuf>,
     accounts: HashMap<Pubkey, AccountSharedData>,
     upgradeable_programs: Vec<UpgradeableProgramInfo>,
     ticks_per_slot: Option<u64>,
@@ -153,6 +160,7 @@ impl Default for TestValidatorGenesis {
             pubsub_config: PubSubConfig::default(),
             rpc_ports: Option::<(u16, u16)>::default(),
             warp_slot: Option::<Slot>::default(),
+            snapshot_archive: Option::<PathBuf>::default(),
             accounts: HashMap::<Pubkey, AccountSharedData>::default(),
             upgradeable_programs: Vec::<UpgradeableProgramInfo>::default(),
             ticks_per_slot: Option::<u64>::default(),
@@ -286,6 +294,17 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Start from the state in a full snapshot archive, such as one created by
+    /// `agave-ledger-tool create-snapshot --minimized`, instead of a new genesis
+    ///
+    /// Configured accounts, programs and feature deactivations are applied on top of the
+    /// snapshot's state, and the test validator's own vote account replaces all of the snapshot's
+    /// vote accounts. The rent, epoch schedule and ticks per slot are taken from the snapshot.
+    pub fn snapshot_archive<P: Into<PathBuf>>(&mut self, snapshot_archive: P) -> &mut Self {
+        self.snapshot_archive = Some(snapshot_archive.into());
+        self
+    }
+
     pub fn gossip_host(&mut self, gossip_host: IpAddr) -> &mut Self {
         self.node_config.gossip_addr.set_ip(gossip_host);
         self
@@ -791,9 +810,6 @@ impl TestValidator {
         let mint_lamports = sol_to_lamports(500_000_000.);
 
         let mut accounts = config.accounts.clone();
-        for (address, account) in solana_program_test::programs::spl_programs(&config.rent) {
-            accounts.entry(address).or_insert(account);
-        }
         for upgradeable_program in &config.upgradeable_programs {
             let data = solana_program_test::read_file(&upgradeable_program.program_path);
             let (programdata_address, _) = Pubkey::find_program_address(
@@ -832,6 +848,17 @@ impl TestValidator {
                 }),
             );
         }
+        // Accounts that must replace those already present when starting from a snapshot
+        let mut overridden_accounts: HashSet<Pubkey> = accounts.keys().copied().collect();
+        overridden_accounts.extend([
+            mint_address,
+            validator_identity.pubkey(),
+            validator_vote_account.pubkey(),
+            validator_stake_account.pubkey(),
+        ]);
+        for (address, account) in solana_program_test::programs::spl_programs(&config.rent) {
+            accounts.entry(address).or_insert(account);
+        }
 
         let mut genesis_config = create_genesis_config_with_leader_ex_no_features(
             mint_lamports,
@@ -868,17 +895,40 @@ impl TestValidator {
                 )
             }
         }
-        for feature in feature_set {
-            genesis_utils::activate_feature(&mut genesis_config, feature);
+        // A snapshot keeps its own feature set, less the explicitly deactivated features
+        if config.snapshot_archive.is_none() {
+            for feature in feature_set {
+                genesis_utils::activate_feature(&mut genesis_config, feature);
+            }
+        }
+
+        if let Some(ledger_path) = &config.ledger_path {
+            if TestValidatorGenesis::ledger_exists(ledger_path) {
+                return Ok(ledger_path.to_path_buf());
+            }
         }
 
+        let snapshot_bank = config
+            .snapshot_archive
+            .as_ref()
+            .map(|snapshot_archive| {
+                let work_dir = tempfile::Builder::new()
+                    .prefix("test-validator-snapshot")
+                    .tempdir()?;
+                let bank = TestValidator::bank_from_snapshot_archive(
+                    snapshot_archive,
+                    work_dir.path(),
+                    &mut genesis_config,
+                    &overridden_accounts,
+                    config,
+                )?;
+                Ok::<_, Box<dyn std::error::Error>>((bank, work_dir))
+            })
+            .transpose()?;
+
         let ledger_path = match &config.ledger_path {
             None => create_new_tmp_ledger!(&genesis_config).0,
             Some(ledger_path) => {
-                if TestValidatorGenesis::ledger_exists(ledger_path) {
-                    return Ok(ledger_path.to_path_buf());
-                }
-
                 let _ = create_new_ledger(
                     ledger_path,
                     &genesis_config,
@@ -898,6 +948,29 @@ impl TestValidator {
             }
         };
 
+        if let Some((bank, work_dir)) = snapshot_bank {
+            let snapshot_archive_info = snapshot_bank_utils::bank_to_full_snapshot_archive(
+                work_dir.path(),
+                &bank,
+                None,
+                &ledger_path,
+                &ledger_path,
+                ArchiveFormat::TarZstd,
+            )
+            .map_err(|err| {
+                format!(
+                    "Failed to create snapshot archive in {}: {err}",
+                    ledger_path.display()
+                )
+            })?;
+            info!(
+                "Starting from snapshot archive {} at slot {}",
+                snapshot_archive_info.path().display(),
+                snapshot_archive_info.slot(),
+            );
+            drop(bank);
+        }
+
         write_keypair_file(
             &validator_identity,
             ledger_path.join("validator-keypair.json").to_str().unwrap(),
@@ -925,6 +998,126 @@ impl TestValidator {
         Ok(ledger_path)
     }
 
+    /// Load the bank in `snapshot_archive` and prepare it for the test validator
+    ///
+    /// The accounts of `genesis_config` are stored into a child of the snapshot's bank. Accounts
+    /// in `overridden_accounts` always replace the snapshot's, the others are only added if
+    /// missing. The snapshot's vote accounts are removed so that the test validator is the only
+    /// staked node, and the bank is then warped at least two epochs ahead for the new stake to be
+    /// reflected in the leader schedule. Finally `genesis_config` is updated to match the bank.
+    fn bank_from_snapshot_archive(
+        snapshot_archive: &Path,
+        work_dir: &Path,
+        genesis_config: &mut GenesisConfig,
+        overridden_accounts: &HashSet<Pubkey>,
+        config: &TestValidatorGenesis,
+    ) -> Result<Arc<Bank>, Box<dyn std::error::Error>> {
+        let full_snapshot_archive_info = FullSnapshotArchiveInfo::new_from_path(
+            snapshot_archive.to_path_buf(),
+        )
+        .map_err(|err| {
+            format!(
+                "Invalid full snapshot archive {}: {err}",
+                snapshot_archive.display()
+            )
+        })?;
+        let account_paths = vec![work_dir.join("accounts")];
+        let bank_snapshots_dir = work_dir.join("snapshot");
+        fs::create_dir_all(&account_paths[0])?;
+        fs::create_dir_all(&bank_snapshots_dir)?;
+
+        let (bank, _) = snapshot_bank_utils::bank_from_snapshot_archives(
+            &account_paths,
+            &bank_snapshots_dir,
+            &full_snapshot_archive_info,
+            None,
+            genesis_config,
+            &RuntimeConfig::default(),
+            None,
+            None,
+            None,
+            false,
+            false,
+            false,
+            false,
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+        )
+        .map_err(|err| {
+            format!(
+                "Failed to load snapshot archive {}: {err}",
+                snapshot_archive.display()
+            )
+        })?;
+        // Only one accounts hash calculation may run at a time, so wait for the startup
+        // verification before the bank is warped and archived again
+        bank.rc
+            .accounts
+            .accounts_db
+            .verify_accounts_hash_in_bg
+            .join_background_thread();
+        let snapshot_slot = bank.slot();
+        let parent = Arc::new(bank);
+        let mut bank =
+            Bank::new_from_parent(parent.clone(), parent.collector_id(), snapshot_slot + 1);
+        // Like a new genesis, skip the PoH speed check and sleep between ticks
+        bank.set_hashes_per_tick(None);
+
+        let scan_config = ScanConfig::new(false);
+        for (address, mut account) in
+            bank.get_program_accounts(&solana_sdk::vote::program::id(), &scan_config)?
+        {
+            account.set_lamports(0);
+            bank.store_account(&address, &account);
+        }
+        for (address, account) in &genesis_config.accounts {
+            if overridden_accounts.contains(address) || bank.get_account(address).is_none() {
+                bank.store_account(address, &AccountSharedData::from(account.clone()));
+            }
+        }
+        for feature in &config.deactivate_feature_set {
+            match bank.get_account(feature) {
+                Some(mut account) => {
+                    account.set_lamports(0);
+                    bank.store_account(feature, &account);
+                    info!("Feature for {:?} deactivated", feature);
+                }
+                None => warn!(
+                    "Feature {:?} set for deactivation is not present in the snapshot",
+                    feature,
+                ),
+            }
+        }
+        while !bank.is_complete() {
+            bank.register_unique_tick();
+        }
+        bank.set_capitalization();
+
+        let epoch_schedule = bank.epoch_schedule().clone();
+        let minimum_warp_slot =
+            epoch_schedule.get_first_slot_in_epoch(epoch_schedule.get_epoch(snapshot_slot) + 2);
+        let warp_slot = config.warp_slot.unwrap_or_default().max(minimum_warp_slot);
+        let bank = Arc::new(bank);
+        // Warping calculates the accounts hash from storages, which requires the bank to be
+        // rooted and its accounts flushed first
+        bank.squash();
+        bank.force_flush_accounts_cache();
+        let bank = Arc::new(Bank::warp_from_parent(
+            bank.clone(),
+            bank.collector_id(),
+            warp_slot,
+            CalcAccountsHashDataSource::Storages,
+        ));
+
+        genesis_config.epoch_schedule = epoch_schedule;
+        genesis_config.rent = bank.rent_collector().rent.clone();
+        genesis_config.ticks_per_slot = bank.ticks_per_slot();
+        genesis_config.poh_config.hashes_per_tick = None;
+        genesis_config.inflation = bank.inflation();
+        Ok(bank)
+    }
+
     /// Starts a TestValidator at the provided ledger directory
     fn start(
         mint_address: Pubkey,
@@ -1021,7 +1214,10 @@ impl TestValidator {
                 incremental_snapshot_archives_dir: ledger_path.to_path_buf(),
                 ..SnapshotConfig::default()
             },
-            warp_slot: config.warp_slot,
+            // When starting from a snapshot, the warp was already applied to the snapshot's bank
+            warp_slot: config
+                .warp_slot
+                .filter(|_| config.snapshot_archive.is_none()),
             validator_exit: config.validator_exit.clone(),
             max_ledger_shreds: config.max_ledger_shreds,
             no_wait_for_vote_to_start_leader: true,
@@ -1305,4 +1501,69 @@ mod test {
         let feature_state: Feature = bincode::deserialize(feature_account.data()).unwrap();
         assert!(feature_state.activated_at.is_some());
     }
+
+    #[tokio::test]
+    async fn test_start_from_snapshot_archive() {
+        let snapshot_dir = tempfile::tempdir().unwrap();
+        let genesis_config_info = genesis_utils::create_genesis_config(sol_to_lamports(1_000.));
+        let bank = Bank::new_with_paths(
+            &genesis_config_info.genesis_config,
+            Arc::new(RuntimeConfig::default()),
+            vec![snapshot_dir.path().join("accounts")],
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+            None,
+            None,
+        );
+        let snapshot_account_address = Pubkey::new_unique();
+        bank.store_account(
+            &snapshot_account_address,
+            &AccountSharedData::new(42, 0, &solana_sdk::system_program::id()),
+        );
+        while !bank.is_complete() {
+            bank.register_unique_tick();
+        }
+        let snapshot_archive_info = snapshot_bank_utils::bank_to_full_snapshot_archive(
+            snapshot_dir.path(),
+            &bank,
+            None,
+            snapshot_dir.path(),
+            snapshot_dir.path(),
+            ArchiveFormat::TarZstd,
+        )
+        .unwrap();
+
+        let added_account_address = Pubkey::new_unique();
+        let (test_validator, payer) = TestValidatorGenesis::default()
+            .snapshot_archive(snapshot_archive_info.path())
+            .add_account(
+                added_account_address,
+                AccountSharedData::new(7, 0, &solana_sdk::system_program::id()),
+            )
+            .start_async()
+            .await;
+        let rpc_client = test_validator.get_async_rpc_client();
+
+        assert!(rpc_client.get_slot().await.unwrap() > bank.slot());
+        assert_eq!(
+            rpc_client
+                .get_balance(&snapshot_account_address)
+                .await
+                .unwrap(),
+            42
+        );
+        assert_eq!(
+            rpc_client
+                .get_balance(&added_account_address)
+                .await
+                .unwrap(),
+            7
+        );
+        assert!(rpc_client.get_balance(&payer.pubkey()).await.unwrap() > 0);
+    }
 }
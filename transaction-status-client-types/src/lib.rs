@@ -110,6 +110,9 @@ pub struct UiConfirmedBlock {
     pub num_reward_partitions: Option<u64>,
     pub block_time: Option<i64>,
     pub block_height: Option<u64>,
+    /// Set when a page of transactions was requested and more transactions follow it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_transaction_cursor: Option<u64>,
 }
 
 /// A duplicate representation of a Transaction for pretty JSON serialization
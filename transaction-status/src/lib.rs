@@ -379,6 +379,7 @@ impl ConfirmedBlock {
             num_reward_partitions: self.num_partitions,
             block_time: self.block_time,
             block_height: self.block_height,
+            next_transaction_cursor: None,
         })
     }
 }
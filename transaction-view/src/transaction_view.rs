@@ -160,6 +160,13 @@ impl<const SANITIZED: bool, D: TransactionData> TransactionView<SANITIZED, D> {
         self.data.data()
     }
 
+    /// Return a reference to the container holding the serialized
+    /// transaction data.
+    #[inline]
+    pub fn inner_data(&self) -> &D {
+        &self.data
+    }
+
     /// Return the serialized **message** data.
     /// This does not include the signatures.
     #[inline]
@@ -8,7 +8,13 @@ use {
     },
     log::*,
     serde::{de::Deserializer, Deserialize, Serialize},
-    solana_accounts_db::accounts_index::AccountIndex,
+    solana_accounts_db::{
+        accounts_db::{
+            owner_stats::OwnerStatsReport,
+            shrink_control::{ShrinkControlStatus, ShrinkRequest, ShrinkRequestKind},
+        },
+        accounts_index::AccountIndex,
+    },
     solana_core::{
         admin_rpc_post_init::AdminRpcRequestMetadataPostInit,
         consensus::{tower_storage::TowerStorage, Tower},
@@ -24,6 +30,7 @@ use {
     solana_rpc::rpc::verify_pubkey,
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
     solana_sdk::{
+        clock::Slot,
         exit::Exit,
         pubkey::Pubkey,
         signature::{read_keypair_file, Keypair, Signer},
@@ -235,6 +242,31 @@ pub trait AdminRpc {
         pubkey_str: String,
     ) -> Result<HashMap<RpcAccountIndex, usize>>;
 
+    #[rpc(meta, name = "getAccountsOwnerStats")]
+    fn get_accounts_owner_stats(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<OwnerStatsReport>;
+
+    #[rpc(meta, name = "pauseAccountsShrink")]
+    fn pause_accounts_shrink(&self, meta: Self::Metadata) -> Result<()>;
+
+    #[rpc(meta, name = "resumeAccountsShrink")]
+    fn resume_accounts_shrink(&self, meta: Self::Metadata) -> Result<()>;
+
+    #[rpc(meta, name = "requestAccountsShrink")]
+    fn request_accounts_shrink(
+        &self,
+        meta: Self::Metadata,
+        kind: ShrinkRequestKind,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<ShrinkRequest>;
+
+    #[rpc(meta, name = "getAccountsShrinkStatus")]
+    fn get_accounts_shrink_status(&self, meta: Self::Metadata) -> Result<ShrinkControlStatus>;
+
     #[rpc(meta, name = "setPublicTpuAddress")]
     fn set_public_tpu_address(
         &self,
@@ -720,9 +752,10 @@ impl AdminRpc for AdminRpcImpl {
                 .indexes
                 .iter()
                 .filter_map(|index| {
+                    let rpc_account_index = rpc_account_index_from_account_index(index)?;
                     accounts_index
                         .get_index_key_size(index, &index_key)
-                        .map(|size| (rpc_account_index_from_account_index(index), size))
+                        .map(|size| (rpc_account_index, size))
                 })
                 .collect::<HashMap<_, _>>();
 
@@ -734,6 +767,85 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn get_accounts_owner_stats(
+        &self,
+        meta: Self::Metadata,
+        limit: Option<usize>,
+    ) -> Result<OwnerStatsReport> {
+        debug!("get_accounts_owner_stats rpc request received: {:?}", limit);
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            Ok(bank.accounts().accounts_db.owner_stats_report(limit))
+        })
+    }
+
+    fn pause_accounts_shrink(&self, meta: Self::Metadata) -> Result<()> {
+        debug!("pause_accounts_shrink rpc request received");
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            bank.accounts()
+                .accounts_db
+                .shrink_control
+                .pause_background_shrink();
+            Ok(())
+        })
+    }
+
+    fn resume_accounts_shrink(&self, meta: Self::Metadata) -> Result<()> {
+        debug!("resume_accounts_shrink rpc request received");
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            bank.accounts()
+                .accounts_db
+                .shrink_control
+                .resume_background_shrink();
+            Ok(())
+        })
+    }
+
+    fn request_accounts_shrink(
+        &self,
+        meta: Self::Metadata,
+        kind: ShrinkRequestKind,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Result<ShrinkRequest> {
+        debug!("request_accounts_shrink rpc request received: {kind:?} {start_slot}..={end_slot}");
+        if start_slot > end_slot {
+            return Err(jsonrpc_core::error::Error::invalid_params(format!(
+                "start slot {start_slot} is greater than end slot {end_slot}"
+            )));
+        }
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            if kind == ShrinkRequestKind::AncientPack
+                && (!bank.are_ancient_storages_enabled()
+                    || bank
+                        .accounts()
+                        .accounts_db
+                        .ancient_append_vec_offset
+                        .is_none())
+            {
+                return Err(jsonrpc_core::error::Error::invalid_params(
+                    "ancient storages are not enabled on this validator",
+                ));
+            }
+            Ok(bank
+                .accounts()
+                .accounts_db
+                .shrink_control
+                .request(kind, start_slot, end_slot))
+        })
+    }
+
+    fn get_accounts_shrink_status(&self, meta: Self::Metadata) -> Result<ShrinkControlStatus> {
+        debug!("get_accounts_shrink_status rpc request received");
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            Ok(bank.accounts().accounts_db.shrink_control.status())
+        })
+    }
+
     fn set_public_tpu_address(
         &self,
         meta: Self::Metadata,
@@ -862,11 +974,13 @@ impl AdminRpcImpl {
     }
 }
 
-fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> RpcAccountIndex {
+fn rpc_account_index_from_account_index(account_index: &AccountIndex) -> Option<RpcAccountIndex> {
     match account_index {
-        AccountIndex::ProgramId => RpcAccountIndex::ProgramId,
-        AccountIndex::SplTokenOwner => RpcAccountIndex::SplTokenOwner,
-        AccountIndex::SplTokenMint => RpcAccountIndex::SplTokenMint,
+        AccountIndex::ProgramId => Some(RpcAccountIndex::ProgramId),
+        AccountIndex::SplTokenOwner => Some(RpcAccountIndex::SplTokenOwner),
+        AccountIndex::SplTokenMint => Some(RpcAccountIndex::SplTokenMint),
+        // user-defined indexes are not keyed by pubkeys, so they are not reported
+        AccountIndex::Custom(_) => None,
     }
 }
 
@@ -3,11 +3,13 @@ use {
         admin_rpc_service, cli, dashboard::Dashboard, ledger_lockfile, lock_ledger,
         println_name_value, redirect_stderr_to_file,
     },
-    clap::{crate_name, value_t, value_t_or_exit, values_t_or_exit},
+    clap::{crate_name, value_t, value_t_or_exit, values_t, values_t_or_exit},
     crossbeam_channel::unbounded,
     itertools::Itertools,
     log::*,
-    solana_accounts_db::accounts_index::{AccountIndex, AccountSecondaryIndexes},
+    solana_accounts_db::accounts_index::{
+        AccountIndex, AccountSecondaryIndexes, CustomAccountIndex,
+    },
     solana_clap_utils::{
         input_parsers::{pubkey_of, pubkeys_of, value_of},
         input_validators::normalize_to_url_if_moniker,
@@ -74,6 +76,12 @@ fn main() {
             "spl-token-owner" => AccountIndex::SplTokenOwner,
             _ => unreachable!(),
         })
+        .chain(
+            values_t!(matches, "account_index_custom", CustomAccountIndex)
+                .unwrap_or_default()
+                .into_iter()
+                .map(AccountIndex::Custom),
+        )
         .collect();
 
     let account_indexes = AccountSecondaryIndexes {
@@ -155,8 +163,11 @@ fn main() {
     let rpc_port = value_t_or_exit!(matches, "rpc_port", u16);
     let enable_vote_subscription = matches.is_present("rpc_pubsub_enable_vote_subscription");
     let enable_block_subscription = matches.is_present("rpc_pubsub_enable_block_subscription");
+    let enable_transaction_subscription =
+        matches.is_present("rpc_pubsub_enable_transaction_subscription");
     let faucet_port = value_t_or_exit!(matches, "faucet_port", u16);
     let ticks_per_slot = value_t!(matches, "ticks_per_slot", u64).ok();
+    let snapshot_archive = value_t!(matches, "snapshot_archive", PathBuf).ok();
     let slots_per_epoch = value_t!(matches, "slots_per_epoch", Slot).ok();
     let gossip_host = matches.value_of("gossip_host").map(|gossip_host| {
         solana_net_utils::parse_host(gossip_host).unwrap_or_else(|err| {
@@ -365,6 +376,7 @@ fn main() {
             ("slots_per_epoch", "--slots-per-epoch"),
             ("faucet_sol", "--faucet-sol"),
             ("deactivate_feature", "--deactivate-feature"),
+            ("snapshot_archive", "--snapshot"),
         ] {
             if matches.is_present(name) {
                 println!("{long} argument ignored, ledger already exists");
@@ -450,6 +462,7 @@ fn main() {
         .pubsub_config(PubSubConfig {
             enable_vote_subscription,
             enable_block_subscription,
+            enable_transaction_subscription,
             ..PubSubConfig::default()
         })
         .rpc_port(rpc_port)
@@ -528,6 +541,10 @@ fn main() {
         genesis.warp_slot(warp_slot);
     }
 
+    if let Some(snapshot_archive) = snapshot_archive {
+        genesis.snapshot_archive(snapshot_archive);
+    }
+
     if let Some(ticks_per_slot) = ticks_per_slot {
         genesis.ticks_per_slot(ticks_per_slot);
     }
@@ -7,6 +7,7 @@ use {
         accounts_db::{
             DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE, DEFAULT_ACCOUNTS_SHRINK_RATIO,
         },
+        accounts_index::CustomAccountIndex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
     },
     solana_clap_utils::{
@@ -24,10 +25,15 @@ use {
         validator::{BlockProductionMethod, BlockVerificationMethod},
     },
     solana_faucet::faucet::{self, FAUCET_PORT},
-    solana_ledger::use_snapshot_archives_at_startup,
+    solana_ledger::{
+        blockstore_options::DEFAULT_ZSTD_COMPRESSION_LEVEL, use_snapshot_archives_at_startup,
+    },
     solana_net_utils::{MINIMUM_VALIDATOR_PORT_RANGE_WIDTH, VALIDATOR_PORT_RANGE},
     solana_rayon_threadlimit::get_thread_count,
-    solana_rpc::{rpc::MAX_REQUEST_BODY_SIZE, rpc_pubsub_service::PubSubConfig},
+    solana_rpc::{
+        rpc::MAX_REQUEST_BODY_SIZE, rpc_grpc_service::GrpcStreamingConfig,
+        rpc_pubsub_service::PubSubConfig,
+    },
     solana_rpc_client_api::request::{DELINQUENT_VALIDATOR_SLOT_DISTANCE, MAX_MULTIPLE_ACCOUNTS},
     solana_runtime::{
         snapshot_bank_utils::{
@@ -345,6 +351,20 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(false)
                 .help("Upload new confirmed blocks into a BigTable instance"),
         )
+        .arg(
+            Arg::with_name("rpc_ledger_storage_url")
+                .long("rpc-ledger-storage-url")
+                .value_name("URL")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(true)
+                .help(
+                    "Fetch historical transaction info from, and with \
+                     --enable-bigtable-ledger-upload upload new confirmed blocks to, the object \
+                     storage at URL instead of a BigTable instance. URL is a local directory or \
+                     s3://BUCKET[/PREFIX], with S3 settings read from the AWS_* environment \
+                     variables",
+                ),
+        )
         .arg(
             Arg::with_name("enable_extended_tx_metadata_storage")
                 .long("enable-extended-tx-metadata-storage")
@@ -772,13 +792,57 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .long("rocksdb-ledger-compression")
                 .value_name("COMPRESSION_TYPE")
                 .takes_value(true)
-                .possible_values(&["none", "lz4", "snappy", "zlib"])
+                .possible_values(&["none", "lz4", "snappy", "zlib", "zstd"])
                 .default_value(&default_args.rocksdb_ledger_compression)
                 .help(
                     "The compression algorithm that is used to compress transaction status data. \
                      Turning on compression can save ~10% of the ledger size.",
                 ),
         )
+        .arg(
+            Arg::with_name("rocksdb_rewards_compression")
+                .hidden(hidden_unless_forced())
+                .long("rocksdb-rewards-compression")
+                .value_name("COMPRESSION_TYPE")
+                .takes_value(true)
+                .possible_values(&["none", "lz4", "snappy", "zlib", "zstd"])
+                .default_value(&default_args.rocksdb_rewards_compression)
+                .help("The compression algorithm that is used to compress rewards data."),
+        )
+        .arg(
+            Arg::with_name("rocksdb_data_shred_compression")
+                .hidden(hidden_unless_forced())
+                .long("rocksdb-data-shred-compression")
+                .value_name("COMPRESSION_TYPE")
+                .takes_value(true)
+                .possible_values(&["none", "lz4", "snappy", "zlib", "zstd"])
+                .default_value(&default_args.rocksdb_data_shred_compression)
+                .help("The compression algorithm that is used to compress data shreds."),
+        )
+        .arg(
+            Arg::with_name("rocksdb_zstd_compression_level")
+                .hidden(hidden_unless_forced())
+                .long("rocksdb-zstd-compression-level")
+                .value_name("LEVEL")
+                .takes_value(true)
+                .validator(is_parsable::<i32>)
+                .default_value(&default_args.rocksdb_zstd_compression_level)
+                .help("The compression level of the columns compressed with zstd."),
+        )
+        .arg(
+            Arg::with_name("rocksdb_zstd_max_dict_bytes")
+                .hidden(hidden_unless_forced())
+                .long("rocksdb-zstd-max-dict-bytes")
+                .value_name("BYTES")
+                .takes_value(true)
+                .validator(is_parsable::<u32>)
+                .default_value(&default_args.rocksdb_zstd_max_dict_bytes)
+                .help(
+                    "The size of the dictionary trained for each SST file of the columns \
+                     compressed with zstd, which improves the compression of small values. \
+                     0 disables dictionary training.",
+                ),
+        )
         .arg(
             Arg::with_name("rocksdb_perf_sample_interval")
                 .hidden(hidden_unless_forced())
@@ -1134,6 +1198,13 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_transaction_subscription")
+                .long("rpc-pubsub-enable-transaction-subscription")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `transactionSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("rpc_pubsub_enable_vote_subscription")
                 .long("rpc-pubsub-enable-vote-subscription")
@@ -1193,6 +1264,73 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                      notifications. 0 will disable RPC PubSub notifications",
                 ),
         )
+        .arg(
+            Arg::with_name("rpc_grpc_port")
+                .long("rpc-grpc-port")
+                .value_name("PORT")
+                .takes_value(true)
+                .requires("rpc_port")
+                .validator(port_validator)
+                .help(
+                    "Enable the gRPC streaming service for account, slot, transaction and block \
+                     updates on this port. The service has no TLS or authentication, so only \
+                     expose it to trusted networks or behind a proxy that provides them",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_max_clients")
+                .long("rpc-grpc-max-clients")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_grpc_max_clients)
+                .help("The maximum number of clients connected to the gRPC streaming service"),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_max_clients_per_ip")
+                .long("rpc-grpc-max-clients-per-ip")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_grpc_max_clients_per_ip)
+                .help(
+                    "The maximum number of clients connected to the gRPC streaming service from \
+                     a single IP address",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_client_queue_capacity")
+                .long("rpc-grpc-client-queue-capacity")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(|s| is_within_range(s, 1..))
+                .default_value(&default_args.rpc_grpc_client_queue_capacity)
+                .help(
+                    "The maximum number of updates queued for a gRPC streaming client. Clients \
+                     falling further behind are disconnected",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_max_filter_keys")
+                .long("rpc-grpc-max-filter-keys")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(is_parsable::<usize>)
+                .default_value(&default_args.rpc_grpc_max_filter_keys)
+                .help(
+                    "The maximum number of addresses in the filters of a gRPC streaming \
+                     subscription",
+                ),
+        )
+        .arg(
+            Arg::with_name("rpc_grpc_worker_threads")
+                .long("rpc-grpc-worker-threads")
+                .takes_value(true)
+                .value_name("NUMBER")
+                .validator(|s| is_within_range(s, 1..))
+                .default_value(&default_args.rpc_grpc_worker_threads)
+                .help("gRPC streaming service worker threads"),
+        )
         .arg(
             Arg::with_name("rpc_send_transaction_retry_ms")
                 .long("rpc-send-retry-ms")
@@ -1298,6 +1436,16 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(&default_args.rpc_max_request_body_size)
                 .help("The maximum request body size accepted by rpc service"),
         )
+        .arg(
+            Arg::with_name("rpc_rate_limit_config")
+                .long("rpc-rate-limit-config")
+                .value_name("FILE")
+                .takes_value(true)
+                .help(
+                    "Enforce the per-client and per-method request quotas in this JSON file on \
+                     the RPC and RPC pubsub services",
+                ),
+        )
         .arg(
             Arg::with_name("geyser_plugin_config")
                 .long("geyser-plugin-config")
@@ -1392,6 +1540,19 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
+        .arg(
+            Arg::with_name("account_index_custom")
+                .long("account-index-custom")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_parsable::<CustomAccountIndex>)
+                .value_name("PROGRAM_ID:OFFSET:LENGTH")
+                .help(
+                    "Enable an accounts index over the LENGTH bytes (at most 32) at OFFSET in \
+                     the data of accounts owned by PROGRAM_ID. getProgramAccounts uses this \
+                     index when a memcmp filter matches its offset",
+                ),
+        )
         .arg(
             Arg::with_name("account_index_exclude_key")
                 .long(EXCLUDE_KEY)
@@ -1460,10 +1621,21 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .long("accounts-db-squash-storages-method")
                 .value_name("METHOD")
                 .takes_value(true)
-                .possible_values(&["pack", "append"])
+                .possible_values(&["pack", "pack-cold", "append"])
+                .requires_if("pack-cold", "snapshot_archive_tiered_storages")
                 .help("Squash multiple account storage files together using this method")
                 .hidden(hidden_unless_forced()),
         )
+        .arg(
+            Arg::with_name("snapshot_archive_tiered_storages")
+                .long("snapshot-archive-tiered-storages")
+                .help(
+                    "Allow snapshot archives to contain tiered account storages, as created by \
+                     --accounts-db-squash-storages-method pack-cold. Validators without support \
+                     for tiered storages cannot unpack these archives",
+                )
+                .hidden(hidden_unless_forced()),
+        )
         .arg(
             Arg::with_name("accounts_db_access_storages_method")
                 .long("accounts-db-access-storages-method")
@@ -1502,6 +1674,20 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .help("The number of ancient storages the ancient slot combining should converge to.")
                 .hidden(hidden_unless_forced()),
         )
+        .arg(
+            Arg::with_name("accounts_db_account_history_slots")
+                .long("accounts-db-account-history-slots")
+                .value_name("SLOTS")
+                .validator(is_parsable::<u64>)
+                .takes_value(true)
+                .help(
+                    "Keep every account version written in this many slots before the highest \
+                     root instead of cleaning them, so getAccountInfo and getMultipleAccounts \
+                     can answer queries with atSlot for the rooted slots among them. Skipped \
+                     slots count towards this number. Increases accounts index memory and \
+                     storage size.",
+                ),
+        )
         .arg(
             Arg::with_name("accounts_db_hash_calculation_pubkey_bins")
                 .long("accounts-db-hash-calculation-pubkey-bins")
@@ -1697,6 +1883,29 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .possible_values(BlockProductionMethod::cli_names())
                 .help(BlockProductionMethod::cli_message()),
         )
+        .arg(
+            Arg::with_name("block_production_forwarding_lookahead_leaders")
+                .long("block-production-forwarding-lookahead-leaders")
+                .value_name("COUNT")
+                .takes_value(true)
+                .validator(|s| is_within_range(s, 1..))
+                .help(
+                    "Spread forwarded transactions across this many upcoming leaders, weighted \
+                     by stake, instead of forwarding everything to the next leader only. Only \
+                     applies when block production forwarding is enabled.",
+                ),
+        )
+        .arg(
+            Arg::with_name("block_production_program_cost_estimation")
+                .long("block-production-program-cost-estimation")
+                .takes_value(false)
+                .help(
+                    "When packing blocks, estimate the execution cost of transactions that do \
+                     not request a compute unit limit from the compute units their instructions \
+                     consumed in previously executed transactions. Estimates are kept in the \
+                     ledger directory across restarts.",
+                ),
+        )
         .arg(
             Arg::with_name("unified_scheduler_handler_threads")
                 .long("unified-scheduler-handler-threads")
@@ -1914,6 +2123,88 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         ),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("accounts-owner-stats")
+                .about(
+                    "Display the accounts index and storage usage of the running validator, \
+                     broken down by account owner",
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .validator(is_parsable::<usize>)
+                        .default_value("20")
+                        .help("Number of owners with the most storage bytes to display"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode"),
+                )
+                .after_help(
+                    "Note: the first query reads every account storage, later queries only read \
+                     the storages created since the previous one",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("accounts-shrink")
+                .about("Control accounts shrink and ancient packing of the running validator")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("pause").about(
+                        "Pause background shrink. Requested shrinks and ancient packs still run",
+                    ),
+                )
+                .subcommand(SubCommand::with_name("resume").about("Resume background shrink"))
+                .subcommand(
+                    SubCommand::with_name("request")
+                        .about("Shrink or ancient pack the storages of a range of slots")
+                        .arg(
+                            Arg::with_name("start_slot")
+                                .long("start-slot")
+                                .takes_value(true)
+                                .value_name("SLOT")
+                                .validator(is_slot)
+                                .required(true)
+                                .help("First slot of the range"),
+                        )
+                        .arg(
+                            Arg::with_name("end_slot")
+                                .long("end-slot")
+                                .takes_value(true)
+                                .value_name("SLOT")
+                                .validator(is_slot)
+                                .required(true)
+                                .help("Last slot of the range, inclusive"),
+                        )
+                        .arg(
+                            Arg::with_name("ancient_pack")
+                                .long("ancient-pack")
+                                .takes_value(false)
+                                .help(
+                                    "Combine the storages of the range into ancient storages \
+                                     instead of shrinking each of them",
+                                ),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("status")
+                        .about("Display background shrink state and shrink request progress")
+                        .arg(
+                            Arg::with_name("output")
+                                .long("output")
+                                .takes_value(true)
+                                .value_name("MODE")
+                                .possible_values(&["json", "json-compact"])
+                                .help("Output display mode"),
+                        ),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("contact-info")
                 .about("Display the validator's contact info")
@@ -2481,6 +2772,11 @@ pub struct DefaultArgs {
     pub rpc_pubsub_max_active_subscriptions: String,
     pub rpc_pubsub_queue_capacity_items: String,
     pub rpc_pubsub_queue_capacity_bytes: String,
+    pub rpc_grpc_max_clients: String,
+    pub rpc_grpc_max_clients_per_ip: String,
+    pub rpc_grpc_client_queue_capacity: String,
+    pub rpc_grpc_max_filter_keys: String,
+    pub rpc_grpc_worker_threads: String,
     pub rpc_send_transaction_retry_ms: String,
     pub rpc_send_transaction_batch_ms: String,
     pub rpc_send_transaction_leader_forward_count: String,
@@ -2514,6 +2810,10 @@ pub struct DefaultArgs {
 
     pub rocksdb_shred_compaction: String,
     pub rocksdb_ledger_compression: String,
+    pub rocksdb_rewards_compression: String,
+    pub rocksdb_data_shred_compression: String,
+    pub rocksdb_zstd_compression_level: String,
+    pub rocksdb_zstd_max_dict_bytes: String,
     pub rocksdb_perf_sample_interval: String,
 
     pub accounts_shrink_optimize_total_space: String,
@@ -2561,6 +2861,15 @@ impl DefaultArgs {
             rpc_pubsub_queue_capacity_bytes: PubSubConfig::default()
                 .queue_capacity_bytes
                 .to_string(),
+            rpc_grpc_max_clients: GrpcStreamingConfig::default().max_clients.to_string(),
+            rpc_grpc_max_clients_per_ip: GrpcStreamingConfig::default()
+                .max_clients_per_ip
+                .to_string(),
+            rpc_grpc_client_queue_capacity: GrpcStreamingConfig::default()
+                .client_queue_capacity
+                .to_string(),
+            rpc_grpc_max_filter_keys: GrpcStreamingConfig::default().max_filter_keys.to_string(),
+            rpc_grpc_worker_threads: GrpcStreamingConfig::default().worker_threads.to_string(),
             send_transaction_service_config: send_transaction_service::Config::default(),
             rpc_send_transaction_retry_ms: default_send_transaction_service_config
                 .retry_rate_ms
@@ -2607,6 +2916,10 @@ impl DefaultArgs {
             snapshot_version: SnapshotVersion::default(),
             rocksdb_shred_compaction: "level".to_string(),
             rocksdb_ledger_compression: "none".to_string(),
+            rocksdb_rewards_compression: "none".to_string(),
+            rocksdb_data_shred_compression: "none".to_string(),
+            rocksdb_zstd_compression_level: DEFAULT_ZSTD_COMPRESSION_LEVEL.to_string(),
+            rocksdb_zstd_max_dict_bytes: "0".to_string(),
             rocksdb_perf_sample_interval: "0".to_string(),
             accounts_shrink_optimize_total_space: DEFAULT_ACCOUNTS_SHRINK_OPTIMIZE_TOTAL_SPACE
                 .to_string(),
@@ -2751,6 +3064,19 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .value_name("INDEX")
                 .help("Enable an accounts index, indexed by the selected account field"),
         )
+        .arg(
+            Arg::with_name("account_index_custom")
+                .long("account-index-custom")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_parsable::<CustomAccountIndex>)
+                .value_name("PROGRAM_ID:OFFSET:LENGTH")
+                .help(
+                    "Enable an accounts index over the LENGTH bytes (at most 32) at OFFSET in \
+                     the data of accounts owned by PROGRAM_ID. getProgramAccounts uses this \
+                     index when a memcmp filter matches its offset",
+                ),
+        )
         .arg(
             Arg::with_name("faucet_port")
                 .long("faucet-port")
@@ -2816,6 +3142,12 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                 .takes_value(false)
                 .help("Enable the unstable RPC PubSub `blockSubscribe` subscription"),
         )
+        .arg(
+            Arg::with_name("rpc_pubsub_enable_transaction_subscription")
+                .long("rpc-pubsub-enable-transaction-subscription")
+                .takes_value(false)
+                .help("Enable the unstable RPC PubSub `transactionSubscribe` subscription"),
+        )
         .arg(
             Arg::with_name("bpf_program")
                 .long("bpf-program")
@@ -2885,6 +3217,22 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                      parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("snapshot_archive")
+                .long("snapshot")
+                .value_name("FULL_SNAPSHOT_ARCHIVE")
+                .takes_value(true)
+                .validator(is_parsable::<PathBuf>)
+                .conflicts_with_all(&["ticks_per_slot", "slots_per_epoch"])
+                .help(
+                    "Start from the state in this full snapshot archive, such as a minimized \
+                     snapshot created by `agave-ledger-tool create-snapshot --minimized`, \
+                     instead of a new genesis. Accounts, programs and feature deactivations are \
+                     applied on top of the snapshot, and the validator's own vote account \
+                     replaces the snapshot's vote accounts. If the ledger already exists then \
+                     this parameter is silently ignored",
+                ),
+        )
         .arg(
             Arg::with_name("ticks_per_slot")
                 .long("ticks-per-slot")
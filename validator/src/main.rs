@@ -17,11 +17,14 @@ use {
     log::*,
     rand::{seq::SliceRandom, thread_rng},
     solana_accounts_db::{
-        accounts_db::{AccountShrinkThreshold, AccountsDb, AccountsDbConfig, CreateAncientStorage},
+        accounts_db::{
+            shrink_control::ShrinkRequestKind, AccountShrinkThreshold, AccountsDb,
+            AccountsDbConfig, CreateAncientStorage,
+        },
         accounts_file::StorageAccess,
         accounts_index::{
             AccountIndex, AccountSecondaryIndexes, AccountSecondaryIndexesIncludeExclude,
-            AccountsIndexConfig, IndexLimitMb, ScanFilter,
+            AccountsIndexConfig, CustomAccountIndex, IndexLimitMb, ScanFilter,
         },
         utils::{
             create_all_accounts_run_and_snapshot_dirs, create_and_canonicalize_directories,
@@ -30,6 +33,7 @@ use {
     },
     solana_clap_utils::input_parsers::{keypair_of, keypairs_of, pubkey_of, value_of, values_of},
     solana_core::{
+        banking_stage::forwarder::ForwardingMode,
         banking_trace::DISABLED_BAKING_TRACE_DIR,
         consensus::tower_storage,
         proxy::{block_engine_stage::BlockEngineConfig, relayer_stage::RelayerConfig},
@@ -57,7 +61,9 @@ use {
     solana_poh::poh_service,
     solana_rpc::{
         rpc::{JsonRpcConfig, RpcBigtableConfig},
+        rpc_grpc_service::GrpcStreamingConfig,
         rpc_pubsub_service::PubSubConfig,
+        rpc_rate_limiter::{RpcRateLimitConfig, RpcRateLimiter},
     },
     solana_rpc_client::rpc_client::RpcClient,
     solana_rpc_client_api::config::RpcLeaderScheduleConfig,
@@ -776,6 +782,102 @@ pub fn main() {
                 _ => unreachable!(),
             }
         }
+        ("accounts-owner-stats", Some(subcommand_matches)) => {
+            let limit = value_t_or_exit!(subcommand_matches, "limit", usize);
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let owner_stats = admin_rpc_service::runtime()
+                .block_on(async move {
+                    admin_client
+                        .await?
+                        .get_accounts_owner_stats(Some(limit))
+                        .await
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("Accounts owner stats query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!("{}", serde_json::to_string_pretty(&owner_stats).unwrap()),
+                    "json-compact" => print!("{}", serde_json::to_string(&owner_stats).unwrap()),
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{owner_stats}");
+            }
+            return;
+        }
+        ("accounts-shrink", Some(shrink_subcommand_matches)) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            match shrink_subcommand_matches.subcommand() {
+                ("pause", _) => {
+                    admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.pause_accounts_shrink().await })
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to pause background shrink: {err}");
+                            exit(1);
+                        });
+                    println!("Background shrink paused");
+                }
+                ("resume", _) => {
+                    admin_rpc_service::runtime()
+                        .block_on(async move { admin_client.await?.resume_accounts_shrink().await })
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to resume background shrink: {err}");
+                            exit(1);
+                        });
+                    println!("Background shrink resumed");
+                }
+                ("request", Some(subcommand_matches)) => {
+                    let start_slot = value_t_or_exit!(subcommand_matches, "start_slot", Slot);
+                    let end_slot = value_t_or_exit!(subcommand_matches, "end_slot", Slot);
+                    let kind = if subcommand_matches.is_present("ancient_pack") {
+                        ShrinkRequestKind::AncientPack
+                    } else {
+                        ShrinkRequestKind::Shrink
+                    };
+                    let request = admin_rpc_service::runtime()
+                        .block_on(async move {
+                            admin_client
+                                .await?
+                                .request_accounts_shrink(kind, start_slot, end_slot)
+                                .await
+                        })
+                        .unwrap_or_else(|err| {
+                            eprintln!("Shrink request failed: {err}");
+                            exit(1);
+                        });
+                    println!("Queued request {request}");
+                }
+                ("status", Some(subcommand_matches)) => {
+                    let output_mode = subcommand_matches.value_of("output");
+                    let status = admin_rpc_service::runtime()
+                        .block_on(
+                            async move { admin_client.await?.get_accounts_shrink_status().await },
+                        )
+                        .unwrap_or_else(|err| {
+                            eprintln!("Shrink status query failed: {err}");
+                            exit(1);
+                        });
+                    if let Some(mode) = output_mode {
+                        match mode {
+                            "json" => {
+                                println!("{}", serde_json::to_string_pretty(&status).unwrap())
+                            }
+                            "json-compact" => {
+                                print!("{}", serde_json::to_string(&status).unwrap())
+                            }
+                            _ => unreachable!(),
+                        }
+                    } else {
+                        print!("{status}");
+                    }
+                }
+                _ => unreachable!(),
+            }
+            return;
+        }
         ("contact-info", Some(subcommand_matches)) => {
             let output_mode = subcommand_matches.value_of("output");
             let admin_client = admin_rpc_service::connect(&ledger_path);
@@ -1193,17 +1295,26 @@ pub fn main() {
         None
     };
 
-    let column_options = LedgerColumnOptions {
-        compression_type: match matches.value_of("rocksdb_ledger_compression") {
-            None => BlockstoreCompressionType::default(),
-            Some(ledger_compression_string) => match ledger_compression_string {
-                "none" => BlockstoreCompressionType::None,
-                "snappy" => BlockstoreCompressionType::Snappy,
-                "lz4" => BlockstoreCompressionType::Lz4,
-                "zlib" => BlockstoreCompressionType::Zlib,
-                _ => panic!("Unsupported ledger_compression: {ledger_compression_string}"),
+    let zstd_compression_level = value_t_or_exit!(matches, "rocksdb_zstd_compression_level", i32);
+    let zstd_max_dict_bytes = value_t_or_exit!(matches, "rocksdb_zstd_max_dict_bytes", u32);
+    let compression_type_of = |name| match matches.value_of(name) {
+        None => BlockstoreCompressionType::default(),
+        Some(ledger_compression_string) => match ledger_compression_string {
+            "none" => BlockstoreCompressionType::None,
+            "snappy" => BlockstoreCompressionType::Snappy,
+            "lz4" => BlockstoreCompressionType::Lz4,
+            "zlib" => BlockstoreCompressionType::Zlib,
+            "zstd" => BlockstoreCompressionType::Zstd {
+                level: zstd_compression_level,
+                max_dict_bytes: zstd_max_dict_bytes,
             },
+            _ => panic!("Unsupported ledger_compression: {ledger_compression_string}"),
         },
+    };
+    let column_options = LedgerColumnOptions {
+        compression_type: compression_type_of("rocksdb_ledger_compression"),
+        rewards_compression_type: compression_type_of("rocksdb_rewards_compression"),
+        data_shred_compression_type: compression_type_of("rocksdb_data_shred_compression"),
         rocks_perf_sample_interval: value_t_or_exit!(
             matches,
             "rocksdb_perf_sample_interval",
@@ -1460,6 +1571,7 @@ pub fn main() {
         .value_of("accounts_db_squash_storages_method")
         .map(|method| match method {
             "pack" => CreateAncientStorage::Pack,
+            "pack-cold" => CreateAncientStorage::PackCold,
             "append" => CreateAncientStorage::Append,
             _ => {
                 // clap will enforce one of the above values is given
@@ -1532,6 +1644,7 @@ pub fn main() {
         num_clean_threads: Some(accounts_db_clean_threads),
         num_foreground_threads: Some(accounts_db_foreground_threads),
         num_hash_threads: Some(accounts_db_hash_threads),
+        account_history_slots: value_t!(matches, "accounts_db_account_history_slots", Slot).ok(),
         ..AccountsDbConfig::default()
     };
 
@@ -1552,6 +1665,7 @@ pub fn main() {
 
     let rpc_bigtable_config = if matches.is_present("enable_rpc_bigtable_ledger_storage")
         || matches.is_present("enable_bigtable_ledger_upload")
+        || matches.is_present("rpc_ledger_storage_url")
     {
         Some(RpcBigtableConfig {
             enable_bigtable_ledger_upload: matches.is_present("enable_bigtable_ledger_upload"),
@@ -1565,11 +1679,20 @@ pub fn main() {
                 .ok()
                 .map(Duration::from_secs),
             max_message_size: value_t_or_exit!(matches, "rpc_bigtable_max_message_size", usize),
+            object_store_url: matches.value_of("rpc_ledger_storage_url").map(String::from),
         })
     } else {
         None
     };
 
+    let rpc_rate_limiter = matches.value_of("rpc_rate_limit_config").map(|path| {
+        let config = RpcRateLimitConfig::load_from_file(Path::new(path)).unwrap_or_else(|err| {
+            eprintln!("Failed to load the RPC rate limit config: {err}");
+            exit(1);
+        });
+        Arc::new(RpcRateLimiter::new(config))
+    });
+
     let rpc_send_retry_rate_ms = value_t_or_exit!(matches, "rpc_send_transaction_retry_ms", u64);
     let rpc_send_batch_size = value_t_or_exit!(matches, "rpc_send_transaction_batch_size", usize);
     let rpc_send_batch_send_rate_ms =
@@ -1698,6 +1821,7 @@ pub fn main() {
                 usize
             )),
             skip_preflight_health_check: matches.is_present("skip_preflight_health_check"),
+            rate_limiter: rpc_rate_limiter.clone(),
         },
         on_start_geyser_plugin_config_files,
         geyser_plugin_always_enabled: matches.is_present("geyser_plugin_always_enabled"),
@@ -1712,6 +1836,8 @@ pub fn main() {
         }),
         pubsub_config: PubSubConfig {
             enable_block_subscription: matches.is_present("rpc_pubsub_enable_block_subscription"),
+            enable_transaction_subscription: matches
+                .is_present("rpc_pubsub_enable_transaction_subscription"),
             enable_vote_subscription: matches.is_present("rpc_pubsub_enable_vote_subscription"),
             max_active_subscriptions: value_t_or_exit!(
                 matches,
@@ -1732,6 +1858,21 @@ pub fn main() {
             notification_threads: value_t!(matches, "rpc_pubsub_notification_threads", usize)
                 .ok()
                 .and_then(NonZeroUsize::new),
+            rate_limiter: rpc_rate_limiter,
+        },
+        grpc_streaming_addr: value_t!(matches, "rpc_grpc_port", u16)
+            .ok()
+            .map(|rpc_grpc_port| SocketAddr::new(rpc_bind_address, rpc_grpc_port)),
+        grpc_streaming_config: GrpcStreamingConfig {
+            max_clients: value_t_or_exit!(matches, "rpc_grpc_max_clients", usize),
+            max_clients_per_ip: value_t_or_exit!(matches, "rpc_grpc_max_clients_per_ip", usize),
+            client_queue_capacity: value_t_or_exit!(
+                matches,
+                "rpc_grpc_client_queue_capacity",
+                usize
+            ),
+            max_filter_keys: value_t_or_exit!(matches, "rpc_grpc_max_filter_keys", usize),
+            worker_threads: value_t_or_exit!(matches, "rpc_grpc_worker_threads", usize),
         },
         voting_disabled: matches.is_present("no_voting") || restricted_repair_only_mode,
         wait_for_supermajority: value_t!(matches, "wait_for_supermajority", Slot).ok(),
@@ -2017,6 +2158,7 @@ pub fn main() {
         maximum_incremental_snapshot_archives_to_retain,
         accounts_hash_debug_verify: validator_config.accounts_db_test_hash_calculation,
         packager_thread_niceness_adj: snapshot_packager_niceness_adj,
+        archive_tiered_storages: matches.is_present("snapshot_archive_tiered_storages"),
     };
 
     // The accounts hash interval shall match the snapshot interval
@@ -2067,6 +2209,16 @@ pub fn main() {
     )
     .unwrap_or_default();
     validator_config.enable_block_production_forwarding = staked_nodes_overrides_path.is_some();
+    validator_config.block_production_forwarding_mode = match value_t!(
+        matches,
+        "block_production_forwarding_lookahead_leaders",
+        usize
+    ) {
+        Ok(num_leaders) if num_leaders > 1 => ForwardingMode::Lookahead { num_leaders },
+        _ => ForwardingMode::NextLeader,
+    };
+    validator_config.enable_program_cost_estimation =
+        matches.is_present("block_production_program_cost_estimation");
     validator_config.unified_scheduler_handler_threads =
         value_t!(matches, "unified_scheduler_handler_threads", usize).ok();
 
@@ -2366,6 +2518,12 @@ fn process_account_indexes(matches: &ArgMatches) -> AccountSecondaryIndexes {
             "spl-token-owner" => AccountIndex::SplTokenOwner,
             _ => unreachable!(),
         })
+        .chain(
+            values_t!(matches, "account_index_custom", CustomAccountIndex)
+                .unwrap_or_default()
+                .into_iter()
+                .map(AccountIndex::Custom),
+        )
         .collect();
 
     let account_indexes_include_keys: HashSet<Pubkey> =